@@ -0,0 +1,46 @@
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    #[cfg(feature = "ffi")]
+    generate_header();
+
+    #[cfg(feature = "grpc")]
+    compile_proto();
+}
+
+/// Writes the C header of the `ffi` module to `include/minesweeper.h`.
+#[cfg(feature = "ffi")]
+fn generate_header() {
+    println!("cargo:rerun-if-changed=src/ffi.rs");
+
+    let mut config = cbindgen::Config::default();
+    config.enumeration.prefix_with_name = true;
+
+    cbindgen::Builder::new()
+        .with_config(config)
+        .with_src("src/ffi.rs")
+        .with_language(cbindgen::Language::C)
+        .with_include_guard("MINESWEEPER_H")
+        .with_documentation(true)
+        .generate()
+        .expect("Header of the C API can be generated.")
+        .write_to_file("include/minesweeper.h");
+}
+
+/// Generates the messages and the server of `proto/minesweeper.proto` for the `grpc` module,
+/// with the protoc shipped by `protoc-bin-vendored` unless `PROTOC` names another one.
+#[cfg(feature = "grpc")]
+fn compile_proto() {
+    println!("cargo:rerun-if-changed=proto/minesweeper.proto");
+    println!("cargo:rerun-if-env-changed=PROTOC");
+
+    if std::env::var_os("PROTOC").is_none() {
+        let protoc = protoc_bin_vendored::protoc_bin_path().expect("protoc is vendored.");
+        std::env::set_var("PROTOC", protoc);
+    }
+
+    tonic_prost_build::configure()
+        .build_client(false)
+        .compile_protos(&["proto/minesweeper.proto"], &["proto"])
+        .expect("The gRPC protocol can be compiled.");
+}
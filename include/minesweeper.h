@@ -0,0 +1,88 @@
+#ifndef MINESWEEPER_H
+#define MINESWEEPER_H
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/**
+ * Cell that is neither explored nor marked.
+ */
+#define MS_CELL_HIDDEN -1
+
+/**
+ * Cell marked with a flag.
+ */
+#define MS_CELL_FLAG -2
+
+/**
+ * Cell marked with a note.
+ */
+#define MS_CELL_NOTE -3
+
+/**
+ * Mine, only shown once the game is lost.
+ */
+#define MS_CELL_MINE -4
+
+/**
+ * Coordinate outside of the board.
+ */
+#define MS_CELL_INVALID -5
+
+/**
+ * Outcome of `ms_command`.
+ */
+typedef enum MsResolve {
+  MsResolve_Continue = 0,
+  MsResolve_Quit = 1,
+  MsResolve_MineHit = 2,
+  MsResolve_Won = 3,
+  /**
+   * The command is malformed, outside of the board or the game is over.
+   */
+  MsResolve_Invalid = -1,
+} MsResolve;
+
+/**
+ * Game handle, owned by the caller until passed to `ms_free`.
+ */
+typedef struct MsGame MsGame;
+
+/**
+ * Creates a game on a board generated from the seed, returns null if the board is empty,
+ * too large or cannot hold the mines.
+ */
+struct MsGame *ms_new(uint32_t width, uint32_t height, uint32_t mines, uint64_t seed);
+
+/**
+ * Applies a command in the terminal notation, e.g. `"flag(0, 1)"`.
+ *
+ * # Safety
+ *
+ * `game` must come from `ms_new` and not be freed, `command` must be a nul terminated
+ * string.
+ */
+enum MsResolve ms_command(struct MsGame *game, const char *command);
+
+/**
+ * Number of neighbouring mines of an explored cell, otherwise one of the `MS_CELL_*`
+ * constants.
+ *
+ * # Safety
+ *
+ * `game` must come from `ms_new` and not be freed.
+ */
+int32_t ms_cell_at(const struct MsGame *game, uint32_t row, uint32_t col);
+
+/**
+ * Releases the game, null is ignored.
+ *
+ * # Safety
+ *
+ * `game` must come from `ms_new` and not be freed already.
+ */
+void ms_free(struct MsGame *game);
+
+#endif  /* MINESWEEPER_H */
@@ -0,0 +1,159 @@
+//! Milestones unlocked by playing, kept in the player's profile.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GameConfiguration, GameResolve, GameResult};
+
+const LIFETIME_CELLS_GOAL: u64 = 1000;
+const EXPERT_TIME_GOAL_SECONDS: u64 = 100;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Achievement {
+    FirstWin,
+    ExpertUnder100,
+    WinWithoutFlags,
+    ThousandCellsRevealed,
+}
+
+impl Achievement {
+    pub const ALL: [Achievement; 4] = [
+        Achievement::FirstWin,
+        Achievement::ExpertUnder100,
+        Achievement::WinWithoutFlags,
+        Achievement::ThousandCellsRevealed,
+    ];
+
+    pub fn title(&self) -> &'static str {
+        match self {
+            Achievement::FirstWin => "First win",
+            Achievement::ExpertUnder100 => "Expert speedster",
+            Achievement::WinWithoutFlags => "No flags needed",
+            Achievement::ThousandCellsRevealed => "Explorer",
+        }
+    }
+
+    pub fn description(&self) -> &'static str {
+        match self {
+            Achievement::FirstWin => "Win a game.",
+            Achievement::ExpertUnder100 => "Win an expert game in under 100 seconds.",
+            Achievement::WinWithoutFlags => "Win a game without placing a single flag.",
+            Achievement::ThousandCellsRevealed => "Reveal 1000 cells over all games.",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Achievements {
+    unlocked: Vec<Achievement>,
+    cells_revealed: u64,
+}
+
+impl Achievements {
+    pub fn is_unlocked(&self, achievement: Achievement) -> bool {
+        self.unlocked.contains(&achievement)
+    }
+
+    pub fn cells_revealed(&self) -> u64 {
+        self.cells_revealed
+    }
+
+    /// Updates the lifetime counters with a finished game and returns the achievements
+    /// it unlocked.
+    pub fn record_game(&mut self, result: &GameResult) -> Vec<Achievement> {
+        self.cells_revealed += result.cells_revealed as u64;
+
+        let won = result.resolve == GameResolve::AllMinesDiscovered;
+        let earned = Achievement::ALL
+            .into_iter()
+            .filter(|achievement| match achievement {
+                Achievement::FirstWin => won,
+                Achievement::ExpertUnder100 => {
+                    won && result.replay.configuration == GameConfiguration::expert()
+                        && result.replay.mask.is_none()
+                        && result.replay.layout.is_none()
+                        && result.is_ranked()
+                        && result.elapsed.as_secs() < EXPERT_TIME_GOAL_SECONDS
+                }
+                Achievement::WinWithoutFlags => won && result.flags_placed == 0,
+                Achievement::ThousandCellsRevealed => self.cells_revealed >= LIFETIME_CELLS_GOAL,
+            });
+
+        let newly_unlocked: Vec<Achievement> = earned
+            .filter(|achievement| !self.unlocked.contains(achievement))
+            .collect();
+        self.unlocked.extend(&newly_unlocked);
+
+        newly_unlocked
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        layout::Layout, replay::Replay, BoardCommand, Coordinate, Efficiency, GameBoard, SafeStart,
+    };
+
+    fn game_result(resolve: GameResolve, flags_placed: u32, cells_revealed: u32) -> GameResult {
+        GameResult {
+            resolve,
+            elapsed: Duration::from_secs(50),
+            replay: Replay::new(GameConfiguration::expert(), 0),
+            flags_placed,
+            cells_revealed,
+            efficiency: Efficiency::default(),
+            board_three_bv: 0,
+            hints_used: 0,
+            forgiven: false,
+            cheated: false,
+            scripted: false,
+            assisted: false,
+            practice: false,
+        }
+    }
+
+    #[test]
+    fn unlock_once_test() {
+        let mut achievements = Achievements::default();
+
+        let unlocked =
+            achievements.record_game(&game_result(GameResolve::AllMinesDiscovered, 99, 381));
+        assert_eq!(
+            vec![Achievement::FirstWin, Achievement::ExpertUnder100],
+            unlocked
+        );
+
+        let unlocked = achievements.record_game(&game_result(GameResolve::MineHit, 0, 700));
+        assert_eq!(vec![Achievement::ThousandCellsRevealed], unlocked);
+
+        let unlocked =
+            achievements.record_game(&game_result(GameResolve::AllMinesDiscovered, 99, 381));
+        assert!(unlocked.is_empty());
+        assert_eq!(1462, achievements.cells_revealed());
+    }
+
+    #[test]
+    fn win_without_flags_test() {
+        // the opening reveals every cell without a mine, nothing is flagged
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*###\n").unwrap());
+        let resolve = game_board.manipulate_cell(BoardCommand::Explore(Coordinate(0, 3)));
+        assert_eq!(GameResolve::AllMinesDiscovered, resolve);
+
+        let mut achievements = Achievements::default();
+        let unlocked =
+            achievements.record_game(&game_result(resolve, 0, game_board.explored_cells()));
+        assert!(unlocked.contains(&Achievement::WinWithoutFlags));
+    }
+
+    #[test]
+    fn safe_start_unranked_test() {
+        let mut result = game_result(GameResolve::AllMinesDiscovered, 99, 381);
+        result.replay.safe_start = SafeStart::Square3;
+        assert!(!result.is_ranked());
+
+        let unlocked = Achievements::default().record_game(&result);
+        assert_eq!(vec![Achievement::FirstWin], unlocked);
+    }
+}
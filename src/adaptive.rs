@@ -0,0 +1,91 @@
+//! Adaptive difficulty. Every won game makes the next board denser, a win streak faster
+//! so, and every lost one makes it sparser. The rating is kept in the profile.
+
+use serde::{Deserialize, Serialize};
+
+use crate::{GameConfiguration, GameResolve};
+
+/// Side length of the boards of the mode.
+const SIDE: u32 = 16;
+
+/// Mine densities in percent the rating stays between.
+const MIN_DENSITY: u32 = 8;
+const MAX_DENSITY: u32 = 30;
+
+/// Density lost with a game, more than a single win gains.
+const LOSS_PENALTY: u32 = 2;
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Rating {
+    /// Share of the cells holding a mine on the next board, in percent.
+    pub density_percent: u32,
+    /// Wins in a row since the last loss.
+    pub streak: u32,
+}
+
+impl Default for Rating {
+    fn default() -> Self {
+        Rating {
+            density_percent: 12,
+            streak: 0,
+        }
+    }
+}
+
+impl Rating {
+    /// Board of the next game at the current rating.
+    pub fn configuration(&self) -> GameConfiguration {
+        let density = self.density_percent.clamp(MIN_DENSITY, MAX_DENSITY);
+        GameConfiguration::new(SIDE, SIDE, SIDE * SIDE * density / 100)
+    }
+
+    /// Moves the rating after a game, quitting leaves it as it is.
+    pub fn record(&mut self, resolve: GameResolve) {
+        match resolve {
+            GameResolve::AllMinesDiscovered => {
+                self.streak += 1;
+                self.density_percent = (self.density_percent + self.streak).min(MAX_DENSITY);
+            }
+            GameResolve::MineHit => {
+                self.streak = 0;
+                self.density_percent = self
+                    .density_percent
+                    .saturating_sub(LOSS_PENALTY)
+                    .max(MIN_DENSITY);
+            }
+            GameResolve::Quit | GameResolve::Continue => {}
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rating_test() {
+        let mut rating = Rating::default();
+        assert_eq!(30, rating.configuration().mines());
+
+        // the second win in a row counts twice
+        rating.record(GameResolve::AllMinesDiscovered);
+        rating.record(GameResolve::AllMinesDiscovered);
+        assert_eq!(15, rating.density_percent);
+        rating.record(GameResolve::Quit);
+        assert_eq!(2, rating.streak);
+
+        rating.record(GameResolve::MineHit);
+        assert_eq!(Rating::default().streak, rating.streak);
+        assert_eq!(13, rating.density_percent);
+
+        for _ in 0..10 {
+            rating.record(GameResolve::AllMinesDiscovered);
+        }
+        assert_eq!(MAX_DENSITY, rating.density_percent);
+        for _ in 0..20 {
+            rating.record(GameResolve::MineHit);
+        }
+        assert_eq!(MIN_DENSITY, rating.density_percent);
+    }
+}
@@ -0,0 +1,108 @@
+//! Snapshots of the board coloured with ANSI escape codes in the palette of the terminal
+//! UI, written to a file that shows the board again when `cat`-ed or pasted into a
+//! terminal or chat that understands the codes.
+
+use std::{io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    protocol::{CellView, Status},
+    storage, Coordinate, GameBoard,
+};
+
+const RESET: &str = "\x1b[0m";
+
+/// How the console draws the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Theme {
+    /// Marks between bars, readable everywhere.
+    #[default]
+    Plain,
+    /// The cells coloured in the palette of the terminal UI.
+    Colour,
+}
+
+/// Select graphic rendition parameters of the cell, none for the terminal's default.
+fn style(view: CellView) -> Option<&'static str> {
+    match view {
+        CellView::Hidden => Some("37"),
+        CellView::Flag => Some("1;31"),
+        CellView::Note => Some("33"),
+        CellView::Mine => Some("91"),
+        CellView::Explored { mines: 0 } | CellView::Void => None,
+        CellView::Explored { mines } => Some(match mines {
+            ..=-1 => "93",
+            1 => "34",
+            2 => "32",
+            3 => "31",
+            4 => "35",
+            5 => "91",
+            6 => "36",
+            _ => "97",
+        }),
+    }
+}
+
+fn symbol(view: CellView) -> String {
+    match view {
+        CellView::Hidden => " · ".to_string(),
+        CellView::Flag => " F ".to_string(),
+        CellView::Note => " ? ".to_string(),
+        CellView::Mine => " * ".to_string(),
+        CellView::Explored { mines: 0 } | CellView::Void => "   ".to_string(),
+        CellView::Explored { mines } => format!("{:^3}", mines),
+    }
+}
+
+/// The text of the cell in its colour.
+pub(crate) fn paint(view: CellView, text: &str) -> String {
+    match style(view) {
+        Some(style) => format!("\x1b[{}m{}{}", style, text, RESET),
+        None => text.to_string(),
+    }
+}
+
+/// The board as the terminal UI draws it, a line per row.
+pub fn render(game_board: &GameBoard, status: Status) -> String {
+    let (width, height) = game_board.get_dimensions();
+    let mut snapshot = String::new();
+    for row in 0..height {
+        for col in 0..width {
+            let view = CellView::at(game_board, Coordinate(row, col), status);
+            snapshot.push_str(&paint(view, &symbol(view)));
+        }
+        snapshot.push('\n');
+    }
+    snapshot
+}
+
+/// Writes the snapshot into the profile's directory and returns its path.
+pub fn export(game_board: &GameBoard, status: Status) -> io::Result<PathBuf> {
+    let file_name = format!(
+        "board-{}-{}.ans",
+        game_board.configuration().key(),
+        game_board.explored_cells()
+    );
+    storage::export_file(&file_name, render(game_board, status).as_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layout::Layout, BoardCommand};
+
+    #[test]
+    fn render_test() {
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*##\n###\n").unwrap());
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 2)));
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(0, 0)));
+
+        assert_eq!(
+            "\x1b[1;31m F \x1b[0m\x1b[34m 1 \x1b[0m   \n\
+             \x1b[37m · \x1b[0m\x1b[34m 1 \x1b[0m   \n",
+            render(&game_board, Status::Playing)
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Timings of the engine for spotting performance regressions, run with the `bench`
+//! subcommand. Every board of the matrix of sizes and densities is generated, a square
+//! board without mines is flood filled from its corner and the solver is run on the
+//! generated board opened at its first empty cell. Each timing is the median of the
+//! runs, builds without optimizations are far slower.
+
+use std::time::{Duration, Instant};
+
+use crate::{
+    layout::{EditCommand, Layout},
+    solver::{self, Verdict},
+    BoardCell, BoardCommand, CellInfo, Coordinate, GameBoard, GameConfiguration, Mark,
+    NeighbourMines,
+};
+
+/// Sides of the square boards benchmarked by default.
+pub const SIZES: [u32; 4] = [16, 64, 256, 1024];
+/// Shares of mine cells benchmarked by default.
+pub const DENSITIES: [f64; 3] = [0.1, 0.15, 0.2];
+/// Longest side the solver is timed on, it searches placements of the whole frontier.
+pub const SOLVER_MAX_SIDE: u32 = 256;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Measurement {
+    pub side: u32,
+    pub density: f64,
+    pub mines: u32,
+    pub generate: Duration,
+    pub flood_fill: Duration,
+    /// Time of the solver and whether it solved the board, none above
+    /// [`SOLVER_MAX_SIDE`] or without an empty cell to open.
+    pub solve: Option<(Duration, bool)>,
+}
+
+/// Measures every combination of the sides and densities, boards the mines do not fit
+/// into are skipped.
+pub fn run(sides: &[u32], densities: &[f64], runs: u32, seed: u64) -> Vec<Measurement> {
+    let runs = runs.max(1);
+    let mut measurements = vec![];
+    for side in sides {
+        let flood_fill = median(runs, || time_flood_fill(*side));
+        for density in densities {
+            let mines = (*side as f64 * *side as f64 * density).round() as u32;
+            let configuration = GameConfiguration::new(*side, *side, mines);
+            if !configuration.fits() {
+                continue;
+            }
+
+            let generate = median(runs, || time_generate(configuration, seed).0);
+            let solve = (*side <= SOLVER_MAX_SIDE)
+                .then(|| opened_layout(time_generate(configuration, seed).1))
+                .flatten()
+                .map(|layout| {
+                    let mut solvable = false;
+                    let duration = median(runs, || {
+                        time(|| solvable = solver::solve(&layout) == Verdict::Solvable)
+                    });
+                    (duration, solvable)
+                });
+
+            measurements.push(Measurement {
+                side: *side,
+                density: *density,
+                mines,
+                generate,
+                flood_fill,
+                solve,
+            });
+        }
+    }
+    measurements
+}
+
+/// The measurements as a table, a row per board.
+pub fn table(measurements: &[Measurement]) -> String {
+    let mut table = format!(
+        "{:>11}{:>9}{:>10}{:>14}{:>14}{:>14}{:>10}\n",
+        "Board", "Density", "Mines", "Generate", "Flood fill", "Solve", "Solved"
+    );
+    for measurement in measurements {
+        let (solve, solved) = match measurement.solve {
+            Some((duration, solved)) => (millis(duration), if solved { "yes" } else { "no" }),
+            None => ("-".to_string(), "-"),
+        };
+        table += &format!(
+            "{:>11}{:>9}{:>10}{:>14}{:>14}{:>14}{:>10}\n",
+            format!("{0}x{0}", measurement.side),
+            format!("{:.0}%", measurement.density * 100.0),
+            measurement.mines,
+            millis(measurement.generate),
+            millis(measurement.flood_fill),
+            solve,
+            solved
+        );
+    }
+    table
+}
+
+fn millis(duration: Duration) -> String {
+    format!("{:.3} ms", duration.as_secs_f64() * 1000.0)
+}
+
+fn time<T>(f: impl FnOnce() -> T) -> Duration {
+    let started = Instant::now();
+    std::hint::black_box(f());
+    started.elapsed()
+}
+
+fn median(runs: u32, mut measure: impl FnMut() -> Duration) -> Duration {
+    let mut durations: Vec<Duration> = (0..runs).map(|_| measure()).collect();
+    durations.sort();
+    durations[durations.len() / 2]
+}
+
+fn time_generate(configuration: GameConfiguration, seed: u64) -> (Duration, GameBoard) {
+    let started = Instant::now();
+    let mut game_board = GameBoard::new(configuration);
+    game_board.generate_world_from_seed(seed);
+    (started.elapsed(), game_board)
+}
+
+fn time_flood_fill(side: u32) -> Duration {
+    let mut game_board = GameBoard::new(GameConfiguration::new(side, side, 0));
+    time(|| game_board.manipulate_cell(BoardCommand::Explore(Coordinate(0, 0))))
+}
+
+/// Layout of the board with its first empty cell explored, the way a player would start.
+fn opened_layout(mut game_board: GameBoard) -> Option<Layout> {
+    let (width, height) = game_board.get_dimensions();
+    let coordinates = (0..height).flat_map(|row| (0..width).map(move |col| Coordinate(row, col)));
+    let opening = coordinates.clone().find(|coordinate| {
+        game_board.get_cell_at(*coordinate)
+            == BoardCell::NoMine(CellInfo(Mark::NoMark, NeighbourMines(0)))
+    })?;
+
+    game_board.manipulate_cell(BoardCommand::Explore(opening));
+
+    let mut layout = Layout::new(width, height);
+    for coordinate in coordinates {
+        match game_board.get_cell_at(coordinate) {
+            BoardCell::Mine(_) => layout.edit(EditCommand::Mine(coordinate)),
+            BoardCell::Explored(_) => layout.edit(EditCommand::Reveal(coordinate, coordinate)),
+            BoardCell::NoMine(_) => {}
+        }
+    }
+    Some(layout)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn run_test() {
+        let measurements = run(&[16, 2], &[0.15, 1.5], 1, 3);
+        // more mines than cells fit on neither board and 2x2 has no empty cell to open
+        assert_eq!(2, measurements.len());
+        assert_eq!(38, measurements[0].mines);
+        assert!(measurements[0].solve.is_some());
+        assert!(measurements[1].solve.is_none());
+
+        let table = table(&measurements);
+        assert_eq!(3, table.lines().count());
+        assert!(table
+            .lines()
+            .nth(1)
+            .unwrap()
+            .starts_with("      16x16      15%        38"));
+    }
+}
@@ -0,0 +1,151 @@
+//! Leaderboard server, accepts scores with attached replays, verifies them by
+//! re-simulating the replay and serves the top lists.
+//!
+//! `POST /scores` takes a [`Submission`] as json, `GET /scores?difficulty=9x9-10`
+//! returns the top list of the difficulty.
+
+use std::{
+    fs,
+    io::{self, Cursor, Read},
+    path::{Path, PathBuf},
+    process,
+};
+
+use clap::Parser;
+use minesweeper::leaderboard::{Leaderboard, Submission, SubmissionError};
+use serde::Serialize;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+#[derive(Parser)]
+#[command(version, about = "Leaderboard server for minesweeper.")]
+struct Args {
+    /// Address the server listens on.
+    #[arg(long, default_value = "127.0.0.1:8080")]
+    address: String,
+
+    /// File the verified scores are kept in.
+    #[arg(long, default_value = "leaderboard.json")]
+    data: PathBuf,
+
+    /// Number of entries in a top list.
+    #[arg(long, default_value_t = 100)]
+    limit: usize,
+}
+
+#[derive(Serialize)]
+struct Message {
+    message: String,
+}
+
+fn main() {
+    let args = Args::parse();
+
+    let mut leaderboard = match load(&args.data) {
+        Ok(leaderboard) => leaderboard,
+        Err(err) => {
+            eprintln!("Could not read {}: {}", args.data.display(), err);
+            process::exit(1);
+        }
+    };
+
+    let server = match Server::http(&args.address) {
+        Ok(server) => server,
+        Err(err) => {
+            eprintln!("Could not listen on {}: {}", args.address, err);
+            process::exit(1);
+        }
+    };
+    println!("Listening on {}", args.address);
+
+    for mut request in server.incoming_requests() {
+        let response = handle(&mut request, &mut leaderboard, &args);
+        if let Err(err) = request.respond(response) {
+            eprintln!("Could not respond: {}", err);
+        }
+    }
+}
+
+fn load(path: &Path) -> io::Result<Leaderboard> {
+    match fs::read_to_string(path) {
+        Ok(content) => Ok(serde_json::from_str(&content)?),
+        Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(Leaderboard::default()),
+        Err(err) => Err(err),
+    }
+}
+
+fn save(path: &Path, leaderboard: &Leaderboard) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, serde_json::to_string(leaderboard)?)?;
+    fs::rename(temporary, path)
+}
+
+fn handle(
+    request: &mut Request,
+    leaderboard: &mut Leaderboard,
+    args: &Args,
+) -> Response<Cursor<Vec<u8>>> {
+    let url = request.url().to_string();
+    let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+
+    match (request.method(), path) {
+        (Method::Post, "/scores") => {
+            let mut body = String::new();
+            if request
+                .as_reader()
+                .take(MAX_BODY_BYTES)
+                .read_to_string(&mut body)
+                .is_err()
+            {
+                return message(400, "could not read the request body");
+            }
+
+            let submission: Submission = match serde_json::from_str(&body) {
+                Ok(submission) => submission,
+                Err(err) => return message(400, &format!("malformed submission, {}", err)),
+            };
+
+            match leaderboard.submit(submission) {
+                Ok(()) => match save(&args.data, leaderboard) {
+                    Ok(()) => message(201, "score accepted"),
+                    Err(err) => {
+                        eprintln!("Could not save {}: {}", args.data.display(), err);
+                        message(500, "could not save the score")
+                    }
+                },
+                Err(SubmissionError::Duplicate) => {
+                    message(409, &SubmissionError::Duplicate.to_string())
+                }
+                Err(err) => message(400, &err.to_string()),
+            }
+        }
+        (Method::Get, "/scores") => {
+            let difficulty = query
+                .split('&')
+                .find_map(|pair| pair.strip_prefix("difficulty="));
+            match difficulty {
+                Some(difficulty) => json(200, &leaderboard.top_list(difficulty, args.limit)),
+                None => message(400, "the difficulty parameter is missing"),
+            }
+        }
+        _ => message(404, "not found"),
+    }
+}
+
+fn message(status: u16, message: &str) -> Response<Cursor<Vec<u8>>> {
+    json(
+        status,
+        &Message {
+            message: message.to_string(),
+        },
+    )
+}
+
+fn json<T: Serialize>(status: u16, body: &T) -> Response<Cursor<Vec<u8>>> {
+    let content_type = Header::from_bytes("Content-Type", "application/json")
+        .expect("Content type header is valid.");
+    Response::from_data(serde_json::to_vec(body).unwrap_or_default())
+        .with_status_code(status)
+        .with_header(content_type)
+}
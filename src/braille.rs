@@ -0,0 +1,66 @@
+//! Compact overview of large boards. Every Braille character covers two columns and four
+//! rows of the board, a raised dot is a cell that is not explored yet, so a board of
+//! 200x100 cells still fits on a terminal. The `braille` command toggles the view.
+
+use crate::{BoardCell, Coordinate, GameBoard};
+
+/// Explains the view, written below the board.
+pub const LEGEND: &str = "Braille view: a character covers 2 columns and 4 rows, raised dots \
+     are unexplored cells. Type braille to return to the normal board.";
+
+/// Bit of the dot at a column and row within a character, in the order of the Unicode
+/// Braille patterns.
+const DOTS: [[u32; 2]; 4] = [[0x01, 0x08], [0x02, 0x10], [0x04, 0x20], [0x40, 0x80]];
+
+/// The board as lines of Braille characters, each line prefixed with its first row.
+pub fn render(game_board: &GameBoard) -> String {
+    let (width, height) = game_board.get_dimensions();
+    let mut lines = vec![];
+    for top in (0..height).step_by(4) {
+        let glyphs: String = (0..width)
+            .step_by(2)
+            .map(|left| {
+                let mut bits = 0;
+                for (row, dots) in DOTS.iter().enumerate() {
+                    for (col, dot) in dots.iter().enumerate() {
+                        let coordinate = Coordinate(top + row as u32, left + col as u32);
+                        if coordinate.0 < height
+                            && coordinate.1 < width
+                            && is_covered(game_board, coordinate)
+                        {
+                            bits |= dot;
+                        }
+                    }
+                }
+                char::from_u32(0x2800 + bits).expect("Braille patterns are valid characters.")
+            })
+            .collect();
+        lines.push(format!(
+            "{:>3} {}",
+            game_board.coordinate_convention().number(top),
+            glyphs
+        ));
+    }
+    lines.join("\n")
+}
+
+fn is_covered(game_board: &GameBoard, coordinate: Coordinate) -> bool {
+    !game_board.is_hole(coordinate)
+        && !matches!(game_board.get_cell_at(coordinate), BoardCell::Explored(_))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layout::Layout, BoardCommand};
+
+    #[test]
+    fn render_test() {
+        let mut game_board =
+            GameBoard::from_layout(Layout::try_from("*##\n###\n###\n###\n###\n").unwrap());
+        assert_eq!("  0 ⣿⡇\n  4 ⠉⠁", render(&game_board));
+
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(4, 2)));
+        assert_eq!("  0 ⠁⠀\n  4 ⠀⠀", render(&game_board));
+    }
+}
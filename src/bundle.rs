@@ -0,0 +1,203 @@
+//! A whole profile in one file, its settings, progress, highscores and game log, to take
+//! it to another computer. Saved games are left out.
+
+use std::{
+    collections::BTreeMap,
+    fmt::Display,
+    io,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    config, endless, gauntlet, highscores, macros, profile, session, splits,
+    statistics::GameRecord, storage::Storage,
+};
+
+/// Format of the bundles, a newer one is refused.
+const VERSION: u32 = 1;
+
+/// Documents a profile keeps besides its game log.
+const DOCUMENTS: [&str; 9] = [
+    config::CONFIG_FILE,
+    profile::PROFILE_FILE,
+    highscores::HIGHSCORES_FILE,
+    highscores::DAILY_HIGHSCORES_FILE,
+    splits::PERSONAL_BESTS_FILE,
+    gauntlet::GAUNTLET_FILE,
+    endless::ENDLESS_FILE,
+    macros::MACROS_FILE,
+    session::LAST_GAME_FILE,
+];
+
+#[derive(Debug)]
+pub enum BundleError {
+    Io(io::Error),
+    Malformed(serde_json::Error),
+    UnknownVersion(u32),
+    /// The profile has data already, which an import would mix with the bundle's.
+    ProfileExists(String),
+}
+
+impl Display for BundleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BundleError::Io(err) => write!(f, "{}", err),
+            BundleError::Malformed(err) => write!(f, "the bundle is malformed, {}", err),
+            BundleError::UnknownVersion(version) => {
+                write!(f, "the bundle is of version {}, which is unknown", version)
+            }
+            BundleError::ProfileExists(profile) => {
+                write!(f, "the profile {} exists already", profile)
+            }
+        }
+    }
+}
+
+impl From<io::Error> for BundleError {
+    fn from(err: io::Error) -> Self {
+        BundleError::Io(err)
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+pub struct ProfileBundle {
+    version: u32,
+    /// Name of the profile the bundle was made of.
+    pub profile: String,
+    /// Seconds since the unix epoch when the bundle was made.
+    #[serde(default)]
+    pub saved_at: u64,
+    /// Json documents by name, as the storage keeps them.
+    documents: BTreeMap<String, String>,
+    games: Vec<GameRecord>,
+}
+
+impl ProfileBundle {
+    pub fn of(storage: &dyn Storage, profile: &str) -> io::Result<Self> {
+        let mut documents = BTreeMap::new();
+        for name in DOCUMENTS {
+            if let Some(content) = storage.load_document(profile, name)? {
+                documents.insert(name.to_string(), content);
+            }
+        }
+        Ok(ProfileBundle {
+            version: VERSION,
+            profile: profile.to_string(),
+            saved_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |since| since.as_secs()),
+            documents,
+            games: storage.games(profile)?,
+        })
+    }
+
+    pub fn from_json(content: &str) -> Result<Self, BundleError> {
+        let bundle: ProfileBundle =
+            serde_json::from_str(content).map_err(BundleError::Malformed)?;
+        if bundle.version > VERSION {
+            return Err(BundleError::UnknownVersion(bundle.version));
+        }
+        Ok(bundle)
+    }
+
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).expect("Serializing a bundle does not fail.")
+    }
+
+    /// Games in the bundle's log.
+    pub fn games(&self) -> usize {
+        self.games.len()
+    }
+
+    /// Writes the bundle into a profile without any data.
+    pub fn restore(&self, storage: &dyn Storage, profile: &str) -> Result<(), BundleError> {
+        if storage
+            .profiles()?
+            .iter()
+            .any(|existing| existing == profile)
+        {
+            return Err(BundleError::ProfileExists(profile.to_string()));
+        }
+        Ok(self.apply(storage, profile)?)
+    }
+
+    /// Overwrites the profile with the bundle, its game log included. Documents the
+    /// bundle lacks are left as they are.
+    pub fn apply(&self, storage: &dyn Storage, profile: &str) -> io::Result<()> {
+        for (name, content) in &self.documents {
+            // only the documents the game reads are written, a crafted name could leave the
+            // profile's directory
+            if DOCUMENTS.contains(&name.as_str()) {
+                storage.save_document(profile, name, content)?;
+            }
+        }
+        storage.replace_games(profile, &self.games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{statistics::Outcome, storage::memory::MemoryStorage, GameConfiguration};
+
+    #[test]
+    fn bundle_round_trip_test() {
+        let storage = MemoryStorage::default();
+        storage
+            .save_document("mom", highscores::HIGHSCORES_FILE, "{\"tables\":{}}")
+            .unwrap();
+        storage
+            .save_document("mom", profile::PROFILE_FILE, "{}")
+            .unwrap();
+        storage
+            .save_document("mom", "notes.txt", "left out")
+            .unwrap();
+        storage
+            .append_game(
+                "mom",
+                &GameRecord {
+                    day: 20_000,
+                    configuration: GameConfiguration::beginner(),
+                    seed: 3,
+                    outcome: Outcome::Won,
+                    time_ms: 9_000,
+                    flags_placed: 10,
+                    cells_revealed: 71,
+                    forgiven: false,
+                    assisted: false,
+                    mine_hit: None,
+                },
+            )
+            .unwrap();
+
+        let bundle = ProfileBundle::of(&storage, "mom").unwrap();
+        assert_eq!(1, bundle.games());
+        let copy = ProfileBundle::from_json(&bundle.to_json()).unwrap();
+        assert_eq!(bundle, copy);
+
+        copy.restore(&storage, "mom2").unwrap();
+        assert_eq!(
+            storage.games("mom").unwrap(),
+            storage.games("mom2").unwrap()
+        );
+        assert_eq!(
+            Some("{}".to_string()),
+            storage
+                .load_document("mom2", profile::PROFILE_FILE)
+                .unwrap()
+        );
+        assert_eq!(None, storage.load_document("mom2", "notes.txt").unwrap());
+        assert!(matches!(
+            copy.restore(&storage, "mom"),
+            Err(BundleError::ProfileExists(_))
+        ));
+
+        let newer = bundle.to_json().replace("\"version\": 1", "\"version\": 2");
+        assert!(matches!(
+            ProfileBundle::from_json(&newer),
+            Err(BundleError::UnknownVersion(2))
+        ));
+    }
+}
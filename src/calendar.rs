@@ -0,0 +1,92 @@
+//! Calendar arithmetic on days since the unix epoch, all dates are UTC.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+
+/// Days elapsed since the unix epoch.
+pub fn today() -> u64 {
+    now_seconds() / SECONDS_PER_DAY
+}
+
+/// Seconds elapsed since the unix epoch.
+pub fn now_seconds() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+/// Converts days since the unix epoch into a gregorian `(year, month, day)`, see
+/// http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+pub fn civil_from_days(days: u64) -> (i64, u32, u32) {
+    let z = days as i64 + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+
+    (year, month as u32, day as u32)
+}
+
+/// Inverse of [`civil_from_days`].
+pub fn days_from_civil(year: i64, month: u32, day: u32) -> u64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year.rem_euclid(400);
+    let shifted_month = (i64::from(month) + 9) % 12;
+    let day_of_year = (153 * shifted_month + 2) / 5 + i64::from(day) - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+
+    (era * 146_097 + day_of_era - 719_468) as u64
+}
+
+/// Formats the day as `YYYY-MM-DD`.
+pub fn format_date(days: u64) -> String {
+    let (year, month, day) = civil_from_days(days);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// ISO 8601 `(year, week)` the day belongs to, weeks start on monday and the
+/// first week of a year is the one containing its first thursday.
+pub fn iso_week(days: u64) -> (i64, u32) {
+    // the epoch was a thursday
+    let weekday_from_monday = (days + 3) % 7;
+    let thursday = days + 3 - weekday_from_monday;
+    let (year, _, _) = civil_from_days(thursday);
+    let week = (thursday - days_from_civil(year, 1, 1)) / 7 + 1;
+
+    (year, week as u32)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn civil_date_test() {
+        assert_eq!("1970-01-01", format_date(0));
+        assert_eq!("2000-02-29", format_date(11_016));
+        assert_eq!("2024-12-31", format_date(20_088));
+        assert_eq!(20_088, days_from_civil(2024, 12, 31));
+    }
+
+    #[test]
+    fn iso_week_test() {
+        assert_eq!((1970, 1), iso_week(0));
+        // 2024-12-30 is a monday that already belongs to 2025
+        assert_eq!((2025, 1), iso_week(days_from_civil(2024, 12, 30)));
+        // 2021-01-03 is a sunday that still belongs to 2020
+        assert_eq!((2020, 53), iso_week(days_from_civil(2021, 1, 3)));
+        assert_eq!((2026, 42), iso_week(days_from_civil(2026, 10, 17)));
+    }
+}
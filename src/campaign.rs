@@ -0,0 +1,131 @@
+//! Campaign of predefined levels, each completed level unlocks the next one.
+
+use std::time::Duration;
+
+use crate::GameConfiguration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LevelVariant {
+    Classic,
+    /// The board has to be cleared within the given number of seconds.
+    TimeLimit(u64),
+}
+
+pub struct Level {
+    pub name: &'static str,
+    pub configuration: GameConfiguration,
+    /// Levels are the same board on every play through.
+    pub seed: u64,
+    pub variant: LevelVariant,
+}
+
+impl Level {
+    /// Whether a won game with the given time completes the level.
+    pub fn is_completed_by(&self, elapsed: Duration) -> bool {
+        match self.variant {
+            LevelVariant::Classic => true,
+            LevelVariant::TimeLimit(seconds) => elapsed <= Duration::from_secs(seconds),
+        }
+    }
+
+    pub fn description(&self) -> String {
+        let configuration = &self.configuration;
+        let variant = match self.variant {
+            LevelVariant::Classic => String::new(),
+            LevelVariant::TimeLimit(seconds) => format!(", clear within {} s", seconds),
+        };
+
+        format!(
+            "{} - {}x{} with {} mines{}",
+            self.name,
+            configuration.w(),
+            configuration.h(),
+            configuration.mines(),
+            variant
+        )
+    }
+}
+
+pub const LEVELS: [Level; 10] = [
+    Level {
+        name: "First steps",
+        configuration: GameConfiguration::new(5, 5, 3),
+        seed: 1,
+        variant: LevelVariant::Classic,
+    },
+    Level {
+        name: "Getting warmer",
+        configuration: GameConfiguration::new(6, 6, 5),
+        seed: 2,
+        variant: LevelVariant::Classic,
+    },
+    Level {
+        name: "Minefield",
+        configuration: GameConfiguration::new(8, 8, 10),
+        seed: 3,
+        variant: LevelVariant::Classic,
+    },
+    Level {
+        name: "Beginner",
+        configuration: GameConfiguration::new(9, 9, 10),
+        seed: 4,
+        variant: LevelVariant::Classic,
+    },
+    Level {
+        name: "In a hurry",
+        configuration: GameConfiguration::new(9, 9, 10),
+        seed: 5,
+        variant: LevelVariant::TimeLimit(120),
+    },
+    Level {
+        name: "Crowded",
+        configuration: GameConfiguration::new(12, 12, 25),
+        seed: 6,
+        variant: LevelVariant::Classic,
+    },
+    Level {
+        name: "Intermediate",
+        configuration: GameConfiguration::new(16, 16, 40),
+        seed: 7,
+        variant: LevelVariant::Classic,
+    },
+    Level {
+        name: "Against the clock",
+        configuration: GameConfiguration::new(16, 16, 40),
+        seed: 8,
+        variant: LevelVariant::TimeLimit(300),
+    },
+    Level {
+        name: "Dense",
+        configuration: GameConfiguration::new(20, 20, 70),
+        seed: 9,
+        variant: LevelVariant::Classic,
+    },
+    Level {
+        name: "Expert",
+        configuration: GameConfiguration::new(30, 16, 99),
+        seed: 10,
+        variant: LevelVariant::Classic,
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn levels_get_harder_test() {
+        for pair in LEVELS.windows(2) {
+            let (previous, next) = (&pair[0].configuration, &pair[1].configuration);
+            assert!(previous.mines() <= next.mines());
+            assert!(previous.w() * previous.h() <= next.w() * next.h());
+        }
+    }
+
+    #[test]
+    fn time_limit_test() {
+        let level = &LEVELS[4];
+        assert!(level.is_completed_by(Duration::from_secs(120)));
+        assert!(!level.is_completed_by(Duration::from_secs(121)));
+    }
+}
@@ -0,0 +1,366 @@
+//! Command line interface, without a subcommand the interactive menu is started.
+
+use std::path::PathBuf;
+
+use clap::{ArgAction, Parser, Subcommand, ValueEnum};
+
+use crate::completions::Shell;
+use minesweeper::{
+    bench, endless, storage, GameConfiguration, GameConfigurationError, MAX_CELLS, MAX_SIDE,
+};
+
+#[derive(Parser)]
+#[command(version, about = "Just a simple implementation of Minesweeper.")]
+pub struct Cli {
+    /// Profile to play as, skips the profile prompt.
+    #[arg(long, global = true, value_parser = parse_profile)]
+    pub profile: Option<String>,
+
+    /// Streams local games to spectators connecting on the address.
+    #[arg(long, global = true)]
+    pub spectators: Option<String>,
+
+    /// Also accepts commands and board queries of tools on the Unix socket at the path
+    /// while playing interactively.
+    #[cfg(unix)]
+    #[arg(long, global = true)]
+    pub control_socket: Option<PathBuf>,
+
+    /// Writes a trace of the game to the file, for attaching to bug reports.
+    #[arg(long, global = true)]
+    pub log_file: Option<PathBuf>,
+
+    /// Logs more detail, once for debug and twice for trace level. Without a log file
+    /// the log goes to the standard error.
+    #[arg(short, long, global = true, action = ArgAction::Count)]
+    pub verbose: u8,
+
+    /// Shows the mines on the board for testing, such games are not ranked.
+    #[arg(long, global = true)]
+    pub debug_show_mines: bool,
+
+    /// Plays in the full screen terminal UI instead of the line-based menu.
+    #[cfg(feature = "tui")]
+    #[arg(long)]
+    pub tui: bool,
+
+    /// How the game talks to the terminal, `json` reads one json command per line and
+    /// answers every line with a json response for programs driving the game.
+    #[arg(long, value_enum, default_value_t = Protocol::Text)]
+    pub protocol: Protocol,
+
+    /// Plays the board generated from the seed, e.g. one a friend shared. Games on a
+    /// chosen seed are not ranked.
+    #[arg(long, conflicts_with = "again")]
+    pub seed: Option<u64>,
+
+    /// Board for the seed, for example "16 40", defaults to the configured default board.
+    #[arg(long, value_parser = parse_board, requires = "seed")]
+    pub board: Option<GameConfiguration>,
+
+    /// Plays the board of a code the `share` command printed.
+    #[arg(long, conflicts_with_all = ["seed", "again"])]
+    pub code: Option<String>,
+
+    /// Plays the board code or layout at the link, e.g. a weekly challenge board. Needs the
+    /// `online` feature.
+    #[arg(long, conflicts_with_all = ["seed", "again", "code"])]
+    pub url: Option<String>,
+
+    /// Plays the board of the previous game again.
+    #[arg(long, visible_alias = "same-board")]
+    pub again: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Protocol {
+    Text,
+    Json,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Writes the profile's statistics and highscores as CSV files.
+    ExportStats {
+        /// Directory the CSV files are written to.
+        #[arg(long, default_value = ".")]
+        output_dir: PathBuf,
+    },
+    /// Hosts a cooperative game other players can join over TCP.
+    Host {
+        /// Address to listen on for joining players.
+        #[arg(long, default_value = "0.0.0.0:7878")]
+        address: String,
+
+        /// Board to play, for example "16 40", defaults to the configured default board.
+        #[arg(long, value_parser = parse_board)]
+        board: Option<GameConfiguration>,
+    },
+    /// Joins a cooperative game hosted at the address.
+    Join {
+        /// Address of the host, for example 192.168.1.10:7878.
+        address: String,
+    },
+    /// Watches a hosted game or the games streamed at the address, read-only.
+    Spectate {
+        /// Address of the host, for example 192.168.1.10:7878.
+        address: String,
+    },
+    /// Serves the engine over WebSocket for browser frontends.
+    #[cfg(feature = "websocket")]
+    Websocket {
+        /// Address to listen on for WebSocket connections.
+        #[arg(long, default_value = "127.0.0.1:9001")]
+        address: String,
+    },
+    /// Serves the engine over gRPC, see `proto/minesweeper.proto`.
+    #[cfg(feature = "grpc")]
+    Grpc {
+        /// Address to listen on for gRPC connections.
+        #[arg(long, default_value = "127.0.0.1:50051")]
+        address: String,
+    },
+    /// Plays a board shaped by a mask file, masked boards are not ranked.
+    Masked {
+        /// Mask file, one line per row with `#` for cells and `.` for holes.
+        mask: PathBuf,
+
+        /// Number of mines hidden in the cells of the mask.
+        mines: u32,
+    },
+    /// Plays on an unbounded board until a mine is hit, scoring the cells revealed.
+    Endless {
+        /// Seed of the board, random if not given.
+        #[arg(long)]
+        seed: Option<u64>,
+
+        /// Share of the cells holding a mine.
+        #[arg(long, default_value_t = endless::DEFAULT_DENSITY)]
+        density: f64,
+    },
+    /// Plays on a 3D board of stacked layers, one layer is shown at a time.
+    Cube {
+        #[arg(long, default_value_t = 5)]
+        width: u16,
+
+        #[arg(long, default_value_t = 5)]
+        height: u16,
+
+        /// Number of layers.
+        #[arg(long, default_value_t = 5)]
+        depth: u16,
+
+        #[arg(long, default_value_t = 15)]
+        mines: u32,
+
+        /// Seed of the board, random if not given.
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Plays several boards at once, `board 2` switches to the second one. A mine hit on
+    /// any board loses, clearing all of them wins.
+    Multiboard {
+        /// Number of boards.
+        #[arg(long, default_value_t = 3)]
+        count: usize,
+
+        /// Board to play, for example "16 40", defaults to the configured default board.
+        #[arg(long, value_parser = parse_board)]
+        board: Option<GameConfiguration>,
+
+        /// Seed of the first board, the next boards take the following seeds. Random if
+        /// not given.
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Applies one command to a game kept between runs and prints the board, for bots
+    /// playing turn by turn, e.g. `move "explore(3, 4)" --game mygame`.
+    Move {
+        /// Command as typed during a game.
+        command: String,
+
+        /// Save slot the game is kept in, a new game is started if it is empty.
+        #[arg(long)]
+        game: String,
+
+        /// Board of a new game, for example "16 40", defaults to the configured default
+        /// board.
+        #[arg(long, value_parser = parse_board)]
+        board: Option<GameConfiguration>,
+
+        /// Seed of a new game, random if not given.
+        #[arg(long)]
+        seed: Option<u64>,
+    },
+    /// Builds a board by hand in the editor, e.g. a puzzle, and saves it as a layout file.
+    Edit {
+        /// Layout file to edit, created on the first save if it does not exist.
+        layout: PathBuf,
+
+        /// Width of a new layout.
+        #[arg(long, default_value_t = 9)]
+        width: u32,
+
+        /// Height of a new layout.
+        #[arg(long, default_value_t = 9)]
+        height: u32,
+    },
+    /// Checks whether a layout can be solved from its revealed cells without guessing,
+    /// listing the cells the solver got stuck at otherwise.
+    ValidatePuzzle {
+        /// Layout file to check.
+        file: PathBuf,
+    },
+    /// Times world generation, flood fill and the solver on square boards of every size
+    /// and density and prints a table, best run from a release build.
+    Bench {
+        /// Sides of the boards, for example 16,64.
+        #[arg(long, value_delimiter = ',', default_values_t = bench::SIZES)]
+        sizes: Vec<u32>,
+
+        /// Shares of the cells holding a mine, for example 0.1,0.2.
+        #[arg(long, value_delimiter = ',', default_values_t = bench::DENSITIES)]
+        densities: Vec<f64>,
+
+        /// Runs of every timing, the median is shown.
+        #[arg(long, default_value_t = 3)]
+        runs: u32,
+
+        /// Seed of the generated boards.
+        #[arg(long, default_value_t = 1)]
+        seed: u64,
+    },
+    /// Re-simulates a saved replay, checking its checksum, that every move is legal and
+    /// that it ends as claimed.
+    Verify {
+        /// Replay file, as saved after a game.
+        replay: PathBuf,
+    },
+    /// Converts a game recorded by another clone into a replay, from a RAWVF file as the
+    /// community's tools make of AVF and RMV videos.
+    Import {
+        /// RAWVF file of the game.
+        video: PathBuf,
+
+        /// File the replay is written to, the video's path ending in .json if not given.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Shows how long every move of a saved replay took, to find where the game stalled.
+    Analyze {
+        /// Replay file, as saved after a game.
+        replay: PathBuf,
+    },
+    /// Renders a saved replay as an animated GIF, a frame per move.
+    #[cfg(feature = "gif")]
+    Gif {
+        /// Replay file, as saved after a game.
+        replay: PathBuf,
+
+        /// File the animation is written to, the replay's path ending in .gif if not given.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Races an opponent over the network on identical boards.
+    Race {
+        #[command(subcommand)]
+        role: RaceRole,
+    },
+    /// Moves profiles between computers, `--profile` selects the one exported.
+    Profile {
+        #[command(subcommand)]
+        action: ProfileAction,
+    },
+    /// Manages the highscore tables.
+    Highscores {
+        #[command(subcommand)]
+        action: HighscoresAction,
+    },
+    /// Prints the completion script of the shell, e.g. for bash
+    /// `minesweeper completions bash > ~/.local/share/bash-completion/completions/minesweeper`.
+    Completions { shell: Shell },
+}
+
+#[derive(Subcommand)]
+pub enum ProfileAction {
+    /// Writes the profile's settings, progress, highscores and game log into one file.
+    Export {
+        /// File the bundle is written to, <profile>.profile.json if not given.
+        #[arg(long)]
+        output: Option<PathBuf>,
+    },
+    /// Creates a profile from an exported bundle.
+    Import {
+        /// Bundle written by `profile export`.
+        file: PathBuf,
+
+        /// Name of the new profile, the exported profile's name if not given.
+        #[arg(long = "as", value_parser = parse_profile)]
+        name: Option<String>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum HighscoresAction {
+    /// Merges the entries of a highscore file, e.g. from another computer, into the
+    /// profile's tables. Entries of the same player and time count once.
+    Import {
+        /// highscores.json or, with --daily, daily_highscores.json of the other profile.
+        file: PathBuf,
+
+        /// Merges daily challenge tables instead of the difficulty tables.
+        #[arg(long)]
+        daily: bool,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum RaceRole {
+    /// Waits for an opponent to join the race.
+    Host {
+        /// Address to listen on for the opponent.
+        #[arg(long, default_value = "0.0.0.0:7879")]
+        address: String,
+
+        /// Board to race on, for example "16 40", defaults to the configured default board.
+        #[arg(long, value_parser = parse_board)]
+        board: Option<GameConfiguration>,
+    },
+    /// Joins the race hosted at the address.
+    Join {
+        /// Address of the host, for example 192.168.1.10:7879.
+        address: String,
+    },
+}
+
+fn parse_profile(name: &str) -> Result<String, String> {
+    if storage::is_valid_profile_name(name) {
+        Ok(name.to_string())
+    } else {
+        Err("profile names may only contain letters, digits, '-' and '_'".to_string())
+    }
+}
+
+/// Names the board arguments take besides the dimension and the mines.
+pub const DIFFICULTIES: [&str; 3] = ["beginner", "intermediate", "expert"];
+
+fn parse_board(value: &str) -> Result<GameConfiguration, String> {
+    if let Some(configuration) = GameConfiguration::named(value) {
+        return Ok(configuration);
+    }
+    GameConfiguration::try_from(value).map_err(|err| match err {
+        GameConfigurationError::OutOfLimits => format!(
+            "boards have at most {} rows and columns and {} cells",
+            MAX_SIDE, MAX_CELLS
+        ),
+        GameConfigurationError::TooManyMines => {
+            "there are more mines than cells on the board".to_string()
+        }
+        _ => "board should look like \"16 40\", the dimension and the mines, or name a \
+              difficulty like expert"
+            .to_string(),
+    })
+}
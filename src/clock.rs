@@ -0,0 +1,113 @@
+//! The game clock. It measures milliseconds and stands still while the game is paused,
+//! its times are shown in the [`TimeFormat`] chosen with `time_format` in the config.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeFormat {
+    /// Seconds with milliseconds, e.g. `62.345 s`.
+    #[default]
+    Seconds,
+    /// Minutes and seconds with milliseconds as `mm:ss.xxx`, e.g. `01:02.345`.
+    Clock,
+}
+
+impl TimeFormat {
+    pub fn format(&self, time: Duration) -> String {
+        let millis = time.as_millis();
+        match self {
+            TimeFormat::Seconds => format!("{}.{:03} s", millis / 1000, millis % 1000),
+            TimeFormat::Clock => format!(
+                "{:02}:{:02}.{:03}",
+                millis / 60_000,
+                millis / 1000 % 60,
+                millis % 1000
+            ),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct GameClock {
+    started: Instant,
+    /// Time spent in pauses that are over.
+    paused: Duration,
+    /// Start of the pause the game is in.
+    paused_since: Option<Instant>,
+}
+
+impl GameClock {
+    pub fn start() -> Self {
+        GameClock {
+            started: Instant::now(),
+            paused: Duration::ZERO,
+            paused_since: None,
+        }
+    }
+
+    /// Clock of a game resumed after the time played on it before.
+    pub fn resume_from(elapsed: Duration) -> Self {
+        let now = Instant::now();
+        GameClock {
+            started: now.checked_sub(elapsed).unwrap_or(now),
+            paused: Duration::ZERO,
+            paused_since: None,
+        }
+    }
+
+    /// Time played so far, without the pauses.
+    pub fn elapsed(&self) -> Duration {
+        let now = self.paused_since.unwrap_or_else(Instant::now);
+        now.duration_since(self.started).saturating_sub(self.paused)
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused_since.is_some()
+    }
+
+    pub fn pause(&mut self) {
+        self.paused_since.get_or_insert_with(Instant::now);
+    }
+
+    pub fn resume(&mut self) {
+        if let Some(paused_since) = self.paused_since.take() {
+            self.paused += paused_since.elapsed();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn time_format_test() {
+        let time = Duration::from_millis(62_345);
+        assert_eq!("62.345 s", TimeFormat::Seconds.format(time));
+        assert_eq!("01:02.345", TimeFormat::Clock.format(time));
+        assert_eq!(
+            "00:00.007",
+            TimeFormat::Clock.format(Duration::from_millis(7))
+        );
+    }
+
+    #[test]
+    fn pause_test() {
+        let mut clock = GameClock::start();
+        clock.pause();
+        assert!(clock.is_paused());
+        let paused = clock.elapsed();
+        std::thread::sleep(Duration::from_millis(20));
+        assert_eq!(paused, clock.elapsed());
+
+        clock.resume();
+        assert!(!clock.is_paused());
+        assert!(clock.elapsed() < paused + Duration::from_millis(20));
+
+        let resumed = GameClock::resume_from(Duration::from_secs(60));
+        assert!(resumed.elapsed() >= Duration::from_secs(60));
+    }
+}
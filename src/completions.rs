@@ -0,0 +1,55 @@
+//! Shell completion scripts generated by `clap_complete` from the clap definitions, printed
+//! by `completions <shell>`. Boards complete to the difficulty names and `--game` to the
+//! save slots the profile has when the script is printed.
+
+use clap::{builder::PossibleValuesParser, Command};
+pub use clap_complete::Shell;
+
+use crate::cli::DIFFICULTIES;
+
+pub fn script(shell: Shell, command: Command, slots: &[String]) -> String {
+    let mut command = with_hints(command, slots);
+    let name = command.get_name().to_string();
+    let mut script = vec![];
+    clap_complete::generate(shell, &mut command, name, &mut script);
+    String::from_utf8(script).expect("Completion scripts are utf-8.")
+}
+
+/// Lists the difficulties and the slots as the values of the boards and games. The command
+/// only generates the scripts, the values are not checked against the lists.
+fn with_hints(command: Command, slots: &[String]) -> Command {
+    command
+        .mut_args(|arg| match arg.get_id().as_str() {
+            "board" => arg.value_parser(PossibleValuesParser::new(DIFFICULTIES)),
+            "game" if !slots.is_empty() => {
+                arg.value_parser(PossibleValuesParser::new(slots.iter().cloned()))
+            }
+            _ => arg,
+        })
+        .mut_subcommands(|subcommand| with_hints(subcommand, slots))
+}
+
+#[cfg(test)]
+mod tests {
+    use clap::CommandFactory;
+
+    use super::*;
+    use crate::cli::Cli;
+
+    #[test]
+    fn script_test() {
+        let slots = vec!["first".to_string(), "second".to_string()];
+        let bash = script(Shell::Bash, Cli::command(), &slots);
+        assert!(bash.contains("minesweeper__subcmd__profile,import)"));
+        assert!(bash.contains("COMPREPLY=($(compgen -W \"beginner intermediate expert\""));
+        assert!(bash.contains("compgen -W \"first second\""));
+
+        let zsh = script(Shell::Zsh, Cli::command(), &[]);
+        assert!(zsh.starts_with("#compdef minesweeper\n"));
+        assert!(zsh.contains("beginner intermediate expert"));
+
+        let fish = script(Shell::Fish, Cli::command(), &slots);
+        assert!(fish.contains("complete -c minesweeper"));
+        assert!(fish.contains("-a \"first\\t''"));
+    }
+}
@@ -0,0 +1,292 @@
+//! Game settings, read from `config.json` in the data directory and overridden
+//! per profile by the profile's own `config.json`.
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ansi::Theme,
+    clock::TimeFormat,
+    generator::{Distribution, Symmetry},
+    locale::Language,
+    sound::Sounds,
+    storage::{self, Backend},
+    Adjacency, CoordinateConvention, Expansion, FlagGuard, GameBoard, GameConfiguration, SafeStart,
+};
+
+pub(crate) const CONFIG_FILE: &str = "config.json";
+
+/// Every setting is optional, an unset setting falls back to the shared config and
+/// then to the built in default.
+#[derive(Serialize, Deserialize, Default, Clone, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Config {
+    /// Board played when no configuration is entered for a new game.
+    pub default_board: Option<GameConfiguration>,
+    /// Whether boards entered for a new game wrap around their edges.
+    pub wrap_edges: Option<bool>,
+    /// Which cells count as neighbours on boards entered for a new game.
+    pub adjacency: Option<Adjacency>,
+    /// Most mines a cell may hold on boards entered for a new game, flags then record
+    /// the suspected count, e.g. `flag(3, 4, 2)`.
+    pub mines_per_cell: Option<u8>,
+    /// Anti-mines on boards entered for a new game as a percentage of their mines, 100
+    /// balances every mine with an anti-mine.
+    pub anti_mines_percent: Option<u32>,
+    /// How the mines are spread on boards entered for a new game.
+    pub distribution: Option<Distribution>,
+    /// Symmetry of the mines on boards entered for a new game, `mirror` or `rotational`
+    /// make for boards that are nice to share.
+    pub symmetry: Option<Symmetry>,
+    /// Whether exploring a flagged cell has to be confirmed or is rejected.
+    pub flag_guard: Option<FlagGuard>,
+    /// How cells are numbered in commands and on the rulers, e.g. `{"one_based": true,
+    /// "order": "xy"}` for `explore(x, y)` counting from 1.
+    pub coordinates: Option<CoordinateConvention>,
+    /// How the board is drawn, `plain` or `colour` in the palette of the terminal UI.
+    pub theme: Option<Theme>,
+    /// Whether the first mine hit of a game is flagged instead of lost, such games are
+    /// not ranked.
+    pub forgive_first_mistake: Option<bool>,
+    /// Whether a game is only won by typing `claim` once every mine and nothing else is
+    /// flagged, a wrong claim loses the game.
+    pub strict_flags: Option<bool>,
+    /// Cells kept free of mines around the first explore of a game: `off`, `cell`,
+    /// `3x3` or `5x5`. Such games are not ranked, daily challenges, tournaments and
+    /// campaign levels are played without.
+    pub safe_start: Option<SafeStart>,
+    /// How far an explore opens the board: `classic`, `conservative` for the explored
+    /// cell only or `aggressive` to also open around numbers whose flags are placed,
+    /// which marks the games as assisted like `auto_chord`.
+    pub expansion: Option<Expansion>,
+    /// Whether every explore is followed by chording the numbers whose flags are all
+    /// placed, such games are marked as assisted in the scores.
+    pub auto_chord: Option<bool>,
+    /// Enables the `cheat` command that shows the mines during a game, such games are
+    /// not ranked.
+    pub allow_cheats: Option<bool>,
+    /// Reads out every change of the board in a sentence, for playing with a screen
+    /// reader.
+    pub announce: Option<bool>,
+    /// Which events ring the terminal bell, e.g. `{"mine_hit": true, "win": true}`.
+    pub sounds: Option<Sounds>,
+    /// How game times are shown, `seconds` like `62.345 s` or `clock` like `01:02.345`.
+    pub time_format: Option<TimeFormat>,
+    /// Language of the texts, unset takes it from `LANG`.
+    pub language: Option<Language>,
+    /// Won games are submitted to the online leaderboard at this url, which also
+    /// provides the global top lists. Unset keeps everything offline.
+    pub leaderboard_url: Option<String>,
+    /// Where game data is kept, only read from the shared config since the profile
+    /// configs are kept in the storage itself.
+    pub storage: Option<Backend>,
+    /// The profile is pulled from this WebDAV or S3-compatible url when the game starts
+    /// and pushed back when it quits, only read from the shared config like `storage`.
+    /// The credentials come from the environment, see [`crate::sync`].
+    pub sync_url: Option<String>,
+}
+
+impl Config {
+    /// Sets the rules, assists and looks of the config on the board, unset ones to their
+    /// defaults.
+    pub fn apply_rules(&self, game_board: &mut GameBoard) {
+        self.apply_presentation(game_board);
+        game_board.set_forgiving(self.forgive_first_mistake.unwrap_or_default());
+        game_board.set_strict_flags(self.strict_flags.unwrap_or_default());
+        game_board.set_safe_start(self.safe_start.unwrap_or_default());
+        game_board.set_expansion(self.expansion.unwrap_or_default());
+        game_board.set_auto_chord(self.auto_chord.unwrap_or_default());
+    }
+
+    /// Sets only the looks and the input settings of the config on the board, restored
+    /// boards keep the rules they were started with.
+    pub fn apply_presentation(&self, game_board: &mut GameBoard) {
+        game_board.set_flag_guard(self.flag_guard.unwrap_or_default());
+        game_board.set_coordinate_convention(self.coordinates.unwrap_or_default());
+        game_board.set_theme(self.theme.unwrap_or_default());
+    }
+
+    pub fn load() -> Self {
+        let profile: Config = storage::load_json(CONFIG_FILE);
+        Config::load_shared().overridden_by(profile)
+    }
+
+    /// The profile's own config without the shared one, none before it was first saved.
+    pub fn load_profile() -> Option<Self> {
+        let content = storage::backend()
+            .load_document(storage::active_profile(), CONFIG_FILE)
+            .ok()??;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Writes the profile's own config, unset settings are left out of the file.
+    pub fn save(&self) -> io::Result<()> {
+        let mut value = serde_json::to_value(self)?;
+        if let serde_json::Value::Object(settings) = &mut value {
+            settings.retain(|_, setting| !setting.is_null());
+        }
+        storage::save_json(CONFIG_FILE, &value)
+    }
+
+    /// The config shared by all profiles, without profile overrides.
+    pub fn load_shared() -> Self {
+        storage::load_global_json(CONFIG_FILE)
+    }
+
+    /// Applies the board variants chosen in the config to the configuration.
+    pub fn apply_variants(&self, configuration: GameConfiguration) -> GameConfiguration {
+        let anti_mines = (configuration.mines() as u64
+            * self.anti_mines_percent.unwrap_or_default() as u64
+            / 100) as u32;
+        configuration
+            .with_wrap_edges(self.wrap_edges.unwrap_or_default())
+            .with_adjacency(self.adjacency.unwrap_or_default())
+            .with_mines_per_cell(self.mines_per_cell.unwrap_or(1))
+            .with_anti_mines(anti_mines)
+            .with_distribution(self.distribution.unwrap_or_default())
+            .with_symmetry(self.symmetry.unwrap_or_default())
+    }
+
+    fn overridden_by(self, overrides: Config) -> Config {
+        Config {
+            default_board: overrides.default_board.or(self.default_board),
+            wrap_edges: overrides.wrap_edges.or(self.wrap_edges),
+            adjacency: overrides.adjacency.or(self.adjacency),
+            mines_per_cell: overrides.mines_per_cell.or(self.mines_per_cell),
+            anti_mines_percent: overrides.anti_mines_percent.or(self.anti_mines_percent),
+            distribution: overrides.distribution.or(self.distribution),
+            symmetry: overrides.symmetry.or(self.symmetry),
+            flag_guard: overrides.flag_guard.or(self.flag_guard),
+            coordinates: overrides.coordinates.or(self.coordinates),
+            theme: overrides.theme.or(self.theme),
+            forgive_first_mistake: overrides
+                .forgive_first_mistake
+                .or(self.forgive_first_mistake),
+            strict_flags: overrides.strict_flags.or(self.strict_flags),
+            safe_start: overrides.safe_start.or(self.safe_start),
+            expansion: overrides.expansion.or(self.expansion),
+            auto_chord: overrides.auto_chord.or(self.auto_chord),
+            allow_cheats: overrides.allow_cheats.or(self.allow_cheats),
+            announce: overrides.announce.or(self.announce),
+            sounds: overrides.sounds.or(self.sounds),
+            time_format: overrides.time_format.or(self.time_format),
+            language: overrides.language.or(self.language),
+            leaderboard_url: overrides.leaderboard_url.or(self.leaderboard_url),
+            storage: self.storage,
+            sync_url: self.sync_url,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::AxisOrder;
+
+    #[test]
+    fn profile_overrides_shared_config_test() {
+        let shared = Config {
+            default_board: Some(GameConfiguration::expert()),
+            wrap_edges: Some(true),
+            adjacency: Some(Adjacency::Knight),
+            mines_per_cell: Some(2),
+            anti_mines_percent: Some(50),
+            distribution: Some(Distribution::Clustered),
+            symmetry: Some(Symmetry::Mirror),
+            flag_guard: Some(FlagGuard::Confirm),
+            coordinates: Some(CoordinateConvention {
+                one_based: true,
+                order: AxisOrder::Xy,
+                letters: false,
+            }),
+            theme: None,
+            forgive_first_mistake: Some(true),
+            strict_flags: None,
+            safe_start: Some(SafeStart::Square3),
+            expansion: None,
+            auto_chord: Some(true),
+            allow_cheats: None,
+            announce: Some(true),
+            sounds: Some(Sounds {
+                mine_hit: true,
+                win: false,
+                invalid_command: true,
+            }),
+            time_format: Some(TimeFormat::Clock),
+            language: Some(Language::German),
+            leaderboard_url: Some("https://example.com".to_string()),
+            storage: Some(Backend::Sqlite),
+            sync_url: Some("https://dav.example.com/minesweeper".to_string()),
+        };
+
+        assert_eq!(
+            shared.clone(),
+            shared.clone().overridden_by(Config::default())
+        );
+
+        let profile = Config {
+            default_board: Some(GameConfiguration::intermediate()),
+            wrap_edges: None,
+            adjacency: Some(Adjacency::Extended),
+            mines_per_cell: None,
+            anti_mines_percent: None,
+            distribution: Some(Distribution::EdgeWeighted),
+            symmetry: None,
+            flag_guard: Some(FlagGuard::Reject),
+            coordinates: None,
+            theme: Some(Theme::Colour),
+            forgive_first_mistake: None,
+            strict_flags: Some(true),
+            safe_start: None,
+            expansion: Some(Expansion::Aggressive),
+            auto_chord: None,
+            allow_cheats: Some(true),
+            announce: None,
+            sounds: None,
+            time_format: None,
+            language: None,
+            leaderboard_url: None,
+            storage: Some(Backend::Json),
+            sync_url: Some("https://elsewhere.example.com".to_string()),
+        };
+        let config = shared.overridden_by(profile);
+        assert_eq!(
+            Some(GameConfiguration::intermediate()),
+            config.default_board
+        );
+        assert_eq!(
+            Some("https://example.com".to_string()),
+            config.leaderboard_url
+        );
+        assert_eq!(Some(true), config.wrap_edges);
+        assert_eq!(Some(Adjacency::Extended), config.adjacency);
+        assert_eq!(Some(2), config.mines_per_cell);
+        assert_eq!(Some(Backend::Sqlite), config.storage);
+        assert_eq!(
+            Some("https://dav.example.com/minesweeper".to_string()),
+            config.sync_url
+        );
+        assert_eq!(Some(Distribution::EdgeWeighted), config.distribution);
+        assert_eq!(Some(Symmetry::Mirror), config.symmetry);
+        assert_eq!(Some(FlagGuard::Reject), config.flag_guard);
+        assert_eq!(AxisOrder::Xy, config.coordinates.unwrap().order);
+        assert_eq!(Some(Theme::Colour), config.theme);
+        assert_eq!(Some(true), config.forgive_first_mistake);
+        assert_eq!(Some(true), config.strict_flags);
+        assert_eq!(Some(SafeStart::Square3), config.safe_start);
+        assert_eq!(Some(Expansion::Aggressive), config.expansion);
+        assert_eq!(Some(true), config.auto_chord);
+        assert_eq!(Some(true), config.allow_cheats);
+        assert_eq!(Some(true), config.announce);
+        assert!(config.sounds.unwrap().mine_hit);
+        assert_eq!(Some(TimeFormat::Clock), config.time_format);
+        assert_eq!(Some(Language::German), config.language);
+        assert_eq!(
+            20,
+            config
+                .apply_variants(GameConfiguration::intermediate())
+                .anti_mines()
+        );
+    }
+}
@@ -0,0 +1,900 @@
+//! The interactive game on the console. [`game_loop`] reads commands from an
+//! [`InputSource`] and writes to an [`OutputSink`], the binary passes the terminal while
+//! tests script the lines and keep what was printed.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Write},
+    time::Duration,
+};
+
+use crate::{
+    ansi, braille,
+    clock::GameClock,
+    config::Config,
+    control::{Control, ControlRequest},
+    describe::{self, Reading},
+    difficulty::Difficulty,
+    estimate::{self, DEFAULT_SAMPLES},
+    html, interrupt,
+    locale::{self, Message},
+    macros::{Macros, Recording},
+    multiplayer::Spectators,
+    profile::Profile,
+    protocol::{Response, Status},
+    replay::Replay,
+    save,
+    share::SharedBoard,
+    sound::Cue,
+    splits::{PersonalBests, SplitTracker},
+    statistics::{self, GameRecord},
+    storage, svg, BoardCommand, BoardCommandError, Efficiency, GameBoard, GameResolve, GameResult,
+    SafeStart,
+};
+
+pub enum Input {
+    Line(String),
+    Control(Control),
+}
+
+/// Where the game reads the commands of the player from.
+pub trait InputSource {
+    /// Blocks until the next line or request of a tool.
+    fn next(&mut self) -> Input;
+}
+
+/// Where the game writes what the player sees.
+pub trait OutputSink {
+    fn line(&mut self, text: &str);
+
+    /// Clears what was written so far, the board is drawn anew after every command.
+    fn clear(&mut self);
+
+    /// Signals an event the player enabled a sound for.
+    fn cue(&mut self, _cue: Cue) {}
+}
+
+/// Prints to the standard output.
+pub struct Stdout;
+
+impl OutputSink for Stdout {
+    fn line(&mut self, text: &str) {
+        println!("{}", text);
+    }
+
+    fn clear(&mut self) {
+        print!("{esc}[2J{esc}[1;1H", esc = 27 as char)
+    }
+
+    fn cue(&mut self, cue: Cue) {
+        print!("\x07");
+        let _ = io::stdout().flush();
+        #[cfg(feature = "audio")]
+        crate::sound::play(cue);
+        #[cfg(not(feature = "audio"))]
+        let _ = cue;
+    }
+}
+
+/// Lines fed to the game one after the other.
+pub struct Script(VecDeque<String>);
+
+impl Script {
+    pub fn new(lines: &[&str]) -> Self {
+        Script(lines.iter().map(|line| format!("{}\n", line)).collect())
+    }
+}
+
+impl InputSource for Script {
+    fn next(&mut self) -> Input {
+        Input::Line(
+            self.0
+                .pop_front()
+                .expect("The script has a line for every command the game reads."),
+        )
+    }
+}
+
+/// Keeps every line written and cue signalled, clearing is ignored.
+#[derive(Default)]
+pub struct Transcript {
+    pub lines: Vec<String>,
+    pub cues: Vec<Cue>,
+}
+
+impl Transcript {
+    pub fn contains(&self, text: &str) -> bool {
+        self.lines.iter().any(|line| line.contains(text))
+    }
+}
+
+impl OutputSink for Transcript {
+    fn line(&mut self, text: &str) {
+        self.lines.push(text.to_string());
+    }
+
+    fn clear(&mut self) {}
+
+    fn cue(&mut self, cue: Cue) {
+        self.cues.push(cue);
+    }
+}
+
+/// Parses a line of commands separated by `;`, all of them or none. `.` or `again`
+/// repeats the command before, `again(x, y)` its verb on another cell. Relative cells
+/// follow the commands before them in the line.
+fn parse_batch(
+    game_board: &GameBoard,
+    line: &str,
+    mut last: Option<BoardCommand>,
+) -> Result<Vec<BoardCommand>, BoardCommandError> {
+    let convention = game_board.coordinate_convention();
+    let mut last_cell = game_board.last_cell();
+    let mut commands = vec![];
+    for command in line.split(';').map(str::trim) {
+        if command.is_empty() {
+            continue;
+        }
+        let command = if command == "." || command.eq_ignore_ascii_case("again") {
+            last.ok_or(BoardCommandError::NotFound)?
+        } else if let Some(cell) = command
+            .get(..5)
+            .filter(|verb| verb.eq_ignore_ascii_case("again"))
+            .map(|_| &command[5..])
+        {
+            let verb = last.ok_or(BoardCommandError::NotFound)?.verb();
+            convention.parse_after(&format!("{}{}", verb, cell), last_cell)?
+        } else {
+            convention.parse_after(command, last_cell)?
+        };
+        last = Some(command);
+        last_cell = command.cell().or(last_cell);
+        commands.push(command);
+    }
+    Ok(commands)
+}
+
+/// The single word following the verb, e.g. the name in `record corner`.
+fn argument<'a>(line: &'a str, verb: &str) -> Option<&'a str> {
+    let (word, argument) = line.trim().split_once(char::is_whitespace)?;
+    let argument = argument.trim();
+    (word.eq_ignore_ascii_case(verb) && !argument.contains(char::is_whitespace)).then_some(argument)
+}
+
+/// Saves the board as shown into the profile's directory, answers where to.
+#[cfg(feature = "png")]
+fn export_png(game_board: &GameBoard) -> String {
+    let file_name = format!(
+        "board-{}-{}.png",
+        game_board.configuration().key(),
+        game_board.explored_cells()
+    );
+    match storage::export_file(&file_name, &crate::png::render(game_board)) {
+        Ok(path) => locale::text(Message::ImageSaved(&path.display().to_string())),
+        Err(err) => locale::text(Message::CouldNotSaveImage(&err.to_string())),
+    }
+}
+
+#[cfg(not(feature = "png"))]
+fn export_png(_game_board: &GameBoard) -> String {
+    locale::text(Message::PngUnsupported)
+}
+
+/// Saves the board as shown, or solved, as a vector image into the profile's directory.
+fn export_svg(game_board: &GameBoard, solution: bool) -> String {
+    let file_name = format!(
+        "{}-{}-{}.svg",
+        if solution { "solution" } else { "board" },
+        game_board.configuration().key(),
+        game_board.explored_cells()
+    );
+    match storage::export_file(&file_name, svg::render(game_board, solution).as_bytes()) {
+        Ok(path) => locale::text(Message::ImageSaved(&path.display().to_string())),
+        Err(err) => locale::text(Message::CouldNotSaveImage(&err.to_string())),
+    }
+}
+
+/// Cells shown by the `estimate` command.
+const SAFEST_CELLS: usize = 5;
+
+/// The cells most likely to be safe with their chance, estimated from sampled layouts.
+fn safest_cells(game_board: &GameBoard) -> String {
+    match estimate::estimate(game_board, DEFAULT_SAMPLES, &mut rand::thread_rng()) {
+        Ok(estimates) => {
+            let cells: Vec<String> = estimates
+                .iter()
+                .take(SAFEST_CELLS)
+                .map(|estimate| {
+                    format!(
+                        "{} {:.0}%",
+                        game_board
+                            .coordinate_convention()
+                            .cell_name(estimate.coordinate),
+                        estimate.survival * 100.0
+                    )
+                })
+                .collect();
+            locale::text(Message::SafestCells(&cells.join(", ")))
+        }
+        Err(_) => locale::text(Message::NoEstimate),
+    }
+}
+
+#[cfg(feature = "scripting")]
+use crate::scripting::{Event, Scripts};
+
+/// Stands in for the scripts of the player when the scripting feature is off.
+#[cfg(not(feature = "scripting"))]
+mod scripting {
+    use crate::{BoardCommand, GameBoard, GameResolve};
+
+    // the events are dropped without scripts
+    #[allow(dead_code)]
+    pub enum Event {
+        Start,
+        Command(BoardCommand),
+        End(GameResolve),
+    }
+
+    pub struct Scripts;
+
+    impl Scripts {
+        pub fn command(
+            &self,
+            _name: &str,
+            _game_board: &GameBoard,
+        ) -> Option<Result<Vec<BoardCommand>, String>> {
+            None
+        }
+
+        pub fn react(
+            &self,
+            _event: Event,
+            _game_board: &GameBoard,
+        ) -> Result<Vec<BoardCommand>, String> {
+            Ok(vec![])
+        }
+    }
+}
+#[cfg(not(feature = "scripting"))]
+use scripting::{Event, Scripts};
+
+/// Loads the scripts of the player, telling about those that failed.
+fn load_scripts(output: &mut dyn OutputSink) -> Scripts {
+    #[cfg(feature = "scripting")]
+    {
+        let (scripts, errors) = Scripts::load();
+        for err in errors {
+            output.line(&locale::text(Message::ScriptFailed(&err.to_string())));
+        }
+        scripts
+    }
+    #[cfg(not(feature = "scripting"))]
+    {
+        let _ = output;
+        Scripts
+    }
+}
+
+/// Commands the scripts answer the event with, none if a script failed.
+fn react(
+    scripts: &Scripts,
+    event: Event,
+    game_board: &GameBoard,
+    output: &mut dyn OutputSink,
+) -> Vec<BoardCommand> {
+    scripts.react(event, game_board).unwrap_or_else(|err| {
+        output.line(&locale::text(Message::ScriptFailed(&err.to_string())));
+        vec![]
+    })
+}
+
+/// Plays the board until it is won, lost or quit, shows the summary and lets the player
+/// save the replay, also as a web page, or retry the board. Retries are practice, the
+/// result is that of the first game.
+pub fn game_loop(
+    game_board: GameBoard,
+    input: &mut dyn InputSource,
+    output: &mut dyn OutputSink,
+    spectators: Option<&Spectators>,
+) -> GameResult {
+    after_game(
+        game_board,
+        Duration::ZERO,
+        Start::New,
+        input,
+        output,
+        spectators,
+    )
+}
+
+/// Same as [`game_loop`] for a board everyone plays from the same seed, e.g. the daily
+/// challenge. Safe start stays off, moving the mines away from the first explore would
+/// make the board a different one for every player.
+pub fn shared_game_loop(
+    game_board: GameBoard,
+    input: &mut dyn InputSource,
+    output: &mut dyn OutputSink,
+    spectators: Option<&Spectators>,
+) -> GameResult {
+    after_game(
+        game_board,
+        Duration::ZERO,
+        Start::Shared,
+        input,
+        output,
+        spectators,
+    )
+}
+
+/// Same as [`game_loop`] for a game resumed from a save after the time played on it
+/// before. Resumed games set no personal bests, their replays start at the save.
+pub fn resume_loop(
+    game_board: GameBoard,
+    elapsed: Duration,
+    input: &mut dyn InputSource,
+    output: &mut dyn OutputSink,
+    spectators: Option<&Spectators>,
+) -> GameResult {
+    let start = if elapsed > Duration::ZERO {
+        Start::Restored
+    } else {
+        Start::New
+    };
+    after_game(game_board, elapsed, start, input, output, spectators)
+}
+
+/// How the rules of the config apply to a board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Start {
+    New,
+    /// Played from a seed everyone shares, without safe start.
+    Shared,
+    /// Resumed or retried, the board keeps the rules of its save or replay. Such games
+    /// are practice.
+    Restored,
+}
+
+/// Plays the board, then offers to save the replay or retry until the player moves on.
+fn after_game(
+    game_board: GameBoard,
+    elapsed: Duration,
+    start: Start,
+    input: &mut dyn InputSource,
+    output: &mut dyn OutputSink,
+    spectators: Option<&Spectators>,
+) -> GameResult {
+    let shows_mines = game_board.shows_mines();
+    let result = play(game_board, elapsed, input, output, spectators, start);
+    let mut replay = result.replay.clone();
+    loop {
+        output.line(&locale::text(Message::AfterGame));
+        let line = match input.next() {
+            Input::Line(line) => line,
+            Input::Control(control) => {
+                control.reply(Response::error("the game is over"));
+                continue;
+            }
+        };
+        match line.trim() {
+            "s" => {
+                let file_name = format!("replay-{}.json", &replay.hash()[..12]);
+                match storage::export_json(&file_name, &replay) {
+                    Ok(path) => {
+                        output.line(&locale::text(Message::ReplaySaved(
+                            &path.display().to_string(),
+                        )));
+                    }
+                    Err(err) => {
+                        output.line(&locale::text(Message::CouldNotSaveReplay(&err.to_string())))
+                    }
+                }
+            }
+            "h" => {
+                let file_name = format!("replay-{}.html", &replay.hash()[..12]);
+                let saved = html::render(&replay)
+                    .map_err(|err| io::Error::other(err.to_string()))
+                    .and_then(|page| storage::export_file(&file_name, page.as_bytes()));
+                match saved {
+                    Ok(path) => output.line(&locale::text(Message::ReplayPageSaved(
+                        &path.display().to_string(),
+                    ))),
+                    Err(err) => {
+                        output.line(&locale::text(Message::CouldNotSaveReplay(&err.to_string())))
+                    }
+                }
+            }
+            "r" => match replay.board() {
+                Ok(mut game_board) => {
+                    game_board.set_show_mines(shows_mines);
+                    replay = play(
+                        game_board,
+                        Duration::ZERO,
+                        input,
+                        output,
+                        spectators,
+                        Start::Restored,
+                    )
+                    .replay;
+                }
+                Err(err) => output.line(&err.to_string()),
+            },
+            _ => return result,
+        }
+    }
+}
+
+/// Plays the board until it is won, lost or quit, then records the statistics, personal
+/// bests and achievements of the game. Practice games set no personal bests. The board
+/// is autosaved after every move until the game is over.
+fn play(
+    mut game_board: GameBoard,
+    elapsed: Duration,
+    input: &mut dyn InputSource,
+    output: &mut dyn OutputSink,
+    spectators: Option<&Spectators>,
+    start: Start,
+) -> GameResult {
+    // game loop
+    // - draw board state
+    // - wait for input
+    // - execute command
+    // - show command result and start a wait thread that is polled
+    // - continue after 3 secs
+
+    let mut difficulty = game_board.configuration().key();
+    let _span =
+        tracing::info_span!("game", board = %difficulty, seed = game_board.seed()).entered();
+    let mut personal_bests = PersonalBests::load();
+    let mut split_tracker = SplitTracker::new();
+    let config = Config::load();
+    let mut practice = start == Start::Restored;
+    match start {
+        Start::New => config.apply_rules(&mut game_board),
+        Start::Shared => {
+            config.apply_rules(&mut game_board);
+            game_board.set_safe_start(SafeStart::Off);
+        }
+        // resumed and retried boards carry the rules of their save or replay
+        Start::Restored => config.apply_presentation(&mut game_board),
+    }
+    let mut replay = Replay::of(&game_board);
+    let mut flags_placed = 0;
+    let mut clicks = 0;
+    let mut wasted_clicks = 0;
+    let mut hints_used = 0;
+    // typed command the `again` shortcut repeats
+    let mut last_command = None;
+    let mut recording: Option<Recording> = None;
+    let scripts = load_scripts(output);
+    // whether scripts applied commands, which leaves the game unranked
+    let mut scripted = false;
+    if let Some(spectators) = spectators {
+        spectators.start(game_board.configuration(), game_board.seed());
+    }
+
+    if game_board.layout().is_none() {
+        output.line(&locale::text(Message::BoardSeed(game_board.seed())));
+        if game_board.explored_cells() == 0 {
+            output.line(&locale::text(Message::DifficultyEstimate(Difficulty::of(
+                &game_board,
+            ))));
+        }
+    }
+
+    let sounds = config.sounds.unwrap_or_default();
+    let cue = |output: &mut dyn OutputSink, cue: Cue| {
+        if sounds.plays(cue) {
+            output.cue(cue);
+        }
+    };
+    let mut braille_view = false;
+    let time_format = config.time_format.unwrap_or_default();
+    let mut pending = react(&scripts, Event::Start, &game_board, output);
+
+    let mut clock = GameClock::resume_from(elapsed);
+    interrupt::track(Some(clock));
+
+    let final_resolve = loop {
+        if braille_view {
+            output.line(&braille::render(&game_board));
+            output.line(braille::LEGEND);
+        } else {
+            output.line(&game_board.to_string());
+        }
+        let best = personal_bests
+            .get(&difficulty)
+            .map(|best| time_format.format(best.finish()));
+        output.line(&locale::text(Message::GameClock(
+            &time_format.format(clock.elapsed()),
+            best.as_deref(),
+        )));
+        let mut cheat = false;
+        let mut outside = false;
+        // commands of scripts are applied before the player types, e.g. on start
+        let mut by_script = !pending.is_empty();
+        let (commands, mut control) = if by_script {
+            (std::mem::take(&mut pending), None)
+        } else {
+            loop {
+                match input.next() {
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("cheat") => {
+                        cheat = true;
+                        break (vec![], None);
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("braille") => {
+                        braille_view = !braille_view;
+                        break (vec![], None);
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("pause") => {
+                        clock.pause();
+                        interrupt::track(Some(clock));
+                        output.clear();
+                        output.line(&locale::text(Message::Paused));
+                        // the board stays hidden until the player goes on
+                        loop {
+                            match input.next() {
+                                Input::Line(_) => break,
+                                Input::Control(control) => {
+                                    control.reply(Response::error("the game is paused"))
+                                }
+                            }
+                        }
+                        clock.resume();
+                        interrupt::track(Some(clock));
+                        break (vec![], None);
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("estimate") => {
+                        hints_used += 1;
+                        output.line(&safest_cells(&game_board));
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("stop") => {
+                        let Some(recording) = recording.take() else {
+                            output.line(&locale::text(Message::NotRecording));
+                            continue;
+                        };
+                        let name = recording.name.clone();
+                        match recording.save() {
+                            Ok(commands) => {
+                                output.line(&locale::text(Message::MacroSaved(&name, commands)))
+                            }
+                            Err(err) => output
+                                .line(&locale::text(Message::CouldNotSaveMacro(&err.to_string()))),
+                        }
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("export-png") => {
+                        output.line(&export_png(&game_board));
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("share") => {
+                        output.line(&match SharedBoard::of(&game_board) {
+                            Some(shared) => locale::text(Message::ShareCode(&shared.code())),
+                            None => locale::text(Message::CannotShare),
+                        });
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("export-ansi") => {
+                        match ansi::export(&game_board, Status::Playing) {
+                            Ok(path) => output.line(&locale::text(Message::SnapshotSaved(
+                                &path.display().to_string(),
+                            ))),
+                            Err(err) => output.line(&locale::text(Message::CouldNotSaveSnapshot(
+                                &err.to_string(),
+                            ))),
+                        }
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("export-svg") => {
+                        output.line(&export_svg(&game_board, false));
+                    }
+                    Input::Line(line) if argument(&line, "export-svg") == Some("solution") => {
+                        // the solution shows every mine, it is a cheat like any other
+                        if config.allow_cheats.unwrap_or_default() {
+                            game_board.mark_cheated();
+                            output.line(&export_svg(&game_board, true));
+                        } else {
+                            output.line(&locale::text(Message::CheatsDisabled));
+                        }
+                    }
+                    Input::Line(line) if line.trim().eq_ignore_ascii_case("saves") => {
+                        let slots: Vec<String> =
+                            save::slots().iter().map(ToString::to_string).collect();
+                        let slots = slots.join("\n");
+                        output.line(&locale::text(if slots.is_empty() {
+                            Message::NoSlots
+                        } else {
+                            Message::Slots(&slots)
+                        }));
+                    }
+                    Input::Line(line) if argument(&line, "save").is_some() => {
+                        let slot = argument(&line, "save").unwrap_or_default();
+                        match save::save_slot(&game_board, slot, clock.elapsed()) {
+                            Ok(()) => output.line(&locale::text(Message::SlotSaved(slot))),
+                            Err(err) => output
+                                .line(&locale::text(Message::SlotFailed(slot, &err.to_string()))),
+                        }
+                    }
+                    Input::Line(line) if argument(&line, "delete").is_some() => {
+                        let slot = argument(&line, "delete").unwrap_or_default();
+                        match save::delete_slot(slot) {
+                            Ok(()) => output.line(&locale::text(Message::SlotDeleted(slot))),
+                            Err(err) => output
+                                .line(&locale::text(Message::SlotFailed(slot, &err.to_string()))),
+                        }
+                    }
+                    Input::Line(line) if argument(&line, "load").is_some() => {
+                        let slot = argument(&line, "load").unwrap_or_default();
+                        let (loaded, elapsed) = match save::open_slot(slot) {
+                            Ok(loaded) => loaded,
+                            Err(err) => {
+                                output.line(&locale::text(Message::SlotFailed(
+                                    slot,
+                                    &err.to_string(),
+                                )));
+                                continue;
+                            }
+                        };
+                        // the game goes on from the slot, its replay starts there
+                        game_board = loaded;
+                        config.apply_presentation(&mut game_board);
+                        difficulty = game_board.configuration().key();
+                        replay = Replay::of(&game_board);
+                        split_tracker = SplitTracker::new();
+                        clock = GameClock::resume_from(elapsed);
+                        interrupt::track(Some(clock));
+                        practice = true;
+                        output.clear();
+                        output.line(&locale::text(Message::SlotLoaded(slot)));
+                        break (vec![], None);
+                    }
+                    Input::Line(line) if argument(&line, "record").is_some() => {
+                        let name = argument(&line, "record").unwrap_or_default();
+                        recording = Some(Recording::new(name));
+                        output.line(&locale::text(Message::RecordingMacro(name)));
+                    }
+                    Input::Line(line) => {
+                        // a macro is played as a batch of its commands
+                        let line = match argument(&line, "play") {
+                            Some(name) => match Macros::load().get(name) {
+                                Some(commands) => commands.join("; "),
+                                None => {
+                                    output.line(&locale::text(Message::NoMacro(name)));
+                                    continue;
+                                }
+                            },
+                            None => line,
+                        };
+                        if let Some(result) = scripts.command(&line, &game_board) {
+                            match result {
+                                Ok(commands) => {
+                                    by_script = true;
+                                    break (commands, None);
+                                }
+                                Err(err) => output
+                                    .line(&locale::text(Message::ScriptFailed(&err.to_string()))),
+                            }
+                            continue;
+                        }
+                        match Reading::try_from(&line[..]) {
+                            Ok(reading) => output.line(&describe::read(&game_board, reading)),
+                            Err(_) => match parse_batch(&game_board, &line, last_command) {
+                                Ok(commands) => {
+                                    if let Some(recording) = recording.as_mut() {
+                                        if commands.iter().all(|cmd| game_board.is_on_board(*cmd)) {
+                                            recording.record(&line);
+                                        }
+                                    }
+                                    break (commands, None);
+                                }
+                                Err(BoardCommandError::OutsideBoard) => {
+                                    outside = true;
+                                    break (vec![], None);
+                                }
+                                Err(_) => {
+                                    cue(output, Cue::InvalidCommand);
+                                    break (vec![], None);
+                                }
+                            },
+                        }
+                    }
+                    Input::Control(control) => match control.request {
+                        ControlRequest::Board => {
+                            control.reply(Response::board(&game_board, Status::Playing))
+                        }
+                        ControlRequest::Command(cmd) => break (vec![cmd], Some(control)),
+                    },
+                }
+            }
+        };
+        output.clear();
+
+        if outside {
+            cue(output, Cue::InvalidCommand);
+            output.line(&locale::text(Message::OutsideBoard));
+        }
+
+        if cheat {
+            if config.allow_cheats.unwrap_or_default() {
+                game_board.set_show_mines(!game_board.shows_mines());
+                output.line(&locale::text(Message::Cheating));
+            } else {
+                output.line(&locale::text(Message::CheatsDisabled));
+            }
+        }
+
+        // a batch with a cell off the board is rejected as a whole
+        if !commands.iter().all(|cmd| game_board.is_on_board(*cmd)) {
+            cue(output, Cue::InvalidCommand);
+            output.line(&locale::text(Message::OutsideBoard));
+            if let Some(control) = control {
+                control.reply(Response::error("coordinate is outside of the board"));
+            }
+            continue;
+        }
+
+        let mut game_over = None;
+        let moved = !commands.is_empty();
+        let typed = control.is_none();
+        // commands of scripts reacting to a command go right after it
+        let mut queue: VecDeque<(BoardCommand, bool)> =
+            commands.into_iter().map(|cmd| (cmd, by_script)).collect();
+        while let Some((cmd, by_script)) = queue.pop_front() {
+            tracing::debug!(command = %cmd, from_control = control.is_some(), "command received");
+            if !game_board.passes_flag_guard(cmd) {
+                let hint = game_board.flag_guard().hint();
+                output.line(&locale::text(Message::FlagGuard(game_board.flag_guard())));
+                if let Some(control) = control.take() {
+                    control.reply(Response::error(hint));
+                }
+                break;
+            }
+
+            if typed && !by_script {
+                last_command = Some(cmd);
+            }
+            scripted |= by_script;
+            let elapsed = clock.elapsed();
+            replay.record(cmd, elapsed);
+            flags_placed += game_board.flags_placed_by(cmd);
+            if !matches!(
+                cmd,
+                BoardCommand::Pass | BoardCommand::Quit | BoardCommand::Claim
+            ) {
+                clicks += 1;
+                wasted_clicks += game_board.is_wasted(cmd) as u32;
+            }
+            if let Some(spectators) = spectators {
+                spectators.broadcast_applied(storage::active_profile(), cmd);
+            }
+            let mistakes_forgiven = game_board.mistakes_forgiven();
+            let explored_cells = game_board.explored_cells();
+            let resolve = game_board.manipulate_cell(cmd);
+            if config.announce.unwrap_or_default() {
+                let opened = game_board.explored_cells() - explored_cells;
+                if let Some(announcement) = describe::announce(&game_board, cmd, opened) {
+                    output.line(&announcement);
+                }
+            }
+            if game_board.mistakes_forgiven() > mistakes_forgiven {
+                output.line(&locale::text(Message::MineForgiven));
+            }
+            if let Some(control) = control.take() {
+                control.reply(Response::board(&game_board, resolve.into()));
+            }
+
+            let (solved, total) = game_board.three_bv();
+            for split_index in split_tracker.update(solved, total, elapsed) {
+                output
+                    .line(&split_tracker.split_line(split_index, personal_bests.get(&difficulty)));
+            }
+
+            match resolve {
+                GameResolve::Continue => {
+                    if typed && !by_script {
+                        let reactions = react(&scripts, Event::Command(cmd), &game_board, output);
+                        for reaction in reactions.into_iter().rev() {
+                            queue.push_front((reaction, true));
+                        }
+                    }
+                    continue;
+                }
+                GameResolve::Quit => {}
+                GameResolve::MineHit => {
+                    cue(output, Cue::MineHit);
+                    output.line(&locale::text(match cmd {
+                        BoardCommand::Claim => Message::ClaimFailed,
+                        _ => Message::HitMine,
+                    }));
+                }
+                GameResolve::AllMinesDiscovered => {
+                    cue(output, Cue::Win);
+                    output.line(&locale::text(Message::YouWon));
+                }
+            }
+            // the rest of the batch is dropped once the game is over
+            game_over = Some(resolve);
+            break;
+        }
+        if let Some(resolve) = game_over {
+            break resolve;
+        }
+        if moved {
+            if let Err(err) = save::save_slot(&game_board, save::AUTOSAVE_SLOT, clock.elapsed()) {
+                tracing::warn!(%err, "could not autosave");
+            }
+        }
+    };
+    interrupt::track(None);
+    if let Err(err) = save::delete_slot(save::AUTOSAVE_SLOT) {
+        tracing::debug!(%err, "no autosave to delete");
+    }
+
+    let elapsed = clock.elapsed();
+    tracing::info!(resolve = ?final_resolve, ?elapsed, "game over");
+    // the game is over, commands answering its end are dropped
+    react(&scripts, Event::End(final_resolve), &game_board, output);
+    split_tracker.finish(elapsed);
+    let (solved_three_bv, board_three_bv) = game_board.three_bv();
+
+    output.line(&split_tracker.summary(personal_bests.get(&difficulty)));
+    replay.seal(final_resolve);
+    // masked and hand made boards share their key with generated ones, their times are
+    // not comparable
+    let result = GameResult {
+        resolve: final_resolve,
+        elapsed,
+        replay,
+        flags_placed,
+        cells_revealed: game_board.explored_cells(),
+        efficiency: Efficiency {
+            three_bv: solved_three_bv,
+            clicks,
+            wasted_clicks,
+        },
+        board_three_bv,
+        hints_used,
+        forgiven: game_board.mistakes_forgiven() > 0,
+        cheated: game_board.is_cheated(),
+        scripted,
+        assisted: game_board.is_assisted(),
+        practice,
+    };
+    if result.forgiven {
+        output.line(&locale::text(Message::ForgivenNotRanked));
+    }
+    output.line(&locale::text(Message::Summary(&result, time_format)));
+    if final_resolve == GameResolve::AllMinesDiscovered
+        && result.is_ranked()
+        && game_board.mask().is_none()
+        && game_board.layout().is_none()
+        && personal_bests.submit(&difficulty, &split_tracker)
+    {
+        output.line(&locale::text(Message::NewPersonalBest));
+        if let Err(err) = personal_bests.save() {
+            output.line(&locale::text(Message::CouldNotSavePersonalBest(
+                &err.to_string(),
+            )));
+        }
+    }
+
+    // debugging sessions stay out of the statistics
+    if !result.cheated {
+        if let Err(err) = statistics::record(GameRecord::new(&result)) {
+            output.line(&locale::text(Message::CouldNotSaveStatistics(
+                &err.to_string(),
+            )));
+        }
+    }
+
+    let mut profile = Profile::load();
+    let unlocked = profile.achievements.record_game(&result);
+    for achievement in &unlocked {
+        output.line(&locale::text(Message::AchievementUnlocked(
+            achievement.title(),
+        )));
+    }
+    if let Err(err) = profile.save() {
+        output.line(&locale::text(Message::CouldNotSaveAchievements(
+            &err.to_string(),
+        )));
+    }
+
+    result
+}
@@ -0,0 +1,145 @@
+//! Control interface of an interactive game on a Unix socket, so tools like a stream
+//! overlay or an external solver can inject moves or query the board while the game is
+//! played in the terminal.
+//!
+//! Every line in is a command in the terminal notation, e.g. `explore(3, 4)`, or `board`
+//! to query the board. Every line is answered with a json [`Response`].
+
+use std::sync::mpsc::Sender;
+#[cfg(unix)]
+use std::{
+    fs,
+    io::{self, BufRead, BufReader, Write},
+    os::unix::net::{UnixListener, UnixStream},
+    path::Path,
+    sync::mpsc::{self, Receiver},
+    thread,
+};
+
+use crate::{protocol::Response, BoardCommand};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ControlRequest {
+    Board,
+    Command(BoardCommand),
+}
+
+impl TryFrom<&str> for ControlRequest {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "board" => Ok(ControlRequest::Board),
+            command => BoardCommand::try_from(command)
+                .map(ControlRequest::Command)
+                .map_err(|_| ()),
+        }
+    }
+}
+
+/// Request of a connected tool, waiting for its response.
+pub struct Control {
+    pub request: ControlRequest,
+    reply: Sender<Response>,
+}
+
+impl Control {
+    pub fn reply(self, response: Response) {
+        // The tool may have disconnected in the meantime.
+        let _ = self.reply.send(response);
+    }
+}
+
+/// Listens on the socket path, replacing a stale socket, and hands out the requests.
+#[cfg(unix)]
+pub fn listen(path: &Path) -> io::Result<Receiver<Control>> {
+    match fs::remove_file(path) {
+        Err(err) if err.kind() != io::ErrorKind::NotFound => return Err(err),
+        _ => {}
+    }
+    let listener = UnixListener::bind(path)?;
+    let (sender, requests) = mpsc::channel();
+
+    thread::spawn(move || {
+        for stream in listener.incoming().flatten() {
+            let sender = sender.clone();
+            thread::spawn(move || serve_tool(stream, sender));
+        }
+    });
+
+    Ok(requests)
+}
+
+#[cfg(unix)]
+fn serve_tool(stream: UnixStream, sender: Sender<Control>) {
+    let Ok(mut writer) = stream.try_clone() else {
+        return;
+    };
+
+    for line in BufReader::new(stream).lines() {
+        let Ok(line) = line else {
+            return;
+        };
+
+        let response = match ControlRequest::try_from(&line[..]) {
+            Ok(request) => {
+                let (reply, response) = mpsc::channel();
+                if sender.send(Control { request, reply }).is_err() {
+                    return;
+                }
+                match response.recv() {
+                    Ok(response) => response,
+                    Err(_) => return,
+                }
+            }
+            Err(()) => Response::error("malformed command"),
+        };
+
+        let Ok(mut line) = serde_json::to_string(&response) else {
+            return;
+        };
+        line.push('\n');
+        if writer.write_all(line.as_bytes()).is_err() {
+            return;
+        }
+    }
+}
+
+#[cfg(all(test, unix))]
+mod tests {
+    use super::*;
+    use crate::{protocol::Status, GameBoard, GameConfiguration};
+
+    #[test]
+    fn requests_are_answered_test() {
+        let path = std::env::temp_dir().join(format!("minesweeper-{}.sock", std::process::id()));
+        let requests = listen(&path).unwrap();
+
+        let stream = UnixStream::connect(&path).unwrap();
+        let mut writer = stream.try_clone().unwrap();
+        let mut reader = BufReader::new(stream);
+        writer.write_all(b"nonsense\nboard\n").unwrap();
+
+        let mut line = String::new();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(
+            Response::error("malformed command"),
+            serde_json::from_str(&line).unwrap()
+        );
+
+        let control = requests.recv().unwrap();
+        assert_eq!(ControlRequest::Board, control.request);
+        let mut game_board = GameBoard::new(GameConfiguration::beginner());
+        game_board.generate_world_from_seed(1);
+        control.reply(Response::board(&game_board, Status::Playing));
+
+        line.clear();
+        reader.read_line(&mut line).unwrap();
+        assert_eq!(
+            Response::board(&game_board, Status::Playing),
+            serde_json::from_str(&line).unwrap()
+        );
+
+        fs::remove_file(path).unwrap();
+    }
+}
@@ -0,0 +1,372 @@
+//! 3D variant played on a stack of layers, every cell has up to 26 neighbours: the 8
+//! around it on its own layer and the 9 each on the layers above and below. Only one
+//! layer is drawn at a time, commands switch between them.
+
+use std::{fmt::Display, num::ParseIntError};
+
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+
+use crate::{BoardCell, CellInfo, GameResolve, Mark, NeighbourMines};
+
+/// Cell of the cube as (row, column, layer), written `explore(x, y, z)` like the classic
+/// notation with the layer appended.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coordinate3(pub u16, pub u16, pub u16);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CubeCommand {
+    Quit,
+    ClearMark(Coordinate3),
+    SetMarkFlag(Coordinate3),
+    SetMarkNote(Coordinate3),
+    Explore(Coordinate3),
+    /// Shows the given layer.
+    Layer(u16),
+    /// Shows the layer above the current one.
+    Up,
+    /// Shows the layer below the current one.
+    Down,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum CubeCommandError {
+    MalformedString,
+    MalformedCoordinate,
+    CoordinateParsing(ParseIntError),
+    NotFound,
+}
+
+impl TryFrom<&str> for CubeCommand {
+    type Error = CubeCommandError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.to_lowercase().trim().to_string();
+        match &value[..] {
+            "quit" => return Ok(CubeCommand::Quit),
+            "up" => return Ok(CubeCommand::Up),
+            "down" => return Ok(CubeCommand::Down),
+            _ => {}
+        }
+
+        let (command, arguments) = value
+            .split_once('(')
+            .ok_or(CubeCommandError::MalformedString)?;
+        let arguments = arguments
+            .trim_end_matches(')')
+            .split(',')
+            .map(|argument| argument.trim().parse::<u16>())
+            .collect::<Result<Vec<u16>, _>>()
+            .map_err(CubeCommandError::CoordinateParsing)?;
+
+        let command = command.trim();
+        if command == "layer" {
+            return match arguments[..] {
+                [layer] => Ok(CubeCommand::Layer(layer)),
+                _ => Err(CubeCommandError::MalformedCoordinate),
+            };
+        }
+
+        let [x, y, z] = arguments[..] else {
+            return Err(CubeCommandError::MalformedCoordinate);
+        };
+        let coordinate = Coordinate3(x, y, z);
+        match command {
+            "clear" => Ok(CubeCommand::ClearMark(coordinate)),
+            "flag" => Ok(CubeCommand::SetMarkFlag(coordinate)),
+            "note" => Ok(CubeCommand::SetMarkNote(coordinate)),
+            "explore" => Ok(CubeCommand::Explore(coordinate)),
+            _ => Err(CubeCommandError::NotFound),
+        }
+    }
+}
+
+pub struct CubeBoard {
+    width: u16,
+    height: u16,
+    depth: u16,
+    total_mines: u32,
+    seed: u64,
+    mines_discovered: u32,
+    /// Flags on cells without a mine, a cube with any of them is not won.
+    wrong_flags: u32,
+    cells: Vec<BoardCell>,
+    /// Layer that is drawn.
+    layer: u16,
+}
+
+impl CubeBoard {
+    /// Cube with the mines placed from the seed, `None` if it is empty or the mines do
+    /// not fit.
+    pub fn new(width: u16, height: u16, depth: u16, total_mines: u32, seed: u64) -> Option<Self> {
+        let size = width as usize * height as usize * depth as usize;
+        if size == 0 || total_mines as usize > size {
+            return None;
+        }
+
+        let mut board = CubeBoard {
+            width,
+            height,
+            depth,
+            total_mines,
+            seed,
+            mines_discovered: 0,
+            wrong_flags: 0,
+            cells: vec![BoardCell::NoMine(CellInfo(Mark::NoMark, NeighbourMines(0))); size],
+            layer: 0,
+        };
+
+        let mut positions: Vec<usize> = (0..size).collect();
+        positions.shuffle(&mut ChaCha8Rng::seed_from_u64(seed));
+        for &position in &positions[..total_mines as usize] {
+            board.cells[position] = BoardCell::Mine(Mark::NoMark);
+        }
+        for &position in &positions[..total_mines as usize] {
+            for neighbour in board.neighbours(board.coordinate(position)) {
+                let index = board.index(neighbour);
+                if let BoardCell::NoMine(CellInfo(mark, NeighbourMines(mines))) = board.cells[index]
+                {
+                    board.cells[index] =
+                        BoardCell::NoMine(CellInfo(mark, NeighbourMines(mines + 1)));
+                }
+            }
+        }
+
+        Some(board)
+    }
+
+    /// Width, height and depth of the cube.
+    pub fn get_dimensions(&self) -> (u16, u16, u16) {
+        (self.width, self.height, self.depth)
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Layer that is drawn.
+    pub fn layer(&self) -> u16 {
+        self.layer
+    }
+
+    pub fn is_on_board(&self, coordinate: Coordinate3) -> bool {
+        coordinate.0 < self.height && coordinate.1 < self.width && coordinate.2 < self.depth
+    }
+
+    pub fn get_cell_at(&self, coordinate: Coordinate3) -> &BoardCell {
+        &self.cells[self.index(coordinate)]
+    }
+
+    /// Applies the command, coordinates must lie on the board.
+    pub fn manipulate_cell(&mut self, command: CubeCommand) -> GameResolve {
+        let resolve = match command {
+            CubeCommand::Quit => return GameResolve::Quit,
+            CubeCommand::Layer(layer) => {
+                self.layer = layer.min(self.depth - 1);
+                GameResolve::Continue
+            }
+            CubeCommand::Up => {
+                self.layer = (self.layer + 1).min(self.depth - 1);
+                GameResolve::Continue
+            }
+            CubeCommand::Down => {
+                self.layer = self.layer.saturating_sub(1);
+                GameResolve::Continue
+            }
+            CubeCommand::ClearMark(coordinate) => self.mark(coordinate, Mark::NoMark),
+            CubeCommand::SetMarkFlag(coordinate) => self.mark(coordinate, Mark::MarkFlag),
+            CubeCommand::SetMarkNote(coordinate) => self.mark(coordinate, Mark::MarkNote),
+            CubeCommand::Explore(coordinate) => {
+                // follow the player to the layer they are playing on
+                self.layer = coordinate.2;
+                self.explore(coordinate)
+            }
+        };
+
+        if resolve == GameResolve::Continue
+            && self.mines_discovered == self.total_mines
+            && self.wrong_flags == 0
+        {
+            GameResolve::AllMinesDiscovered
+        } else {
+            resolve
+        }
+    }
+
+    fn mark(&mut self, coordinate: Coordinate3, mark: Mark) -> GameResolve {
+        let index = self.index(coordinate);
+        match self.cells[index] {
+            BoardCell::NoMine(CellInfo(current, mines)) => {
+                if current == Mark::MarkFlag {
+                    self.wrong_flags -= 1;
+                }
+                if mark == Mark::MarkFlag {
+                    self.wrong_flags += 1;
+                }
+                self.cells[index] = BoardCell::NoMine(CellInfo(mark, mines));
+            }
+            BoardCell::Mine(current) => {
+                if current == Mark::MarkFlag {
+                    self.mines_discovered -= 1;
+                }
+                if mark == Mark::MarkFlag {
+                    self.mines_discovered += 1;
+                }
+                self.cells[index] = BoardCell::Mine(mark);
+            }
+            BoardCell::Explored(_) => {}
+        }
+        GameResolve::Continue
+    }
+
+    fn explore(&mut self, coordinate: Coordinate3) -> GameResolve {
+        if let BoardCell::Mine(_) = self.cells[self.index(coordinate)] {
+            return GameResolve::MineHit;
+        }
+
+        let mut queue = vec![coordinate];
+        while let Some(coordinate) = queue.pop() {
+            let index = self.index(coordinate);
+            if let BoardCell::NoMine(CellInfo(mark, mines)) = self.cells[index] {
+                if mark == Mark::MarkFlag {
+                    self.wrong_flags -= 1;
+                }
+                self.cells[index] = BoardCell::Explored(mines);
+                if mines.0 == 0 {
+                    queue.extend(self.neighbours(coordinate));
+                }
+            }
+        }
+        GameResolve::Continue
+    }
+
+    fn index(&self, coordinate: Coordinate3) -> usize {
+        (coordinate.2 as usize * self.height as usize + coordinate.0 as usize) * self.width as usize
+            + coordinate.1 as usize
+    }
+
+    fn coordinate(&self, index: usize) -> Coordinate3 {
+        let layer_size = self.width as usize * self.height as usize;
+        let in_layer = index % layer_size;
+        Coordinate3(
+            (in_layer / self.width as usize) as u16,
+            (in_layer % self.width as usize) as u16,
+            (index / layer_size) as u16,
+        )
+    }
+
+    /// The up to 26 cells around the coordinate.
+    fn neighbours(&self, center: Coordinate3) -> Vec<Coordinate3> {
+        let mut neighbours = vec![];
+        for i in -1..=1 {
+            for j in -1..=1 {
+                for k in -1..=1 {
+                    if (i, j, k) == (0, 0, 0) {
+                        continue;
+                    }
+                    let x = center.0 as i32 + i;
+                    let y = center.1 as i32 + j;
+                    let z = center.2 as i32 + k;
+                    if x < 0
+                        || y < 0
+                        || z < 0
+                        || x >= self.height as i32
+                        || y >= self.width as i32
+                        || z >= self.depth as i32
+                    {
+                        continue;
+                    }
+                    neighbours.push(Coordinate3(x as u16, y as u16, z as u16));
+                }
+            }
+        }
+        neighbours
+    }
+}
+
+impl Display for CubeBoard {
+    /// Draws the current layer like a classic board.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Layer {} of {} (z)", self.layer, self.depth - 1)?;
+
+        write!(f, "{:>3}", "")?;
+        for col in 0..self.width {
+            write!(f, "{:>3}", col)?;
+        }
+        writeln!(f)?;
+
+        for row in 0..self.height {
+            write!(f, "{:>3}|", row)?;
+            for col in 0..self.width {
+                let symbol = match self.get_cell_at(Coordinate3(row, col, self.layer)) {
+                    BoardCell::NoMine(CellInfo(mark, _)) | BoardCell::Mine(mark) => match mark {
+                        Mark::NoMark => "|X|".to_string(),
+                        Mark::MarkNote => "|N|".to_string(),
+                        Mark::MarkFlag => "|F|".to_string(),
+                    },
+                    BoardCell::Explored(NeighbourMines(0)) => "| |".to_string(),
+                    BoardCell::Explored(NeighbourMines(mines)) => format!("|{}|", mines),
+                };
+                write!(f, "{:>3}", symbol)?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cube_neighbours_test() {
+        assert_eq!(
+            Ok(CubeCommand::Explore(Coordinate3(1, 2, 3))),
+            CubeCommand::try_from("explore(1, 2, 3)")
+        );
+        assert_eq!(Ok(CubeCommand::Layer(2)), CubeCommand::try_from("layer(2)"));
+        assert_eq!(
+            Err(CubeCommandError::MalformedCoordinate),
+            CubeCommand::try_from("explore(1, 2)")
+        );
+
+        assert!(CubeBoard::new(2, 2, 2, 9, 1).is_none());
+        assert!(CubeBoard::new(2, 2, 0, 0, 1).is_none());
+        let mut board = CubeBoard::new(3, 3, 3, 1, 1).unwrap();
+        assert_eq!(26, board.neighbours(Coordinate3(1, 1, 1)).len());
+        assert_eq!(7, board.neighbours(Coordinate3(0, 0, 0)).len());
+
+        // with a single mine every other cell borders it or opens up
+        let mine = (0..27)
+            .map(|index| board.coordinate(index))
+            .find(|coordinate| matches!(board.get_cell_at(*coordinate), BoardCell::Mine(_)))
+            .unwrap();
+        let safe = (0..27)
+            .map(|index| board.coordinate(index))
+            .find(|coordinate| *coordinate != mine)
+            .unwrap();
+        assert_eq!(
+            GameResolve::Continue,
+            board.manipulate_cell(CubeCommand::Explore(safe))
+        );
+        assert_eq!(safe.2, board.layer());
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            board.manipulate_cell(CubeCommand::SetMarkFlag(mine))
+        );
+
+        // a wrong flag keeps the cube from being won
+        let mut board = CubeBoard::new(3, 3, 3, 1, 1).unwrap();
+        board.manipulate_cell(CubeCommand::SetMarkFlag(safe));
+        assert_eq!(
+            GameResolve::Continue,
+            board.manipulate_cell(CubeCommand::SetMarkFlag(mine))
+        );
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            board.manipulate_cell(CubeCommand::ClearMark(safe))
+        );
+    }
+}
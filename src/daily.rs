@@ -0,0 +1,49 @@
+//! The daily challenge, one expert board per UTC day that is the same for every player.
+
+use crate::calendar;
+
+pub struct DailyChallenge {
+    date: String,
+    seed: u64,
+}
+
+impl DailyChallenge {
+    pub fn today() -> Self {
+        DailyChallenge::for_day(calendar::today())
+    }
+
+    /// Challenge for the given number of days since the unix epoch.
+    pub fn for_day(days: u64) -> Self {
+        DailyChallenge {
+            date: calendar::format_date(days),
+            // spreads consecutive days over the whole seed range
+            seed: days.wrapping_mul(0x9E37_79B9_7F4A_7C15),
+        }
+    }
+
+    /// UTC date of the challenge formatted as `YYYY-MM-DD`.
+    pub fn date(&self) -> &str {
+        &self.date
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn same_day_same_seed_test() {
+        assert_eq!(
+            DailyChallenge::for_day(20_000).seed(),
+            DailyChallenge::for_day(20_000).seed()
+        );
+        assert_ne!(
+            DailyChallenge::for_day(20_000).seed(),
+            DailyChallenge::for_day(20_001).seed()
+        );
+    }
+}
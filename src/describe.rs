@@ -0,0 +1,302 @@
+//! Plain language descriptions of the board for screen readers. The `describe(x, y)`
+//! command reads out a single cell and `readrow N` and `readcol N` a whole line of the
+//! board, with announcements enabled every change is read out as well.
+
+use crate::{BoardCell, BoardCommand, Coordinate, CoordinateConvention, GameBoard, Mark};
+
+/// What a reading command asks to read out.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Reading {
+    /// `describe(x, y)`, the coordinate as in the other commands.
+    Cell(Coordinate),
+    /// `readrow N`
+    Row(u32),
+    /// `readcol N`
+    Column(u32),
+}
+
+impl TryFrom<&str> for Reading {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.trim().to_lowercase();
+        if let Some(row) = value.strip_prefix("readrow") {
+            return row.trim().parse().map(Reading::Row).map_err(|_| ());
+        }
+        if let Some(column) = value.strip_prefix("readcol") {
+            return column.trim().parse().map(Reading::Column).map_err(|_| ());
+        }
+
+        let arguments = value
+            .strip_prefix("describe")
+            .and_then(|value| value.trim().strip_prefix('('))
+            .and_then(|value| value.strip_suffix(')'))
+            .ok_or(())?;
+        let (x, y) = arguments.split_once(',').ok_or(())?;
+        let x = x.trim().parse().map_err(|_| ())?;
+        let y = y.trim().parse().map_err(|_| ())?;
+        Ok(Reading::Cell(Coordinate(x, y)))
+    }
+}
+
+/// Reads out what the command asks for.
+/// The numbers are those the player typed, in the board's coordinate convention.
+pub fn read(game_board: &GameBoard, reading: Reading) -> String {
+    match reading {
+        Reading::Cell(typed) => match game_board.coordinate_convention().to_board(typed) {
+            Some(coordinate) => describe(game_board, coordinate),
+            None => format!("({}, {}): outside of the board", typed.0, typed.1),
+        },
+        Reading::Row(row) => read_line(game_board, "row", row, "column", |row, col| {
+            Coordinate(row, col)
+        }),
+        Reading::Column(col) => read_line(game_board, "column", col, "row", |col, row| {
+            Coordinate(row, col)
+        }),
+    }
+}
+
+/// Reads a row or column from one end to the other. Neighbouring cells in the same state
+/// are read together, e.g. "row 2: columns 0 to 3 unexplored; column 4 flagged".
+fn read_line(
+    game_board: &GameBoard,
+    line: &str,
+    number: u32,
+    cell: &str,
+    coordinate: impl Fn(u32, u32) -> Coordinate,
+) -> String {
+    let (width, height) = game_board.get_dimensions();
+    let (lines, cells) = if line == "row" {
+        (height, width)
+    } else {
+        (width, height)
+    };
+    let convention = game_board.coordinate_convention();
+    let Some(index) = convention.index(number).filter(|index| *index < lines) else {
+        return format!("{} {}: outside of the board", line, number);
+    };
+
+    let mut runs: Vec<(u32, u32, String)> = vec![];
+    for position in 0..cells {
+        let state = state(game_board, coordinate(index, position));
+        match runs.last_mut() {
+            Some((_, end, last)) if *last == state => *end = position,
+            _ => runs.push((position, position, state)),
+        }
+    }
+    let runs: Vec<String> = runs
+        .into_iter()
+        .map(|(start, end, state)| {
+            let (start, end) = (label(convention, cell, start), label(convention, cell, end));
+            if start == end {
+                format!("{} {} {}", cell, start, state)
+            } else {
+                format!("{}s {} to {} {}", cell, start, end, state)
+            }
+        })
+        .collect();
+    format!(
+        "{} {}: {}",
+        line,
+        label(convention, line, index),
+        runs.join("; ")
+    )
+}
+
+/// Sentence on what the player knows of the cell, e.g. "row 3, column 4: explored, 2
+/// adjacent mines; unexplored neighbours at row 2 column 4 and row 3 column 5".
+pub fn describe(game_board: &GameBoard, coordinate: Coordinate) -> String {
+    let convention = game_board.coordinate_convention();
+    let place = place(convention, coordinate);
+    if !game_board.is_on_board(BoardCommand::Explore(coordinate)) {
+        return format!("{}: outside of the board", place);
+    }
+
+    let state = state(game_board, coordinate);
+
+    let unexplored: Vec<String> = game_board
+        .neighbours(coordinate)
+        .into_iter()
+        .filter(|neighbour| !matches!(game_board.get_cell_at(*neighbour), BoardCell::Explored(_)))
+        .map(|neighbour| {
+            format!(
+                "row {} column {}",
+                convention.number(neighbour.0),
+                convention.column_label(neighbour.1)
+            )
+        })
+        .collect();
+    match &unexplored[..] {
+        [] => format!("{}: {}; no unexplored neighbours", place, state),
+        [neighbour] => format!(
+            "{}: {}; unexplored neighbour at {}",
+            place, state, neighbour
+        ),
+        [neighbours @ .., last] => format!(
+            "{}: {}; unexplored neighbours at {} and {}",
+            place,
+            state,
+            neighbours.join(", "),
+            last
+        ),
+    }
+}
+
+/// Announcement of what the command changed, `opened` is the number of cells it explored.
+pub fn announce(game_board: &GameBoard, command: BoardCommand, opened: u32) -> Option<String> {
+    let coordinate = match command {
+        BoardCommand::Pass | BoardCommand::Quit | BoardCommand::Claim => return None,
+        BoardCommand::ClearMark(coordinate)
+        | BoardCommand::SetMarkFlag(coordinate)
+        | BoardCommand::SetMarkFlagCount(coordinate, _)
+        | BoardCommand::SetMarkNote(coordinate)
+        | BoardCommand::Explore(coordinate) => coordinate,
+        BoardCommand::Plugin(command) => command.cell?,
+        BoardCommand::SetMarkFlagRange(from, to) | BoardCommand::ClearMarkRange(from, to) => {
+            let convention = game_board.coordinate_convention();
+            let marks = match command {
+                BoardCommand::SetMarkFlagRange(..) => "flagged",
+                _ => "cleared",
+            };
+            return Some(format!(
+                "{} to {}: {}.",
+                place(convention, from),
+                place(convention, to),
+                marks
+            ));
+        }
+    };
+
+    let description = describe(game_board, coordinate);
+    Some(match opened {
+        0 | 1 => format!("{}.", description),
+        opened => format!("{}. {} cells were opened.", description, opened),
+    })
+}
+
+/// What the player knows of the cell.
+fn state(game_board: &GameBoard, coordinate: Coordinate) -> String {
+    if game_board.is_hole(coordinate) {
+        return "a hole, not part of the board".to_string();
+    }
+    match game_board.get_cell_at(coordinate) {
+        BoardCell::Explored(neighbour_mines) => {
+            format!("explored, {}", adjacent_mines(neighbour_mines.0))
+        }
+        BoardCell::Mine(Mark::NoMark) if game_board.shows_mines() => "a mine".to_string(),
+        BoardCell::NoMine(cell_info) => marked(cell_info.0),
+        BoardCell::Mine(mark) => marked(mark),
+    }
+}
+
+fn place(convention: CoordinateConvention, coordinate: Coordinate) -> String {
+    format!(
+        "row {}, column {}",
+        convention.number(coordinate.0),
+        convention.column_label(coordinate.1)
+    )
+}
+
+/// Row number or column label as on the rulers.
+fn label(convention: CoordinateConvention, line: &str, index: u32) -> String {
+    match line {
+        "column" => convention.column_label(index),
+        _ => convention.number(index).to_string(),
+    }
+}
+
+fn marked(mark: Mark) -> String {
+    match mark {
+        Mark::NoMark => "unexplored",
+        Mark::MarkNote => "unexplored, noted",
+        Mark::MarkFlag => "flagged",
+    }
+    .to_string()
+}
+
+fn adjacent_mines(count: i16) -> String {
+    match count {
+        0 => "no adjacent mines".to_string(),
+        1 => "1 adjacent mine".to_string(),
+        count => format!("{} adjacent mines", count),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layout::Layout, AxisOrder};
+
+    #[test]
+    fn describe_test() {
+        assert_eq!(
+            Ok(Reading::Cell(Coordinate(3, 4))),
+            Reading::try_from("Describe(3, 4)\n")
+        );
+        assert_eq!(Ok(Reading::Column(2)), Reading::try_from("readcol 2"));
+        assert_eq!(Err(()), Reading::try_from("explore(3, 4)"));
+
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*##\n#..\n...\n").unwrap());
+        assert_eq!(
+            "row 1, column 1: explored, 1 adjacent mine; unexplored neighbours at row 0 \
+             column 0, row 0 column 1, row 0 column 2 and row 1 column 0",
+            describe(&game_board, Coordinate(1, 1))
+        );
+        assert_eq!(
+            "row 2, column 2: explored, no adjacent mines; no unexplored neighbours",
+            describe(&game_board, Coordinate(2, 2))
+        );
+
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(0, 0)));
+        assert_eq!(
+            Some(
+                "row 0, column 0: flagged; unexplored neighbours at row 0 column 1 and row 1 \
+                 column 0."
+                    .to_string()
+            ),
+            announce(&game_board, BoardCommand::SetMarkFlag(Coordinate(0, 0)), 0)
+        );
+        assert_eq!(None, announce(&game_board, BoardCommand::Pass, 0));
+
+        assert_eq!(
+            "row 0: column 0 flagged; columns 1 to 2 unexplored",
+            read(&game_board, Reading::Row(0))
+        );
+        assert_eq!(
+            "column 2: row 0 unexplored; rows 1 to 2 explored, no adjacent mines",
+            read(&game_board, Reading::Column(2))
+        );
+        assert_eq!(
+            "row 3: outside of the board",
+            read(&game_board, Reading::Row(3))
+        );
+
+        // one based (x, y) coordinates are read back one based
+        game_board.set_coordinate_convention(CoordinateConvention {
+            one_based: true,
+            order: AxisOrder::Xy,
+            letters: false,
+        });
+        assert_eq!(
+            "row 1, column 3: unexplored; unexplored neighbour at row 1 column 2",
+            read(&game_board, Reading::Cell(Coordinate(3, 1)))
+        );
+        assert_eq!(
+            "row 1: column 1 flagged; columns 2 to 3 unexplored",
+            read(&game_board, Reading::Row(1))
+        );
+        assert_eq!(
+            "row 0: outside of the board",
+            read(&game_board, Reading::Row(0))
+        );
+
+        game_board.set_coordinate_convention(CoordinateConvention {
+            letters: true,
+            ..CoordinateConvention::default()
+        });
+        assert_eq!(
+            "row 1: column A flagged; columns B to C unexplored",
+            read(&game_board, Reading::Row(1))
+        );
+    }
+}
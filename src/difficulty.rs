@@ -0,0 +1,102 @@
+//! Estimate of how hard a generated board is, shown before the first move so a custom
+//! board does not come as a surprise. With a safe start the mines are placed again around
+//! the first explore, the guesses of the board shown are then left out.
+
+use crate::{
+    layout::{EditCommand, Layout},
+    solver, BoardCell, Coordinate, GameBoard,
+};
+
+/// Largest board the solver counts the guesses of, larger boards would keep the player
+/// waiting for the first board.
+pub const MAX_GUESS_CELLS: u64 = 1_600;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Difficulty {
+    pub three_bv: u32,
+    pub mines: u32,
+    /// Cells of the board without holes.
+    pub cells: u64,
+    /// Guesses the solver needs, none for boards it cannot play, that are too large or too
+    /// hard to count, or whose mines move with a safe start.
+    pub guesses: Option<u32>,
+}
+
+impl Difficulty {
+    pub fn of(game_board: &GameBoard) -> Self {
+        let configuration = game_board.configuration();
+        let (width, height) = game_board.get_dimensions();
+        let cells = match game_board.mask() {
+            Some(mask) => mask.cell_count() as u64,
+            None => width as u64 * height as u64,
+        };
+        // the solver knows classic boards only
+        let classic = configuration.adjacency().is_standard()
+            && !configuration.wrap_edges()
+            && configuration.mines_per_cell() == 1
+            && configuration.anti_mines() == 0
+            && game_board.mask().is_none();
+        let guesses = (classic && cells <= MAX_GUESS_CELLS && game_board.safe_start().is_off())
+            .then(|| solver::count_guesses(&mines_layout(game_board)))
+            .flatten();
+
+        Difficulty {
+            three_bv: game_board.three_bv().1,
+            mines: configuration.mines(),
+            cells,
+            guesses,
+        }
+    }
+
+    /// Share of the cells holding a mine.
+    pub fn density(&self) -> f64 {
+        self.mines as f64 / self.cells.max(1) as f64
+    }
+}
+
+/// Layout of the board's mines with nothing revealed.
+fn mines_layout(game_board: &GameBoard) -> Layout {
+    let (width, height) = game_board.get_dimensions();
+    let mut layout = Layout::new(width, height);
+    for coordinate in (0..height).flat_map(|row| (0..width).map(move |col| Coordinate(row, col))) {
+        if let BoardCell::Mine(_) = game_board.get_cell_at(coordinate) {
+            layout.edit(EditCommand::Mine(coordinate));
+        }
+    }
+    layout
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{Adjacency, GameConfiguration, SafeStart};
+
+    #[test]
+    fn difficulty_test() {
+        // the empty left opens the board, the two cells on the right are a coin flip
+        let game_board = GameBoard::from_layout(Layout::try_from("###*\n####\n").unwrap());
+        let difficulty = Difficulty::of(&game_board);
+        assert_eq!(
+            Difficulty {
+                three_bv: 2,
+                mines: 1,
+                cells: 8,
+                guesses: Some(1),
+            },
+            difficulty
+        );
+        assert_eq!(0.125, difficulty.density());
+
+        let mut game_board =
+            GameBoard::new(GameConfiguration::new(9, 9, 10).with_adjacency(Adjacency::Knight));
+        game_board.generate_world_from_seed(7);
+        assert_eq!(None, Difficulty::of(&game_board).guesses);
+
+        // the mines are placed again around the first explore
+        let mut game_board = GameBoard::new(GameConfiguration::new(9, 9, 10));
+        game_board.generate_world_from_seed(7);
+        assert!(Difficulty::of(&game_board).guesses.is_some());
+        game_board.set_safe_start(SafeStart::Square3);
+        assert_eq!(None, Difficulty::of(&game_board).guesses);
+    }
+}
@@ -0,0 +1,369 @@
+//! Endless mode on an unbounded board. The mines are generated lazily chunk by chunk
+//! around the explored regions, every chunk from the seed and its own coordinate so the
+//! same seed always yields the same world. The score is the number of cells safely
+//! revealed before a mine is hit.
+
+use std::{collections::HashMap, fmt::Write, io, num::ParseIntError};
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    protocol::{CellView, Status},
+    storage, GameResolve, Mark,
+};
+
+pub(crate) const ENDLESS_FILE: &str = "endless.json";
+
+/// Side length of the square chunks the board is generated in.
+const CHUNK_SIZE: i64 = 16;
+
+/// Share of the cells holding a mine unless chosen otherwise.
+pub const DEFAULT_DENSITY: f64 = 0.15;
+
+/// Sparser boards have openings that never end.
+const MIN_DENSITY: f64 = 0.12;
+
+/// Cells a single exploration reveals at most, a safety net for huge openings.
+const MAX_REVEALED_PER_MOVE: usize = 4096;
+
+/// Cell of the unbounded board as (row, column), the start is at the origin.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Position(pub i64, pub i64);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+struct ChunkCoord(i64, i64);
+
+impl ChunkCoord {
+    fn of(position: Position) -> (ChunkCoord, usize) {
+        let chunk = ChunkCoord(
+            position.0.div_euclid(CHUNK_SIZE),
+            position.1.div_euclid(CHUNK_SIZE),
+        );
+        let index =
+            position.0.rem_euclid(CHUNK_SIZE) * CHUNK_SIZE + position.1.rem_euclid(CHUNK_SIZE);
+        (chunk, index as usize)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EndlessCell {
+    Hidden(Mark),
+    Explored(u8),
+}
+
+struct Chunk {
+    mines: Vec<bool>,
+    cells: Vec<EndlessCell>,
+}
+
+impl Chunk {
+    fn generate(seed: u64, coord: ChunkCoord, density: f64) -> Chunk {
+        // mix the chunk coordinate into the seed, neighbouring chunks get unrelated mines
+        let chunk_seed = seed
+            ^ (coord.0 as u64).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+            ^ (coord.1 as u64).wrapping_mul(0xC2B2_AE3D_27D4_EB4F);
+        let mut rng = ChaCha8Rng::seed_from_u64(chunk_seed);
+
+        let mines = (0..CHUNK_SIZE * CHUNK_SIZE)
+            .map(|index| {
+                let row = coord.0 * CHUNK_SIZE + index / CHUNK_SIZE;
+                let col = coord.1 * CHUNK_SIZE + index % CHUNK_SIZE;
+                let is_mine = rng.gen_bool(density);
+                // the start and its neighbours are always safe
+                is_mine && (row.abs() > 1 || col.abs() > 1)
+            })
+            .collect();
+
+        Chunk {
+            mines,
+            cells: vec![EndlessCell::Hidden(Mark::NoMark); (CHUNK_SIZE * CHUNK_SIZE) as usize],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EndlessCommand {
+    Quit,
+    ClearMark(Position),
+    SetMarkFlag(Position),
+    SetMarkNote(Position),
+    Explore(Position),
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EndlessCommandError {
+    MalformedString,
+    MalformedCoordinate,
+    CoordinateParsing(ParseIntError),
+    NotFound,
+}
+
+impl TryFrom<&str> for EndlessCommand {
+    type Error = EndlessCommandError;
+
+    /// Same notation as the classic game, but coordinates may be negative.
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.to_lowercase().trim().to_string();
+        if value == "quit" {
+            return Ok(EndlessCommand::Quit);
+        }
+
+        let (command, coordinate) = value
+            .split_once('(')
+            .ok_or(EndlessCommandError::MalformedString)?;
+        let (row, col) = coordinate
+            .trim_end_matches(')')
+            .split_once(',')
+            .ok_or(EndlessCommandError::MalformedCoordinate)?;
+        let position = Position(
+            row.trim()
+                .parse()
+                .map_err(EndlessCommandError::CoordinateParsing)?,
+            col.trim()
+                .parse()
+                .map_err(EndlessCommandError::CoordinateParsing)?,
+        );
+
+        match command.trim() {
+            "clear" => Ok(EndlessCommand::ClearMark(position)),
+            "flag" => Ok(EndlessCommand::SetMarkFlag(position)),
+            "note" => Ok(EndlessCommand::SetMarkNote(position)),
+            "explore" => Ok(EndlessCommand::Explore(position)),
+            _ => Err(EndlessCommandError::NotFound),
+        }
+    }
+}
+
+pub struct EndlessBoard {
+    seed: u64,
+    density: f64,
+    chunks: HashMap<ChunkCoord, Chunk>,
+    revealed: u64,
+    status: Status,
+}
+
+impl EndlessBoard {
+    pub fn new(seed: u64) -> Self {
+        EndlessBoard::with_density(seed, DEFAULT_DENSITY)
+    }
+
+    /// Board with the share of mines, clamped so openings stay finite.
+    pub fn with_density(seed: u64, density: f64) -> Self {
+        EndlessBoard {
+            seed,
+            density: density.clamp(MIN_DENSITY, 0.9),
+            chunks: HashMap::new(),
+            revealed: 0,
+            status: Status::Playing,
+        }
+    }
+
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Cells safely revealed so far.
+    pub fn score(&self) -> u64 {
+        self.revealed
+    }
+
+    pub fn status(&self) -> Status {
+        self.status
+    }
+
+    /// Number of chunks generated so far.
+    pub fn chunk_count(&self) -> usize {
+        self.chunks.len()
+    }
+
+    pub fn apply(&mut self, command: EndlessCommand) -> GameResolve {
+        if self.status != Status::Playing {
+            return GameResolve::Quit;
+        }
+
+        let resolve = match command {
+            EndlessCommand::Quit => GameResolve::Quit,
+            EndlessCommand::ClearMark(position) => self.mark(position, Mark::NoMark),
+            EndlessCommand::SetMarkFlag(position) => self.mark(position, Mark::MarkFlag),
+            EndlessCommand::SetMarkNote(position) => self.mark(position, Mark::MarkNote),
+            EndlessCommand::Explore(position) => self.explore(position),
+        };
+        self.status = resolve.into();
+        resolve
+    }
+
+    fn chunk(&mut self, coord: ChunkCoord) -> &mut Chunk {
+        let (seed, density) = (self.seed, self.density);
+        self.chunks
+            .entry(coord)
+            .or_insert_with(|| Chunk::generate(seed, coord, density))
+    }
+
+    fn cell_mut(&mut self, position: Position) -> &mut EndlessCell {
+        let (coord, index) = ChunkCoord::of(position);
+        &mut self.chunk(coord).cells[index]
+    }
+
+    fn is_mine(&mut self, position: Position) -> bool {
+        let (coord, index) = ChunkCoord::of(position);
+        self.chunk(coord).mines[index]
+    }
+
+    fn neighbours(position: Position) -> impl Iterator<Item = Position> {
+        (-1..=1)
+            .flat_map(|i| (-1..=1).map(move |j| (i, j)))
+            .filter(|&(i, j)| i != 0 || j != 0)
+            .map(move |(i, j)| Position(position.0 + i, position.1 + j))
+    }
+
+    fn mark(&mut self, position: Position, mark: Mark) -> GameResolve {
+        if let EndlessCell::Hidden(current) = self.cell_mut(position) {
+            *current = mark;
+        }
+        GameResolve::Continue
+    }
+
+    fn explore(&mut self, position: Position) -> GameResolve {
+        if self.is_mine(position) {
+            return GameResolve::MineHit;
+        }
+
+        let mut queue = vec![position];
+        let mut revealed = 0;
+        while let Some(position) = queue.pop() {
+            if revealed == MAX_REVEALED_PER_MOVE {
+                break;
+            }
+            if let EndlessCell::Explored(_) = *self.cell_mut(position) {
+                continue;
+            }
+
+            let mines = EndlessBoard::neighbours(position)
+                .filter(|neighbour| self.is_mine(*neighbour))
+                .count() as u8;
+            *self.cell_mut(position) = EndlessCell::Explored(mines);
+            revealed += 1;
+
+            if mines == 0 {
+                queue.extend(EndlessBoard::neighbours(position));
+            }
+        }
+
+        self.revealed += revealed as u64;
+        GameResolve::Continue
+    }
+
+    /// What the player may see of the cell, mines are only shown once the game is lost.
+    pub fn cell_view(&self, position: Position) -> CellView {
+        let (coord, index) = ChunkCoord::of(position);
+        let Some(chunk) = self.chunks.get(&coord) else {
+            return CellView::Hidden;
+        };
+
+        match chunk.cells[index] {
+            EndlessCell::Explored(mines) => CellView::Explored {
+                mines: mines.into(),
+            },
+            _ if chunk.mines[index] && self.status == Status::Lost => CellView::Mine,
+            EndlessCell::Hidden(Mark::NoMark) => CellView::Hidden,
+            EndlessCell::Hidden(Mark::MarkFlag) => CellView::Flag,
+            EndlessCell::Hidden(Mark::MarkNote) => CellView::Note,
+        }
+    }
+
+    /// Draws the window of the board around the center, labelled with the positions.
+    pub fn render(&self, center: Position, width: i64, height: i64) -> String {
+        let rows = center.0 - height / 2..center.0 - height / 2 + height;
+        let cols = center.1 - width / 2..center.1 - width / 2 + width;
+
+        let mut board = format!("{:>5}", "");
+        for col in cols.clone() {
+            let _ = write!(board, "{:>4}", col);
+        }
+        board.push('\n');
+
+        for row in rows {
+            let _ = write!(board, "{:>5}|", row);
+            for col in cols.clone() {
+                let symbol = match self.cell_view(Position(row, col)) {
+                    CellView::Hidden => "|X|".to_string(),
+                    CellView::Flag => "|F|".to_string(),
+                    CellView::Note => "|N|".to_string(),
+                    CellView::Mine => "|*|".to_string(),
+                    CellView::Explored { mines: 0 } | CellView::Void => "| |".to_string(),
+                    CellView::Explored { mines } => format!("|{}|", mines),
+                };
+                let _ = write!(board, "{:>4}", symbol);
+            }
+            board.push('\n');
+        }
+
+        board
+    }
+}
+
+/// Best endless score of the profile.
+#[derive(Serialize, Deserialize, Default, Debug, Clone, Copy, PartialEq, Eq)]
+pub struct EndlessRecord {
+    pub best_score: u64,
+}
+
+impl EndlessRecord {
+    pub fn load() -> Self {
+        storage::load_json(ENDLESS_FILE)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::save_json(ENDLESS_FILE, self)
+    }
+
+    /// Keeps the score if it beats the best one, returns whether it did.
+    pub fn submit(&mut self, score: u64) -> bool {
+        if score > self.best_score {
+            self.best_score = score;
+            true
+        } else {
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn endless_board_test() {
+        assert_eq!(
+            Ok(EndlessCommand::Explore(Position(-20, 3))),
+            EndlessCommand::try_from("explore(-20, 3)")
+        );
+
+        let mut board = EndlessBoard::new(7);
+        assert_eq!(
+            GameResolve::Continue,
+            board.apply(EndlessCommand::Explore(Position(0, 0)))
+        );
+        assert!(board.score() > 0);
+        assert!(board.chunk_count() >= 1);
+
+        // the same seed generates the same mines far away from the start
+        let mut other = EndlessBoard::new(7);
+        let far = Position(-1000, 4000);
+        assert_eq!(board.is_mine(far), other.is_mine(far));
+
+        let mine = (0..)
+            .map(|col| Position(100, col))
+            .find(|position| board.is_mine(*position))
+            .unwrap();
+        let score = board.score();
+        assert_eq!(
+            GameResolve::MineHit,
+            board.apply(EndlessCommand::Explore(mine))
+        );
+        assert_eq!(score, board.score());
+        assert_eq!(CellView::Mine, board.cell_view(mine));
+    }
+}
@@ -0,0 +1,319 @@
+//! Estimates of how likely every hidden cell is to be safe, for positions too large to
+//! solve exactly. Layouts of the mines that agree with the numbers shown and the mine
+//! count are sampled with a Markov chain: a mine is moved to a random cell without one
+//! and the move is kept when every number still agrees. The moves are symmetric, so in
+//! the long run every agreeing layout is sampled equally often. Flags are the player's
+//! guesses and are ignored.
+
+use rand::{seq::SliceRandom, Rng};
+
+use crate::{BoardCell, Coordinate, GameBoard};
+
+/// Samples taken by the `estimate` command.
+pub const DEFAULT_SAMPLES: u32 = 1000;
+
+/// Placements tried when looking for a first agreeing layout.
+const MAX_SEARCH_NODES: usize = 2_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Estimate {
+    pub coordinate: Coordinate,
+    /// Share of the sampled layouts without a mine in the cell.
+    pub survival: f64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EstimateError {
+    /// Only boards with at most a mine per cell and without anti-mines are sampled.
+    Unsupported,
+    /// No layout agreeing with the board was found within the search limit.
+    NoLayout,
+}
+
+/// Survival chance of exploring every hidden cell, the safest first.
+pub fn estimate(
+    game_board: &GameBoard,
+    samples: u32,
+    rng: &mut impl Rng,
+) -> Result<Vec<Estimate>, EstimateError> {
+    let configuration = game_board.configuration();
+    if configuration.anti_mines() > 0 || configuration.mines_per_cell() > 1 {
+        return Err(EstimateError::Unsupported);
+    }
+
+    let mut chain = Chain::new(game_board);
+    if !chain.start(configuration.mines() as usize, rng) {
+        return Err(EstimateError::NoLayout);
+    }
+
+    let mut mined = vec![0u32; chain.hidden.len()];
+    // every sample moves each mine about once
+    let steps = chain.mines.len().max(1);
+    for _ in 0..steps * 10 {
+        chain.step(rng);
+    }
+    for _ in 0..samples {
+        for _ in 0..steps {
+            chain.step(rng);
+        }
+        for mine in &chain.mines {
+            mined[*mine] += 1;
+        }
+    }
+
+    let samples = samples.max(1) as f64;
+    let mut estimates: Vec<Estimate> = chain
+        .hidden
+        .iter()
+        .zip(mined)
+        .map(|(coordinate, mined)| Estimate {
+            coordinate: *coordinate,
+            survival: 1.0 - mined as f64 / samples,
+        })
+        .collect();
+    estimates.sort_by(|a, b| {
+        b.survival
+            .total_cmp(&a.survival)
+            .then((a.coordinate.0, a.coordinate.1).cmp(&(b.coordinate.0, b.coordinate.1)))
+    });
+    Ok(estimates)
+}
+
+/// Hidden cells of the board and the numbers they have to agree with.
+struct Chain {
+    hidden: Vec<Coordinate>,
+    /// Numbers next to every hidden cell.
+    constraints_of: Vec<Vec<usize>>,
+    /// Mines every number shows and how many the current layout places next to it.
+    targets: Vec<u32>,
+    counts: Vec<u32>,
+    is_mine: Vec<bool>,
+    mines: Vec<usize>,
+    safe: Vec<usize>,
+}
+
+impl Chain {
+    fn new(game_board: &GameBoard) -> Self {
+        let (width, height) = game_board.get_dimensions();
+        let coordinates =
+            (0..height).flat_map(|row| (0..width).map(move |col| Coordinate(row, col)));
+
+        let mut hidden = vec![];
+        let mut hidden_index = vec![None; width as usize * height as usize];
+        let linear =
+            |Coordinate(row, col): Coordinate| row as usize * width as usize + col as usize;
+        for coordinate in coordinates.clone() {
+            if !game_board.is_hole(coordinate)
+                && !matches!(game_board.get_cell_at(coordinate), BoardCell::Explored(_))
+            {
+                hidden_index[linear(coordinate)] = Some(hidden.len());
+                hidden.push(coordinate);
+            }
+        }
+
+        let mut constraints_of = vec![vec![]; hidden.len()];
+        let mut targets = vec![];
+        for coordinate in coordinates {
+            let BoardCell::Explored(neighbour_mines) = game_board.get_cell_at(coordinate) else {
+                continue;
+            };
+            let neighbours: Vec<usize> = game_board
+                .neighbours(coordinate)
+                .into_iter()
+                .filter_map(|neighbour| hidden_index[linear(neighbour)])
+                .collect();
+            if neighbours.is_empty() {
+                continue;
+            }
+            for neighbour in neighbours {
+                constraints_of[neighbour].push(targets.len());
+            }
+            targets.push(neighbour_mines.0.max(0) as u32);
+        }
+
+        Chain {
+            counts: vec![0; targets.len()],
+            is_mine: vec![false; hidden.len()],
+            hidden,
+            constraints_of,
+            targets,
+            mines: vec![],
+            safe: vec![],
+        }
+    }
+
+    /// Finds a first layout of the mines, cells next to a number are placed by a search
+    /// trying mine or safe in random order, the rest get the remaining mines at random.
+    fn start(&mut self, mines: usize, rng: &mut impl Rng) -> bool {
+        let frontier: Vec<usize> = (0..self.hidden.len())
+            .filter(|cell| !self.constraints_of[*cell].is_empty())
+            .collect();
+        let interior = self.hidden.len() - frontier.len();
+        let mut unassigned = vec![0u32; self.targets.len()];
+        for cell in &frontier {
+            for constraint in &self.constraints_of[*cell] {
+                unassigned[*constraint] += 1;
+            }
+        }
+
+        // the search keeps its own stack, frontiers of large boards are long
+        let mut choices = vec![[true, false]; frontier.len()];
+        let mut tried = vec![0; frontier.len() + 1];
+        let mut level = 0;
+        let mut placed = 0;
+        let mut nodes = 0;
+        if let Some(first) = choices.first_mut() {
+            first.shuffle(rng);
+        }
+        loop {
+            if nodes > MAX_SEARCH_NODES {
+                return false;
+            }
+
+            let backtrack = if level == frontier.len() {
+                if mines - placed <= interior {
+                    break;
+                }
+                true
+            } else if tried[level] < 2 {
+                let cell = frontier[level];
+                let mine = choices[level][tried[level]];
+                tried[level] += 1;
+                nodes += 1;
+                let fits = (!mine || placed < mines)
+                    && self.constraints_of[cell].iter().all(|constraint| {
+                        let count = self.counts[*constraint] + mine as u32;
+                        let left = unassigned[*constraint] - 1;
+                        count <= self.targets[*constraint]
+                            && count + left >= self.targets[*constraint]
+                    });
+                if fits {
+                    self.is_mine[cell] = mine;
+                    placed += mine as usize;
+                    for constraint in &self.constraints_of[cell] {
+                        self.counts[*constraint] += mine as u32;
+                        unassigned[*constraint] -= 1;
+                    }
+                    level += 1;
+                    tried[level] = 0;
+                    if let Some(next) = choices.get_mut(level) {
+                        next.shuffle(rng);
+                    }
+                }
+                false
+            } else {
+                true
+            };
+
+            if backtrack {
+                if level == 0 {
+                    return false;
+                }
+                level -= 1;
+                let cell = frontier[level];
+                let mine = self.is_mine[cell];
+                self.is_mine[cell] = false;
+                placed -= mine as usize;
+                for constraint in &self.constraints_of[cell] {
+                    self.counts[*constraint] -= mine as u32;
+                    unassigned[*constraint] += 1;
+                }
+            }
+        }
+
+        let mut rest: Vec<usize> = (0..self.hidden.len())
+            .filter(|cell| self.constraints_of[*cell].is_empty())
+            .collect();
+        rest.shuffle(rng);
+        for cell in rest.into_iter().take(mines - placed) {
+            self.is_mine[cell] = true;
+        }
+        for cell in 0..self.hidden.len() {
+            if self.is_mine[cell] {
+                self.mines.push(cell);
+            } else {
+                self.safe.push(cell);
+            }
+        }
+        true
+    }
+
+    /// Moves a random mine to a random cell without one if the numbers still agree.
+    fn step(&mut self, rng: &mut impl Rng) {
+        if self.mines.is_empty() || self.safe.is_empty() {
+            return;
+        }
+        let from = rng.gen_range(0..self.mines.len());
+        let to = rng.gen_range(0..self.safe.len());
+        let (mine, cell) = (self.mines[from], self.safe[to]);
+
+        for constraint in &self.constraints_of[mine] {
+            self.counts[*constraint] -= 1;
+        }
+        for constraint in &self.constraints_of[cell] {
+            self.counts[*constraint] += 1;
+        }
+        let agrees = self.constraints_of[mine]
+            .iter()
+            .chain(&self.constraints_of[cell])
+            .all(|constraint| self.counts[*constraint] == self.targets[*constraint]);
+
+        if agrees {
+            self.is_mine[mine] = false;
+            self.is_mine[cell] = true;
+            self.mines[from] = cell;
+            self.safe[to] = mine;
+        } else {
+            for constraint in &self.constraints_of[mine] {
+                self.counts[*constraint] += 1;
+            }
+            for constraint in &self.constraints_of[cell] {
+                self.counts[*constraint] -= 1;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Layout;
+    use rand::{rngs::StdRng, SeedableRng};
+
+    fn survival(estimates: &[Estimate], coordinate: Coordinate) -> f64 {
+        estimates
+            .iter()
+            .find(|estimate| estimate.coordinate == coordinate)
+            .unwrap()
+            .survival
+    }
+
+    #[test]
+    fn estimate_test() {
+        // the 1 and the two 3s leave a single layout of the three mines
+        let game_board = GameBoard::from_layout(Layout::try_from(".*#\n..#\n**#\n").unwrap());
+        let mut rng = StdRng::seed_from_u64(4);
+        let estimates = estimate(&game_board, 100, &mut rng).unwrap();
+        assert_eq!(6, estimates.len());
+        assert_eq!(1.0, estimates[0].survival);
+        assert_eq!(1.0, survival(&estimates, Coordinate(1, 2)));
+        assert_eq!(0.0, survival(&estimates, Coordinate(0, 1)));
+        assert_eq!(0.0, survival(&estimates, Coordinate(2, 1)));
+
+        // both 1s see the same two cells, either may hold the mine
+        let game_board = GameBoard::from_layout(Layout::try_from(".*\n.#\n").unwrap());
+        let estimates = estimate(&game_board, 2000, &mut rng).unwrap();
+        assert!((survival(&estimates, Coordinate(0, 1)) - 0.5).abs() < 0.1);
+        assert!((survival(&estimates, Coordinate(1, 1)) - 0.5).abs() < 0.1);
+    }
+
+    #[test]
+    fn unsupported_test() {
+        let game_board = GameBoard::new(crate::GameConfiguration::new(9, 9, 10).with_anti_mines(2));
+        let mut rng = StdRng::seed_from_u64(4);
+        assert_eq!(
+            Err(EstimateError::Unsupported),
+            estimate(&game_board, 10, &mut rng)
+        );
+    }
+}
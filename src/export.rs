@@ -0,0 +1,127 @@
+//! CSV export of the active profile's statistics and highscores for use in spreadsheets.
+
+use std::{
+    fmt::Write,
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use crate::{
+    calendar,
+    highscores::{DailyHighscores, Highscores},
+    splits::PersonalBests,
+    statistics::Statistics,
+    storage,
+};
+
+const GAMES_FILE: &str = "games.csv";
+const HIGHSCORES_FILE: &str = "highscores.csv";
+
+/// Writes `games.csv` and `highscores.csv` into the directory and returns their paths.
+pub fn export_stats(dir: &Path) -> io::Result<Vec<PathBuf>> {
+    fs::create_dir_all(dir)?;
+
+    let games_path = dir.join(GAMES_FILE);
+    fs::write(&games_path, games_csv(&Statistics::load()))?;
+
+    let highscores_path = dir.join(HIGHSCORES_FILE);
+    fs::write(
+        &highscores_path,
+        highscores_csv(
+            &Highscores::load(),
+            &DailyHighscores::load(),
+            &PersonalBests::load(),
+        ),
+    )?;
+
+    Ok(vec![games_path, highscores_path])
+}
+
+fn games_csv(statistics: &Statistics) -> String {
+    let mut csv = String::from(
+        "date,difficulty,width,height,mines,seed,outcome,time_s,flags_placed,cells_revealed,forgiven\n",
+    );
+
+    for game in statistics.games() {
+        let configuration = &game.configuration;
+        let _ = writeln!(
+            csv,
+            "{},{},{},{},{},{},{},{:.3},{},{},{}",
+            calendar::format_date(game.day),
+            configuration.key(),
+            configuration.w(),
+            configuration.h(),
+            configuration.mines(),
+            game.seed,
+            game.outcome,
+            game.time_ms as f64 / 1000.0,
+            game.flags_placed,
+            game.cells_revealed,
+            game.forgiven
+        );
+    }
+
+    csv
+}
+
+fn highscores_csv(
+    highscores: &Highscores,
+    daily_highscores: &DailyHighscores,
+    personal_bests: &PersonalBests,
+) -> String {
+    let mut csv = String::from("table,rank,name,time_s\n");
+
+    let tables = highscores
+        .tables()
+        .map(|(difficulty, table)| (difficulty.clone(), table))
+        .chain(
+            daily_highscores
+                .tables()
+                .map(|(date, table)| (format!("daily {}", date), table)),
+        );
+    for (title, table) in tables {
+        for (rank, entry) in table.entries().iter().enumerate() {
+            let _ = writeln!(
+                csv,
+                "{},{},{},{:.3}",
+                csv_field(&title),
+                rank + 1,
+                csv_field(&entry.name),
+                entry.time_ms as f64 / 1000.0
+            );
+        }
+    }
+
+    for (difficulty, personal_best) in personal_bests.iter() {
+        let _ = writeln!(
+            csv,
+            "{},1,{},{:.3}",
+            csv_field(&format!("personal best {}", difficulty)),
+            csv_field(storage::active_profile()),
+            personal_best.finish().as_secs_f64()
+        );
+    }
+
+    csv
+}
+
+/// Quotes the field when it contains characters with a meaning in CSV.
+fn csv_field(value: &str) -> String {
+    if value.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", value.replace('"', "\"\""))
+    } else {
+        value.to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_field_test() {
+        assert_eq!("plain", csv_field("plain"));
+        assert_eq!("\"a, b\"", csv_field("a, b"));
+        assert_eq!("\"say \"\"hi\"\"\"", csv_field("say \"hi\""));
+    }
+}
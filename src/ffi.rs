@@ -0,0 +1,167 @@
+//! C API of the engine for embedding it in C/C++ frontends. The header is generated into
+//! `include/minesweeper.h` when building with the `ffi` feature.
+//!
+//! A game is created with `ms_new`, driven with `ms_command` using the terminal notation,
+//! e.g. `"explore(3, 4)"`, inspected with `ms_cell_at` and released with `ms_free`.
+
+use std::{
+    ffi::{c_char, CStr},
+    ptr,
+};
+
+use crate::{
+    protocol::{CellView, Status},
+    BoardCommand, Coordinate, GameBoard, GameConfiguration,
+};
+
+/// Cell that is neither explored nor marked.
+pub const MS_CELL_HIDDEN: i32 = -1;
+/// Cell marked with a flag.
+pub const MS_CELL_FLAG: i32 = -2;
+/// Cell marked with a note.
+pub const MS_CELL_NOTE: i32 = -3;
+/// Mine, only shown once the game is lost.
+pub const MS_CELL_MINE: i32 = -4;
+/// Coordinate outside of the board.
+pub const MS_CELL_INVALID: i32 = -5;
+
+/// Outcome of `ms_command`.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MsResolve {
+    Continue = 0,
+    Quit = 1,
+    MineHit = 2,
+    Won = 3,
+    /// The command is malformed, outside of the board or the game is over.
+    Invalid = -1,
+}
+
+/// Game handle, owned by the caller until passed to `ms_free`.
+pub struct MsGame {
+    game_board: GameBoard,
+    status: Status,
+}
+
+/// Creates a game on a board generated from the seed, returns null if the board is empty,
+/// too large or cannot hold the mines.
+#[no_mangle]
+pub extern "C" fn ms_new(width: u32, height: u32, mines: u32, seed: u64) -> *mut MsGame {
+    let configuration = GameConfiguration::new(width, height, mines);
+    if !configuration.fits() {
+        return ptr::null_mut();
+    }
+
+    let mut game_board = GameBoard::new(configuration);
+    game_board.generate_world_from_seed(seed);
+    Box::into_raw(Box::new(MsGame {
+        game_board,
+        status: Status::Playing,
+    }))
+}
+
+/// Applies a command in the terminal notation, e.g. `"flag(0, 1)"`.
+///
+/// # Safety
+///
+/// `game` must come from `ms_new` and not be freed, `command` must be a nul terminated
+/// string.
+#[no_mangle]
+pub unsafe extern "C" fn ms_command(game: *mut MsGame, command: *const c_char) -> MsResolve {
+    let (Some(game), false) = (game.as_mut(), command.is_null()) else {
+        return MsResolve::Invalid;
+    };
+    let Ok(command) = CStr::from_ptr(command).to_str() else {
+        return MsResolve::Invalid;
+    };
+    let Ok(command) = BoardCommand::try_from(command) else {
+        return MsResolve::Invalid;
+    };
+    if game.status != Status::Playing || !game.game_board.is_on_board(command) {
+        return MsResolve::Invalid;
+    }
+
+    game.status = game.game_board.manipulate_cell(command).into();
+    match game.status {
+        Status::Playing => MsResolve::Continue,
+        Status::Quit => MsResolve::Quit,
+        Status::Lost => MsResolve::MineHit,
+        Status::Won => MsResolve::Won,
+    }
+}
+
+/// Number of neighbouring mines of an explored cell, otherwise one of the `MS_CELL_*`
+/// constants.
+///
+/// # Safety
+///
+/// `game` must come from `ms_new` and not be freed.
+#[no_mangle]
+pub unsafe extern "C" fn ms_cell_at(game: *const MsGame, row: u32, col: u32) -> i32 {
+    let Some(game) = game.as_ref() else {
+        return MS_CELL_INVALID;
+    };
+    let coordinate = Coordinate(row, col);
+    if !game
+        .game_board
+        .is_on_board(BoardCommand::Explore(coordinate))
+    {
+        return MS_CELL_INVALID;
+    }
+
+    match CellView::new(game.game_board.get_cell_at(coordinate), game.status) {
+        CellView::Hidden => MS_CELL_HIDDEN,
+        CellView::Flag => MS_CELL_FLAG,
+        CellView::Note => MS_CELL_NOTE,
+        CellView::Mine => MS_CELL_MINE,
+        CellView::Explored { mines } => mines as i32,
+        CellView::Void => MS_CELL_INVALID,
+    }
+}
+
+/// Releases the game, null is ignored.
+///
+/// # Safety
+///
+/// `game` must come from `ms_new` and not be freed already.
+#[no_mangle]
+pub unsafe extern "C" fn ms_free(game: *mut MsGame) {
+    if !game.is_null() {
+        drop(Box::from_raw(game));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::CString;
+
+    use super::*;
+
+    #[test]
+    fn game_through_c_api_test() {
+        assert!(ms_new(2, 2, 5, 1).is_null());
+        assert!(ms_new(0, 4, 0, 1).is_null());
+        assert!(ms_new(u32::MAX, u32::MAX, 1, 1).is_null());
+
+        let game = ms_new(4, 4, 0, 1);
+        unsafe {
+            assert_eq!(MS_CELL_HIDDEN, ms_cell_at(game, 0, 0));
+            assert_eq!(MS_CELL_INVALID, ms_cell_at(game, 4, 0));
+
+            let malformed = CString::new("explode(0, 0)").unwrap();
+            assert_eq!(MsResolve::Invalid, ms_command(game, malformed.as_ptr()));
+
+            // flags on cells without mines keep the game from being won
+            let flag = CString::new("flag(1, 2)").unwrap();
+            assert_eq!(MsResolve::Continue, ms_command(game, flag.as_ptr()));
+            assert_eq!(MS_CELL_FLAG, ms_cell_at(game, 1, 2));
+
+            let explore = CString::new("explore(0, 0)").unwrap();
+            assert_eq!(MsResolve::Won, ms_command(game, explore.as_ptr()));
+            assert_eq!(0, ms_cell_at(game, 1, 2));
+            assert_eq!(MsResolve::Invalid, ms_command(game, explore.as_ptr()));
+
+            ms_free(game);
+        }
+    }
+}
@@ -0,0 +1,129 @@
+//! Gauntlet of boards that grow larger and denser, played until the first board is not
+//! cleared. The score is every safe cell revealed on the way, the lost board included.
+
+use std::{fmt::Display, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{storage, GameConfiguration};
+
+pub(crate) const GAUNTLET_FILE: &str = "gauntlet.json";
+const TABLE_SIZE: usize = 10;
+
+/// Side length of the first board, every stage adds two.
+const FIRST_SIDE: u32 = 6;
+const MAX_SIDE: u32 = 30;
+
+/// Mine densities in percent of the first and the densest board.
+const FIRST_DENSITY: u32 = 10;
+const MAX_DENSITY: u32 = 25;
+
+/// Board of the stage, counting from zero.
+pub fn stage(index: u32) -> GameConfiguration {
+    let side = FIRST_SIDE
+        .saturating_add(index.saturating_mul(2))
+        .min(MAX_SIDE);
+    let density = FIRST_DENSITY.saturating_add(index).min(MAX_DENSITY);
+    GameConfiguration::new(side, side, side * side * density / 100)
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GauntletEntry {
+    pub name: String,
+    /// Safe cells revealed over all boards.
+    pub score: u64,
+    /// Boards cleared before the run ended.
+    pub stages: u32,
+}
+
+/// The best runs, highest score first.
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct GauntletTable {
+    entries: Vec<GauntletEntry>,
+}
+
+impl GauntletTable {
+    pub fn load() -> Self {
+        storage::load_json(GAUNTLET_FILE)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::save_json(GAUNTLET_FILE, self)
+    }
+
+    pub fn entries(&self) -> &[GauntletEntry] {
+        &self.entries
+    }
+
+    /// Whether the score would make it into the table.
+    pub fn qualifies(&self, score: u64) -> bool {
+        score > 0
+            && (self.entries.len() < TABLE_SIZE
+                || self
+                    .entries
+                    .last()
+                    .is_some_and(|lowest| score > lowest.score))
+    }
+
+    pub fn insert(&mut self, name: String, score: u64, stages: u32) {
+        let position = self.entries.partition_point(|entry| entry.score >= score);
+        self.entries.insert(
+            position,
+            GauntletEntry {
+                name,
+                score,
+                stages,
+            },
+        );
+        self.entries.truncate(TABLE_SIZE);
+    }
+}
+
+impl Display for GauntletTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (rank, entry) in self.entries.iter().enumerate() {
+            writeln!(
+                f,
+                "{:>3}. {:<20}{:>10} cells{:>4} boards",
+                rank + 1,
+                entry.name,
+                entry.score,
+                entry.stages
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stages_test() {
+        assert_eq!(GameConfiguration::new(6, 6, 3), stage(0));
+        assert_eq!(GameConfiguration::new(10, 10, 12), stage(2));
+        assert_eq!(GameConfiguration::new(30, 30, 225), stage(u32::MAX));
+        assert!((0..40).all(|index| stage(index).fits()));
+    }
+
+    #[test]
+    fn gauntlet_table_test() {
+        let mut table = GauntletTable::default();
+        assert!(!table.qualifies(0));
+        for score in 1..=TABLE_SIZE as u64 {
+            table.insert(format!("player{}", score), score * 10, 1);
+        }
+        assert!(!table.qualifies(10));
+        assert!(table.qualifies(11));
+
+        table.insert("best".to_string(), 500, 4);
+        assert_eq!(TABLE_SIZE, table.entries().len());
+        assert_eq!("best", table.entries()[0].name);
+        assert_eq!(20, table.entries().last().unwrap().score);
+        assert!(table
+            .to_string()
+            .starts_with("  1. best                       500 cells   4 boards\n"));
+    }
+}
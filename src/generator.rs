@@ -0,0 +1,504 @@
+//! Mine placement. A [`BoardGenerator`] picks the cells holding mines out of the
+//! candidates of a board, the [`Distribution`] of the game configuration selects which
+//! one generates its world and the [`Symmetry`] whether it is mirrored.
+
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::Display,
+};
+
+use rand::{seq::SliceRandom, Rng, RngCore};
+use serde::{Deserialize, Serialize};
+
+use crate::GameConfiguration;
+
+/// Picks where the mines go.
+pub trait BoardGenerator {
+    /// Picks `count` of the candidate positions, linear indices of the board that may be
+    /// listed once for every mine the cell can hold. Whatever the generator picks is
+    /// drawn from the rng only, so the same seed yields the same board.
+    fn pick(
+        &self,
+        configuration: &GameConfiguration,
+        candidates: Vec<u32>,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<u32>;
+}
+
+/// How the mines are spread over the board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Distribution {
+    /// Every cell is equally likely, the classic game.
+    #[default]
+    Uniform,
+    /// Mines attract each other and form clumps.
+    Clustered,
+    /// Mines keep their distance, spreading evenly like blue noise.
+    AntiClustered,
+    /// Mines favour the cells close to the edges.
+    EdgeWeighted,
+}
+
+impl Distribution {
+    pub fn generator(&self) -> &'static dyn BoardGenerator {
+        match self {
+            Distribution::Uniform => &Uniform,
+            Distribution::Clustered => &Clustered,
+            Distribution::AntiClustered => &AntiClustered,
+            Distribution::EdgeWeighted => &EdgeWeighted,
+        }
+    }
+
+    pub fn is_uniform(&self) -> bool {
+        *self == Distribution::Uniform
+    }
+}
+
+impl Display for Distribution {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Distribution::Uniform => write!(f, "uniform"),
+            Distribution::Clustered => write!(f, "clustered"),
+            Distribution::AntiClustered => write!(f, "anti_clustered"),
+            Distribution::EdgeWeighted => write!(f, "edge_weighted"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Distribution {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "uniform" => Ok(Distribution::Uniform),
+            "clustered" => Ok(Distribution::Clustered),
+            "anti_clustered" => Ok(Distribution::AntiClustered),
+            "edge_weighted" => Ok(Distribution::EdgeWeighted),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Symmetry of the mine layout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Symmetry {
+    #[default]
+    Asymmetric,
+    /// The right half mirrors the left one.
+    Mirror,
+    /// The board looks the same turned upside down.
+    Rotational,
+}
+
+impl Symmetry {
+    pub fn is_asymmetric(&self) -> bool {
+        *self == Symmetry::Asymmetric
+    }
+
+    /// The position the symmetry maps the position to.
+    fn image(&self, configuration: &GameConfiguration, position: u32) -> u32 {
+        let (width, height) = (configuration.w(), configuration.h());
+        let (row, col) = (position / width, position % width);
+        match self {
+            Symmetry::Asymmetric => position,
+            Symmetry::Mirror => row * width + width - 1 - col,
+            Symmetry::Rotational => (height - 1 - row) * width + width - 1 - col,
+        }
+    }
+}
+
+impl Display for Symmetry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Symmetry::Asymmetric => write!(f, "asymmetric"),
+            Symmetry::Mirror => write!(f, "mirror"),
+            Symmetry::Rotational => write!(f, "rotational"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Symmetry {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "asymmetric" => Ok(Symmetry::Asymmetric),
+            "mirror" => Ok(Symmetry::Mirror),
+            "rotational" => Ok(Symmetry::Rotational),
+            _ => Err(()),
+        }
+    }
+}
+
+/// Places the mines of another generator in symmetric pairs. The other generator picks
+/// one cell of every pair, mines left over by an odd count go to cells that are their own
+/// image, e.g. the center of the board, or break the symmetry if there are none.
+pub struct Symmetric<'a> {
+    pub symmetry: Symmetry,
+    pub generator: &'a dyn BoardGenerator,
+}
+
+impl BoardGenerator for Symmetric<'_> {
+    fn pick(
+        &self,
+        configuration: &GameConfiguration,
+        candidates: Vec<u32>,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<u32> {
+        if self.symmetry.is_asymmetric() {
+            return self.generator.pick(configuration, candidates, count, rng);
+        }
+
+        // on masked boards cells whose image is a hole can't be part of a pair
+        let positions: HashSet<u32> = candidates.iter().copied().collect();
+        let (mut pairs, mut singles) = (vec![], vec![]);
+        for position in &candidates {
+            let image = self.symmetry.image(configuration, *position);
+            if image == *position {
+                singles.push(*position);
+            } else if *position < image && positions.contains(&image) {
+                pairs.push(*position);
+            }
+        }
+
+        let pair_count = (count / 2).min(pairs.len());
+        let mut picked = vec![];
+        for position in self.generator.pick(configuration, pairs, pair_count, rng) {
+            picked.push(position);
+            picked.push(self.symmetry.image(configuration, position));
+        }
+
+        singles.shuffle(rng);
+        picked.extend(singles.into_iter().take(count - picked.len()));
+
+        if picked.len() < count {
+            let mut rest = candidates;
+            for position in &picked {
+                if let Some(index) = rest.iter().position(|other| other == position) {
+                    rest.swap_remove(index);
+                }
+            }
+            let missing = count - picked.len();
+            picked.extend(Uniform.pick(configuration, rest, missing, rng));
+        }
+        picked
+    }
+}
+
+pub struct Uniform;
+
+impl BoardGenerator for Uniform {
+    fn pick(
+        &self,
+        _configuration: &GameConfiguration,
+        mut candidates: Vec<u32>,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<u32> {
+        candidates.shuffle(rng);
+        candidates.truncate(count);
+        candidates
+    }
+}
+
+pub struct Clustered;
+
+impl BoardGenerator for Clustered {
+    fn pick(
+        &self,
+        configuration: &GameConfiguration,
+        candidates: Vec<u32>,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<u32> {
+        // every mine already placed around a cell makes it five times as likely
+        pick_weighted(configuration, candidates, count, rng, |_, nearby| {
+            1.0 + 4.0 * nearby as f64
+        })
+    }
+}
+
+pub struct AntiClustered;
+
+impl BoardGenerator for AntiClustered {
+    fn pick(
+        &self,
+        configuration: &GameConfiguration,
+        candidates: Vec<u32>,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<u32> {
+        // crowded cells are all but ruled out while free ones are left
+        pick_weighted(configuration, candidates, count, rng, |_, nearby| {
+            1.0 / (1.0 + 4.0 * nearby as f64).powi(2)
+        })
+    }
+}
+
+pub struct EdgeWeighted;
+
+impl BoardGenerator for EdgeWeighted {
+    fn pick(
+        &self,
+        configuration: &GameConfiguration,
+        candidates: Vec<u32>,
+        count: usize,
+        rng: &mut dyn RngCore,
+    ) -> Vec<u32> {
+        let (width, height) = (configuration.w(), configuration.h());
+        pick_weighted(configuration, candidates, count, rng, |position, _| {
+            let (row, col) = (position / width, position % width);
+            let distance = row.min(col).min(height - 1 - row).min(width - 1 - col);
+            1.0 / (1.0 + distance as f64)
+        })
+    }
+}
+
+/// Draws `count` distinct values below `bound` that `accept` lets through, drawing again
+/// whenever a value is rejected or already drawn. Only the values drawn are kept, which
+/// suits picks of far fewer values than are accepted, like the mines of a large board.
+pub fn sample_sparse(
+    bound: u64,
+    count: usize,
+    rng: &mut dyn RngCore,
+    accept: impl Fn(u64) -> bool,
+) -> Vec<u64> {
+    let mut drawn = HashSet::with_capacity(count);
+    let mut picked = Vec::with_capacity(count);
+    while picked.len() < count {
+        let value = rng.gen_range(0..bound);
+        if accept(value) && drawn.insert(value) {
+            picked.push(value);
+        }
+    }
+    picked
+}
+
+/// Draws the candidates one after the other, each with a chance proportional to its
+/// weight. The weight gets the position and the number of mines picked so far in the
+/// 3x3 square around it, only the weights around a picked cell change.
+fn pick_weighted(
+    configuration: &GameConfiguration,
+    candidates: Vec<u32>,
+    count: usize,
+    rng: &mut dyn RngCore,
+    weight: impl Fn(u32, u32) -> f64,
+) -> Vec<u32> {
+    let (width, height) = (configuration.w() as i64, configuration.h() as i64);
+    // a position is listed once for every mine its cell can hold
+    let mut indices: HashMap<u32, Vec<usize>> = HashMap::new();
+    for (index, position) in candidates.iter().enumerate() {
+        indices.entry(*position).or_default().push(index);
+    }
+    let mut weights: Vec<f64> = candidates
+        .iter()
+        .map(|position| weight(*position, 0))
+        .collect();
+    let mut sums = Fenwick::new(&weights);
+    let mut nearby: HashMap<u32, u32> = HashMap::new();
+    let mut picked = Vec::with_capacity(count);
+
+    while picked.len() < count && picked.len() < candidates.len() {
+        let target = rng.gen::<f64>() * sums.total();
+        let mut index = sums.find(target);
+        // rounding may point past the last weight
+        if index >= weights.len() || weights[index] == 0.0 {
+            index = weights
+                .iter()
+                .rposition(|weight| *weight > 0.0)
+                .expect("Candidates are left to pick.");
+        }
+        sums.add(index, -weights[index]);
+        weights[index] = 0.0;
+        let position = candidates[index];
+        picked.push(position);
+
+        let (row, col) = (position as i64 / width, position as i64 % width);
+        for i in row - 1..=row + 1 {
+            for j in col - 1..=col + 1 {
+                if !(0..height).contains(&i) || !(0..width).contains(&j) {
+                    continue;
+                }
+                let neighbour = (i * width + j) as u32;
+                let mines = nearby.entry(neighbour).or_default();
+                *mines += 1;
+                for index in indices.get(&neighbour).into_iter().flatten() {
+                    if weights[*index] > 0.0 {
+                        let updated = weight(neighbour, *mines);
+                        sums.add(*index, updated - weights[*index]);
+                        weights[*index] = updated;
+                    }
+                }
+            }
+        }
+    }
+
+    picked
+}
+
+/// Prefix sums of weights, a Fenwick tree, changed and searched in logarithmic time.
+struct Fenwick {
+    tree: Vec<f64>,
+}
+
+impl Fenwick {
+    fn new(weights: &[f64]) -> Self {
+        let mut tree = weights.to_vec();
+        for index in 0..tree.len() {
+            let parent = index | (index + 1);
+            if parent < tree.len() {
+                tree[parent] += tree[index];
+            }
+        }
+        Fenwick { tree }
+    }
+
+    fn add(&mut self, mut index: usize, change: f64) {
+        while index < self.tree.len() {
+            self.tree[index] += change;
+            index |= index + 1;
+        }
+    }
+
+    fn total(&self) -> f64 {
+        let mut sum = 0.0;
+        let mut end = self.tree.len();
+        while end > 0 {
+            sum += self.tree[end - 1];
+            end &= end - 1;
+        }
+        sum
+    }
+
+    /// Index of the first weight whose prefix sum, the weight included, exceeds the
+    /// target.
+    fn find(&self, mut target: f64) -> usize {
+        let mut index = 0;
+        let mut step = self.tree.len().checked_next_power_of_two().unwrap_or(0);
+        while step > 0 {
+            let next = index + step;
+            if next <= self.tree.len() && self.tree[next - 1] <= target {
+                target -= self.tree[next - 1];
+                index = next;
+            }
+            step /= 2;
+        }
+        index
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+    use rand_chacha::ChaCha8Rng;
+
+    use super::*;
+
+    /// Mines picked on the board that have another one next to them.
+    fn crowded_mines(distribution: Distribution) -> usize {
+        let configuration = GameConfiguration::new(30, 30, 90);
+        let candidates: Vec<u32> = (0..900).collect();
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let picked = distribution
+            .generator()
+            .pick(&configuration, candidates, 90, &mut rng);
+        assert_eq!(90, picked.len());
+
+        picked
+            .iter()
+            .filter(|mine| {
+                picked.iter().any(|other| {
+                    let (row, col) = (**mine as i64 / 30, **mine as i64 % 30);
+                    let (other_row, other_col) = (*other as i64 / 30, *other as i64 % 30);
+                    *other != **mine && (row - other_row).abs() <= 1 && (col - other_col).abs() <= 1
+                })
+            })
+            .count()
+    }
+
+    #[test]
+    fn distribution_test() {
+        let uniform = crowded_mines(Distribution::Uniform);
+        assert!(crowded_mines(Distribution::Clustered) > uniform);
+        assert!(crowded_mines(Distribution::AntiClustered) < uniform);
+
+        let configuration = GameConfiguration::new(20, 20, 40);
+        let mut rng = ChaCha8Rng::seed_from_u64(3);
+        let picked = Distribution::EdgeWeighted.generator().pick(
+            &configuration,
+            (0..400).collect(),
+            40,
+            &mut rng,
+        );
+        let on_edge = picked
+            .iter()
+            .filter(|mine| {
+                let (row, col) = (**mine / 20, **mine % 20);
+                row == 0 || col == 0 || row == 19 || col == 19
+            })
+            .count();
+        // the edge is a fifth of the board
+        assert!(on_edge > 8);
+
+        assert_eq!(
+            Ok(Distribution::AntiClustered),
+            Distribution::try_from(&Distribution::AntiClustered.to_string()[..])
+        );
+    }
+
+    #[test]
+    fn symmetric_generation_test() {
+        let configuration = GameConfiguration::new(9, 7, 21);
+        for symmetry in [Symmetry::Mirror, Symmetry::Rotational] {
+            let generator = Symmetric {
+                symmetry,
+                generator: Distribution::Clustered.generator(),
+            };
+            let mut rng = ChaCha8Rng::seed_from_u64(8);
+            let picked = generator.pick(&configuration, (0..63).collect(), 21, &mut rng);
+
+            assert_eq!(21, picked.len());
+            assert_eq!(21, picked.iter().collect::<HashSet<_>>().len());
+            assert!(picked
+                .iter()
+                .all(|position| picked.contains(&symmetry.image(&configuration, *position))));
+        }
+    }
+
+    #[test]
+    fn sample_sparse_test() {
+        let mut rng = ChaCha8Rng::seed_from_u64(4);
+        // odd values only, out of a range far too large to list
+        let picked = sample_sparse(1 << 40, 50, &mut rng, |value| value % 2 == 1);
+        assert_eq!(50, picked.len());
+        assert_eq!(50, picked.iter().collect::<HashSet<_>>().len());
+        assert!(picked
+            .iter()
+            .all(|value| value % 2 == 1 && *value < 1 << 40));
+
+        let mut again = ChaCha8Rng::seed_from_u64(4);
+        assert_eq!(
+            picked,
+            sample_sparse(1 << 40, 50, &mut again, |value| value % 2 == 1)
+        );
+    }
+
+    #[test]
+    fn fenwick_test() {
+        let mut sums = Fenwick::new(&[1.0, 2.0, 0.5, 4.0, 2.5]);
+        assert_eq!(10.0, sums.total());
+        assert_eq!(0, sums.find(0.5));
+        assert_eq!(1, sums.find(1.0));
+        assert_eq!(3, sums.find(3.5));
+        assert_eq!(4, sums.find(9.9));
+
+        // a weight taken out is skipped
+        sums.add(3, -4.0);
+        assert_eq!(6.0, sums.total());
+        assert_eq!(4, sums.find(3.5));
+    }
+}
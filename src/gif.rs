@@ -0,0 +1,152 @@
+//! Replays as animated GIFs, a frame per move in the classic look of the image exports.
+//! Every frame is shown as long as the player took for the next move, within limits, and
+//! a lost game ends on a frame showing the mines.
+
+use std::{collections::HashMap, fmt::Display};
+
+use gif::{Encoder, Frame, Repeat};
+
+use crate::{
+    replay::{Replay, ReplayError},
+    tiles::{self, Canvas, Rgb, BACKGROUND, BLACK, FACE, LIGHT, NUMBER_COLOURS, RED, SHADOW},
+    BoardCommand, GameResolve,
+};
+
+/// Shortest and longest time a frame is shown, in hundredths of a second.
+const MIN_DELAY: u64 = 10;
+const MAX_DELAY: u64 = 200;
+
+/// Time the last frame is shown before the animation starts over.
+const FINAL_DELAY: u64 = 300;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum GifError {
+    Replay(ReplayError),
+    /// The board does not fit into the 65535 pixels a GIF may be wide or high.
+    TooLarge,
+}
+
+impl Display for GifError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GifError::Replay(err) => write!(f, "the replay is invalid, {}", err),
+            GifError::TooLarge => write!(f, "the board is too large for a GIF"),
+        }
+    }
+}
+
+/// The replay as an animated GIF, looping forever.
+pub fn animate(replay: &Replay) -> Result<Vec<u8>, GifError> {
+    let resolve = replay.simulate().map_err(GifError::Replay)?;
+    let mut game_board = replay.board().map_err(GifError::Replay)?;
+    let (width, height) = game_board.get_dimensions();
+    let side = |cells: u32| u16::try_from(cells as usize * tiles::TILE).ok();
+    let (Some(width), Some(height)) = (side(width), side(height)) else {
+        return Err(GifError::TooLarge);
+    };
+
+    let palette = palette();
+    let mut table = vec![0u8; palette.len() * 3];
+    for (colour, index) in &palette {
+        let offset = *index as usize * 3;
+        table[offset..offset + 3].copy_from_slice(colour);
+    }
+
+    let mut gif = vec![];
+    let mut encoder =
+        Encoder::new(&mut gif, width, height, &table).expect("GIFs are written to memory.");
+    encoder
+        .set_repeat(Repeat::Infinite)
+        .expect("GIFs are written to memory.");
+    let mut previous_ms = 0;
+    for (index, replay_move) in replay.moves.iter().enumerate() {
+        let delay =
+            (replay_move.time_ms.saturating_sub(previous_ms) / 10).clamp(MIN_DELAY, MAX_DELAY);
+        write_frame(&mut encoder, &tiles::draw(&game_board), &palette, delay);
+        previous_ms = replay_move.time_ms;
+
+        let command = BoardCommand::try_from(&replay_move.command[..])
+            .map_err(|_| GifError::Replay(ReplayError::MalformedCommand(index)))?;
+        game_board.manipulate_cell(command);
+    }
+    if resolve == GameResolve::MineHit {
+        game_board.set_show_mines(true);
+    }
+    write_frame(
+        &mut encoder,
+        &tiles::draw(&game_board),
+        &palette,
+        FINAL_DELAY,
+    );
+    drop(encoder);
+    Ok(gif)
+}
+
+/// Index of every colour the tiles are drawn in.
+fn palette() -> HashMap<Rgb, u8> {
+    let mut palette = HashMap::new();
+    let colours = [BACKGROUND, SHADOW, FACE, LIGHT, BLACK, RED];
+    for colour in colours.iter().chain(&NUMBER_COLOURS) {
+        let index = palette.len() as u8;
+        palette.entry(*colour).or_insert(index);
+    }
+    palette
+}
+
+fn write_frame(
+    encoder: &mut Encoder<&mut Vec<u8>>,
+    canvas: &Canvas,
+    palette: &HashMap<Rgb, u8>,
+    delay: u64,
+) {
+    let indices: Vec<u8> = canvas
+        .pixels
+        .chunks(3)
+        .map(|pixel| palette.get(pixel).copied().unwrap_or_default())
+        .collect();
+    let mut frame =
+        Frame::from_indexed_pixels(canvas.width as u16, canvas.height as u16, indices, None);
+    frame.delay = delay as u16;
+    encoder
+        .write_frame(&frame)
+        .expect("GIFs are written to memory.");
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{layout::Layout, Coordinate, GameBoard};
+
+    #[test]
+    fn animate_test() {
+        let layout = Layout::try_from("*##\n###\n").unwrap();
+        let mut replay = Replay::of(&GameBoard::from_layout(layout));
+        replay.record(
+            BoardCommand::SetMarkNote(Coordinate(0, 0)),
+            Duration::from_millis(50),
+        );
+        replay.record(
+            BoardCommand::Explore(Coordinate(1, 2)),
+            Duration::from_secs(9),
+        );
+        let gif = animate(&replay).unwrap();
+
+        assert_eq!(b"GIF89a", &gif[..6]);
+        let mut decoder = gif::DecodeOptions::new().read_info(&gif[..]).unwrap();
+        assert_eq!((48, 32), (decoder.width(), decoder.height()));
+        // a frame for the board before every move and one for the end
+        let mut delays = vec![];
+        while let Some(frame) = decoder.read_next_frame().unwrap() {
+            delays.push(frame.delay);
+        }
+        assert_eq!(vec![10, 200, 300], delays);
+
+        replay.moves[1].command = "explode".to_string();
+        assert_eq!(
+            Err(GifError::Replay(ReplayError::MalformedCommand(1))),
+            animate(&replay)
+        );
+    }
+}
@@ -0,0 +1,468 @@
+//! gRPC server of `proto/minesweeper.proto`, for backends in other languages and bot
+//! tournaments. Every call is handed to a [`Session`] of the json protocol, so both answer
+//! alike: games started by `NewGame` are kept under a random id that only their client
+//! knows, `Play` plays one game of its own for as long as the stream is open.
+//!
+//! A kept game is dropped once it is left alone for [`IDLE_TIMEOUT`], or for
+//! [`FINISHED_TIMEOUT`] after it is over. Boards of more than [`MAX_NETWORK_CELLS`] cells
+//! are refused, as are new games while [`MAX_GAMES`] are kept.
+
+use std::{
+    collections::HashMap,
+    io,
+    net::{SocketAddr, ToSocketAddrs},
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use tokio::sync::mpsc;
+use tokio_stream::wrappers::ReceiverStream;
+use tonic::{Request, Response, Status, Streaming};
+
+use crate::{
+    generator::{Distribution, Symmetry},
+    protocol::{self, CellView, Session, MAX_NETWORK_CELLS},
+    Adjacency, BoardCommand, Coordinate, GameConfiguration,
+};
+
+/// Messages and service generated from `proto/minesweeper.proto`.
+pub mod proto {
+    tonic::include_proto!("minesweeper.v1");
+}
+
+use proto::minesweeper_server::{Minesweeper, MinesweeperServer};
+
+/// Games a server keeps at most, each holds a whole board.
+pub const MAX_GAMES: usize = 1_000;
+/// How long a running game is kept without a call for it.
+pub const IDLE_TIMEOUT: Duration = Duration::from_secs(15 * 60);
+/// How long a game that is over is kept for a last look at its board.
+pub const FINISHED_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Game started by `NewGame` and when its client last called for it.
+struct KeptGame {
+    session: Session,
+    last_used: Instant,
+}
+
+impl KeptGame {
+    fn is_expired(&self, now: Instant) -> bool {
+        let timeout = match self.session.status() {
+            Some(protocol::Status::Playing) => IDLE_TIMEOUT,
+            _ => FINISHED_TIMEOUT,
+        };
+        now.saturating_duration_since(self.last_used) > timeout
+    }
+}
+
+/// Service of the games started by `NewGame`.
+#[derive(Default)]
+pub struct Games {
+    games: Mutex<HashMap<String, KeptGame>>,
+}
+
+impl From<proto::Adjacency> for Adjacency {
+    fn from(adjacency: proto::Adjacency) -> Self {
+        match adjacency {
+            proto::Adjacency::Standard => Adjacency::Standard,
+            proto::Adjacency::Knight => Adjacency::Knight,
+            proto::Adjacency::Extended => Adjacency::Extended,
+        }
+    }
+}
+
+impl From<proto::Distribution> for Distribution {
+    fn from(distribution: proto::Distribution) -> Self {
+        match distribution {
+            proto::Distribution::Uniform => Distribution::Uniform,
+            proto::Distribution::Clustered => Distribution::Clustered,
+            proto::Distribution::AntiClustered => Distribution::AntiClustered,
+            proto::Distribution::EdgeWeighted => Distribution::EdgeWeighted,
+        }
+    }
+}
+
+impl From<proto::Symmetry> for Symmetry {
+    fn from(symmetry: proto::Symmetry) -> Self {
+        match symmetry {
+            proto::Symmetry::Asymmetric => Symmetry::Asymmetric,
+            proto::Symmetry::Mirror => Symmetry::Mirror,
+            proto::Symmetry::Rotational => Symmetry::Rotational,
+        }
+    }
+}
+
+impl From<protocol::Status> for proto::Status {
+    fn from(status: protocol::Status) -> Self {
+        match status {
+            protocol::Status::Playing => proto::Status::Playing,
+            protocol::Status::Won => proto::Status::Won,
+            protocol::Status::Lost => proto::Status::Lost,
+            protocol::Status::Quit => proto::Status::Quit,
+        }
+    }
+}
+
+impl From<CellView> for proto::Cell {
+    fn from(cell: CellView) -> Self {
+        use proto::{cell::State, Unit};
+
+        let state = match cell {
+            CellView::Hidden => State::Hidden(Unit {}),
+            CellView::Flag => State::Flag(Unit {}),
+            CellView::Note => State::Note(Unit {}),
+            CellView::Mine => State::Mine(Unit {}),
+            CellView::Explored { mines } => State::Explored(mines.into()),
+            CellView::Void => State::Void(Unit {}),
+        };
+        proto::Cell { state: Some(state) }
+    }
+}
+
+fn configuration(configuration: proto::Configuration) -> Result<GameConfiguration, Status> {
+    let mines_per_cell = match configuration.mines_per_cell {
+        Some(mines) => {
+            u8::try_from(mines).map_err(|_| Status::invalid_argument("too many mines per cell"))?
+        }
+        None => 1,
+    };
+    Ok(GameConfiguration::new(
+        configuration.width,
+        configuration.height,
+        configuration.mines,
+    )
+    .with_wrap_edges(configuration.wrap_edges)
+    .with_adjacency(configuration.adjacency().into())
+    .with_mines_per_cell(mines_per_cell)
+    .with_anti_mines(configuration.anti_mines)
+    .with_distribution(configuration.distribution().into())
+    .with_symmetry(configuration.symmetry().into()))
+}
+
+fn coordinate(coordinate: Option<proto::Coordinate>) -> Result<Coordinate, Status> {
+    coordinate
+        .map(|proto::Coordinate { x, y }| Coordinate(x, y))
+        .ok_or_else(|| Status::invalid_argument("missing coordinate"))
+}
+
+fn command(command: Option<proto::Command>) -> Result<BoardCommand, Status> {
+    use proto::command::Command;
+
+    match command.and_then(|command| command.command) {
+        Some(Command::Explore(cell)) => Ok(BoardCommand::Explore(coordinate(Some(cell))?)),
+        Some(Command::Flag(proto::Flag { cell, count })) => {
+            let cell = coordinate(cell)?;
+            match count {
+                Some(count) => i8::try_from(count)
+                    .map(|count| BoardCommand::SetMarkFlagCount(cell, count))
+                    .map_err(|_| Status::invalid_argument("too many mines flagged")),
+                None => Ok(BoardCommand::SetMarkFlag(cell)),
+            }
+        }
+        Some(Command::Note(cell)) => Ok(BoardCommand::SetMarkNote(coordinate(Some(cell))?)),
+        Some(Command::Clear(cell)) => Ok(BoardCommand::ClearMark(coordinate(Some(cell))?)),
+        Some(Command::Quit(_)) => Ok(BoardCommand::Quit),
+        None => Err(Status::invalid_argument("missing command")),
+    }
+}
+
+/// The game of the response, or the error it holds as an invalid argument.
+fn game(id: &str, response: protocol::Response) -> Result<proto::Game, Status> {
+    match response {
+        protocol::Response::Board { seed, status, view } => Ok(proto::Game {
+            id: Some(proto::GameId { id: id.to_string() }),
+            seed,
+            status: proto::Status::from(status).into(),
+            board: Some(proto::Board {
+                width: view.width,
+                height: view.height,
+                cells: view.cells.into_iter().flatten().map(Into::into).collect(),
+            }),
+        }),
+        protocol::Response::Error { message } => Err(Status::invalid_argument(message)),
+    }
+}
+
+fn new_game(session: &mut Session, request: proto::NewGameRequest) -> protocol::Response {
+    match request.configuration.map(configuration) {
+        Some(Ok(configuration)) => session.new_game(configuration, request.seed),
+        Some(Err(status)) => protocol::Response::error(status.message()),
+        None => protocol::Response::error("missing configuration"),
+    }
+}
+
+fn play(session: &mut Session, request: proto::PlayRequest) -> proto::Event {
+    use proto::{event::Event, play_request::Request};
+
+    let response = match request.request {
+        Some(Request::NewGame(request)) => new_game(session, request),
+        Some(Request::Command(request)) => match command(Some(request)) {
+            Ok(command) => session.apply(command),
+            Err(status) => protocol::Response::error(status.message()),
+        },
+        None => protocol::Response::error("missing request"),
+    };
+    let event = match game("", response) {
+        Ok(game) => Event::Board(game),
+        Err(status) => Event::Error(status.message().to_string()),
+    };
+    proto::Event { event: Some(event) }
+}
+
+impl Games {
+    fn with_session(
+        &self,
+        id: Option<proto::GameId>,
+        f: impl FnOnce(&mut Session) -> protocol::Response,
+    ) -> Result<Response<proto::Game>, Status> {
+        let id = id
+            .ok_or_else(|| Status::invalid_argument("missing game id"))?
+            .id;
+        let now = Instant::now();
+        let mut games = self.games.lock().unwrap();
+        let kept = games
+            .get_mut(&id)
+            .filter(|kept| !kept.is_expired(now))
+            .ok_or_else(|| Status::not_found(format!("no game {}", id)))?;
+        kept.last_used = now;
+        game(&id, f(&mut kept.session)).map(Response::new)
+    }
+
+    /// Drops the games that were left alone for too long.
+    fn evict(&self, now: Instant) {
+        self.games
+            .lock()
+            .unwrap()
+            .retain(|_, kept| !kept.is_expired(now));
+    }
+}
+
+#[tonic::async_trait]
+impl Minesweeper for Games {
+    async fn new_game(
+        &self,
+        request: Request<proto::NewGameRequest>,
+    ) -> Result<Response<proto::Game>, Status> {
+        let mut session = Session::with_max_cells(MAX_NETWORK_CELLS);
+        let response = new_game(&mut session, request.into_inner());
+        // the id is all a client needs to play a game, it must not be guessed
+        let id = format!("{:032x}", rand::random::<u128>());
+        let game = game(&id, response)?;
+
+        let now = Instant::now();
+        self.evict(now);
+        let mut games = self.games.lock().unwrap();
+        if games.len() >= MAX_GAMES {
+            return Err(Status::resource_exhausted("too many games are running"));
+        }
+        games.insert(
+            id,
+            KeptGame {
+                session,
+                last_used: now,
+            },
+        );
+        Ok(Response::new(game))
+    }
+
+    async fn apply(
+        &self,
+        request: Request<proto::ApplyRequest>,
+    ) -> Result<Response<proto::Game>, Status> {
+        let request = request.into_inner();
+        let command = command(request.command)?;
+        self.with_session(request.game, |session| session.apply(command))
+    }
+
+    async fn get_board(
+        &self,
+        request: Request<proto::GameId>,
+    ) -> Result<Response<proto::Game>, Status> {
+        self.with_session(Some(request.into_inner()), |session| session.board())
+    }
+
+    type PlayStream = ReceiverStream<Result<proto::Event, Status>>;
+
+    async fn play(
+        &self,
+        request: Request<Streaming<proto::PlayRequest>>,
+    ) -> Result<Response<Self::PlayStream>, Status> {
+        let mut requests = request.into_inner();
+        let (events, stream) = mpsc::channel(4);
+        tokio::spawn(async move {
+            let mut session = Session::with_max_cells(MAX_NETWORK_CELLS);
+            loop {
+                let event = match requests.message().await {
+                    Ok(Some(request)) => Ok(play(&mut session, request)),
+                    Ok(None) => break,
+                    Err(status) => Err(status),
+                };
+                let failed = event.is_err();
+                if events.send(event).await.is_err() || failed {
+                    break;
+                }
+            }
+        });
+        Ok(Response::new(ReceiverStream::new(stream)))
+    }
+}
+
+/// Serves the games on the address until the server fails.
+pub fn serve<A: ToSocketAddrs>(address: A) -> io::Result<()> {
+    let address: SocketAddr = address
+        .to_socket_addrs()?
+        .next()
+        .ok_or_else(|| io::Error::other("the address resolves to nothing"))?;
+
+    tokio::runtime::Runtime::new()?.block_on(async {
+        tonic::transport::Server::builder()
+            .add_service(MinesweeperServer::new(Games::default()))
+            .serve(address)
+            .await
+            .map_err(io::Error::other)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn new_game_request(width: u32, height: u32, mines: u32) -> proto::NewGameRequest {
+        proto::NewGameRequest {
+            configuration: Some(proto::Configuration {
+                width,
+                height,
+                mines,
+                ..Default::default()
+            }),
+            seed: Some(5),
+        }
+    }
+
+    fn explore(x: u32, y: u32) -> proto::Command {
+        proto::Command {
+            command: Some(proto::command::Command::Explore(proto::Coordinate { x, y })),
+        }
+    }
+
+    #[tokio::test]
+    async fn games_test() {
+        let games = Games::default();
+        let game = games
+            .new_game(Request::new(new_game_request(3, 3, 0)))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(proto::Status::Playing, game.status());
+        let board = game.board.unwrap();
+        assert_eq!((3, 3, 9), (board.width, board.height, board.cells.len()));
+        assert_eq!(
+            Some(proto::cell::State::Hidden(proto::Unit {})),
+            board.cells[0].state
+        );
+
+        let id = game.id.unwrap();
+        let game = games
+            .apply(Request::new(proto::ApplyRequest {
+                game: Some(id.clone()),
+                command: Some(explore(1, 1)),
+            }))
+            .await
+            .unwrap()
+            .into_inner();
+        assert_eq!(proto::Status::Won, game.status());
+        assert_eq!(
+            Some(proto::cell::State::Explored(0)),
+            game.board.unwrap().cells[4].state
+        );
+        let board = games.get_board(Request::new(id)).await.unwrap();
+        assert_eq!(proto::Status::Won, board.into_inner().status());
+
+        let status = games
+            .new_game(Request::new(new_game_request(1_000, 1_000, 1)))
+            .await
+            .unwrap_err();
+        assert_eq!(tonic::Code::InvalidArgument, status.code());
+        let status = games
+            .get_board(Request::new(proto::GameId {
+                id: "nonsense".to_string(),
+            }))
+            .await
+            .unwrap_err();
+        assert_eq!(tonic::Code::NotFound, status.code());
+    }
+
+    #[tokio::test]
+    async fn eviction_test() {
+        let games = Games::default();
+        let mut ids = Vec::new();
+        for _ in 0..2 {
+            let game = games
+                .new_game(Request::new(new_game_request(3, 3, 0)))
+                .await
+                .unwrap()
+                .into_inner();
+            ids.push(game.id.unwrap());
+        }
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(32, ids[0].id.len());
+
+        // the first game is won and over, the second one still runs
+        games
+            .apply(Request::new(proto::ApplyRequest {
+                game: Some(ids[0].clone()),
+                command: Some(explore(1, 1)),
+            }))
+            .await
+            .unwrap();
+        let now = Instant::now();
+        games.evict(now + FINISHED_TIMEOUT * 2);
+        assert_eq!(1, games.games.lock().unwrap().len());
+        assert!(games.games.lock().unwrap().contains_key(&ids[1].id));
+        games.evict(now + IDLE_TIMEOUT * 2);
+        assert!(games.games.lock().unwrap().is_empty());
+    }
+
+    #[test]
+    fn play_test() {
+        use proto::{event::Event, play_request::Request};
+
+        let mut session = Session::with_max_cells(MAX_NETWORK_CELLS);
+        let event = play(
+            &mut session,
+            proto::PlayRequest {
+                request: Some(Request::Command(explore(0, 0))),
+            },
+        );
+        assert!(matches!(event.event, Some(Event::Error(_))));
+
+        play(
+            &mut session,
+            proto::PlayRequest {
+                request: Some(Request::NewGame(new_game_request(4, 3, 0))),
+            },
+        );
+        let event = play(
+            &mut session,
+            proto::PlayRequest {
+                request: Some(Request::Command(explore(3, 4))),
+            },
+        );
+        assert_eq!(
+            Some(Event::Error(
+                "coordinate is outside of the board".to_string()
+            )),
+            event.event
+        );
+        let event = play(
+            &mut session,
+            proto::PlayRequest {
+                request: Some(Request::Command(explore(2, 3))),
+            },
+        );
+        let Some(Event::Board(game)) = event.event else {
+            panic!("command should respond with the board");
+        };
+        assert_eq!(proto::Status::Won, game.status());
+    }
+}
@@ -0,0 +1,139 @@
+//! Where on the board the lost games of the log hit their mine. Boards of every size are
+//! scaled onto one grid, so a habit like guessing in the corners shows up across
+//! difficulties.
+
+use std::fmt::Display;
+
+use crate::statistics::GameRecord;
+
+/// Rows and columns of the grid the boards are scaled onto.
+const SIZE: usize = 10;
+
+/// Shades from the fewest hits to the most.
+const SHADES: [char; 5] = [' ', '░', '▒', '▓', '█'];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Heatmap {
+    /// Mine hits of every region, row by row.
+    hits: [[u32; SIZE]; SIZE],
+    games: u32,
+}
+
+impl Heatmap {
+    pub fn of(games: &[GameRecord]) -> Self {
+        let mut heatmap = Heatmap {
+            hits: [[0; SIZE]; SIZE],
+            games: 0,
+        };
+        for game in games {
+            let Some((row, col)) = game.mine_hit else {
+                continue;
+            };
+            let (width, height) = (game.configuration.w(), game.configuration.h());
+            if row >= height || col >= width {
+                continue;
+            }
+            // the middle of the cell decides its region, so small boards spread evenly
+            let region = |cell: u32, cells: u32| {
+                ((2 * u64::from(cell) + 1) * SIZE as u64 / (2 * u64::from(cells))) as usize
+            };
+            heatmap.hits[region(row, height)][region(col, width)] += 1;
+            heatmap.games += 1;
+        }
+        heatmap
+    }
+
+    /// Lost games that hit a mine on a known cell.
+    pub fn games(&self) -> u32 {
+        self.games
+    }
+
+    pub fn hits(&self, row: usize, col: usize) -> u32 {
+        self.hits[row][col]
+    }
+}
+
+impl Display for Heatmap {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let most = self
+            .hits
+            .iter()
+            .flatten()
+            .copied()
+            .max()
+            .unwrap_or_default();
+        writeln!(
+            f,
+            "Mines hit in {} games, by region of the board",
+            self.games
+        )?;
+        writeln!(f, "+{}+", "-".repeat(SIZE * 2))?;
+        for row in &self.hits {
+            let shades: String = row
+                .iter()
+                .map(|&hits| {
+                    // any hit is shown, the most hit regions are solid
+                    let level = if hits == 0 {
+                        0
+                    } else {
+                        (hits * (SHADES.len() as u32 - 1)).div_ceil(most) as usize
+                    };
+                    SHADES[level].to_string().repeat(2)
+                })
+                .collect();
+            writeln!(f, "|{}|", shades)?;
+        }
+        writeln!(f, "+{}+", "-".repeat(SIZE * 2))?;
+        writeln!(
+            f,
+            "{} fewest to most hits, at most {} in a region",
+            SHADES[1..].iter().collect::<String>(),
+            most
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{statistics::Outcome, GameConfiguration};
+
+    fn lost(configuration: GameConfiguration, mine_hit: Option<(u32, u32)>) -> GameRecord {
+        GameRecord {
+            day: 0,
+            configuration,
+            seed: 0,
+            outcome: Outcome::Lost,
+            time_ms: 0,
+            flags_placed: 0,
+            cells_revealed: 0,
+            forgiven: false,
+            assisted: false,
+            mine_hit,
+        }
+    }
+
+    #[test]
+    fn heatmap_test() {
+        let heatmap = Heatmap::of(&[
+            lost(GameConfiguration::beginner(), Some((0, 0))),
+            lost(GameConfiguration::expert(), Some((0, 1))),
+            lost(GameConfiguration::expert(), Some((15, 29))),
+            lost(GameConfiguration::expert(), Some((8, 15))),
+            lost(GameConfiguration::expert(), None),
+            lost(GameConfiguration::beginner(), Some((9, 9))),
+        ]);
+
+        // the top left corner of both boards, the bottom right one and the middle, a hit
+        // outside the board is left out
+        assert_eq!(4, heatmap.games());
+        assert_eq!(2, heatmap.hits(0, 0));
+        assert_eq!(1, heatmap.hits(9, 9));
+        assert_eq!(1, heatmap.hits(5, 5));
+
+        let shown = heatmap.to_string();
+        assert!(shown.starts_with("Mines hit in 4 games"));
+        assert!(shown.contains("|██  "));
+        assert!(shown.contains("  ▒▒|\n"));
+    }
+}
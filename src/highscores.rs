@@ -0,0 +1,371 @@
+//! Highscore tables, a table keeps the fastest winning times.
+
+use std::{collections::BTreeMap, fmt::Display, io, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{storage, Efficiency, GameConfiguration};
+
+pub(crate) const HIGHSCORES_FILE: &str = "highscores.json";
+pub(crate) const DAILY_HIGHSCORES_FILE: &str = "daily_highscores.json";
+const TABLE_SIZE: usize = 10;
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct HighscoreEntry {
+    pub name: String,
+    pub time_ms: u64,
+    /// Missing for entries of older versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub efficiency: Option<Efficiency>,
+    /// Whether satisfied numbers were chorded for the player.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub assisted: bool,
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Debug)]
+pub struct HighscoreTable {
+    entries: Vec<HighscoreEntry>,
+}
+
+impl HighscoreTable {
+    pub fn entries(&self) -> &[HighscoreEntry] {
+        &self.entries
+    }
+
+    /// Whether the time would make it into the table.
+    pub fn qualifies(&self, time: Duration) -> bool {
+        self.entries.len() < TABLE_SIZE
+            || self
+                .entries
+                .last()
+                .is_some_and(|slowest| (time.as_millis() as u64) < slowest.time_ms)
+    }
+
+    pub fn insert(
+        &mut self,
+        name: String,
+        time: Duration,
+        efficiency: Option<Efficiency>,
+        assisted: bool,
+    ) {
+        let time_ms = time.as_millis() as u64;
+        let position = self
+            .entries
+            .partition_point(|entry| entry.time_ms <= time_ms);
+        self.entries.insert(
+            position,
+            HighscoreEntry {
+                name,
+                time_ms,
+                efficiency,
+                assisted,
+            },
+        );
+        self.entries.truncate(TABLE_SIZE);
+    }
+
+    /// Adds the entries of the other table, e.g. of another computer, and returns how many
+    /// made it into this one. An entry of the same player and time is the same game, which
+    /// keeps the efficiency either copy knows and counts as assisted if either says so.
+    pub fn merge(&mut self, other: &HighscoreTable) -> usize {
+        let known: Vec<(String, u64)> = self
+            .entries
+            .iter()
+            .map(|entry| (entry.name.clone(), entry.time_ms))
+            .collect();
+        for entry in &other.entries {
+            match self
+                .entries
+                .iter_mut()
+                .find(|own| own.name == entry.name && own.time_ms == entry.time_ms)
+            {
+                Some(own) => {
+                    own.efficiency = own.efficiency.or(entry.efficiency);
+                    own.assisted |= entry.assisted;
+                }
+                None => {
+                    let position = self
+                        .entries
+                        .partition_point(|own| own.time_ms <= entry.time_ms);
+                    self.entries.insert(position, entry.clone());
+                }
+            }
+        }
+        self.entries.truncate(TABLE_SIZE);
+        self.entries
+            .iter()
+            .filter(|entry| !known.contains(&(entry.name.clone(), entry.time_ms)))
+            .count()
+    }
+}
+
+impl Display for HighscoreTable {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (rank, entry) in self.entries.iter().enumerate() {
+            write!(
+                f,
+                "{:>3}. {:<20}{:>10.2} s",
+                rank + 1,
+                entry.name,
+                entry.time_ms as f64 / 1000.0
+            )?;
+            if let Some(efficiency) = entry.efficiency {
+                write!(
+                    f,
+                    "{:>8.2} 3BV/s{:>5}%",
+                    efficiency.three_bv_per_second(Duration::from_millis(entry.time_ms)),
+                    efficiency.percent()
+                )?;
+            }
+            if entry.assisted {
+                write!(f, "  assisted")?;
+            }
+            writeln!(f)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// A table for every difficulty, keyed by [`GameConfiguration::key`].
+#[derive(Serialize, Deserialize, Default)]
+pub struct Highscores {
+    tables: BTreeMap<String, HighscoreTable>,
+}
+
+/// Merges every table of the other tables into the one of the same key, see
+/// [`HighscoreTable::merge`].
+fn merge_tables(
+    tables: &mut BTreeMap<String, HighscoreTable>,
+    other: &BTreeMap<String, HighscoreTable>,
+) -> usize {
+    other
+        .iter()
+        .map(|(key, table)| tables.entry(key.clone()).or_default().merge(table))
+        .sum()
+}
+
+impl Highscores {
+    pub fn load() -> Self {
+        storage::load_json(HIGHSCORES_FILE)
+    }
+
+    /// Adds the entries of another highscore file and returns how many were new.
+    pub fn merge(&mut self, other: &Highscores) -> usize {
+        merge_tables(&mut self.tables, &other.tables)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::save_json(HIGHSCORES_FILE, self)
+    }
+
+    /// All tables ordered by key.
+    pub fn tables(&self) -> impl Iterator<Item = (&String, &HighscoreTable)> {
+        self.tables.iter()
+    }
+
+    pub fn table_mut(&mut self, configuration: &GameConfiguration) -> &mut HighscoreTable {
+        self.tables.entry(configuration.key()).or_default()
+    }
+
+    /// Titled tables in the order they are shown, the classic difficulties come first
+    /// even without entries, followed by the custom ones.
+    pub fn tables_for_display(&self) -> Vec<(String, Option<&HighscoreTable>)> {
+        let classic = [
+            GameConfiguration::beginner(),
+            GameConfiguration::intermediate(),
+            GameConfiguration::expert(),
+        ];
+
+        let mut tables: Vec<(String, Option<&HighscoreTable>)> = classic
+            .iter()
+            .map(|configuration| {
+                (
+                    configuration
+                        .difficulty_name()
+                        .unwrap_or_default()
+                        .to_string(),
+                    self.tables.get(&configuration.key()),
+                )
+            })
+            .collect();
+
+        let classic_keys: Vec<String> = classic.iter().map(GameConfiguration::key).collect();
+        tables.extend(
+            self.tables
+                .iter()
+                .filter(|(key, _)| !classic_keys.contains(key))
+                .map(|(key, table)| (format!("Custom {}", key), Some(table))),
+        );
+
+        tables
+    }
+}
+
+/// Daily challenge tables, keyed by the challenge date.
+#[derive(Serialize, Deserialize, Default)]
+pub struct DailyHighscores {
+    tables: BTreeMap<String, HighscoreTable>,
+}
+
+impl DailyHighscores {
+    pub fn load() -> Self {
+        storage::load_json(DAILY_HIGHSCORES_FILE)
+    }
+
+    /// Adds the entries of another daily highscore file and returns how many were new.
+    pub fn merge(&mut self, other: &DailyHighscores) -> usize {
+        merge_tables(&mut self.tables, &other.tables)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::save_json(DAILY_HIGHSCORES_FILE, self)
+    }
+
+    pub fn table(&self, date: &str) -> Option<&HighscoreTable> {
+        self.tables.get(date)
+    }
+
+    /// All tables ordered by date.
+    pub fn tables(&self) -> impl Iterator<Item = (&String, &HighscoreTable)> {
+        self.tables.iter()
+    }
+
+    pub fn table_mut(&mut self, date: &str) -> &mut HighscoreTable {
+        self.tables.entry(date.to_string()).or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn classic_tables_come_first_test() {
+        let mut highscores = Highscores::default();
+        highscores
+            .table_mut(&GameConfiguration::new(5, 5, 3))
+            .insert("custom".to_string(), Duration::from_secs(3), None, true);
+        highscores.table_mut(&GameConfiguration::expert()).insert(
+            "expert".to_string(),
+            Duration::from_secs(90),
+            None,
+            false,
+        );
+
+        let titles: Vec<(String, bool)> = highscores
+            .tables_for_display()
+            .into_iter()
+            .map(|(title, table)| (title, table.is_some()))
+            .collect();
+        assert_eq!(
+            vec![
+                ("Beginner".to_string(), false),
+                ("Intermediate".to_string(), false),
+                ("Expert".to_string(), true),
+                ("Custom 5x5-3".to_string(), true),
+            ],
+            titles
+        );
+        assert!(highscores
+            .table_mut(&GameConfiguration::new(5, 5, 3))
+            .to_string()
+            .ends_with("assisted\n"));
+    }
+
+    #[test]
+    fn table_keeps_fastest_entries_test() {
+        let mut table = HighscoreTable::default();
+        for seconds in (1..=TABLE_SIZE as u64).rev() {
+            table.insert(
+                format!("player{}", seconds),
+                Duration::from_secs(seconds + 1),
+                None,
+                false,
+            );
+        }
+
+        assert!(!table.qualifies(Duration::from_secs(20)));
+        assert!(table.qualifies(Duration::from_secs(1)));
+
+        let efficiency = Efficiency {
+            three_bv: 5,
+            clicks: 10,
+            wasted_clicks: 1,
+        };
+        table.insert(
+            "fastest".to_string(),
+            Duration::from_secs(1),
+            Some(efficiency),
+            false,
+        );
+        assert_eq!(TABLE_SIZE, table.entries().len());
+        assert_eq!("fastest", table.entries()[0].name);
+        assert_eq!(
+            (TABLE_SIZE as u64) * 1000,
+            table.entries().last().unwrap().time_ms
+        );
+        assert!(table
+            .to_string()
+            .starts_with("  1. fastest                   1.00 s    5.00 3BV/s   50%\n"));
+    }
+
+    #[test]
+    fn merge_test() {
+        let efficiency = Efficiency {
+            three_bv: 5,
+            clicks: 10,
+            wasted_clicks: 1,
+        };
+        let mut own = Highscores::default();
+        let mut other = Highscores::default();
+        let expert = GameConfiguration::expert();
+        own.table_mut(&expert)
+            .insert("ann".to_string(), Duration::from_secs(90), None, false);
+        own.table_mut(&expert)
+            .insert("bob".to_string(), Duration::from_secs(95), None, false);
+        other.table_mut(&expert).insert(
+            "ann".to_string(),
+            Duration::from_secs(90),
+            Some(efficiency),
+            true,
+        );
+        other
+            .table_mut(&expert)
+            .insert("ann".to_string(), Duration::from_secs(80), None, false);
+        other.table_mut(&GameConfiguration::beginner()).insert(
+            "bob".to_string(),
+            Duration::from_secs(9),
+            None,
+            false,
+        );
+
+        assert_eq!(2, own.merge(&other));
+        let entries = own.table_mut(&expert).entries().to_vec();
+        let times: Vec<(&str, u64)> = entries
+            .iter()
+            .map(|entry| (&entry.name[..], entry.time_ms))
+            .collect();
+        assert_eq!(
+            vec![("ann", 80_000), ("ann", 90_000), ("bob", 95_000)],
+            times
+        );
+        // the same game keeps what either copy knows of it
+        assert_eq!(Some(efficiency), entries[1].efficiency);
+        assert!(entries[1].assisted);
+        assert_eq!(0, own.merge(&other), "merging again adds nothing");
+
+        let mut full = HighscoreTable::default();
+        for seconds in 1..=TABLE_SIZE as u64 {
+            full.insert(
+                "fast".to_string(),
+                Duration::from_secs(seconds),
+                None,
+                false,
+            );
+        }
+        let table = own.table_mut(&expert);
+        assert_eq!(TABLE_SIZE, table.merge(&full), "only the fastest are kept");
+        assert!(table.entries().iter().all(|entry| entry.name == "fast"));
+    }
+}
@@ -0,0 +1,167 @@
+//! Local hot-seat mode, two players alternate commands on one board. Flagging a mine
+//! scores, a wrong flag or a detonation costs points. A detonated mine is flagged for
+//! nobody and the game goes on until the board is finished.
+
+use std::fmt::Display;
+
+use crate::{BoardCell, BoardCommand, CellInfo, Coordinate, GameBoard, GameResolve, Mark};
+
+pub const FLAG_POINTS: i32 = 1;
+pub const WRONG_FLAG_PENALTY: i32 = 1;
+pub const DETONATION_PENALTY: i32 = 5;
+
+pub struct HotSeat {
+    players: [String; 2],
+    scores: [i32; 2],
+    current: usize,
+    claimed_mines: Vec<Coordinate>,
+}
+
+impl HotSeat {
+    pub fn new(players: [String; 2]) -> HotSeat {
+        HotSeat {
+            players,
+            scores: [0; 2],
+            current: 0,
+            claimed_mines: Vec::new(),
+        }
+    }
+
+    pub fn current_player(&self) -> &str {
+        &self.players[self.current]
+    }
+
+    /// Whether the command may be played in turns. Claims and plugin commands are left out,
+    /// they would explore or judge the board without scoring it.
+    pub fn allows(command: BoardCommand) -> bool {
+        !matches!(command, BoardCommand::Claim | BoardCommand::Plugin(_))
+    }
+
+    /// Applies the command of the current player, scores it and hands the turn over.
+    /// Commands that are not [allowed](HotSeat::allows) are ignored and keep the turn.
+    pub fn play_turn(&mut self, game_board: &mut GameBoard, command: BoardCommand) -> GameResolve {
+        if !HotSeat::allows(command) {
+            return GameResolve::Continue;
+        }
+        let (command, points) = match command {
+            BoardCommand::SetMarkFlag(coordinate)
+            | BoardCommand::SetMarkFlagCount(coordinate, _) => {
+                (command, self.flag_points(game_board, coordinate))
+            }
+            // every flag of a range scores on its own
+            BoardCommand::SetMarkFlagRange(from, to) => {
+                let points = from
+                    .rectangle(to)
+                    .filter(|coordinate| !game_board.is_hole(*coordinate))
+                    .map(|coordinate| self.flag_points(game_board, coordinate))
+                    .sum();
+                (command, points)
+            }
+            BoardCommand::Explore(coordinate) => match game_board.get_cell_at(coordinate) {
+                BoardCell::Mine(_) => {
+                    if !self.claimed_mines.contains(&coordinate) {
+                        self.claimed_mines.push(coordinate);
+                    }
+                    let count = game_board.mine_count(coordinate);
+                    (
+                        BoardCommand::SetMarkFlagCount(coordinate, count),
+                        -DETONATION_PENALTY,
+                    )
+                }
+                _ => (command, 0),
+            },
+            _ => (command, 0),
+        };
+
+        let resolve = game_board.manipulate_cell(command);
+        self.scores[self.current] += points;
+        self.current = 1 - self.current;
+        resolve
+    }
+
+    fn flag_points(&mut self, game_board: &GameBoard, coordinate: Coordinate) -> i32 {
+        match game_board.get_cell_at(coordinate) {
+            // Every mine scores once, no matter how often it is flagged.
+            BoardCell::Mine(_) if !self.claimed_mines.contains(&coordinate) => {
+                self.claimed_mines.push(coordinate);
+                FLAG_POINTS
+            }
+            BoardCell::NoMine(CellInfo(Mark::MarkFlag, _))
+            | BoardCell::Mine(_)
+            | BoardCell::Explored(_) => 0,
+            BoardCell::NoMine(_) => -WRONG_FLAG_PENALTY,
+        }
+    }
+
+    /// Player with more points, `None` on a tie.
+    pub fn winner(&self) -> Option<&str> {
+        match self.scores[0].cmp(&self.scores[1]) {
+            std::cmp::Ordering::Greater => Some(&self.players[0]),
+            std::cmp::Ordering::Less => Some(&self.players[1]),
+            std::cmp::Ordering::Equal => None,
+        }
+    }
+}
+
+impl Display for HotSeat {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for (player, score) in self.players.iter().zip(self.scores) {
+            writeln!(f, "{:<16} {:>4} points", player, score)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::GameConfiguration;
+
+    #[test]
+    fn turns_are_scored_test() {
+        let mut game_board = GameBoard::new(GameConfiguration::new(4, 4, 2));
+        game_board.generate_world_from_seed(3);
+        let mut hot_seat = HotSeat::new(["ann".to_string(), "bob".to_string()]);
+
+        let mut mines = Vec::new();
+        let mut safe = Vec::new();
+        for row in 0..4 {
+            for col in 0..4 {
+                match game_board.get_cell_at(Coordinate(row, col)) {
+                    BoardCell::Mine(_) => mines.push(Coordinate(row, col)),
+                    _ => safe.push(Coordinate(row, col)),
+                }
+            }
+        }
+
+        // ann flags a mine, bob flags a safe cell.
+        hot_seat.play_turn(&mut game_board, BoardCommand::SetMarkFlag(mines[0]));
+        hot_seat.play_turn(&mut game_board, BoardCommand::SetMarkFlag(safe[0]));
+        // Flagging the claimed mine again does not score.
+        hot_seat.play_turn(&mut game_board, BoardCommand::ClearMark(mines[0]));
+        hot_seat.play_turn(&mut game_board, BoardCommand::SetMarkFlag(mines[0]));
+        assert_eq!([FLAG_POINTS, -WRONG_FLAG_PENALTY], hot_seat.scores);
+        assert_eq!(Some("ann"), hot_seat.winner());
+
+        // claims are not played in turns, ann keeps hers
+        assert_eq!(
+            GameResolve::Continue,
+            hot_seat.play_turn(&mut game_board, BoardCommand::Claim)
+        );
+        assert_eq!("ann", hot_seat.current_player());
+
+        // a wrong flag keeps the board from being finished, ann clears it
+        hot_seat.play_turn(&mut game_board, BoardCommand::ClearMark(safe[0]));
+        hot_seat.play_turn(&mut game_board, BoardCommand::Pass);
+        // ann detonates the last mine, which finishes the board.
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            hot_seat.play_turn(&mut game_board, BoardCommand::Explore(mines[1]))
+        );
+        assert_eq!(
+            [FLAG_POINTS - DETONATION_PENALTY, -WRONG_FLAG_PENALTY],
+            hot_seat.scores
+        );
+        assert_eq!(Some("bob"), hot_seat.winner());
+    }
+}
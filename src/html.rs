@@ -0,0 +1,219 @@
+//! Finished games as a single HTML file to share, with a small viewer stepping through the
+//! replay. The board is simulated here and every move's view embedded, so the page needs
+//! neither the engine nor a server.
+
+use serde::Serialize;
+
+use crate::{
+    protocol::{CellView, Status},
+    replay::{Replay, ReplayError},
+    BoardCommand, Coordinate, GameBoard, GameResolve,
+};
+
+const TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>Minesweeper replay</title>
+<style>
+body { font-family: sans-serif; background: #e0e0e0; margin: 2em; }
+#board { display: inline-grid; gap: 0; border: 3px solid #808080; background: #808080; }
+#board div { width: 20px; height: 20px; line-height: 20px; text-align: center;
+  font: bold 14px monospace; box-sizing: border-box; }
+.hidden, .flag, .note { background: #c0c0c0; border: 3px outset #ffffff; }
+.open, .mine { background: #c0c0c0; border: 1px solid #a0a0a0; }
+.flag { color: #ff0000; }
+.mine { color: #000000; background: #ff8080; }
+.void { background: #808080; }
+.n1 { color: #0000ff; } .n2 { color: #008000; } .n3 { color: #ff0000; }
+.n4 { color: #000080; } .n5 { color: #800000; } .n6 { color: #008080; }
+.n7 { color: #000000; } .n8 { color: #808080; }
+#controls { margin: 1em 0; }
+#step { width: 20em; vertical-align: middle; }
+</style>
+</head>
+<body>
+<h1 id="title"></h1>
+<div id="board"></div>
+<div id="controls">
+<button id="first">&#x23EE;</button>
+<button id="previous">&#x25C0;</button>
+<button id="play">Play</button>
+<button id="next">&#x25B6;</button>
+<button id="last">&#x23ED;</button>
+<input id="step" type="range" min="0" value="0">
+</div>
+<div id="move"></div>
+<script>
+const game = /*GAME*/;
+const board = document.getElementById("board");
+const step = document.getElementById("step");
+const play = document.getElementById("play");
+let timer = null;
+board.style.gridTemplateColumns = "repeat(" + game.width + ", 20px)";
+step.max = game.frames.length - 1;
+document.getElementById("title").textContent = game.title;
+
+function cell(symbol) {
+  const div = document.createElement("div");
+  if (symbol === ".") { div.className = "hidden"; }
+  else if (symbol === "F") { div.className = "flag"; div.textContent = "⚑"; }
+  else if (symbol === "?") { div.className = "note"; div.textContent = "?"; }
+  else if (symbol === "*") { div.className = "mine"; div.textContent = "✹"; }
+  else if (symbol === " ") { div.className = "void"; }
+  else if (symbol === "0") { div.className = "open"; }
+  else { div.className = "open n" + symbol; div.textContent = symbol; }
+  return div;
+}
+
+function show(index) {
+  const frame = game.frames[index];
+  step.value = index;
+  board.replaceChildren(...Array.from(frame.cells, cell));
+  document.getElementById("move").textContent = index === 0
+    ? "Start, " + (game.frames.length - 1) + " moves"
+    : "Move " + index + ": " + frame.command + " at " + (frame.time_ms / 1000).toFixed(3) + " s"
+      + (index === game.frames.length - 1 ? ", " + game.outcome : "");
+}
+
+function stop() {
+  clearTimeout(timer);
+  timer = null;
+  play.textContent = "Play";
+}
+
+function advance() {
+  const index = Number(step.value);
+  if (index + 1 >= game.frames.length) { stop(); return; }
+  show(index + 1);
+  const wait = index + 2 < game.frames.length
+    ? game.frames[index + 2].time_ms - game.frames[index + 1].time_ms : 0;
+  timer = setTimeout(advance, Math.min(Math.max(wait, 100), 2000));
+}
+
+play.onclick = () => {
+  if (timer !== null) { stop(); return; }
+  if (Number(step.value) + 1 >= game.frames.length) { show(0); }
+  play.textContent = "Pause";
+  timer = setTimeout(advance, 300);
+};
+document.getElementById("first").onclick = () => { stop(); show(0); };
+document.getElementById("previous").onclick = () => { stop(); show(Math.max(Number(step.value) - 1, 0)); };
+document.getElementById("next").onclick = () => { stop(); show(Math.min(Number(step.value) + 1, game.frames.length - 1)); };
+document.getElementById("last").onclick = () => { stop(); show(game.frames.length - 1); };
+step.oninput = () => { stop(); show(Number(step.value)); };
+show(0);
+</script>
+</body>
+</html>
+"#;
+
+#[derive(Serialize)]
+struct Frame {
+    time_ms: u64,
+    command: String,
+    /// A character per cell row by row, see [`symbol`].
+    cells: String,
+}
+
+#[derive(Serialize)]
+struct Game {
+    title: String,
+    width: u32,
+    outcome: &'static str,
+    frames: Vec<Frame>,
+}
+
+/// `.` covered, `F` flagged, `?` noted, `*` a mine, a space a hole and the digit of the
+/// neighbouring mines if explored, `+` for ten or more and `-` for anti-mines.
+fn symbol(view: CellView) -> char {
+    match view {
+        CellView::Hidden => '.',
+        CellView::Flag => 'F',
+        CellView::Note => '?',
+        CellView::Mine => '*',
+        CellView::Void => ' ',
+        CellView::Explored { mines } => match mines {
+            ..=-1 => '-',
+            0..=9 => char::from(b'0' + mines as u8),
+            _ => '+',
+        },
+    }
+}
+
+fn cells(game_board: &GameBoard, status: Status) -> String {
+    let (width, height) = game_board.get_dimensions();
+    (0..height)
+        .flat_map(|row| (0..width).map(move |col| Coordinate(row, col)))
+        .map(|coordinate| symbol(CellView::at(game_board, coordinate, status)))
+        .collect()
+}
+
+/// The page showing the replay, a frame before the first move and one after every move.
+pub fn render(replay: &Replay) -> Result<String, ReplayError> {
+    let resolve = replay.simulate()?;
+    let mut game_board = replay.board()?;
+    let mut frames = vec![Frame {
+        time_ms: 0,
+        command: String::new(),
+        cells: cells(&game_board, Status::Playing),
+    }];
+    for (index, replay_move) in replay.moves.iter().enumerate() {
+        let command = BoardCommand::try_from(&replay_move.command[..])
+            .map_err(|_| ReplayError::MalformedCommand(index))?;
+        let status = game_board.manipulate_cell(command).into();
+        frames.push(Frame {
+            time_ms: replay_move.time_ms,
+            command: replay_move.command.clone(),
+            cells: cells(&game_board, status),
+        });
+    }
+
+    let game = Game {
+        title: format!("Minesweeper {}", replay.configuration.key()),
+        width: replay.configuration.w(),
+        outcome: match resolve {
+            GameResolve::AllMinesDiscovered => "won",
+            GameResolve::MineHit => "lost",
+            GameResolve::Quit => "quit",
+            GameResolve::Continue => "unfinished",
+        },
+        frames,
+    };
+    // a closing tag in a string would end the script early
+    let json = serde_json::to_string(&game)
+        .expect("Serializing the game does not fail.")
+        .replace("</", "<\\/");
+    Ok(TEMPLATE.replace("/*GAME*/", &json))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::layout::Layout;
+
+    #[test]
+    fn render_test() {
+        let layout = Layout::try_from("*##\n###\n").unwrap();
+        let mut replay = Replay::of(&GameBoard::from_layout(layout));
+        replay.record(
+            BoardCommand::Explore(Coordinate(1, 2)),
+            Duration::from_secs(2),
+        );
+        replay.record(
+            BoardCommand::Explore(Coordinate(0, 0)),
+            Duration::from_secs(3),
+        );
+
+        let html = render(&replay).unwrap();
+        assert!(html.starts_with("<!DOCTYPE html>"));
+        assert!(!html.contains("/*GAME*/"));
+        assert!(html.contains(r#""cells":"......""#));
+        assert!(html.contains(r#""cells":".10.10""#));
+        // the mines are shown once the game is lost
+        assert!(html.contains(r#""cells":"*10.10""#));
+        assert!(html.contains(r#""outcome":"lost""#));
+    }
+}
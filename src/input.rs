@@ -0,0 +1,91 @@
+//! Input of the interactive game. The terminal is read directly unless a control socket is
+//! enabled, then reader threads merge the terminal lines with the requests of the tools.
+
+use std::{
+    io::stdin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Receiver},
+        Mutex, OnceLock,
+    },
+    thread,
+};
+
+use minesweeper::{
+    console::{Input, InputSource},
+    control::Control,
+    protocol::Response,
+};
+
+/// The terminal, merged with the requests of the tools if the control socket is enabled.
+pub struct Terminal;
+
+impl InputSource for Terminal {
+    fn next(&mut self) -> Input {
+        next()
+    }
+}
+
+static INPUT: OnceLock<Mutex<Receiver<Input>>> = OnceLock::new();
+static TERMINAL_CLOSED: AtomicBool = AtomicBool::new(false);
+
+/// Merges the requests of the tools into the input, call once at start-up.
+pub fn enable_control(requests: Receiver<Control>) {
+    let (sender, input) = mpsc::channel();
+
+    let terminal = sender.clone();
+    thread::spawn(move || loop {
+        let mut line = String::new();
+        match stdin().read_line(&mut line) {
+            Ok(0) | Err(_) => {
+                TERMINAL_CLOSED.store(true, Ordering::SeqCst);
+                let _ = terminal.send(Input::Line(line));
+                break;
+            }
+            Ok(_) => {
+                if terminal.send(Input::Line(line)).is_err() {
+                    break;
+                }
+            }
+        }
+    });
+
+    thread::spawn(move || {
+        for control in requests {
+            if sender.send(Input::Control(control)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let _ = INPUT.set(Mutex::new(input));
+}
+
+/// Next line of the terminal or request of a tool, a closed terminal reads empty lines.
+pub fn next() -> Input {
+    let Some(input) = INPUT.get() else {
+        let mut line = String::new();
+        stdin().read_line(&mut line).expect("Did not enter string?");
+        return Input::Line(line);
+    };
+
+    let input = input.lock().expect("Input lock is not poisoned.");
+    if TERMINAL_CLOSED.load(Ordering::SeqCst) {
+        return input
+            .try_recv()
+            .unwrap_or_else(|_| Input::Line(String::new()));
+    }
+    input
+        .recv()
+        .expect("Terminal reader keeps the input open until it is closed.")
+}
+
+/// Next line of the terminal, tools are told no game is running meanwhile.
+pub fn read_line() -> String {
+    loop {
+        match next() {
+            Input::Line(line) => return line,
+            Input::Control(control) => control.reply(Response::error("no game is running")),
+        }
+    }
+}
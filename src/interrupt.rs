@@ -0,0 +1,56 @@
+//! Ctrl+C. Instead of killing the process in the middle of a draw, the handler autosaves
+//! the game in progress with the time played so far, restores the terminal and exits.
+
+use std::{
+    io::{self, Write},
+    process,
+    sync::Mutex,
+};
+
+use crate::{clock::GameClock, save};
+
+/// Exit code of a process ended by SIGINT.
+const INTERRUPTED: i32 = 130;
+
+/// Clock of the game in progress, none outside of games.
+static CLOCK: Mutex<Option<GameClock>> = Mutex::new(None);
+
+/// Handles Ctrl+C for the rest of the session.
+#[cfg(not(target_arch = "wasm32"))]
+pub fn install() -> io::Result<()> {
+    ctrlc::set_handler(|| {
+        autosave();
+        restore_terminal();
+        process::exit(INTERRUPTED);
+    })
+    .map_err(io::Error::other)
+}
+
+/// Keeps the clock of the game in progress for the autosave, to be called whenever the
+/// clock is paused or resumed. None once the game is over.
+pub fn track(clock: Option<GameClock>) {
+    *CLOCK.lock().expect("Clock lock is not poisoned.") = clock;
+}
+
+/// Saves the autosave of the last move again with the time played until now.
+fn autosave() {
+    let Some(clock) = *CLOCK.lock().expect("Clock lock is not poisoned.") else {
+        return;
+    };
+    let saved = save::open_slot(save::AUTOSAVE_SLOT).and_then(|(game_board, _)| {
+        save::save_slot(&game_board, save::AUTOSAVE_SLOT, clock.elapsed())
+    });
+    if let Err(err) = saved {
+        tracing::debug!(%err, "nothing autosaved on interrupt");
+    }
+}
+
+/// Leaves the alternate screen and raw mode of the terminal UI and shows the cursor.
+fn restore_terminal() {
+    #[cfg(feature = "tui")]
+    ratatui::restore();
+    let mut stdout = io::stdout();
+    // the console does not use the alternate screen, leaving it does no harm there
+    let _ = writeln!(stdout, "\x1b[?1049l\x1b[?25h");
+    let _ = stdout.flush();
+}
@@ -0,0 +1,370 @@
+//! Layouts are hand made boards, e.g. puzzles. A layout file has one line per row, `#` is
+//! a hidden cell, `*` a mine and `.` a cell that is revealed when the game starts:
+//!
+//! ```text
+//! ..#*
+//! ..##
+//! #*##
+//! ```
+//!
+//! Layouts are built in the editor with [`EditCommand`]s.
+
+use std::{fmt::Display, fs, io, num::ParseIntError, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{Coordinate, GameConfiguration};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LayoutCell {
+    Hidden,
+    Mine,
+    Revealed,
+}
+
+/// Board with every mine and the revealed cells given, kept as the text of the layout
+/// file in replays.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Layout {
+    width: u32,
+    height: u32,
+    cells: Vec<LayoutCell>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LayoutError {
+    Empty,
+    UnevenRows(usize),
+    UnknownSymbol(char),
+    TooLarge,
+}
+
+impl Display for LayoutError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayoutError::Empty => write!(f, "the layout has no cells"),
+            LayoutError::UnevenRows(row) => {
+                write!(f, "row {} is not as long as the first one", row + 1)
+            }
+            LayoutError::UnknownSymbol(symbol) => write!(
+                f,
+                "'{}' is neither a hidden cell '#', a mine '*' nor a revealed cell '.'",
+                symbol
+            ),
+            LayoutError::TooLarge => write!(f, "the layout is too large for a board"),
+        }
+    }
+}
+
+impl Layout {
+    /// Layout of hidden cells without mines.
+    pub fn new(width: u32, height: u32) -> Self {
+        Layout {
+            width,
+            height,
+            cells: vec![LayoutCell::Hidden; width as usize * height as usize],
+        }
+    }
+
+    /// Reads the layout file, parse errors are reported as invalid data.
+    pub fn load(path: &Path) -> io::Result<Layout> {
+        let content = fs::read_to_string(path)?;
+        Layout::try_from(&content[..])
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        fs::write(path, self.to_string())
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    pub fn is_on_layout(&self, coordinate: Coordinate) -> bool {
+        coordinate.0 < self.height && coordinate.1 < self.width
+    }
+
+    pub fn cell(&self, coordinate: Coordinate) -> LayoutCell {
+        self.cells[self.index(coordinate)]
+    }
+
+    /// Configuration of a board of the layout's size and mines.
+    pub fn configuration(&self) -> GameConfiguration {
+        GameConfiguration::new(self.width, self.height, self.mines())
+    }
+
+    pub fn mines(&self) -> u32 {
+        self.cells
+            .iter()
+            .filter(|cell| **cell == LayoutCell::Mine)
+            .count() as u32
+    }
+
+    /// Mines around the coordinate.
+    pub fn neighbour_mines(&self, coordinate: Coordinate) -> u8 {
+        let mut mines = 0;
+        for row in coordinate.0.saturating_sub(1)..=coordinate.0 + 1 {
+            for col in coordinate.1.saturating_sub(1)..=coordinate.1 + 1 {
+                let neighbour = Coordinate(row, col);
+                if neighbour != coordinate
+                    && self.is_on_layout(neighbour)
+                    && self.cell(neighbour) == LayoutCell::Mine
+                {
+                    mines += 1;
+                }
+            }
+        }
+        mines
+    }
+
+    /// Applies the edit, coordinates must lie on the layout. Placing a mine hides the
+    /// cell, revealing a region skips its mines.
+    pub fn edit(&mut self, command: EditCommand) {
+        match command {
+            EditCommand::Mine(coordinate) => self.set(coordinate, LayoutCell::Mine),
+            EditCommand::Remove(coordinate) => {
+                if self.cell(coordinate) == LayoutCell::Mine {
+                    self.set(coordinate, LayoutCell::Hidden);
+                }
+            }
+            EditCommand::Reveal(from, to) => {
+                for coordinate in Layout::region(from, to) {
+                    if self.cell(coordinate) == LayoutCell::Hidden {
+                        self.set(coordinate, LayoutCell::Revealed);
+                    }
+                }
+            }
+            EditCommand::Hide(from, to) => {
+                for coordinate in Layout::region(from, to) {
+                    if self.cell(coordinate) == LayoutCell::Revealed {
+                        self.set(coordinate, LayoutCell::Hidden);
+                    }
+                }
+            }
+            EditCommand::Save | EditCommand::Quit => {}
+        }
+    }
+
+    /// Draws the layout like a board, revealed cells show their number.
+    pub fn render(&self) -> String {
+        let mut board = format!("{:>3}", "");
+        for col in 0..self.width {
+            board += &format!("{:>3}", col);
+        }
+        board.push('\n');
+
+        for row in 0..self.height {
+            board += &format!("{:>3}|", row);
+            for col in 0..self.width {
+                let coordinate = Coordinate(row, col);
+                let symbol = match self.cell(coordinate) {
+                    LayoutCell::Hidden => "|X|".to_string(),
+                    LayoutCell::Mine => "|*|".to_string(),
+                    LayoutCell::Revealed => match self.neighbour_mines(coordinate) {
+                        0 => "| |".to_string(),
+                        mines => format!("|{}|", mines),
+                    },
+                };
+                board += &format!("{:>3}", symbol);
+            }
+            board.push('\n');
+        }
+        board
+    }
+
+    fn set(&mut self, coordinate: Coordinate, cell: LayoutCell) {
+        let index = self.index(coordinate);
+        self.cells[index] = cell;
+    }
+
+    fn index(&self, coordinate: Coordinate) -> usize {
+        coordinate.0 as usize * self.width as usize + coordinate.1 as usize
+    }
+
+    /// The cells of the rectangle spanned by the corners.
+    fn region(from: Coordinate, to: Coordinate) -> impl Iterator<Item = Coordinate> {
+        let rows = from.0.min(to.0)..=from.0.max(to.0);
+        let cols = from.1.min(to.1)..=from.1.max(to.1);
+        rows.flat_map(move |row| cols.clone().map(move |col| Coordinate(row, col)))
+    }
+}
+
+impl TryFrom<&str> for Layout {
+    type Error = LayoutError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let rows: Vec<&str> = value
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+        if width == 0 {
+            return Err(LayoutError::Empty);
+        }
+
+        let mut cells = Vec::with_capacity(width * rows.len());
+        for (index, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(LayoutError::UnevenRows(index));
+            }
+            for symbol in row.chars() {
+                match symbol {
+                    '#' => cells.push(LayoutCell::Hidden),
+                    '*' => cells.push(LayoutCell::Mine),
+                    '.' => cells.push(LayoutCell::Revealed),
+                    symbol => return Err(LayoutError::UnknownSymbol(symbol)),
+                }
+            }
+        }
+
+        Ok(Layout {
+            width: u32::try_from(width).map_err(|_| LayoutError::TooLarge)?,
+            height: u32::try_from(rows.len()).map_err(|_| LayoutError::TooLarge)?,
+            cells,
+        })
+    }
+}
+
+impl TryFrom<String> for Layout {
+    type Error = LayoutError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Layout::try_from(&value[..])
+    }
+}
+
+impl From<Layout> for String {
+    fn from(layout: Layout) -> Self {
+        layout.to_string()
+    }
+}
+
+impl Display for Layout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.cells.chunks(self.width as usize) {
+            let row: String = row
+                .iter()
+                .map(|cell| match cell {
+                    LayoutCell::Hidden => '#',
+                    LayoutCell::Mine => '*',
+                    LayoutCell::Revealed => '.',
+                })
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+/// Commands of the layout editor.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EditCommand {
+    /// Places a mine, `mine(x, y)`.
+    Mine(Coordinate),
+    /// Removes the mine, `remove(x, y)`.
+    Remove(Coordinate),
+    /// Reveals the cell `reveal(x, y)` or the rectangle `reveal(x1, y1, x2, y2)` when the
+    /// game starts.
+    Reveal(Coordinate, Coordinate),
+    /// Hides the cell or rectangle again, `hide(x, y)` or `hide(x1, y1, x2, y2)`.
+    Hide(Coordinate, Coordinate),
+    Save,
+    Quit,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum EditCommandError {
+    MalformedString,
+    MalformedCoordinate,
+    CoordinateParsing(ParseIntError),
+    NotFound,
+}
+
+impl EditCommand {
+    /// Coordinates the command touches, the corners for regions.
+    pub fn coordinates(&self) -> Vec<Coordinate> {
+        match *self {
+            EditCommand::Mine(coordinate) | EditCommand::Remove(coordinate) => vec![coordinate],
+            EditCommand::Reveal(from, to) | EditCommand::Hide(from, to) => vec![from, to],
+            EditCommand::Save | EditCommand::Quit => vec![],
+        }
+    }
+}
+
+impl TryFrom<&str> for EditCommand {
+    type Error = EditCommandError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let value = value.to_lowercase().trim().to_string();
+        match &value[..] {
+            "save" => return Ok(EditCommand::Save),
+            "quit" => return Ok(EditCommand::Quit),
+            _ => {}
+        }
+
+        let (command, arguments) = value
+            .split_once('(')
+            .ok_or(EditCommandError::MalformedString)?;
+        let arguments = arguments
+            .trim_end_matches(')')
+            .split(',')
+            .map(|argument| argument.trim().parse::<u32>())
+            .collect::<Result<Vec<u32>, _>>()
+            .map_err(EditCommandError::CoordinateParsing)?;
+
+        let (from, to) = match arguments[..] {
+            [x, y] => (Coordinate(x, y), Coordinate(x, y)),
+            [x1, y1, x2, y2] => (Coordinate(x1, y1), Coordinate(x2, y2)),
+            _ => return Err(EditCommandError::MalformedCoordinate),
+        };
+        match command.trim() {
+            "mine" if from == to => Ok(EditCommand::Mine(from)),
+            "remove" if from == to => Ok(EditCommand::Remove(from)),
+            "mine" | "remove" => Err(EditCommandError::MalformedCoordinate),
+            "reveal" => Ok(EditCommand::Reveal(from, to)),
+            "hide" => Ok(EditCommand::Hide(from, to)),
+            _ => Err(EditCommandError::NotFound),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn edit_layout_test() {
+        assert_eq!(
+            Ok(EditCommand::Reveal(Coordinate(0, 0), Coordinate(1, 2))),
+            EditCommand::try_from("reveal(0, 0, 1, 2)")
+        );
+        assert_eq!(
+            Err(EditCommandError::MalformedCoordinate),
+            EditCommand::try_from("mine(0, 0, 1, 2)")
+        );
+
+        let mut layout = Layout::new(4, 3);
+        layout.edit(EditCommand::Mine(Coordinate(0, 3)));
+        layout.edit(EditCommand::Mine(Coordinate(2, 1)));
+        layout.edit(EditCommand::Reveal(Coordinate(1, 1), Coordinate(0, 0)));
+        layout.edit(EditCommand::Reveal(Coordinate(0, 3), Coordinate(0, 3)));
+        assert_eq!("..#*\n..##\n#*##\n", layout.to_string());
+        assert_eq!(2, layout.mines());
+        assert_eq!(1, layout.neighbour_mines(Coordinate(1, 1)));
+
+        layout.edit(EditCommand::Remove(Coordinate(0, 3)));
+        layout.edit(EditCommand::Hide(Coordinate(1, 0), Coordinate(1, 1)));
+        assert_eq!("..##\n####\n#*##\n", layout.to_string());
+        assert_eq!(layout, Layout::try_from(layout.to_string()).unwrap());
+
+        assert_eq!(Err(LayoutError::UnevenRows(1)), Layout::try_from("##\n#"));
+        assert_eq!(Err(LayoutError::UnknownSymbol('x')), Layout::try_from("#x"));
+    }
+}
@@ -0,0 +1,392 @@
+//! Online leaderboard. The client is opt-in by setting `leaderboard_url` in the config,
+//! won games are submitted together with their replay so the server, which keeps a
+//! [`Leaderboard`], can re-simulate them. That proves the moves win the board in the time
+//! claimed, not that a player made them: the replay's checksum only catches edited files,
+//! anyone with the game can seal a replay of their own.
+
+use std::{collections::BTreeMap, fmt::Display, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    replay::{Replay, ReplayError},
+    storage, Expansion, GameConfiguration, GameResolve,
+};
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Submission {
+    pub player: String,
+    pub seed: u64,
+    pub configuration: GameConfiguration,
+    pub time_ms: u64,
+    pub replay_hash: String,
+    pub replay: Replay,
+}
+
+impl Submission {
+    pub fn new(player: &str, replay: &Replay) -> Self {
+        Submission {
+            player: player.to_string(),
+            seed: replay.seed,
+            configuration: replay.configuration,
+            time_ms: replay.duration().as_millis() as u64,
+            replay_hash: replay.hash(),
+            replay: replay.clone(),
+        }
+    }
+
+    /// Checks the claims of the submission by re-simulating its replay.
+    pub fn verify(&self) -> Result<(), SubmissionError> {
+        if !storage::is_valid_profile_name(&self.player) {
+            return Err(SubmissionError::InvalidPlayer);
+        }
+        if self.replay.hash() != self.replay_hash {
+            return Err(SubmissionError::HashMismatch);
+        }
+        if self.replay.seed != self.seed || self.replay.configuration != self.configuration {
+            return Err(SubmissionError::BoardMismatch);
+        }
+        if self.replay.duration().as_millis() as u64 != self.time_ms {
+            return Err(SubmissionError::TimeMismatch);
+        }
+        // the lists compare games under the classic rules, the client's filters are no
+        // guarantee
+        let assisted = self.replay.forgiving
+            || self.replay.auto_chord
+            || !self.replay.safe_start.is_off()
+            || self.replay.expansion == Expansion::Aggressive;
+        if self.replay.layout.is_some() || self.replay.mask.is_some() || assisted {
+            return Err(SubmissionError::Unranked);
+        }
+        self.replay
+            .verify_checksum()
+            .map_err(SubmissionError::Replay)?;
+
+        match self.replay.simulate().map_err(SubmissionError::Replay)? {
+            GameResolve::AllMinesDiscovered => Ok(()),
+            _ => Err(SubmissionError::NotWon),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SubmissionError {
+    InvalidPlayer,
+    HashMismatch,
+    BoardMismatch,
+    TimeMismatch,
+    /// Games on hand made or shaped boards or with assists have no place on the lists of
+    /// the difficulties.
+    Unranked,
+    NotWon,
+    Replay(ReplayError),
+    Duplicate,
+}
+
+impl Display for SubmissionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SubmissionError::InvalidPlayer => write!(f, "the player name is invalid"),
+            SubmissionError::HashMismatch => write!(f, "the replay hash does not match"),
+            SubmissionError::BoardMismatch => {
+                write!(f, "the replay was played on a different board")
+            }
+            SubmissionError::TimeMismatch => write!(f, "the time does not match the replay"),
+            SubmissionError::Unranked => write!(f, "the game is not ranked"),
+            SubmissionError::NotWon => write!(f, "the replay does not win the game"),
+            SubmissionError::Replay(err) => write!(f, "the replay is invalid, {}", err),
+            SubmissionError::Duplicate => write!(f, "the replay was already submitted"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+struct Score {
+    player: String,
+    time_ms: u64,
+    replay_hash: String,
+    replay: Replay,
+}
+
+/// Verified scores of every difficulty, kept by the leaderboard server.
+#[derive(Serialize, Deserialize, Default, Debug)]
+pub struct Leaderboard {
+    scores: BTreeMap<String, Vec<Score>>,
+}
+
+impl Leaderboard {
+    pub fn submit(&mut self, submission: Submission) -> Result<(), SubmissionError> {
+        submission.verify()?;
+
+        if self
+            .scores
+            .values()
+            .flatten()
+            .any(|score| score.replay_hash == submission.replay_hash)
+        {
+            return Err(SubmissionError::Duplicate);
+        }
+
+        let scores = self
+            .scores
+            .entry(submission.configuration.key())
+            .or_default();
+        let position = scores.partition_point(|score| score.time_ms <= submission.time_ms);
+        scores.insert(
+            position,
+            Score {
+                player: submission.player,
+                time_ms: submission.time_ms,
+                replay_hash: submission.replay_hash,
+                replay: submission.replay,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Fastest scores of the difficulty, see [`GameConfiguration::key`].
+    pub fn top_list(&self, difficulty: &str, limit: usize) -> Vec<LeaderboardEntry> {
+        self.scores
+            .get(difficulty)
+            .map(|scores| {
+                scores
+                    .iter()
+                    .take(limit)
+                    .map(|score| LeaderboardEntry {
+                        player: score.player.clone(),
+                        time_ms: score.time_ms,
+                    })
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct LeaderboardEntry {
+    pub player: String,
+    pub time_ms: u64,
+}
+
+fn scores_url(url: &str) -> String {
+    format!("{}/scores", url.trim_end_matches('/'))
+}
+
+pub fn submit(url: &str, submission: &Submission) -> io::Result<()> {
+    post_json(&scores_url(url), submission)
+}
+
+/// Fetches the global top list of the difficulty, fastest first.
+pub fn top_list(url: &str, configuration: &GameConfiguration) -> io::Result<Vec<LeaderboardEntry>> {
+    get_json(&scores_url(url), &configuration.key())
+}
+
+#[cfg(feature = "online")]
+fn post_json(url: &str, body: &impl Serialize) -> io::Result<()> {
+    ureq::post(url)
+        .send_json(body)
+        .map(|_| ())
+        .map_err(io::Error::other)
+}
+
+#[cfg(feature = "online")]
+fn get_json<T: serde::de::DeserializeOwned>(url: &str, difficulty: &str) -> io::Result<T> {
+    ureq::get(url)
+        .query("difficulty", difficulty)
+        .call()
+        .map_err(io::Error::other)?
+        .into_json()
+}
+
+#[cfg(not(feature = "online"))]
+fn post_json(_url: &str, _body: &impl Serialize) -> io::Result<()> {
+    Err(offline_error())
+}
+
+#[cfg(not(feature = "online"))]
+fn get_json<T>(_url: &str, _difficulty: &str) -> io::Result<T> {
+    Err(offline_error())
+}
+
+#[cfg(not(feature = "online"))]
+fn offline_error() -> io::Error {
+    io::Error::other("the leaderboard needs the `online` feature")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::{
+        layout::Layout, mask::Mask, BoardCell, BoardCommand, Coordinate, GameBoard, SafeStart,
+    };
+
+    fn won_replay(seed: u64, seconds: u64) -> Replay {
+        let configuration = GameConfiguration::new(4, 4, 3);
+        let mut game_board = GameBoard::new(configuration);
+        game_board.generate_world_from_seed(seed);
+
+        let mut replay = Replay::new(configuration, seed);
+        for row in 0..configuration.h() {
+            for col in 0..configuration.w() {
+                let coordinate = Coordinate(row, col);
+                if let BoardCell::Mine(_) = game_board.get_cell_at(coordinate) {
+                    replay.record(
+                        BoardCommand::SetMarkFlag(coordinate),
+                        Duration::from_secs(seconds),
+                    );
+                }
+            }
+        }
+        replay.seal(GameResolve::AllMinesDiscovered);
+        replay
+    }
+
+    #[test]
+    fn leaderboard_verifies_submissions_test() {
+        let mut leaderboard = Leaderboard::default();
+
+        let slow = Submission::new("slow", &won_replay(1, 20));
+        assert_eq!(Ok(()), leaderboard.submit(slow.clone()));
+        assert_eq!(Err(SubmissionError::Duplicate), leaderboard.submit(slow));
+
+        let mut cheated = Submission::new("cheater", &won_replay(2, 10));
+        cheated.time_ms = 1;
+        assert_eq!(
+            Err(SubmissionError::TimeMismatch),
+            leaderboard.submit(cheated)
+        );
+
+        let mut tampered = Submission::new("tamper", &won_replay(3, 10));
+        tampered.replay.moves.pop();
+        assert_eq!(
+            Err(SubmissionError::HashMismatch),
+            leaderboard.submit(tampered)
+        );
+
+        let mut edited = Submission::new("edited", &won_replay(6, 10));
+        edited.replay.moves[0].time_ms = 1_000;
+        edited.time_ms = edited.replay.duration().as_millis() as u64;
+        edited.replay_hash = edited.replay.hash();
+        assert_eq!(
+            Err(SubmissionError::Replay(ReplayError::ChecksumMismatch)),
+            leaderboard.submit(edited)
+        );
+
+        let mut lost = won_replay(4, 10);
+        lost.moves.pop();
+        lost.seal(GameResolve::Continue);
+        assert_eq!(
+            Err(SubmissionError::NotWon),
+            leaderboard.submit(Submission::new("lost", &lost))
+        );
+
+        assert_eq!(
+            Ok(()),
+            leaderboard.submit(Submission::new("fast", &won_replay(5, 10)))
+        );
+        let top_list = leaderboard.top_list("4x4-3", 10);
+        assert_eq!(
+            vec!["fast", "slow"],
+            top_list
+                .iter()
+                .map(|entry| entry.player.as_str())
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn layout_submission_test() {
+        let layout = Layout::try_from("*#\n").unwrap();
+        let mut replay = Replay::new(GameConfiguration::expert(), 1);
+        replay.layout = Some(layout.clone());
+        replay.record(
+            BoardCommand::SetMarkFlag(Coordinate(0, 0)),
+            Duration::from_millis(5),
+        );
+        replay.seal(GameResolve::AllMinesDiscovered);
+        assert_eq!(Err(ReplayError::InvalidConfiguration), replay.verify());
+        let mut leaderboard = Leaderboard::default();
+        assert_eq!(
+            Err(SubmissionError::Unranked),
+            leaderboard.submit(Submission::new("layout", &replay))
+        );
+
+        replay.configuration = layout.configuration();
+        replay.seal(GameResolve::AllMinesDiscovered);
+        assert_eq!(Ok(GameResolve::AllMinesDiscovered), replay.verify());
+        assert_eq!(
+            Err(SubmissionError::Unranked),
+            leaderboard.submit(Submission::new("layout", &replay))
+        );
+        assert!(leaderboard.top_list("30x16-99", 10).is_empty());
+    }
+
+    #[test]
+    fn masked_submission_test() {
+        let cells = "#".repeat(99) + ".".repeat(30 * 16 - 99).as_str();
+        let rows: Vec<&str> = (0..16).map(|row| &cells[row * 30..][..30]).collect();
+        let mut replay = Replay::new(GameConfiguration::expert(), 1);
+        replay.mask = Some(Mask::try_from(&rows.join("\n")[..]).unwrap());
+        for index in 0..99 {
+            replay.record(
+                BoardCommand::SetMarkFlag(Coordinate(index / 30, index % 30)),
+                Duration::from_millis(5),
+            );
+        }
+        replay.seal(GameResolve::AllMinesDiscovered);
+        assert_eq!(Ok(GameResolve::AllMinesDiscovered), replay.verify());
+
+        let mut leaderboard = Leaderboard::default();
+        assert_eq!(
+            Err(SubmissionError::Unranked),
+            leaderboard.submit(Submission::new("masked", &replay))
+        );
+        assert!(leaderboard.top_list("30x16-99", 10).is_empty());
+    }
+
+    #[test]
+    fn assisted_submission_test() {
+        let mut leaderboard = Leaderboard::default();
+        let assists: [fn(&mut Replay); 4] = [
+            |replay| replay.forgiving = true,
+            |replay| replay.auto_chord = true,
+            |replay| replay.safe_start = SafeStart::Cell,
+            |replay| replay.expansion = Expansion::Aggressive,
+        ];
+        for assist in assists {
+            let mut replay = won_replay(7, 10);
+            assist(&mut replay);
+            replay.seal(GameResolve::AllMinesDiscovered);
+            assert_eq!(
+                Err(SubmissionError::Unranked),
+                leaderboard.submit(Submission::new("assisted", &replay))
+            );
+        }
+
+        // a harder rule is no assist
+        let mut replay = won_replay(7, 10);
+        replay.expansion = Expansion::Conservative;
+        replay.seal(GameResolve::AllMinesDiscovered);
+        assert_eq!(
+            Ok(()),
+            leaderboard.submit(Submission::new("conservative", &replay))
+        );
+    }
+
+    #[test]
+    fn submission_test() {
+        let replay = Replay::new(GameConfiguration::beginner(), 42);
+        let submission = Submission::new("mom", &replay);
+
+        assert_eq!(42, submission.seed);
+        assert_eq!(replay.hash(), submission.replay_hash);
+        assert_eq!(64, submission.replay_hash.len());
+        assert_eq!(
+            "https://example.com/scores",
+            scores_url("https://example.com/")
+        );
+    }
+}
@@ -0,0 +1,3085 @@
+use std::{
+    collections::{HashMap, VecDeque},
+    fmt::Display,
+    num::ParseIntError,
+    time::{Duration, Instant},
+};
+
+use ansi::Theme;
+use generator::BoardGenerator;
+use generator::{Distribution, Symmetric, Symmetry};
+use layout::{Layout, LayoutCell};
+use mask::Mask;
+use packed::{BitSet, Counts, PackedCells};
+use plugin::PluginCommand;
+use rand::{seq::SliceRandom, Rng, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use replay::Replay;
+use serde::{Deserialize, Serialize};
+
+pub mod achievements;
+pub mod adaptive;
+pub mod ansi;
+pub mod bench;
+pub mod braille;
+pub mod bundle;
+pub mod calendar;
+pub mod campaign;
+pub mod clock;
+pub mod config;
+pub mod console;
+pub mod control;
+pub mod cube;
+pub mod daily;
+pub mod describe;
+pub mod difficulty;
+pub mod endless;
+pub mod estimate;
+pub mod export;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod gauntlet;
+pub mod generator;
+#[cfg(feature = "gif")]
+pub mod gif;
+#[cfg(feature = "grpc")]
+pub mod grpc;
+pub mod heatmap;
+pub mod highscores;
+pub mod hotseat;
+pub mod html;
+pub mod interrupt;
+pub mod layout;
+pub mod leaderboard;
+pub mod locale;
+pub mod macros;
+pub mod mask;
+pub mod multiboard;
+pub mod multiplayer;
+pub mod pace;
+mod packed;
+pub mod plugin;
+#[cfg(feature = "png")]
+pub mod png;
+pub mod profile;
+pub mod protocol;
+pub mod puzzles;
+pub mod race;
+pub mod rawvf;
+pub mod replay;
+pub mod save;
+#[cfg(feature = "scripting")]
+pub mod scripting;
+pub mod session;
+pub mod share;
+pub mod solver;
+pub mod sound;
+pub mod splits;
+pub mod statistics;
+pub mod storage;
+pub mod svg;
+pub mod sync;
+#[cfg(any(feature = "png", feature = "gif"))]
+mod tiles;
+pub mod tournament;
+#[cfg(feature = "wasm")]
+pub mod wasm;
+#[cfg(feature = "websocket")]
+pub mod websocket;
+pub mod wizard;
+
+/// Summary of a finished game.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GameResult {
+    pub resolve: GameResolve,
+    pub elapsed: Duration,
+    pub replay: Replay,
+    pub flags_placed: u32,
+    pub cells_revealed: u32,
+    pub efficiency: Efficiency,
+    /// 3BV of the whole board, solved or not.
+    pub board_three_bv: u32,
+    /// Estimates asked for during the game.
+    pub hints_used: u32,
+    /// Whether a mine hit was forgiven, such games are not ranked.
+    pub forgiven: bool,
+    /// Whether the mines were shown during the game, such games are not ranked.
+    pub cheated: bool,
+    /// Whether scripts applied commands during the game, such games are not ranked.
+    pub scripted: bool,
+    /// Whether satisfied numbers were chorded for the player, such games are marked in
+    /// the scores.
+    pub assisted: bool,
+    /// Whether the game was resumed or loaded from a save, such games are not ranked.
+    pub practice: bool,
+}
+
+impl GameResult {
+    /// Whether the game may enter highscores and personal bests. Safe start moves the
+    /// mines of the seeded board away from the first explore, such games are not ranked.
+    pub fn is_ranked(&self) -> bool {
+        !self.forgiven
+            && !self.cheated
+            && !self.scripted
+            && !self.practice
+            && self.replay.safe_start.is_off()
+    }
+}
+
+/// Clicks of a game against the 3BV it solved, the metrics competitive players compare.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Efficiency {
+    /// 3BV solved by the end of the game, all of it when the game was won.
+    pub three_bv: u32,
+    /// Every explore, mark and clear of a mark.
+    pub clicks: u32,
+    /// Clicks that left the board as it was.
+    pub wasted_clicks: u32,
+}
+
+impl Efficiency {
+    pub fn three_bv_per_second(&self, elapsed: Duration) -> f64 {
+        match elapsed.as_secs_f64() {
+            0.0 => 0.0,
+            seconds => self.three_bv as f64 / seconds,
+        }
+    }
+
+    /// 3BV per click in percent, a perfect game clicks once for every 3BV.
+    pub fn percent(&self) -> u32 {
+        match self.clicks {
+            0 => 0,
+            clicks => (self.three_bv as f64 * 100.0 / clicks as f64).round() as u32,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Coordinate(pub u32, pub u32);
+
+impl Coordinate {
+    /// Every cell of the rectangle from this corner to the other, both included, row by
+    /// row.
+    pub fn rectangle(self, corner: Coordinate) -> impl Iterator<Item = Coordinate> {
+        let rows = self.0.min(corner.0)..=self.0.max(corner.0);
+        let cols = self.1.min(corner.1)..=self.1.max(corner.1);
+        rows.flat_map(move |row| cols.clone().map(move |col| Coordinate(row, col)))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BoardCommandError {
+    MalformedString,
+    MalformedCoordinate,
+    CoordinateParsing(ParseIntError),
+    NotFound,
+    /// The coordinate is before the first row or column of the convention.
+    OutsideBoard,
+    /// A relative coordinate was given before any cell was acted on.
+    NoLastCell,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardCommand {
+    Pass,
+    Quit,
+    /// Claims the win under strict flagging, every mine and nothing else has to be
+    /// flagged. Without strict flagging it does nothing.
+    Claim,
+    ClearMark(Coordinate),
+    SetMarkFlag(Coordinate),
+    /// Flag recording how many mines the cell is suspected to hold, negative for
+    /// anti-mines.
+    SetMarkFlagCount(Coordinate, i8),
+    SetMarkNote(Coordinate),
+    Explore(Coordinate),
+    /// Flags every cell of the rectangle between the corners, e.g. `flag(3-6, 2)`.
+    SetMarkFlagRange(Coordinate, Coordinate),
+    /// Clears the marks of every cell of the rectangle between the corners, e.g.
+    /// `clear(0-9, 0-9)`.
+    ClearMarkRange(Coordinate, Coordinate),
+    /// Command of a registered [`plugin::CommandPlugin`].
+    Plugin(PluginCommand),
+}
+
+impl Display for BoardCommand {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            BoardCommand::Pass => write!(f, "pass"),
+            BoardCommand::Quit => write!(f, "quit"),
+            BoardCommand::Claim => write!(f, "claim"),
+            BoardCommand::ClearMark(Coordinate(x, y)) => write!(f, "clear({}, {})", x, y),
+            BoardCommand::SetMarkFlag(Coordinate(x, y)) => write!(f, "flag({}, {})", x, y),
+            BoardCommand::SetMarkFlagCount(Coordinate(x, y), count) => {
+                write!(f, "flag({}, {}, {})", x, y, count)
+            }
+            BoardCommand::SetMarkNote(Coordinate(x, y)) => write!(f, "note({}, {})", x, y),
+            BoardCommand::Explore(Coordinate(x, y)) => write!(f, "explore({}, {})", x, y),
+            BoardCommand::SetMarkFlagRange(from, to) => write!(f, "flag({})", Span(*from, *to)),
+            BoardCommand::ClearMarkRange(from, to) => write!(f, "clear({})", Span(*from, *to)),
+            BoardCommand::Plugin(command) => write!(f, "{}", plugin::format(*command)),
+        }
+    }
+}
+
+impl BoardCommand {
+    /// Name of the command as typed, e.g. `flag`.
+    pub fn verb(&self) -> &'static str {
+        match self {
+            BoardCommand::Pass => "pass",
+            BoardCommand::Quit => "quit",
+            BoardCommand::Claim => "claim",
+            BoardCommand::ClearMark(_) | BoardCommand::ClearMarkRange(..) => "clear",
+            BoardCommand::SetMarkFlag(_)
+            | BoardCommand::SetMarkFlagCount(..)
+            | BoardCommand::SetMarkFlagRange(..) => "flag",
+            BoardCommand::SetMarkNote(_) => "note",
+            BoardCommand::Explore(_) => "explore",
+            BoardCommand::Plugin(command) => plugin::verb(*command),
+        }
+    }
+
+    /// Cell the command acts on, the far corner for ranges.
+    pub fn cell(&self) -> Option<Coordinate> {
+        match self {
+            BoardCommand::Pass | BoardCommand::Quit | BoardCommand::Claim => None,
+            BoardCommand::ClearMark(coordinate)
+            | BoardCommand::SetMarkFlag(coordinate)
+            | BoardCommand::SetMarkFlagCount(coordinate, _)
+            | BoardCommand::SetMarkNote(coordinate)
+            | BoardCommand::Explore(coordinate)
+            | BoardCommand::SetMarkFlagRange(_, coordinate)
+            | BoardCommand::ClearMarkRange(_, coordinate) => Some(*coordinate),
+            BoardCommand::Plugin(command) => command.cell,
+        }
+    }
+
+    /// The same command on another cell, ranges shrink to the cell.
+    pub fn at(self, coordinate: Coordinate) -> BoardCommand {
+        match self {
+            BoardCommand::Pass | BoardCommand::Quit | BoardCommand::Claim => self,
+            BoardCommand::ClearMark(_) | BoardCommand::ClearMarkRange(..) => {
+                BoardCommand::ClearMark(coordinate)
+            }
+            BoardCommand::SetMarkFlag(_) | BoardCommand::SetMarkFlagRange(..) => {
+                BoardCommand::SetMarkFlag(coordinate)
+            }
+            BoardCommand::SetMarkFlagCount(_, count) => {
+                BoardCommand::SetMarkFlagCount(coordinate, count)
+            }
+            BoardCommand::SetMarkNote(_) => BoardCommand::SetMarkNote(coordinate),
+            BoardCommand::Explore(_) => BoardCommand::Explore(coordinate),
+            BoardCommand::Plugin(command) => BoardCommand::Plugin(PluginCommand {
+                cell: Some(coordinate),
+                ..command
+            }),
+        }
+    }
+}
+
+/// Corners of a range as typed, `3-6, 2` for rows 3 to 6 of column 2.
+struct Span(Coordinate, Coordinate);
+
+impl Display for Span {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let Span(from, to) = self;
+        for (index, (first, last)) in [(from.0, to.0), (from.1, to.1)].into_iter().enumerate() {
+            if index > 0 {
+                write!(f, ", ")?;
+            }
+            if first == last {
+                write!(f, "{}", first)?;
+            } else {
+                write!(f, "{}-{}", first, last)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A single number or a range of them like `3-6`.
+fn parse_span(value: &str) -> Result<(u32, u32), BoardCommandError> {
+    let (first, last) = value.split_once('-').unwrap_or((value, value));
+    let first = first
+        .trim()
+        .parse::<u32>()
+        .map_err(BoardCommandError::CoordinateParsing)?;
+    let last = last
+        .trim()
+        .parse::<u32>()
+        .map_err(BoardCommandError::CoordinateParsing)?;
+    Ok((first.min(last), first.max(last)))
+}
+
+impl TryFrom<&str> for BoardCommand {
+    type Error = BoardCommandError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        // registered plugins get the commands the game does not know
+        parse_command(value).or_else(|err| plugin::parse(value).unwrap_or(Err(err)))
+    }
+}
+
+fn parse_command(value: &str) -> Result<BoardCommand, BoardCommandError> {
+    let value = value.to_lowercase().trim().to_string();
+
+    if value == "pass" {
+        return Ok(BoardCommand::Pass);
+    }
+
+    if value == "quit" {
+        return Ok(BoardCommand::Quit);
+    }
+
+    if value == "claim" {
+        return Ok(BoardCommand::Claim);
+    }
+
+    let command_coordinate = value
+        .split_once('(')
+        .ok_or(BoardCommandError::MalformedString)?;
+
+    let value = command_coordinate.1;
+
+    let value = value.trim();
+    let (value_x, value_y) = value
+        .split_once(',')
+        .ok_or(BoardCommandError::MalformedCoordinate)?;
+
+    let value_x = parse_span(value_x)?;
+
+    let value_y = value_y.replace(['\n', ')'], "").trim().to_string();
+    let command = command_coordinate.0.trim();
+
+    // flags may record a mine count as third argument
+    let (value_y, count) = match value_y.split_once(',') {
+        Some((value_y, count)) if command == "flag" => (
+            value_y.trim().to_string(),
+            Some(
+                count
+                    .trim()
+                    .parse::<i8>()
+                    .map_err(BoardCommandError::CoordinateParsing)?,
+            ),
+        ),
+        _ => (value_y, None),
+    };
+    let value_y = parse_span(&value_y)?;
+
+    // ranges expand to their cells on the board, only flags and clears take them
+    let (from, to) = (
+        Coordinate(value_x.0, value_y.0),
+        Coordinate(value_x.1, value_y.1),
+    );
+    if from != to {
+        return match (command, count) {
+            ("flag", None) => Ok(BoardCommand::SetMarkFlagRange(from, to)),
+            ("clear", None) => Ok(BoardCommand::ClearMarkRange(from, to)),
+            _ => Err(BoardCommandError::MalformedCoordinate),
+        };
+    }
+    let (value_x, value_y) = (from.0, from.1);
+
+    if let Some(count) = count {
+        return Ok(BoardCommand::SetMarkFlagCount(
+            Coordinate(value_x, value_y),
+            count,
+        ));
+    }
+
+    match command {
+        "clear" => Ok(BoardCommand::ClearMark(Coordinate(value_x, value_y))),
+        "flag" => Ok(BoardCommand::SetMarkFlag(Coordinate(value_x, value_y))),
+        "note" => Ok(BoardCommand::SetMarkNote(Coordinate(value_x, value_y))),
+        "explore" => Ok(BoardCommand::Explore(Coordinate(value_x, value_y))),
+        _ => Err(BoardCommandError::NotFound),
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NeighbourMines(pub i16);
+
+#[allow(clippy::enum_variant_names)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Mark {
+    NoMark,
+    MarkNote,
+    MarkFlag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CellInfo(pub Mark, pub NeighbourMines);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BoardCell {
+    Explored(NeighbourMines),
+    NoMine(CellInfo),
+    Mine(Mark),
+}
+
+/// Which cells count as neighbours, for the mine counts as well as for exploring.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Adjacency {
+    /// The eight surrounding cells.
+    #[default]
+    Standard,
+    /// The cells a chess knight could jump to.
+    Knight,
+    /// The 5x5 square around the cell.
+    Extended,
+}
+
+impl Adjacency {
+    const STANDARD: [(i32, i32); 8] = [
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+        (0, -1),
+        (0, 1),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+    ];
+    const KNIGHT: [(i32, i32); 8] = [
+        (-2, -1),
+        (-2, 1),
+        (-1, -2),
+        (-1, 2),
+        (1, -2),
+        (1, 2),
+        (2, -1),
+        (2, 1),
+    ];
+    const EXTENDED: [(i32, i32); 24] = [
+        (-2, -2),
+        (-2, -1),
+        (-2, 0),
+        (-2, 1),
+        (-2, 2),
+        (-1, -2),
+        (-1, -1),
+        (-1, 0),
+        (-1, 1),
+        (-1, 2),
+        (0, -2),
+        (0, -1),
+        (0, 1),
+        (0, 2),
+        (1, -2),
+        (1, -1),
+        (1, 0),
+        (1, 1),
+        (1, 2),
+        (2, -2),
+        (2, -1),
+        (2, 0),
+        (2, 1),
+        (2, 2),
+    ];
+
+    /// Row and column offsets of the neighbours.
+    pub fn offsets(&self) -> &'static [(i32, i32)] {
+        match self {
+            Adjacency::Standard => &Adjacency::STANDARD,
+            Adjacency::Knight => &Adjacency::KNIGHT,
+            Adjacency::Extended => &Adjacency::EXTENDED,
+        }
+    }
+
+    pub fn is_standard(&self) -> bool {
+        *self == Adjacency::Standard
+    }
+}
+
+impl Display for Adjacency {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Adjacency::Standard => write!(f, "standard"),
+            Adjacency::Knight => write!(f, "knight"),
+            Adjacency::Extended => write!(f, "extended"),
+        }
+    }
+}
+
+impl TryFrom<&str> for Adjacency {
+    type Error = ();
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        match value.trim() {
+            "standard" => Ok(Adjacency::Standard),
+            "knight" => Ok(Adjacency::Knight),
+            "extended" => Ok(Adjacency::Extended),
+            _ => Err(()),
+        }
+    }
+}
+
+/// What happens when a flagged cell is explored, which is almost always a typo.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagGuard {
+    /// The cell is explored like any other.
+    #[default]
+    Off,
+    /// The cell is only explored if the same explore follows right after.
+    Confirm,
+    /// The flag has to be cleared first.
+    Reject,
+}
+
+impl FlagGuard {
+    /// Tells the player why an explore was held back.
+    pub fn hint(&self) -> &'static str {
+        match self {
+            FlagGuard::Confirm => "The cell is flagged, explore it again to confirm.",
+            _ => "The cell is flagged, clear the flag to explore it.",
+        }
+    }
+}
+
+/// Cells kept free of mines around the first explore of a game. The mines are placed
+/// again from the same seed once the first cell is explored, so the same seed and first
+/// explore give the same board.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SafeStart {
+    /// The mines are where the seed put them, the first explore may hit one.
+    #[default]
+    Off,
+    /// The first explored cell holds no mine.
+    Cell,
+    /// The first explored cell and the cells around it hold no mines.
+    #[serde(rename = "3x3")]
+    Square3,
+    /// The 5x5 square around the first explored cell holds no mines.
+    #[serde(rename = "5x5")]
+    Square5,
+}
+
+impl SafeStart {
+    pub fn is_off(&self) -> bool {
+        *self == SafeStart::Off
+    }
+
+    /// Cells of the square around the coordinate, cut off at the edges of the board.
+    fn cells(&self, Coordinate(row, col): Coordinate, width: u32, height: u32) -> Vec<Coordinate> {
+        let reach = match self {
+            SafeStart::Off => return vec![],
+            SafeStart::Cell => 0,
+            SafeStart::Square3 => 1,
+            SafeStart::Square5 => 2,
+        };
+        let rows = row.saturating_sub(reach)..=(row + reach).min(height - 1);
+        let cols = col.saturating_sub(reach)..=(col + reach).min(width - 1);
+        rows.flat_map(|row| cols.clone().map(move |col| Coordinate(row, col)))
+            .collect()
+    }
+}
+
+/// How far an explore opens the board beyond the explored cell.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Expansion {
+    /// Opens across cells without neighbouring mines and stops at the numbers around
+    /// them.
+    #[default]
+    Classic,
+    /// Opens the explored cell only.
+    Conservative,
+    /// Opens like classic and also around every opened number with as many flags next
+    /// to it as its count, a wrong flag there loses the game.
+    Aggressive,
+}
+
+impl Expansion {
+    pub fn is_classic(&self) -> bool {
+        *self == Expansion::Classic
+    }
+}
+
+/// Which comes first in the coordinates the player types.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AxisOrder {
+    /// `(row, column)`
+    #[default]
+    RowColumn,
+    /// `(x, y)`, the column first.
+    Xy,
+}
+
+/// How the player numbers the cells, in commands as well as on the printed rulers. The
+/// board itself always counts rows and columns from 0. Spreadsheet style cells like
+/// `explore C7` are understood in every convention.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CoordinateConvention {
+    /// Whether the first row and column are 1 instead of 0.
+    pub one_based: bool,
+    pub order: AxisOrder,
+    /// Whether the columns are labelled with letters like in a spreadsheet, the rows
+    /// then count from 1.
+    pub letters: bool,
+}
+
+impl CoordinateConvention {
+    /// Number the player sees for a row or column of the board.
+    pub fn number(&self, index: u32) -> u32 {
+        index + self.first()
+    }
+
+    /// Row or column of the board the player means, none below the first number.
+    pub fn index(&self, number: u32) -> Option<u32> {
+        number.checked_sub(self.first())
+    }
+
+    fn first(&self) -> u32 {
+        (self.one_based || self.letters) as u32
+    }
+
+    /// Label of a column on the rulers, `A` to `Z`, then `AA` and on with letters.
+    pub fn column_label(&self, col: u32) -> String {
+        if !self.letters {
+            return self.number(col).to_string();
+        }
+        let mut letters = vec![];
+        let mut col = col as u64;
+        loop {
+            letters.push(char::from(b'A' + (col % 26) as u8));
+            if col < 26 {
+                break;
+            }
+            col = col / 26 - 1;
+        }
+        letters.into_iter().rev().collect()
+    }
+
+    /// The cell as it is written to the player, `(3, 4)` or `E4` with letters.
+    pub fn cell_name(&self, coordinate: Coordinate) -> String {
+        if self.letters {
+            return format!(
+                "{}{}",
+                self.column_label(coordinate.1),
+                self.number(coordinate.0)
+            );
+        }
+        let Coordinate(first, second) = self.to_player(coordinate);
+        format!("({}, {})", first, second)
+    }
+
+    /// Parses a typed command, `explore(3, 4)` in this convention or `explore C7`.
+    pub fn parse(&self, value: &str) -> Result<BoardCommand, BoardCommandError> {
+        self.parse_after(value, None)
+    }
+
+    /// Parses a typed command like [`parse`](Self::parse), signed coordinates like
+    /// `explore(+1, 0)` are taken relative to the last cell acted on.
+    pub fn parse_after(
+        &self,
+        value: &str,
+        last_cell: Option<Coordinate>,
+    ) -> Result<BoardCommand, BoardCommandError> {
+        if let Some(relative) = parse_relative_command(value) {
+            let (command, first, second) = relative?;
+            let Coordinate(row, col) = last_cell.ok_or(BoardCommandError::NoLastCell)?;
+            let (rows, cols) = match self.order {
+                AxisOrder::RowColumn => (first, second),
+                AxisOrder::Xy => (second, first),
+            };
+            let cell = row
+                .checked_add_signed(rows)
+                .zip(col.checked_add_signed(cols))
+                .ok_or(BoardCommandError::OutsideBoard)?;
+            return Ok(command.at(Coordinate(cell.0, cell.1)));
+        }
+        if let Some(command) = parse_spreadsheet_command(value) {
+            return command.or_else(|err| plugin::parse(value).unwrap_or(Err(err)));
+        }
+        let command = BoardCommand::try_from(value)?;
+        self.command(command).ok_or(BoardCommandError::OutsideBoard)
+    }
+
+    /// Cell of the board the player means with the typed coordinate.
+    pub fn to_board(&self, Coordinate(first, second): Coordinate) -> Option<Coordinate> {
+        let (row, col) = match self.order {
+            AxisOrder::RowColumn => (first, second),
+            AxisOrder::Xy => (second, first),
+        };
+        Some(Coordinate(self.index(row)?, self.index(col)?))
+    }
+
+    /// The cell as the player would type it.
+    pub fn to_player(&self, Coordinate(row, col): Coordinate) -> Coordinate {
+        let (row, col) = (self.number(row), self.number(col));
+        match self.order {
+            AxisOrder::RowColumn => Coordinate(row, col),
+            AxisOrder::Xy => Coordinate(col, row),
+        }
+    }
+
+    /// The typed command with its cell on the board, none if the cell cannot be on it.
+    fn command(&self, command: BoardCommand) -> Option<BoardCommand> {
+        Some(match command {
+            BoardCommand::Pass | BoardCommand::Quit | BoardCommand::Claim => command,
+            BoardCommand::ClearMark(coordinate) => {
+                BoardCommand::ClearMark(self.to_board(coordinate)?)
+            }
+            BoardCommand::SetMarkFlag(coordinate) => {
+                BoardCommand::SetMarkFlag(self.to_board(coordinate)?)
+            }
+            BoardCommand::SetMarkFlagCount(coordinate, count) => {
+                BoardCommand::SetMarkFlagCount(self.to_board(coordinate)?, count)
+            }
+            BoardCommand::SetMarkNote(coordinate) => {
+                BoardCommand::SetMarkNote(self.to_board(coordinate)?)
+            }
+            BoardCommand::Explore(coordinate) => BoardCommand::Explore(self.to_board(coordinate)?),
+            BoardCommand::SetMarkFlagRange(from, to) => {
+                BoardCommand::SetMarkFlagRange(self.to_board(from)?, self.to_board(to)?)
+            }
+            BoardCommand::ClearMarkRange(from, to) => {
+                BoardCommand::ClearMarkRange(self.to_board(from)?, self.to_board(to)?)
+            }
+            BoardCommand::Plugin(plugin_command) => match plugin_command.cell {
+                Some(cell) => command.at(self.to_board(cell)?),
+                None => command,
+            },
+        })
+    }
+}
+
+/// Parses commands with signed coordinates like `flag(-1, +1)` into the command and its
+/// offsets, none if no part of the coordinate is signed.
+fn parse_relative_command(
+    value: &str,
+) -> Option<Result<(BoardCommand, i32, i32), BoardCommandError>> {
+    let (verb, arguments) = value.trim().split_once('(')?;
+    let arguments = arguments.trim().strip_suffix(')')?;
+    let (first, second) = arguments.split_once(',')?;
+    let (first, second) = (first.trim(), second.trim());
+    if ![first, second]
+        .iter()
+        .any(|offset| offset.starts_with(['+', '-']))
+    {
+        return None;
+    }
+
+    let offsets = first
+        .parse::<i32>()
+        .and_then(|first| Ok((first, second.parse::<i32>()?)))
+        .map_err(|_| BoardCommandError::MalformedCoordinate);
+    Some(offsets.and_then(|(first, second)| {
+        let command = BoardCommand::try_from(&format!("{}(0, 0)", verb)[..])?;
+        Ok((command, first, second))
+    }))
+}
+
+/// Parses spreadsheet style commands like `explore C7` or `flag C7 2`, none if the value
+/// is not written that way.
+fn parse_spreadsheet_command(value: &str) -> Option<Result<BoardCommand, BoardCommandError>> {
+    let value = value.trim().to_lowercase();
+    let mut words = value.split_whitespace();
+    let (command, cell) = (words.next()?, words.next()?);
+    let count = words.next();
+    if words.next().is_some() || !cell.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    let digits = cell.find(|c: char| c.is_ascii_digit())?;
+    let (letters, row) = cell.split_at(digits);
+    if !letters.chars().all(|c| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let row = match row.parse::<u32>() {
+        Ok(row) => row,
+        Err(err) => return Some(Err(BoardCommandError::CoordinateParsing(err))),
+    };
+    // letters count A to Z and then AA on, every letter worth 26 times its follower
+    let col = letters.bytes().try_fold(0u32, |col, letter| {
+        col.checked_mul(26)?.checked_add((letter - b'a') as u32 + 1)
+    });
+    let (Some(row), Some(col)) = (row.checked_sub(1), col.map(|col| col - 1)) else {
+        return Some(Err(BoardCommandError::OutsideBoard));
+    };
+    let coordinate = Coordinate(row, col);
+
+    Some(match (command, count) {
+        ("flag", Some(count)) => count
+            .parse::<i8>()
+            .map(|count| BoardCommand::SetMarkFlagCount(coordinate, count))
+            .map_err(BoardCommandError::CoordinateParsing),
+        (_, Some(_)) => Err(BoardCommandError::MalformedCoordinate),
+        ("clear", None) => Ok(BoardCommand::ClearMark(coordinate)),
+        ("flag", None) => Ok(BoardCommand::SetMarkFlag(coordinate)),
+        ("note", None) => Ok(BoardCommand::SetMarkNote(coordinate)),
+        ("explore", None) => Ok(BoardCommand::Explore(coordinate)),
+        _ => Err(BoardCommandError::NotFound),
+    })
+}
+
+/// Boards of at least this many cells count their mines on several threads.
+#[cfg(feature = "parallel")]
+const PARALLEL_CELLS: usize = 1 << 16;
+
+/// Boards of at least this many cells keep their cells sparse if they have few mines.
+const SPARSE_CELLS: usize = 1 << 22;
+/// Cells per mine a board needs at least to be kept sparse.
+const SPARSE_DENSITY: usize = 50;
+
+/// Longest side a board may have.
+pub const MAX_SIDE: u32 = 100_000;
+/// Most cells a board may have, every cell takes a few bytes.
+pub const MAX_CELLS: u64 = 100_000_000;
+/// Most mines a cell may hold, counts are flagged as an `i8`.
+pub const MAX_MINES_PER_CELL: u8 = i8::MAX as u8;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameConfiguration {
+    width: u32,
+    height: u32,
+    total_mines: u32,
+    /// Whether the edges wrap around, the board then being a torus.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    wrap_edges: bool,
+    #[serde(default, skip_serializing_if = "Adjacency::is_standard")]
+    adjacency: Adjacency,
+    /// Most mines a single cell may hold.
+    #[serde(
+        default = "GameConfiguration::single_mine",
+        skip_serializing_if = "GameConfiguration::is_single_mine"
+    )]
+    mines_per_cell: u8,
+    /// Anti-mines subtract from the counts of their neighbours, hitting one loses all
+    /// the same.
+    #[serde(default, skip_serializing_if = "GameConfiguration::is_zero")]
+    anti_mines: u32,
+    #[serde(default, skip_serializing_if = "Distribution::is_uniform")]
+    distribution: Distribution,
+    /// Symmetry of the mine layout, anti-mines are placed without.
+    #[serde(default, skip_serializing_if = "Symmetry::is_asymmetric")]
+    symmetry: Symmetry,
+}
+
+impl GameConfiguration {
+    pub const fn new(width: u32, height: u32, total_mines: u32) -> Self {
+        GameConfiguration {
+            width,
+            height,
+            total_mines,
+            wrap_edges: false,
+            adjacency: Adjacency::Standard,
+            mines_per_cell: 1,
+            anti_mines: 0,
+            distribution: Distribution::Uniform,
+            symmetry: Symmetry::Asymmetric,
+        }
+    }
+
+    pub const fn with_wrap_edges(mut self, wrap_edges: bool) -> Self {
+        self.wrap_edges = wrap_edges;
+        self
+    }
+
+    pub const fn with_adjacency(mut self, adjacency: Adjacency) -> Self {
+        self.adjacency = adjacency;
+        self
+    }
+
+    /// Lets cells hold up to the given number of mines, at least one.
+    pub fn with_mines_per_cell(mut self, mines_per_cell: u8) -> Self {
+        self.mines_per_cell = mines_per_cell.clamp(1, MAX_MINES_PER_CELL);
+        self
+    }
+
+    pub const fn with_anti_mines(mut self, anti_mines: u32) -> Self {
+        self.anti_mines = anti_mines;
+        self
+    }
+
+    pub const fn with_distribution(mut self, distribution: Distribution) -> Self {
+        self.distribution = distribution;
+        self
+    }
+
+    pub const fn with_symmetry(mut self, symmetry: Symmetry) -> Self {
+        self.symmetry = symmetry;
+        self
+    }
+
+    fn single_mine() -> u8 {
+        1
+    }
+
+    fn is_single_mine(mines_per_cell: &u8) -> bool {
+        *mines_per_cell == 1
+    }
+
+    fn is_zero(anti_mines: &u32) -> bool {
+        *anti_mines == 0
+    }
+
+    /// The classic beginner difficulty, 9x9 with 10 mines.
+    pub fn beginner() -> Self {
+        GameConfiguration::new(9, 9, 10)
+    }
+
+    /// The classic intermediate difficulty, 16x16 with 40 mines.
+    pub fn intermediate() -> Self {
+        GameConfiguration::new(16, 16, 40)
+    }
+
+    /// The classic expert difficulty, 30x16 with 99 mines.
+    pub fn expert() -> Self {
+        GameConfiguration::new(30, 16, 99)
+    }
+
+    /// The classic difficulty of the name, like `expert`.
+    pub fn named(name: &str) -> Option<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "beginner" => Some(GameConfiguration::beginner()),
+            "intermediate" => Some(GameConfiguration::intermediate()),
+            "expert" => Some(GameConfiguration::expert()),
+            _ => None,
+        }
+    }
+
+    pub fn w(&self) -> u32 {
+        self.width
+    }
+
+    pub fn h(&self) -> u32 {
+        self.height
+    }
+
+    pub fn mines(&self) -> u32 {
+        self.total_mines
+    }
+
+    pub fn wrap_edges(&self) -> bool {
+        self.wrap_edges
+    }
+
+    pub fn adjacency(&self) -> Adjacency {
+        self.adjacency
+    }
+
+    pub fn mines_per_cell(&self) -> u8 {
+        self.mines_per_cell
+    }
+
+    pub fn anti_mines(&self) -> u32 {
+        self.anti_mines
+    }
+
+    pub fn distribution(&self) -> Distribution {
+        self.distribution
+    }
+
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    /// Mines and anti-mines, every one of them has to be flagged to win.
+    pub fn mines_to_discover(&self) -> u32 {
+        self.total_mines + self.anti_mines
+    }
+
+    /// Whether the board is within the limits and the mines and anti-mines fit on it.
+    pub fn fits(&self) -> bool {
+        self.within_limits() && self.fits_cells(self.width as u64 * self.height as u64)
+    }
+
+    /// Whether the board has cells and stays within [`MAX_SIDE`] and [`MAX_CELLS`], and a
+    /// cell holds at most [`MAX_MINES_PER_CELL`]. Deserialized configurations skip the
+    /// clamp of [`GameConfiguration::with_mines_per_cell`].
+    pub fn within_limits(&self) -> bool {
+        (1..=MAX_SIDE).contains(&self.width)
+            && (1..=MAX_SIDE).contains(&self.height)
+            && self.width as u64 * self.height as u64 <= MAX_CELLS
+            && (1..=MAX_MINES_PER_CELL).contains(&self.mines_per_cell)
+    }
+
+    /// Whether the mines and anti-mines fit into the number of cells. Anti-mines need
+    /// cells of their own and the mines may be spread over one cell each.
+    pub fn fits_cells(&self, cells: u64) -> bool {
+        let mines = self.total_mines as u64;
+        let anti_mines = self.anti_mines as u64;
+        mines <= cells * self.mines_per_cell as u64
+            && (anti_mines == 0 || mines + anti_mines <= cells)
+    }
+
+    /// Name of the classic difficulty the configuration matches, if any.
+    pub fn difficulty_name(&self) -> Option<&'static str> {
+        if *self == GameConfiguration::beginner() {
+            Some("Beginner")
+        } else if *self == GameConfiguration::intermediate() {
+            Some("Intermediate")
+        } else if *self == GameConfiguration::expert() {
+            Some("Expert")
+        } else {
+            None
+        }
+    }
+
+    /// Identifies the difficulty, boards with equal keys are compared against each other.
+    pub fn key(&self) -> String {
+        let mut key = format!("{}x{}-{}", self.width, self.height, self.total_mines);
+        if self.wrap_edges {
+            key += "-wrap";
+        }
+        if !self.adjacency.is_standard() {
+            key = format!("{}-{}", key, self.adjacency);
+        }
+        if self.mines_per_cell > 1 {
+            key = format!("{}-multi{}", key, self.mines_per_cell);
+        }
+        if self.anti_mines > 0 {
+            key = format!("{}-anti{}", key, self.anti_mines);
+        }
+        if !self.distribution.is_uniform() {
+            key = format!("{}-{}", key, self.distribution);
+        }
+        if !self.symmetry.is_asymmetric() {
+            key = format!("{}-{}", key, self.symmetry);
+        }
+        key
+    }
+}
+
+impl Default for GameConfiguration {
+    fn default() -> Self {
+        GameConfiguration {
+            width: 5,
+            height: 5,
+            total_mines: 10,
+            wrap_edges: false,
+            adjacency: Adjacency::Standard,
+            mines_per_cell: 1,
+            anti_mines: 0,
+            distribution: Distribution::Uniform,
+            symmetry: Symmetry::Asymmetric,
+        }
+    }
+}
+
+#[allow(dead_code)]
+#[derive(Debug)]
+pub enum GameConfigurationError {
+    MalformedString,
+    MalformedInteger(ParseIntError),
+    /// The board is empty or larger than the limits.
+    OutOfLimits,
+    /// There are more mines than cells.
+    TooManyMines,
+}
+
+impl TryFrom<&str> for GameConfiguration {
+    type Error = GameConfigurationError;
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let (dimensions, mines) = value
+            .split_once(" ")
+            .ok_or(GameConfigurationError::MalformedString)?;
+
+        let configuration = GameConfiguration::new(
+            dimensions
+                .trim()
+                .parse::<u32>()
+                .map_err(GameConfigurationError::MalformedInteger)?,
+            dimensions
+                .trim()
+                .parse::<u32>()
+                .map_err(GameConfigurationError::MalformedInteger)?,
+            mines
+                .trim()
+                .parse::<u32>()
+                .map_err(GameConfigurationError::MalformedInteger)?,
+        );
+        if !configuration.within_limits() {
+            return Err(GameConfigurationError::OutOfLimits);
+        }
+        if !configuration.fits() {
+            return Err(GameConfigurationError::TooManyMines);
+        }
+        Ok(configuration)
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum GameResolve {
+    Quit,
+    Continue,
+    MineHit,
+    AllMinesDiscovered,
+}
+
+pub struct GameBoard {
+    game_configuration: GameConfiguration,
+    /// Seed the mines were generated from.
+    seed: u64,
+    mines_discovered: u32,
+    /// Flags on cells without a mine, a board with any of them is not won.
+    wrong_flags: u32,
+    /// Cells without a mine that are not explored yet, holes left out. The board is won
+    /// without a single flag once there are none.
+    safe_cells_left: u64,
+    cells: PackedCells,
+    /// Number of mines in every cell, more than one if the configuration allows it and
+    /// negative for anti-mines.
+    mine_counts: Counts,
+    /// Number of mines every flag was placed for.
+    flag_counts: Counts,
+    /// Shape of the board, every cell is part of it without one.
+    mask: Option<Mask>,
+    /// Hand made layout the board was built from instead of a seed.
+    layout: Option<Layout>,
+    flag_guard: FlagGuard,
+    /// How the rulers number the rows and columns.
+    convention: CoordinateConvention,
+    theme: Theme,
+    /// Cell the last command acted on, relative coordinates start from it.
+    last_cell: Option<Coordinate>,
+    /// Flagged cell whose explore was held back, waiting for confirmation.
+    held_back: Option<Coordinate>,
+    /// Whether the first mine hit is flagged instead of lost.
+    forgiving: bool,
+    /// Whether the game is only won by a claim once the flags are exactly on the mines.
+    strict_flags: bool,
+    /// Cells kept free of mines around the first explore.
+    safe_start: SafeStart,
+    /// How far an explore opens the board.
+    expansion: Expansion,
+    /// Whether every explore is followed by chording the satisfied numbers.
+    auto_chord: bool,
+    mistakes_forgiven: u32,
+    /// Whether hidden mines are drawn, for debugging.
+    show_mines: bool,
+    /// Whether the mines were ever shown before the game ended.
+    cheated: bool,
+}
+
+impl GameBoard {
+    pub fn new(game_configuration: GameConfiguration) -> GameBoard {
+        let size = game_configuration.w() as usize * game_configuration.h() as usize;
+        let mines = game_configuration.mines() as usize + game_configuration.anti_mines() as usize;
+        // almost every cell of a huge board with few mines stays empty
+        let sparse = size >= SPARSE_CELLS && mines * SPARSE_DENSITY <= size;
+        GameBoard {
+            game_configuration,
+            seed: 0,
+            mines_discovered: 0,
+            wrong_flags: 0,
+            safe_cells_left: size as u64,
+            cells: if sparse {
+                PackedCells::sparse(size)
+            } else {
+                PackedCells::new(size)
+            },
+            mine_counts: if sparse {
+                Counts::sparse(size)
+            } else {
+                Counts::new(size)
+            },
+            flag_counts: if sparse {
+                Counts::sparse(size)
+            } else {
+                Counts::new(size)
+            },
+            mask: None,
+            layout: None,
+            flag_guard: FlagGuard::Off,
+            convention: CoordinateConvention::default(),
+            theme: Theme::default(),
+            last_cell: None,
+            held_back: None,
+            forgiving: false,
+            strict_flags: false,
+            safe_start: SafeStart::Off,
+            expansion: Expansion::Classic,
+            auto_chord: false,
+            mistakes_forgiven: 0,
+            show_mines: false,
+            cheated: false,
+        }
+    }
+
+    /// Board with the mines of the layout and its revealed cells explored, there is no
+    /// world left to generate.
+    pub fn from_layout(layout: Layout) -> GameBoard {
+        let mut game_board = GameBoard::new(layout.configuration());
+
+        let cells = (0..layout.height())
+            .flat_map(|row| (0..layout.width()).map(move |col| Coordinate(row, col)));
+        let mine_positions: Vec<u32> = cells
+            .clone()
+            .filter(|coordinate| layout.cell(*coordinate) == LayoutCell::Mine)
+            .map(|coordinate| game_board.compute_linear_index(coordinate) as u32)
+            .collect();
+        game_board.place_mines(&mine_positions, 1);
+
+        for coordinate in
+            cells.filter(|coordinate| layout.cell(*coordinate) == LayoutCell::Revealed)
+        {
+            let lin_index = game_board.compute_linear_index(coordinate);
+            if let BoardCell::NoMine(CellInfo(_, neighbour_mines)) = game_board.cells.get(lin_index)
+            {
+                game_board
+                    .cells
+                    .set(lin_index, BoardCell::Explored(neighbour_mines));
+                game_board.safe_cells_left -= 1;
+            }
+        }
+
+        game_board.layout = Some(layout);
+        game_board
+    }
+
+    pub fn layout(&self) -> Option<&Layout> {
+        self.layout.as_ref()
+    }
+
+    pub fn set_flag_guard(&mut self, flag_guard: FlagGuard) {
+        self.flag_guard = flag_guard;
+        self.held_back = None;
+    }
+
+    pub fn flag_guard(&self) -> FlagGuard {
+        self.flag_guard
+    }
+
+    pub fn set_coordinate_convention(&mut self, convention: CoordinateConvention) {
+        self.convention = convention;
+    }
+
+    pub fn coordinate_convention(&self) -> CoordinateConvention {
+        self.convention
+    }
+
+    pub fn set_theme(&mut self, theme: Theme) {
+        self.theme = theme;
+    }
+
+    /// Cell the last command acted on.
+    pub fn last_cell(&self) -> Option<Coordinate> {
+        self.last_cell
+    }
+
+    /// Forgives the first mine hit, the mine is flagged instead and the game goes on.
+    pub fn set_forgiving(&mut self, forgiving: bool) {
+        self.forgiving = forgiving;
+    }
+
+    pub fn is_forgiving(&self) -> bool {
+        self.forgiving
+    }
+
+    /// Wins the game only by a `claim`, which checks that every mine and nothing else is
+    /// flagged and loses the game otherwise.
+    pub fn set_strict_flags(&mut self, strict_flags: bool) {
+        self.strict_flags = strict_flags;
+    }
+
+    pub fn is_strict_flags(&self) -> bool {
+        self.strict_flags
+    }
+
+    /// Keeps the cells around the first explore free of mines. The mines are placed anew
+    /// at the first explore, marks set before it are dropped.
+    pub fn set_safe_start(&mut self, safe_start: SafeStart) {
+        self.safe_start = safe_start;
+    }
+
+    pub fn safe_start(&self) -> SafeStart {
+        self.safe_start
+    }
+
+    pub fn set_expansion(&mut self, expansion: Expansion) {
+        self.expansion = expansion;
+    }
+
+    pub fn expansion(&self) -> Expansion {
+        self.expansion
+    }
+
+    /// Chords every explored number whose flags are placed after each explore, until no
+    /// such number is left. A wrong flag then loses the game.
+    pub fn set_auto_chord(&mut self, auto_chord: bool) {
+        self.auto_chord = auto_chord;
+    }
+
+    pub fn is_auto_chord(&self) -> bool {
+        self.auto_chord
+    }
+
+    /// Whether satisfied numbers are chorded for the player, by auto-chording or by the
+    /// aggressive expansion.
+    pub fn is_assisted(&self) -> bool {
+        self.auto_chord || self.expansion == Expansion::Aggressive
+    }
+
+    /// Mine hits that were forgiven, at most one.
+    pub fn mistakes_forgiven(&self) -> u32 {
+        self.mistakes_forgiven
+    }
+
+    /// Draws the hidden mines, which disqualifies the game from being ranked.
+    pub fn set_show_mines(&mut self, show_mines: bool) {
+        self.show_mines = show_mines;
+        self.cheated |= show_mines;
+    }
+
+    pub fn shows_mines(&self) -> bool {
+        self.show_mines
+    }
+
+    /// Disqualifies the game from being ranked, e.g. once its solution was exported.
+    pub fn mark_cheated(&mut self) {
+        self.cheated = true;
+    }
+
+    /// Whether the mines were shown at some point.
+    pub fn is_cheated(&self) -> bool {
+        self.cheated
+    }
+
+    /// Checks the command against the flag guard, false if it explores a flagged cell
+    /// and is held back. Frontends check every command before applying it, so replays
+    /// only hold the commands that were applied. The coordinate must lie on the board.
+    pub fn passes_flag_guard(&mut self, command: BoardCommand) -> bool {
+        let held_back = match command {
+            BoardCommand::Explore(coordinate) if self.is_flagged(coordinate) => {
+                match self.flag_guard {
+                    FlagGuard::Off => false,
+                    FlagGuard::Confirm => self.held_back != Some(coordinate),
+                    FlagGuard::Reject => true,
+                }
+            }
+            _ => false,
+        };
+        self.held_back = match command {
+            BoardCommand::Explore(coordinate) if held_back => Some(coordinate),
+            _ => None,
+        };
+        !held_back
+    }
+
+    fn is_flagged(&self, coordinate: Coordinate) -> bool {
+        matches!(
+            self.get_cell_at(coordinate),
+            BoardCell::Mine(Mark::MarkFlag) | BoardCell::NoMine(CellInfo(Mark::MarkFlag, _))
+        )
+    }
+
+    /// Board shaped by the mask, the configuration takes the dimensions of the mask.
+    pub fn with_mask(game_configuration: GameConfiguration, mask: Mask) -> GameBoard {
+        let mut game_configuration = game_configuration;
+        game_configuration.width = mask.width();
+        game_configuration.height = mask.height();
+
+        let mut game_board = GameBoard::new(game_configuration);
+        game_board.safe_cells_left = mask.cell_count() as u64;
+        game_board.mask = Some(mask);
+        game_board
+    }
+
+    pub fn configuration(&self) -> GameConfiguration {
+        self.game_configuration
+    }
+
+    pub fn mask(&self) -> Option<&Mask> {
+        self.mask.as_ref()
+    }
+
+    /// Whether the coordinate lies in a hole of the mask.
+    pub fn is_hole(&self, coordinate: Coordinate) -> bool {
+        self.mask
+            .as_ref()
+            .is_some_and(|mask| mask.is_hole(coordinate))
+    }
+
+    /// Seed the mines were generated from.
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+
+    /// Generates a world from a seed drawn from the rng, pass `rand::thread_rng()` to play
+    /// and a seeded rng to get the same worlds in tests. The seed is kept for the replay.
+    pub fn generate_world(&mut self, rng: &mut impl Rng) {
+        self.generate_world_from_seed(rng.gen());
+    }
+
+    /// Generates the same world for the same seed on every machine.
+    pub fn generate_world_from_seed(&mut self, seed: u64) {
+        self.seed = seed;
+        self.generate_world_with(&mut ChaCha8Rng::seed_from_u64(seed), &[]);
+    }
+
+    /// Places the mines of the seed again with the safe start around the coordinate free.
+    /// Crowded boards keep only the cell itself free, or nothing if even that is too much.
+    fn generate_world_around(&mut self, coordinate: Coordinate) {
+        let (width, height) = self.get_dimensions();
+        let playable = match &self.mask {
+            Some(mask) => mask.cell_count() as u64,
+            None => width as u64 * height as u64,
+        };
+        let safe: Vec<u32> = [
+            self.safe_start.cells(coordinate, width, height),
+            vec![coordinate],
+            vec![],
+        ]
+        .into_iter()
+        .map(|cells| {
+            cells
+                .into_iter()
+                .filter(|cell| !self.is_hole(*cell))
+                .map(|cell| self.compute_linear_index(cell) as u32)
+                .collect::<Vec<u32>>()
+        })
+        .find(|safe| {
+            self.game_configuration
+                .fits_cells(playable - safe.len() as u64)
+        })
+        .unwrap_or_default();
+
+        let fresh = GameBoard::new(self.game_configuration);
+        self.cells = fresh.cells;
+        self.mine_counts = fresh.mine_counts;
+        self.flag_counts = fresh.flag_counts;
+        self.mines_discovered = 0;
+        self.wrong_flags = 0;
+        self.safe_cells_left = playable;
+        self.generate_world_with(&mut ChaCha8Rng::seed_from_u64(self.seed), &safe);
+    }
+
+    /// Places the mines outside of the safe positions, linear indices of the board.
+    fn generate_world_with<R: Rng>(&mut self, rng: &mut R, safe: &[u32]) {
+        let _span = tracing::debug_span!(
+            "generate_world",
+            seed = self.seed,
+            width = self.game_configuration.w(),
+            height = self.game_configuration.h(),
+            mines = self.game_configuration.mines(),
+        )
+        .entered();
+        let started = Instant::now();
+        let (width, height) = self.get_dimensions();
+        let cells = width as u64 * height as u64;
+        let free = match &self.mask {
+            Some(mask) => mask.cell_count() as u64,
+            None => cells,
+        } - safe.len() as u64;
+        let mines_per_cell = self.game_configuration.mines_per_cell() as u64;
+        let (mines, anti_mines) = (
+            self.game_configuration.mines() as usize,
+            self.game_configuration.anti_mines() as usize,
+        );
+        let is_candidate = |board: &GameBoard, position: u32| {
+            !board.is_hole(board.compute_coordinate(position as usize)) && !safe.contains(&position)
+        };
+
+        // uniform mines that leave most cells free are drawn without listing every cell
+        let sparse = self.game_configuration.distribution().is_uniform()
+            && self.game_configuration.symmetry().is_asymmetric()
+            && (mines + anti_mines) as u64 * 2 <= free;
+        let mine_positions: Vec<u32> = if sparse {
+            // every cell has a slot for each mine it can hold
+            generator::sample_sparse(cells * mines_per_cell, mines, rng, |slot| {
+                is_candidate(self, (slot / mines_per_cell) as u32)
+            })
+            .into_iter()
+            .map(|slot| (slot / mines_per_cell) as u32)
+            .collect()
+        } else {
+            let generator = Symmetric {
+                symmetry: self.game_configuration.symmetry(),
+                generator: self.game_configuration.distribution().generator(),
+            };
+            generator.pick(
+                &self.game_configuration,
+                (0..cells as u32)
+                    .filter(|position| is_candidate(self, *position))
+                    .flat_map(|position| std::iter::repeat_n(position, mines_per_cell as usize))
+                    .collect(),
+                mines,
+                rng,
+            )
+        };
+        self.place_mines(&mine_positions, 1);
+
+        if anti_mines > 0 {
+            let is_free = |board: &GameBoard, position: u32| {
+                is_candidate(board, position) && board.mine_counts.get(position as usize) == 0
+            };
+            let anti_mine_positions: Vec<u32> = if sparse {
+                generator::sample_sparse(cells, anti_mines, rng, |position| {
+                    is_free(self, position as u32)
+                })
+                .into_iter()
+                .map(|position| position as u32)
+                .collect()
+            } else {
+                let mut free_positions: Vec<u32> = (0..cells as u32)
+                    .filter(|position| is_free(self, *position))
+                    .collect();
+                free_positions.shuffle(rng);
+                free_positions.truncate(anti_mines);
+                free_positions
+            };
+            self.place_mines(&anti_mine_positions, -1);
+        }
+        tracing::debug!(elapsed = ?started.elapsed(), "world generated");
+    }
+
+    /// Places mines of the charge, 1 for mines and -1 for anti-mines.
+    fn place_mines(&mut self, mine_positions: &[u32], charge: i8) {
+        // a position may be listed several times, once for every mine in the cell
+        for mine_lin_index in mine_positions {
+            let mine_lin_index = *mine_lin_index as usize;
+            if let BoardCell::NoMine(_) = self.cells.get(mine_lin_index) {
+                self.safe_cells_left -= 1;
+            }
+            self.cells
+                .set(mine_lin_index, BoardCell::Mine(Mark::NoMark));
+            self.mine_counts.set(
+                mine_lin_index,
+                self.mine_counts.get(mine_lin_index) + charge,
+            );
+        }
+
+        if self.cells.is_sparse() {
+            for (lin_index, change) in self.sparse_count_changes(mine_positions, charge) {
+                self.change_count(lin_index, change);
+            }
+        } else {
+            let count_changes = self.count_changes(mine_positions, charge);
+            for (lin_index, change) in count_changes.into_iter().enumerate() {
+                self.change_count(lin_index, change);
+            }
+        }
+    }
+
+    fn change_count(&mut self, lin_index: usize, change: i16) {
+        if change == 0 {
+            return;
+        }
+        if let BoardCell::NoMine(cell_info) = self.cells.get(lin_index) {
+            self.wrong_flags -= self.wrong_flag(lin_index);
+            self.cells.set(
+                lin_index,
+                BoardCell::NoMine(CellInfo(
+                    Mark::NoMark,
+                    NeighbourMines(cell_info.1 .0 + change),
+                )),
+            );
+        }
+    }
+
+    pub fn manipulate_cell(&mut self, command: BoardCommand) -> GameResolve {
+        self.last_cell = command.cell().or(self.last_cell);
+        let command_result = match command {
+            BoardCommand::Quit => GameResolve::Quit,
+            BoardCommand::Pass => GameResolve::Continue,
+            BoardCommand::Claim if self.strict_flags => self.claim(),
+            BoardCommand::Claim => GameResolve::Continue,
+            BoardCommand::ClearMark(coordinate) => self.clear_mark(coordinate),
+            BoardCommand::SetMarkFlag(coordinate) => self.set_mark_flag(coordinate, 1),
+            BoardCommand::SetMarkFlagCount(coordinate, count) => {
+                self.set_mark_flag(coordinate, count)
+            }
+            BoardCommand::SetMarkNote(coordinate) => self.set_mark_note(coordinate),
+            BoardCommand::Explore(coordinate) => match self.explore(coordinate) {
+                GameResolve::Continue if self.auto_chord => self.chord_satisfied(),
+                resolve => resolve,
+            },
+            BoardCommand::SetMarkFlagRange(from, to) => {
+                for coordinate in self.marked_in(from, to) {
+                    self.set_mark_flag(coordinate, 1);
+                }
+                GameResolve::Continue
+            }
+            BoardCommand::ClearMarkRange(from, to) => {
+                for coordinate in self.marked_in(from, to) {
+                    self.clear_mark(coordinate);
+                }
+                GameResolve::Continue
+            }
+            BoardCommand::Plugin(command) => plugin::execute(command, self),
+        };
+
+        tracing::trace!(%command, resolve = ?command_result, "cell manipulated");
+        match command_result {
+            // under strict flagging only a claim wins
+            GameResolve::Continue if self.strict_flags => GameResolve::Continue,
+            GameResolve::Continue | GameResolve::AllMinesDiscovered => {
+                if (self.mines_discovered == self.game_configuration.mines_to_discover()
+                    && self.wrong_flags == 0)
+                    || (matches!(command, BoardCommand::Explore(_)) && self.safe_cells_left == 0)
+                {
+                    GameResolve::AllMinesDiscovered
+                } else {
+                    GameResolve::Continue
+                }
+            }
+            other => other,
+        }
+    }
+
+    /// Cells without a mine that are not explored, counted cell by cell for saves that
+    /// did not keep the count.
+    fn count_safe_cells_left(&self) -> u64 {
+        (0..self.cells.len())
+            .filter(|linear_index| {
+                matches!(self.cells.get(*linear_index), BoardCell::NoMine(_))
+                    && !self.is_hole(self.compute_coordinate(*linear_index))
+            })
+            .count() as u64
+    }
+
+    /// Won if every mine is flagged for its count and no other cell is flagged, lost
+    /// otherwise.
+    fn claim(&self) -> GameResolve {
+        let flags_exact =
+            (0..self.cells.len()).all(|linear_index| match self.cells.get(linear_index) {
+                BoardCell::Mine(_) => self.discovered_mines(linear_index) > 0,
+                BoardCell::NoMine(CellInfo(mark, _)) => mark != Mark::MarkFlag,
+                BoardCell::Explored(_) => true,
+            });
+        if flags_exact {
+            GameResolve::AllMinesDiscovered
+        } else {
+            GameResolve::MineHit
+        }
+    }
+
+    /// Cells of the range that take marks, explored cells and holes are left out.
+    fn marked_in(&self, from: Coordinate, to: Coordinate) -> Vec<Coordinate> {
+        from.rectangle(to)
+            .filter(|coordinate| {
+                !self.is_hole(*coordinate)
+                    && !matches!(self.get_cell_at(*coordinate), BoardCell::Explored(_))
+            })
+            .collect()
+    }
+
+    fn clear_mark(&mut self, coordinate: Coordinate) -> GameResolve {
+        let linear_index = self.compute_linear_index(coordinate);
+        self.mines_discovered -= self.discovered_mines(linear_index);
+        self.wrong_flags -= self.wrong_flag(linear_index);
+
+        match self.cells.get(linear_index) {
+            BoardCell::NoMine(cell_info) => {
+                self.cells.set(
+                    linear_index,
+                    BoardCell::NoMine(CellInfo(Mark::NoMark, cell_info.1)),
+                );
+            }
+            BoardCell::Mine(_) => {
+                self.cells.set(linear_index, BoardCell::Mine(Mark::NoMark));
+            }
+            _ => {}
+        }
+
+        GameResolve::Continue
+    }
+
+    fn set_mark_flag(&mut self, coordinate: Coordinate, count: i8) -> GameResolve {
+        let linear_index = self.compute_linear_index(coordinate);
+        self.mines_discovered -= self.discovered_mines(linear_index);
+        self.wrong_flags -= self.wrong_flag(linear_index);
+
+        match self.cells.get(linear_index) {
+            BoardCell::NoMine(cell_info) => {
+                self.cells.set(
+                    linear_index,
+                    BoardCell::NoMine(CellInfo(Mark::MarkFlag, cell_info.1)),
+                );
+            }
+            BoardCell::Mine(_) => {
+                self.cells
+                    .set(linear_index, BoardCell::Mine(Mark::MarkFlag));
+            }
+            _ => {}
+        }
+        self.flag_counts.set(linear_index, count);
+        self.mines_discovered += self.discovered_mines(linear_index);
+        self.wrong_flags += self.wrong_flag(linear_index);
+
+        GameResolve::Continue
+    }
+
+    fn set_mark_note(&mut self, coordinate: Coordinate) -> GameResolve {
+        let linear_index = self.compute_linear_index(coordinate);
+        self.mines_discovered -= self.discovered_mines(linear_index);
+        self.wrong_flags -= self.wrong_flag(linear_index);
+
+        match self.cells.get(linear_index) {
+            BoardCell::NoMine(cell_info) => {
+                self.cells.set(
+                    linear_index,
+                    BoardCell::NoMine(CellInfo(Mark::MarkNote, cell_info.1)),
+                );
+            }
+            BoardCell::Mine(_) => {
+                self.cells
+                    .set(linear_index, BoardCell::Mine(Mark::MarkNote));
+            }
+            _ => {}
+        }
+
+        GameResolve::Continue
+    }
+
+    /// Mines of the cell that count as discovered, which takes a flag for the right count.
+    fn discovered_mines(&self, linear_index: usize) -> u32 {
+        match self.cells.get(linear_index) {
+            BoardCell::Mine(Mark::MarkFlag)
+                if self.flag_counts.get(linear_index) == self.mine_counts.get(linear_index) =>
+            {
+                self.mine_counts.get(linear_index).unsigned_abs() as u32
+            }
+            _ => 0,
+        }
+    }
+
+    /// Whether the cell is flagged without holding a mine.
+    fn wrong_flag(&self, linear_index: usize) -> u32 {
+        matches!(
+            self.cells.get(linear_index),
+            BoardCell::NoMine(CellInfo(Mark::MarkFlag, _))
+        ) as u32
+    }
+
+    /// Number of mines the cell holds, -1 for an anti-mine.
+    pub fn mine_count(&self, coordinate: Coordinate) -> i8 {
+        self.mine_counts.get(self.compute_linear_index(coordinate))
+    }
+
+    /// Number of mines the flag on the cell was placed for, zero without a flag.
+    pub fn flag_count(&self, coordinate: Coordinate) -> i8 {
+        let linear_index = self.compute_linear_index(coordinate);
+        match self.cells.get(linear_index) {
+            BoardCell::Mine(Mark::MarkFlag) | BoardCell::NoMine(CellInfo(Mark::MarkFlag, _)) => {
+                self.flag_counts.get(linear_index)
+            }
+            _ => 0,
+        }
+    }
+
+    fn explore(&mut self, coordinate: Coordinate) -> GameResolve {
+        // hand made boards have their mines where they were put
+        if !self.safe_start.is_off() && self.layout.is_none() && self.explored_cells() == 0 {
+            self.generate_world_around(coordinate);
+        }
+        let linear_index = self.compute_linear_index(coordinate);
+
+        match self.cells.get(linear_index) {
+            BoardCell::NoMine(_) => self.explore_cells(coordinate),
+            BoardCell::Mine(_) if self.forgiving && self.mistakes_forgiven == 0 => {
+                self.mistakes_forgiven += 1;
+                self.set_mark_flag(coordinate, self.mine_counts.get(linear_index))
+            }
+            BoardCell::Mine(_) => GameResolve::MineHit,
+            _ => GameResolve::Continue,
+        }
+    }
+
+    fn compute_linear_index(&self, coordinate: Coordinate) -> usize {
+        coordinate.0 as usize * self.game_configuration.w() as usize + coordinate.1 as usize
+    }
+
+    fn compute_coordinate(&self, linear_index: usize) -> Coordinate {
+        let width = self.game_configuration.w() as usize;
+        Coordinate((linear_index / width) as u32, (linear_index % width) as u32)
+    }
+
+    /// Explores the cell and, breadth first, the cells around every explored cell without
+    /// neighbouring mines. Numbered cells are explored but end the opening, like in
+    /// classic Minesweeper. The [`Expansion`] of the board can stop at the cell or go on
+    /// around numbers whose flags are all placed, which hits a mine behind a wrong flag.
+    fn explore_cells(&mut self, coordinate: Coordinate) -> GameResolve {
+        let mut visited = BitSet::new(self.cells.len());
+        visited.insert(self.compute_linear_index(coordinate));
+        let mut queue = VecDeque::from([coordinate]);
+        let mut neighbours: Vec<Coordinate> = vec![];
+        let mut revealed = 0;
+
+        while let Some(cell_coordinate) = queue.pop_front() {
+            let linear_index = self.compute_linear_index(cell_coordinate);
+            let BoardCell::NoMine(cell_info) = self.cells.get(linear_index) else {
+                continue;
+            };
+            // explored cells lose their flags
+            self.wrong_flags -= self.wrong_flag(linear_index);
+            self.cells
+                .set(linear_index, BoardCell::Explored(cell_info.1));
+            self.safe_cells_left -= 1;
+            revealed += 1;
+            let opens = match self.expansion {
+                Expansion::Conservative => false,
+                Expansion::Classic => cell_info.1 .0 == 0,
+                Expansion::Aggressive => {
+                    cell_info.1 .0 == 0 || self.is_satisfied(cell_coordinate, cell_info.1 .0)
+                }
+            };
+            if !opens {
+                continue;
+            }
+
+            self.add_neighbours(&mut neighbours, cell_coordinate);
+            for neighbour in neighbours.drain(..) {
+                let neighbour_index = self.compute_linear_index(neighbour);
+                match self.cells.get(neighbour_index) {
+                    // the numbers around a cell without mines never hide one
+                    BoardCell::Mine(mark) if cell_info.1 .0 != 0 && mark != Mark::MarkFlag => {
+                        tracing::debug!(?coordinate, revealed, "cells revealed");
+                        return GameResolve::MineHit;
+                    }
+                    BoardCell::NoMine(CellInfo(Mark::MarkFlag, _)) if cell_info.1 .0 != 0 => {}
+                    _ => {
+                        if visited.insert(neighbour_index) {
+                            queue.push_back(neighbour);
+                        }
+                    }
+                }
+            }
+        }
+        tracing::debug!(?coordinate, revealed, "cells revealed");
+        GameResolve::Continue
+    }
+
+    /// Explores the hidden cells without flags around every explored number whose flags
+    /// are placed, again and again as long as that opens cells.
+    fn chord_satisfied(&mut self) -> GameResolve {
+        loop {
+            let hidden: Vec<Coordinate> = (0..self.cells.len())
+                .filter_map(|linear_index| match self.cells.get(linear_index) {
+                    BoardCell::Explored(NeighbourMines(count)) if count != 0 => {
+                        let coordinate = self.compute_coordinate(linear_index);
+                        (!self.is_hole(coordinate) && self.is_satisfied(coordinate, count))
+                            .then(|| self.neighbours(coordinate))
+                    }
+                    _ => None,
+                })
+                .flatten()
+                .filter(|neighbour| match self.get_cell_at(*neighbour) {
+                    BoardCell::Mine(mark) | BoardCell::NoMine(CellInfo(mark, _)) => {
+                        mark != Mark::MarkFlag
+                    }
+                    BoardCell::Explored(_) => false,
+                })
+                .collect();
+            if hidden.is_empty() {
+                return GameResolve::Continue;
+            }
+
+            for neighbour in hidden {
+                let resolve = self.explore(neighbour);
+                if resolve != GameResolve::Continue {
+                    return resolve;
+                }
+            }
+        }
+    }
+
+    /// Whether the flags next to the number add up to its count.
+    fn is_satisfied(&self, coordinate: Coordinate, count: i16) -> bool {
+        let flags: i16 = self
+            .neighbours(coordinate)
+            .into_iter()
+            .map(|neighbour| self.flag_count(neighbour) as i16)
+            .sum();
+        flags == count
+    }
+
+    fn add_neighbours(&self, queue: &mut Vec<Coordinate>, center: Coordinate) {
+        if self.game_configuration.wrap_edges() {
+            return self.add_wrapped_neighbours(queue, center);
+        }
+
+        for (i, j) in self.game_configuration.adjacency().offsets() {
+            let x = center.0 as i32 + i;
+            let y = center.1 as i32 + j;
+
+            if x < 0
+                || y < 0
+                || x >= self.game_configuration.h() as i32
+                || y >= self.game_configuration.w() as i32
+                || self.is_hole(Coordinate(x as u32, y as u32))
+            {
+                continue;
+            }
+
+            queue.push(Coordinate(x as u32, y as u32))
+        }
+    }
+
+    /// How much the neighbouring mine count of every cell changes with mines of the
+    /// charge at the positions.
+    fn count_changes(&self, mine_positions: &[u32], charge: i8) -> Vec<i16> {
+        #[cfg(feature = "parallel")]
+        if self.cells.len() >= PARALLEL_CELLS {
+            return self.count_changes_parallel(mine_positions, charge);
+        }
+
+        let mut count_changes = vec![0i16; self.cells.len()];
+        for mine_lin_index in mine_positions {
+            let center = self.compute_coordinate(*mine_lin_index as usize);
+            self.for_each_neighbour(center, |lin_index| {
+                count_changes[lin_index] += charge as i16
+            });
+        }
+        count_changes
+    }
+
+    /// Same changes as [`GameBoard::count_changes`] for the cells next to a mine only, a
+    /// vector would take two bytes for every cell of a sparse board.
+    fn sparse_count_changes(&self, mine_positions: &[u32], charge: i8) -> HashMap<usize, i16> {
+        let mut count_changes = HashMap::new();
+        for mine_lin_index in mine_positions {
+            let center = self.compute_coordinate(*mine_lin_index as usize);
+            self.for_each_neighbour(center, |lin_index| {
+                *count_changes.entry(lin_index).or_default() += charge as i16
+            });
+        }
+        count_changes
+    }
+
+    /// Same changes as [`GameBoard::count_changes`], gathered by every cell from its
+    /// neighbours with the rows spread over threads. Neighbourhoods are symmetric, so the
+    /// sums are the same.
+    #[cfg(feature = "parallel")]
+    fn count_changes_parallel(&self, mine_positions: &[u32], charge: i8) -> Vec<i16> {
+        use rayon::prelude::*;
+
+        let mut charges = vec![0i16; self.cells.len()];
+        for mine_lin_index in mine_positions {
+            charges[*mine_lin_index as usize] += charge as i16;
+        }
+
+        let mut count_changes = vec![0i16; self.cells.len()];
+        count_changes
+            .par_chunks_mut(self.game_configuration.w() as usize)
+            .enumerate()
+            .for_each(|(row, changes)| {
+                for (col, change) in changes.iter_mut().enumerate() {
+                    let coordinate = Coordinate(row as u32, col as u32);
+                    // holes are nobody's neighbour and keep their count
+                    if !self.is_hole(coordinate) {
+                        self.for_each_neighbour(coordinate, |lin_index| {
+                            *change += charges[lin_index]
+                        });
+                    }
+                }
+            });
+        count_changes
+    }
+
+    /// Calls `f` with the linear index of every neighbour of the cell, computed directly
+    /// instead of collecting the coordinates first.
+    fn for_each_neighbour(&self, center: Coordinate, mut f: impl FnMut(usize)) {
+        if self.game_configuration.wrap_edges() {
+            let mut neighbours = vec![];
+            self.add_wrapped_neighbours(&mut neighbours, center);
+            for neighbour in neighbours {
+                f(self.compute_linear_index(neighbour));
+            }
+            return;
+        }
+
+        let height = self.game_configuration.h() as i64;
+        let width = self.game_configuration.w() as i64;
+        for (i, j) in self.game_configuration.adjacency().offsets() {
+            let x = center.0 as i64 + *i as i64;
+            let y = center.1 as i64 + *j as i64;
+
+            if x < 0
+                || y < 0
+                || x >= height
+                || y >= width
+                || self.is_hole(Coordinate(x as u32, y as u32))
+            {
+                continue;
+            }
+
+            f((x * width + y) as usize)
+        }
+    }
+
+    /// Neighbours across the edges, each cell once even on boards too small for the
+    /// adjacency to fit.
+    fn add_wrapped_neighbours(&self, queue: &mut Vec<Coordinate>, center: Coordinate) {
+        let height = self.game_configuration.h() as i32;
+        let width = self.game_configuration.w() as i32;
+        let start = queue.len();
+
+        for (i, j) in self.game_configuration.adjacency().offsets() {
+            let x = (center.0 as i32 + i).rem_euclid(height);
+            let y = (center.1 as i32 + j).rem_euclid(width);
+            let neighbour = Coordinate(x as u32, y as u32);
+
+            if neighbour == center || self.is_hole(neighbour) || queue[start..].contains(&neighbour)
+            {
+                continue;
+            }
+
+            queue.push(neighbour)
+        }
+    }
+
+    /// Cells next to the coordinate under the adjacency of the board, without holes.
+    pub fn neighbours(&self, coordinate: Coordinate) -> Vec<Coordinate> {
+        let mut neighbours = vec![];
+        self.add_neighbours(&mut neighbours, coordinate);
+        neighbours
+    }
+
+    /// Whether the command would leave the board as it is, like exploring an explored
+    /// cell or flagging a flagged one. Commands without a coordinate are no clicks at all.
+    pub fn is_wasted(&self, command: BoardCommand) -> bool {
+        let (coordinate, mark) = match command {
+            // only the plugin knows what its command changes
+            BoardCommand::Pass
+            | BoardCommand::Quit
+            | BoardCommand::Claim
+            | BoardCommand::Plugin(_) => return false,
+            BoardCommand::SetMarkFlagRange(from, to) => {
+                return from
+                    .rectangle(to)
+                    .all(|coordinate| self.is_wasted(BoardCommand::SetMarkFlag(coordinate)))
+            }
+            BoardCommand::ClearMarkRange(from, to) => {
+                return from
+                    .rectangle(to)
+                    .all(|coordinate| self.is_wasted(BoardCommand::ClearMark(coordinate)))
+            }
+            BoardCommand::Explore(coordinate) => (coordinate, None),
+            BoardCommand::ClearMark(coordinate) => (coordinate, Some(Mark::NoMark)),
+            BoardCommand::SetMarkFlag(coordinate)
+            | BoardCommand::SetMarkFlagCount(coordinate, _) => (coordinate, Some(Mark::MarkFlag)),
+            BoardCommand::SetMarkNote(coordinate) => (coordinate, Some(Mark::MarkNote)),
+        };
+        let flag_count = match command {
+            BoardCommand::SetMarkFlagCount(_, count) => count,
+            _ => 1,
+        };
+
+        match self.get_cell_at(coordinate) {
+            BoardCell::Explored(_) => true,
+            BoardCell::Mine(current) | BoardCell::NoMine(CellInfo(current, _)) => {
+                Some(current) == mark
+                    && (current != Mark::MarkFlag || self.flag_count(coordinate) == flag_count)
+            }
+        }
+    }
+
+    /// Cells the command puts a flag on, every covered cell of a flagged range.
+    pub fn flags_placed_by(&self, command: BoardCommand) -> u32 {
+        match command {
+            BoardCommand::SetMarkFlag(_) | BoardCommand::SetMarkFlagCount(..) => 1,
+            BoardCommand::SetMarkFlagRange(from, to) => self.marked_in(from, to).len() as u32,
+            _ => 0,
+        }
+    }
+
+    /// Whether the command's coordinate lies on the board, commands without one always do.
+    pub fn is_on_board(&self, command: BoardCommand) -> bool {
+        match command {
+            BoardCommand::Pass | BoardCommand::Quit | BoardCommand::Claim => true,
+            BoardCommand::ClearMark(coordinate)
+            | BoardCommand::SetMarkFlag(coordinate)
+            | BoardCommand::SetMarkFlagCount(coordinate, _)
+            | BoardCommand::SetMarkNote(coordinate)
+            | BoardCommand::Explore(coordinate) => {
+                coordinate.0 < self.game_configuration.h()
+                    && coordinate.1 < self.game_configuration.w()
+                    && !self.is_hole(coordinate)
+            }
+            // holes within a range are skipped
+            BoardCommand::SetMarkFlagRange(from, to) | BoardCommand::ClearMarkRange(from, to) => {
+                from.0.max(to.0) < self.game_configuration.h()
+                    && from.1.max(to.1) < self.game_configuration.w()
+            }
+            BoardCommand::Plugin(command) => command
+                .cell
+                .is_none_or(|cell| self.is_on_board(BoardCommand::Explore(cell))),
+        }
+    }
+
+    pub fn get_dimensions(&self) -> (u32, u32) {
+        (self.game_configuration.w(), self.game_configuration.h())
+    }
+
+    pub fn get_cell_at(&self, coordinate: Coordinate) -> BoardCell {
+        self.cells.get(self.compute_linear_index(coordinate))
+    }
+
+    pub fn explored_cells(&self) -> u32 {
+        self.cells.explored() as u32
+    }
+
+    fn neighbour_mines(&self, linear_index: usize) -> Option<i16> {
+        if self.is_hole(self.compute_coordinate(linear_index)) {
+            return None;
+        }
+        match self.cells.get(linear_index) {
+            BoardCell::Explored(neighbour_mines)
+            | BoardCell::NoMine(CellInfo(_, neighbour_mines)) => Some(neighbour_mines.0),
+            BoardCell::Mine(_) => None,
+        }
+    }
+
+    /// Returns the solved and the total 3BV of the board, the minimum number of
+    /// clicks needed to clear it. Every opening counts as one click, as does every
+    /// numbered cell that does not border an opening.
+    pub fn three_bv(&self) -> (u32, u32) {
+        let mut covered = BitSet::new(self.cells.len());
+        let mut solved = 0;
+        let mut total = 0;
+
+        for linear_index in 0..self.cells.len() {
+            if covered.contains(linear_index) || self.neighbour_mines(linear_index) != Some(0) {
+                continue;
+            }
+
+            total += 1;
+            let mut opening_solved = false;
+            let mut queue = vec![self.compute_coordinate(linear_index)];
+            covered.insert(linear_index);
+
+            while let Some(coordinate) = queue.pop() {
+                let index = self.compute_linear_index(coordinate);
+                if self.neighbour_mines(index) != Some(0) {
+                    continue;
+                }
+                opening_solved |= matches!(self.cells.get(index), BoardCell::Explored(_));
+
+                let mut neighbours: Vec<Coordinate> = vec![];
+                self.add_neighbours(&mut neighbours, coordinate);
+                for neighbour in neighbours {
+                    let neighbour_index = self.compute_linear_index(neighbour);
+                    if covered.insert(neighbour_index) {
+                        queue.push(neighbour);
+                    }
+                }
+            }
+
+            if opening_solved {
+                solved += 1;
+            }
+        }
+
+        for (linear_index, cell) in self.cells.iter().enumerate() {
+            if covered.contains(linear_index) {
+                continue;
+            }
+            match cell {
+                BoardCell::Explored(_) => {
+                    solved += 1;
+                    total += 1;
+                }
+                BoardCell::NoMine(_) => total += 1,
+                BoardCell::Mine(_) => {}
+            }
+        }
+
+        (solved, total)
+    }
+}
+
+impl GameBoard {
+    /// Symbol of the cell in the terminal, empty for holes.
+    fn cell_symbol(&self, coordinate: Coordinate) -> String {
+        if self.is_hole(coordinate) {
+            return String::new();
+        }
+        let flag = match self.flag_count(coordinate) {
+            1 => "|F|".to_string(),
+            count => format!("|F{}|", count),
+        };
+
+        match self.get_cell_at(coordinate) {
+            BoardCell::NoMine(cell_info) => match cell_info.0 {
+                Mark::NoMark => "|X|".to_string(),
+                Mark::MarkNote => "|N|".to_string(),
+                Mark::MarkFlag => flag,
+            },
+            BoardCell::Mine(mark_info) => match mark_info {
+                Mark::NoMark if self.show_mines => match self.mine_count(coordinate) {
+                    1 => "|*|".to_string(),
+                    count => format!("|*{}|", count),
+                },
+                Mark::NoMark => "|X|".to_string(),
+                Mark::MarkNote => "|N|".to_string(),
+                Mark::MarkFlag => flag,
+            },
+            BoardCell::Explored(neighbour_info) => {
+                if neighbour_info.0 == 0 {
+                    "| |".to_string()
+                } else {
+                    format!("|{}|", neighbour_info.0)
+                }
+            }
+        }
+    }
+}
+
+impl Display for GameBoard {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (width, height) = self.get_dimensions();
+        // Wrapping edges are drawn with tildes instead of bars.
+        let edge = if self.game_configuration.wrap_edges() {
+            '~'
+        } else {
+            '|'
+        };
+
+        let symbols: Vec<Vec<String>> = (0..height)
+            .map(|row| {
+                (0..width)
+                    .map(|col| self.cell_symbol(Coordinate(row, col)))
+                    .collect()
+            })
+            .collect();
+        // counts of several mines or anti-mines need wider columns to stay aligned
+        let labels: Vec<String> = (0..width)
+            .map(|col| self.convention.column_label(col))
+            .collect();
+        let cell_width = symbols
+            .iter()
+            .flatten()
+            .map(|symbol| symbol.chars().count())
+            .max()
+            .unwrap_or_default()
+            .max(3);
+        // letters of the far columns get a space to their neighbours
+        let cell_width = if self.convention.letters {
+            labels
+                .iter()
+                .map(|label| label.len() + 1)
+                .fold(cell_width, usize::max)
+        } else {
+            cell_width
+        };
+
+        write!(f, "{:>3}", "")?;
+        for label in &labels {
+            write!(f, "{:>cell_width$}", label)?;
+        }
+        writeln!(f)?;
+
+        for (row, symbols) in (0..).zip(&symbols) {
+            write!(f, "{:>3}{}", self.convention.number(row), edge)?;
+
+            for (col, symbol) in (0..).zip(symbols) {
+                let symbol = format!("{:>cell_width$}", symbol);
+                let symbol = match self.theme {
+                    Theme::Plain => symbol,
+                    Theme::Colour => {
+                        let view = protocol::CellView::at(
+                            self,
+                            Coordinate(row, col),
+                            protocol::Status::Playing,
+                        );
+                        ansi::paint(view, &symbol)
+                    }
+                };
+                write!(f, "{}", symbol)
+                    .expect("Writing a new symbol failed in game board display.");
+            }
+            if self.game_configuration.wrap_edges() {
+                write!(f, "{}", edge)?;
+            }
+            writeln!(f).expect("Writing new line failed in game board display.");
+        }
+        if self.game_configuration.wrap_edges() {
+            writeln!(
+                f,
+                "{:>3}{}",
+                "",
+                "~".repeat(cell_width * width as usize + 2)
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn create_command_test() {
+        let command = "pass";
+        assert_eq!(BoardCommand::Pass, command.try_into().unwrap());
+
+        let command = "quit";
+        assert_eq!(BoardCommand::Quit, command.try_into().unwrap());
+
+        let command = "clear(0, 0)";
+        assert_eq!(
+            BoardCommand::ClearMark(Coordinate(0, 0)),
+            command.try_into().unwrap()
+        );
+
+        let command = "note(2,1)";
+        assert_eq!(
+            BoardCommand::SetMarkNote(Coordinate(2, 1)),
+            command.try_into().unwrap()
+        );
+
+        let command = "flag(100, 21)";
+        assert_eq!(
+            BoardCommand::SetMarkFlag(Coordinate(100, 21)),
+            command.try_into().unwrap()
+        );
+
+        let command = "explore(20, 20)";
+        assert_eq!(
+            BoardCommand::Explore(Coordinate(20, 20)),
+            command.try_into().unwrap()
+        );
+    }
+
+    #[test]
+    #[allow(clippy::assertions_on_constants)]
+    fn fail_to_create_command_test() {
+        let command = "asd";
+        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
+        assert_eq!(Err(BoardCommandError::MalformedString), result);
+
+        let command = "mark(10,10,10)";
+        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
+        // not the best way to handle these errors in such a way. One thing is that the msg is lost
+        if let Err(BoardCommandError::CoordinateParsing(_)) = result {
+            assert!(true);
+        } else {
+            assert!(false);
+        }
+
+        let command = "mark(10.10)";
+        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
+        assert_eq!(Err(BoardCommandError::MalformedCoordinate), result);
+
+        let command = "flag(1000_000, 10)";
+        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
+        if let Err(BoardCommandError::CoordinateParsing(_)) = result {
+            assert!(true);
+        } else {
+            assert!(false);
+        }
+
+        let command = "flag((1000, 20))";
+        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
+        if let Err(BoardCommandError::CoordinateParsing(_)) = result {
+            assert!(true);
+        } else {
+            assert!(false);
+        }
+
+        let command = "test(10, 10)";
+        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
+        assert_eq!(Err(BoardCommandError::NotFound), result);
+    }
+
+    #[test]
+    fn three_bv_test() {
+        let mut game_board = GameBoard::new(GameConfiguration::new(3, 3, 1));
+        game_board.place_mines(&[4], 1);
+        assert_eq!((0, 8), game_board.three_bv());
+
+        let mut game_board = GameBoard::new(GameConfiguration::new(5, 5, 1));
+        game_board.place_mines(&[0], 1);
+        assert_eq!((0, 1), game_board.three_bv());
+
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(4, 4)));
+        assert_eq!((1, 1), game_board.three_bv());
+    }
+
+    #[test]
+    fn efficiency_test() {
+        let mut game_board = GameBoard::new(GameConfiguration::new(3, 3, 1));
+        game_board.place_mines(&[0], 1);
+        assert!(!game_board.is_wasted(BoardCommand::SetMarkFlag(Coordinate(0, 0))));
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(0, 0)));
+        assert!(game_board.is_wasted(BoardCommand::SetMarkFlag(Coordinate(0, 0))));
+        assert!(!game_board.is_wasted(BoardCommand::SetMarkFlagCount(Coordinate(0, 0), 2)));
+        assert!(!game_board.is_wasted(BoardCommand::SetMarkNote(Coordinate(0, 0))));
+        assert!(game_board.is_wasted(BoardCommand::ClearMark(Coordinate(1, 1))));
+        assert!(!game_board.is_wasted(BoardCommand::Explore(Coordinate(2, 2))));
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(2, 2)));
+        assert!(game_board.is_wasted(BoardCommand::Explore(Coordinate(2, 2))));
+        assert!(game_board.is_wasted(BoardCommand::SetMarkNote(Coordinate(1, 1))));
+        assert!(!game_board.is_wasted(BoardCommand::Pass));
+
+        let efficiency = Efficiency {
+            three_bv: 30,
+            clicks: 40,
+            wasted_clicks: 3,
+        };
+        assert_eq!(75, efficiency.percent());
+        assert_eq!(1.5, efficiency.three_bv_per_second(Duration::from_secs(20)));
+        assert_eq!(0, Efficiency::default().percent());
+    }
+
+    #[test]
+    fn wrapped_neighbours_test() {
+        let configuration = GameConfiguration::new(4, 4, 0).with_wrap_edges(true);
+        assert_eq!("4x4-0-wrap", configuration.key());
+        let game_board = GameBoard::new(configuration);
+
+        let mut queue = Vec::new();
+        game_board.add_neighbours(&mut queue, Coordinate(0, 0));
+        assert_eq!(8, queue.len());
+        assert!(queue.contains(&Coordinate(3, 3)));
+        assert!(queue.contains(&Coordinate(0, 3)));
+
+        let game_board = GameBoard::new(GameConfiguration::new(2, 2, 0).with_wrap_edges(true));
+        let mut queue = Vec::new();
+        game_board.add_neighbours(&mut queue, Coordinate(0, 0));
+        assert_eq!(3, queue.len());
+    }
+
+    #[test]
+    fn knight_adjacency_test() {
+        let configuration = GameConfiguration::new(5, 5, 0).with_adjacency(Adjacency::Knight);
+        assert_eq!("5x5-0-knight", configuration.key());
+        let game_board = GameBoard::new(configuration);
+
+        let mut queue = Vec::new();
+        game_board.add_neighbours(&mut queue, Coordinate(0, 0));
+        assert_eq!(vec![Coordinate(1, 2), Coordinate(2, 1)], queue);
+
+        let mut queue = Vec::new();
+        game_board.add_neighbours(&mut queue, Coordinate(2, 2));
+        assert_eq!(8, queue.len());
+        assert!(!queue.contains(&Coordinate(1, 1)));
+    }
+
+    #[test]
+    fn masked_board_test() {
+        let mask = Mask::try_from("#.#\n#.#\n###").unwrap();
+        let mut game_board = GameBoard::with_mask(GameConfiguration::new(1, 1, 7), mask);
+        game_board.generate_world_from_seed(3);
+        assert_eq!((3, 3), game_board.get_dimensions());
+
+        assert!(!game_board.is_on_board(BoardCommand::Explore(Coordinate(0, 1))));
+        for row in 0..3 {
+            for col in 0..3 {
+                let is_mine = matches!(
+                    game_board.get_cell_at(Coordinate(row, col)),
+                    BoardCell::Mine(_)
+                );
+                assert_eq!(!game_board.is_hole(Coordinate(row, col)), is_mine);
+            }
+        }
+
+        let mut queue = Vec::new();
+        game_board.add_neighbours(&mut queue, Coordinate(0, 0));
+        assert_eq!(vec![Coordinate(1, 0)], queue);
+    }
+
+    #[test]
+    fn safe_cells_left_test() {
+        let mask = Mask::try_from("####.\n####.\n#####\n#####").unwrap();
+        let configuration = GameConfiguration::new(1, 1, 4).with_mines_per_cell(2);
+        let mut game_board = GameBoard::with_mask(configuration, mask);
+        game_board.generate_world_from_seed(9);
+        assert_eq!(
+            game_board.count_safe_cells_left(),
+            game_board.safe_cells_left
+        );
+        game_board.set_safe_start(SafeStart::Square3);
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(2, 2)));
+        assert_eq!(
+            game_board.count_safe_cells_left(),
+            game_board.safe_cells_left
+        );
+
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*#\n#.\n").unwrap());
+        assert_eq!(2, game_board.safe_cells_left);
+        assert_eq!(
+            GameResolve::Continue,
+            game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 0)))
+        );
+        assert_eq!(1, game_board.safe_cells_left);
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            game_board.manipulate_cell(BoardCommand::Explore(Coordinate(0, 1)))
+        );
+    }
+
+    #[test]
+    fn multi_mine_cells_test() {
+        assert_eq!(
+            BoardCommand::SetMarkFlagCount(Coordinate(3, 4), 2),
+            "flag(3, 4, 2)".try_into().unwrap()
+        );
+        let result: Result<BoardCommand, BoardCommandError> = "explore(3, 4, 2)".try_into();
+        assert!(matches!(
+            result,
+            Err(BoardCommandError::CoordinateParsing(_))
+        ));
+
+        let configuration = GameConfiguration::new(2, 2, 8).with_mines_per_cell(3);
+        assert_eq!("2x2-8-multi3", configuration.key());
+        let mut game_board = GameBoard::new(configuration);
+        game_board.generate_world_from_seed(11);
+
+        let coordinates = [0, 1].map(|row| [0, 1].map(|col| Coordinate(row, col)));
+        let coordinates: Vec<Coordinate> = coordinates.into_iter().flatten().collect();
+        let counts: Vec<i8> = coordinates
+            .iter()
+            .map(|coordinate| game_board.mine_count(*coordinate))
+            .collect();
+        assert_eq!(8, counts.iter().map(|count| *count as u32).sum::<u32>());
+        assert!(counts.iter().all(|count| *count <= 3));
+
+        // the counts of a cell have to stay within a flag's count
+        let crowded: GameConfiguration = serde_json::from_str(
+            r#"{"width":1,"height":1,"total_mines":200,"mines_per_cell":200}"#,
+        )
+        .unwrap();
+        assert!(!crowded.fits());
+
+        // a flag for the wrong count does not discover the mines
+        let crowded = coordinates[counts.iter().position(|count| *count > 1).unwrap()];
+        let resolve = game_board.manipulate_cell(BoardCommand::SetMarkFlag(crowded));
+        assert_eq!(GameResolve::Continue, resolve);
+
+        let mut resolve = GameResolve::Continue;
+        for (coordinate, count) in coordinates.iter().zip(counts) {
+            if count > 0 {
+                resolve =
+                    game_board.manipulate_cell(BoardCommand::SetMarkFlagCount(*coordinate, count));
+            }
+        }
+        assert_eq!(GameResolve::AllMinesDiscovered, resolve);
+    }
+
+    #[test]
+    fn anti_mines_test() {
+        let configuration = GameConfiguration::new(5, 5, 3).with_anti_mines(8);
+        assert_eq!("5x5-3-anti8", configuration.key());
+        assert!(!GameConfiguration::new(3, 3, 5).with_anti_mines(5).fits());
+        let mut game_board = GameBoard::new(configuration);
+        game_board.generate_world_from_seed(5);
+
+        let coordinates: Vec<Coordinate> = (0..5)
+            .flat_map(|row| (0..5).map(move |col| Coordinate(row, col)))
+            .collect();
+        let counts: Vec<i8> = coordinates
+            .iter()
+            .map(|coordinate| game_board.mine_count(*coordinate))
+            .collect();
+        assert_eq!(3, counts.iter().filter(|count| **count == 1).count());
+        assert_eq!(8, counts.iter().filter(|count| **count == -1).count());
+        // anti-mines outweigh the mines around some cells
+        assert!(coordinates.iter().any(|coordinate| matches!(
+            game_board.get_cell_at(*coordinate),
+            BoardCell::NoMine(CellInfo(_, NeighbourMines(..=-1)))
+        )));
+
+        // anti-mines are discovered with a negative flag
+        let mut resolve = GameResolve::Continue;
+        for (coordinate, count) in coordinates.iter().zip(&counts) {
+            if *count != 0 {
+                resolve =
+                    game_board.manipulate_cell(BoardCommand::SetMarkFlagCount(*coordinate, *count));
+            }
+        }
+        assert_eq!(GameResolve::AllMinesDiscovered, resolve);
+
+        // exploring an anti-mine loses like a mine
+        let mut game_board = GameBoard::new(configuration);
+        game_board.generate_world_from_seed(5);
+        let anti_mine = coordinates[counts.iter().position(|count| *count == -1).unwrap()];
+        assert_eq!(
+            GameResolve::MineHit,
+            game_board.manipulate_cell(BoardCommand::Explore(anti_mine))
+        );
+    }
+
+    #[test]
+    fn coordinate_convention_test() {
+        let convention = CoordinateConvention {
+            one_based: true,
+            order: AxisOrder::Xy,
+            letters: false,
+        };
+        assert_eq!(
+            Some(Coordinate(1, 2)),
+            convention.to_board(Coordinate(3, 2))
+        );
+        assert_eq!(Coordinate(3, 2), convention.to_player(Coordinate(1, 2)));
+        assert_eq!(None, convention.to_board(Coordinate(0, 2)));
+        assert_eq!(
+            Some(BoardCommand::SetMarkFlagCount(Coordinate(0, 4), 2)),
+            convention.command(BoardCommand::SetMarkFlagCount(Coordinate(5, 1), 2))
+        );
+        assert_eq!(
+            Some(BoardCommand::Explore(Coordinate(3, 2))),
+            CoordinateConvention::default().command(BoardCommand::Explore(Coordinate(3, 2)))
+        );
+
+        let mut game_board = GameBoard::new(GameConfiguration::new(3, 2, 0));
+        assert!(game_board.to_string().starts_with("     0  1  2\n  0|"));
+        game_board.set_coordinate_convention(convention);
+        let board = game_board.to_string();
+        assert!(board.starts_with("     1  2  3\n  1|"));
+        assert!(board.contains("\n  2|"));
+    }
+
+    #[test]
+    fn spreadsheet_coordinates_test() {
+        let convention = CoordinateConvention::default();
+        assert_eq!(
+            Ok(BoardCommand::Explore(Coordinate(6, 2))),
+            convention.parse("explore C7")
+        );
+        assert_eq!(
+            Ok(BoardCommand::SetMarkFlagCount(Coordinate(0, 27), 2)),
+            convention.parse("Flag ab1 2")
+        );
+        assert_eq!(
+            Ok(BoardCommand::Explore(Coordinate(6, 2))),
+            convention.parse("explore(6, 2)")
+        );
+        assert_eq!(
+            Err(BoardCommandError::OutsideBoard),
+            convention.parse("explore C0")
+        );
+        assert_eq!(
+            Err(BoardCommandError::NotFound),
+            convention.parse("open C7")
+        );
+        assert_eq!(
+            Err(BoardCommandError::MalformedCoordinate),
+            convention.parse("explore C7 2")
+        );
+
+        let convention = CoordinateConvention {
+            letters: true,
+            ..CoordinateConvention::default()
+        };
+        assert_eq!("Z", convention.column_label(25));
+        assert_eq!("AA", convention.column_label(26));
+        assert_eq!("ZZ", convention.column_label(701));
+        assert_eq!("AAA", convention.column_label(702));
+        assert_eq!("C7", convention.cell_name(Coordinate(6, 2)));
+        // the rows count from 1 as in a spreadsheet
+        assert_eq!(
+            Ok(BoardCommand::Explore(Coordinate(5, 1))),
+            convention.parse("explore(6, 2)")
+        );
+
+        let mut game_board = GameBoard::new(GameConfiguration::new(3, 2, 0));
+        game_board.set_coordinate_convention(convention);
+        assert!(game_board.to_string().starts_with("     A  B  C\n  1|"));
+    }
+
+    #[test]
+    fn range_command_test() {
+        let flags = BoardCommand::try_from("flag(3-6, 2)").unwrap();
+        assert_eq!(
+            BoardCommand::SetMarkFlagRange(Coordinate(3, 2), Coordinate(6, 2)),
+            flags
+        );
+        assert_eq!("flag(3-6, 2)", flags.to_string());
+        assert_eq!(
+            Ok(BoardCommand::ClearMarkRange(
+                Coordinate(0, 0),
+                Coordinate(9, 9)
+            )),
+            BoardCommand::try_from("clear(9-0, 0-9)")
+        );
+        assert_eq!(
+            Ok(BoardCommand::SetMarkFlag(Coordinate(2, 2))),
+            BoardCommand::try_from("flag(2-2, 2)")
+        );
+        assert_eq!(
+            Err(BoardCommandError::MalformedCoordinate),
+            BoardCommand::try_from("explore(0-2, 1)")
+        );
+        assert_eq!(
+            Err(BoardCommandError::MalformedCoordinate),
+            BoardCommand::try_from("flag(0-2, 1, 2)")
+        );
+
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*##\n#..\n...\n").unwrap());
+        let everything = BoardCommand::SetMarkFlagRange(Coordinate(0, 0), Coordinate(2, 2));
+        assert!(game_board.is_on_board(everything));
+        assert!(!game_board.is_on_board(BoardCommand::ClearMarkRange(
+            Coordinate(0, 0),
+            Coordinate(3, 0)
+        )));
+        // explored cells keep no flags
+        assert_eq!(4, game_board.flags_placed_by(everything));
+        game_board.manipulate_cell(everything);
+        assert!(game_board.is_flagged(Coordinate(0, 2)));
+        assert!(game_board.is_flagged(Coordinate(1, 0)));
+        assert_eq!(
+            BoardCell::Explored(NeighbourMines(0)),
+            game_board.get_cell_at(Coordinate(2, 2))
+        );
+        assert!(game_board.is_wasted(everything));
+
+        game_board.manipulate_cell(BoardCommand::ClearMarkRange(
+            Coordinate(0, 1),
+            Coordinate(0, 2),
+        ));
+        assert!(game_board.is_flagged(Coordinate(0, 0)));
+        assert!(!game_board.is_flagged(Coordinate(0, 1)));
+        assert!(!game_board.is_wasted(everything));
+    }
+
+    #[test]
+    fn relative_command_test() {
+        let convention = CoordinateConvention::default();
+        let last_cell = Some(Coordinate(3, 4));
+        assert_eq!(
+            Ok(BoardCommand::Explore(Coordinate(4, 4))),
+            convention.parse_after("explore(+1, 0)", last_cell)
+        );
+        assert_eq!(
+            Ok(BoardCommand::SetMarkFlag(Coordinate(2, 5))),
+            convention.parse_after("flag(-1,+1)", last_cell)
+        );
+        assert_eq!(
+            Err(BoardCommandError::OutsideBoard),
+            convention.parse_after("flag(-4, 0)", last_cell)
+        );
+        assert_eq!(
+            Err(BoardCommandError::NoLastCell),
+            convention.parse("explore(+1, 0)")
+        );
+        assert_eq!(
+            Err(BoardCommandError::NotFound),
+            convention.parse_after("open(+1, 0)", last_cell)
+        );
+        // offsets follow the order of the convention
+        let xy = CoordinateConvention {
+            order: AxisOrder::Xy,
+            ..convention
+        };
+        assert_eq!(
+            Ok(BoardCommand::Explore(Coordinate(3, 5))),
+            xy.parse_after("explore(+1, 0)", last_cell)
+        );
+
+        let mut game_board = GameBoard::new(GameConfiguration::new(9, 9, 0));
+        assert_eq!(None, game_board.last_cell());
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(1, 2)));
+        game_board.manipulate_cell(BoardCommand::Pass);
+        assert_eq!(Some(Coordinate(1, 2)), game_board.last_cell());
+        game_board.manipulate_cell(BoardCommand::ClearMarkRange(
+            Coordinate(0, 0),
+            Coordinate(2, 3),
+        ));
+        assert_eq!(Some(Coordinate(2, 3)), game_board.last_cell());
+    }
+
+    #[test]
+    fn flag_guard_test() {
+        let mut game_board = GameBoard::new(GameConfiguration::new(3, 3, 1));
+        game_board.generate_world_from_seed(2);
+        let flag = BoardCommand::SetMarkFlag(Coordinate(1, 1));
+        let explore = BoardCommand::Explore(Coordinate(1, 1));
+        game_board.manipulate_cell(flag);
+        assert!(game_board.passes_flag_guard(explore));
+
+        game_board.set_flag_guard(FlagGuard::Confirm);
+        assert!(!game_board.passes_flag_guard(explore));
+        assert!(game_board.passes_flag_guard(explore));
+        // anything in between asks again
+        assert!(!game_board.passes_flag_guard(explore));
+        assert!(game_board.passes_flag_guard(flag));
+        assert!(!game_board.passes_flag_guard(explore));
+
+        game_board.set_flag_guard(FlagGuard::Reject);
+        assert!(!game_board.passes_flag_guard(explore));
+        assert!(!game_board.passes_flag_guard(explore));
+        assert!(game_board.passes_flag_guard(BoardCommand::Explore(Coordinate(0, 0))));
+    }
+
+    #[test]
+    fn forgive_first_mistake_test() {
+        let mut game_board = GameBoard::new(GameConfiguration::new(3, 3, 2));
+        game_board.generate_world_from_seed(4);
+        game_board.set_forgiving(true);
+        let mines: Vec<Coordinate> = (0..9)
+            .map(|index| game_board.compute_coordinate(index))
+            .filter(|coordinate| matches!(game_board.get_cell_at(*coordinate), BoardCell::Mine(_)))
+            .collect();
+
+        let resolve = game_board.manipulate_cell(BoardCommand::Explore(mines[0]));
+        assert_eq!(GameResolve::Continue, resolve);
+        assert_eq!(1, game_board.mistakes_forgiven());
+        assert_eq!(
+            BoardCell::Mine(Mark::MarkFlag),
+            game_board.get_cell_at(mines[0])
+        );
+        assert_eq!(
+            GameResolve::MineHit,
+            game_board.manipulate_cell(BoardCommand::Explore(mines[1]))
+        );
+    }
+
+    #[test]
+    fn wrong_flags_test() {
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*#\n##\n").unwrap());
+        // flagging every cell flags the mine as well, but does not win
+        assert_eq!(
+            GameResolve::Continue,
+            game_board.manipulate_cell(BoardCommand::SetMarkFlagRange(
+                Coordinate(0, 0),
+                Coordinate(1, 1)
+            ))
+        );
+        game_board.manipulate_cell(BoardCommand::ClearMark(Coordinate(0, 1)));
+        game_board.manipulate_cell(BoardCommand::SetMarkNote(Coordinate(1, 0)));
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 1)))
+        );
+    }
+
+    #[test]
+    fn strict_flags_test() {
+        let layout = Layout::try_from("*#\n##\n").unwrap();
+        let mut game_board = GameBoard::from_layout(layout.clone());
+        // without strict flagging there is nothing to claim
+        assert_eq!(
+            GameResolve::Continue,
+            game_board.manipulate_cell(BoardCommand::Claim)
+        );
+        game_board.set_strict_flags(true);
+        assert_eq!(Ok(BoardCommand::Claim), BoardCommand::try_from(" Claim "));
+
+        // flagging the mine no longer wins, an extra flag spoils the claim
+        assert_eq!(
+            GameResolve::Continue,
+            game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(0, 0)))
+        );
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(1, 1)));
+        assert_eq!(
+            GameResolve::MineHit,
+            game_board.manipulate_cell(BoardCommand::Claim)
+        );
+
+        let mut game_board = GameBoard::from_layout(layout);
+        game_board.set_strict_flags(true);
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(0, 0)));
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            game_board.manipulate_cell(BoardCommand::Claim)
+        );
+    }
+
+    #[test]
+    fn safe_start_test() {
+        // the 9 cells around the first explore are all the cells without mines, exploring
+        // them wins
+        let mut game_board = GameBoard::new(GameConfiguration::new(5, 5, 16));
+        game_board.generate_world_from_seed(7);
+        game_board.set_safe_start(SafeStart::Square3);
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            game_board.manipulate_cell(BoardCommand::Explore(Coordinate(2, 2)))
+        );
+        assert_eq!(9, game_board.explored_cells());
+
+        // too crowded for the square, only the cell itself is kept free
+        let mut game_board = GameBoard::new(GameConfiguration::new(5, 5, 24));
+        game_board.generate_world_from_seed(7);
+        game_board.set_safe_start(SafeStart::Square5);
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            game_board.manipulate_cell(BoardCommand::Explore(Coordinate(0, 4)))
+        );
+        assert_eq!(
+            BoardCell::Explored(NeighbourMines(3)),
+            game_board.get_cell_at(Coordinate(0, 4))
+        );
+    }
+
+    #[test]
+    fn expansion_test() {
+        let layout = Layout::try_from("*###\n####\n####\n").unwrap();
+        let explore = |expansion, flag, cell| {
+            let mut game_board = GameBoard::from_layout(layout.clone());
+            game_board.set_expansion(expansion);
+            game_board.manipulate_cell(BoardCommand::SetMarkFlag(flag));
+            let resolve = game_board.manipulate_cell(BoardCommand::Explore(cell));
+            (resolve, game_board.explored_cells())
+        };
+        let mine = Coordinate(0, 0);
+
+        assert_eq!(11, explore(Expansion::Classic, mine, Coordinate(2, 3)).1);
+        assert_eq!(
+            1,
+            explore(Expansion::Conservative, mine, Coordinate(2, 3)).1
+        );
+        assert_eq!(1, explore(Expansion::Classic, mine, Coordinate(1, 1)).1);
+        // the flagged number opens around it, unless the flag is wrong
+        assert_eq!(11, explore(Expansion::Aggressive, mine, Coordinate(1, 1)).1);
+        assert_eq!(
+            GameResolve::MineHit,
+            explore(Expansion::Aggressive, Coordinate(0, 1), Coordinate(1, 1)).0
+        );
+
+        // chording for the player assists like auto-chording
+        let mut game_board = GameBoard::from_layout(layout.clone());
+        assert!(!game_board.is_assisted());
+        game_board.set_expansion(Expansion::Aggressive);
+        assert!(game_board.is_assisted());
+    }
+
+    #[test]
+    fn auto_chord_test() {
+        let layout = Layout::try_from("*#*#\n####\n####\n").unwrap();
+        let mut game_board = GameBoard::from_layout(layout.clone());
+        game_board.set_auto_chord(true);
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(0, 0)));
+        // the 1 next to the flag opens its neighbours, the 1s next to the other mine
+        // wait for its flag
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 0)));
+        assert_eq!(9, game_board.explored_cells());
+        assert_eq!(
+            BoardCell::NoMine(CellInfo(Mark::NoMark, NeighbourMines(1))),
+            game_board.get_cell_at(Coordinate(0, 3))
+        );
+
+        // a wrong flag satisfies the number as well
+        let mut game_board = GameBoard::from_layout(layout);
+        game_board.set_auto_chord(true);
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(0, 1)));
+        assert_eq!(
+            GameResolve::MineHit,
+            game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 0)))
+        );
+    }
+
+    #[test]
+    fn show_mines_test() {
+        let mut game_board = GameBoard::new(GameConfiguration::new(3, 3, 1));
+        game_board.place_mines(&[4], 1);
+        assert!(!game_board.to_string().contains("|*|"));
+        assert!(!game_board.is_cheated());
+
+        game_board.set_show_mines(true);
+        assert!(game_board.to_string().contains("|*|"));
+
+        // hiding them again doesn't make the game ranked
+        game_board.set_show_mines(false);
+        assert!(!game_board.to_string().contains("|*|"));
+        assert!(game_board.is_cheated());
+    }
+
+    #[test]
+    fn generate_world_test() {
+        let configuration = GameConfiguration::new(8, 8, 10);
+        let boards: Vec<GameBoard> = (0..2)
+            .map(|_| {
+                let mut game_board = GameBoard::new(configuration);
+                game_board.generate_world(&mut ChaCha8Rng::seed_from_u64(7));
+                game_board.set_show_mines(true);
+                game_board
+            })
+            .collect();
+        assert_eq!(boards[0].seed(), boards[1].seed());
+        assert_eq!(boards[0].to_string(), boards[1].to_string());
+
+        // the seed drawn from the rng rebuilds the world
+        let mut replayed = GameBoard::new(configuration);
+        replayed.generate_world_from_seed(boards[0].seed());
+        replayed.set_show_mines(true);
+        assert_eq!(boards[0].to_string(), replayed.to_string());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn parallel_count_changes_test() {
+        let configuration = GameConfiguration::new(40, 40, 300)
+            .with_wrap_edges(true)
+            .with_mines_per_cell(3);
+        let mask = Mask::try_from(&"#.##\n".repeat(40)[..]).unwrap();
+        for mut game_board in [
+            GameBoard::new(configuration),
+            GameBoard::new(configuration.with_adjacency(Adjacency::Knight)),
+            GameBoard::with_mask(GameConfiguration::new(1, 1, 60), mask),
+        ] {
+            game_board.generate_world_from_seed(5);
+            let mines: Vec<u32> = (0..game_board.cells.len() as u32)
+                .filter(|position| game_board.mine_counts.get(*position as usize) > 0)
+                .collect();
+            assert_eq!(
+                game_board.count_changes(&mines, -1),
+                game_board.count_changes_parallel(&mines, -1)
+            );
+        }
+    }
+
+    #[test]
+    fn opening_stops_at_numbers_test() {
+        let layout = || Layout::try_from("*###\n####\n####\n").unwrap();
+        let mut game_board = GameBoard::from_layout(layout());
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 1)));
+        assert_eq!(1, game_board.explored_cells());
+
+        let mut game_board = GameBoard::from_layout(layout());
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(2, 3)));
+        assert_eq!(11, game_board.explored_cells());
+    }
+
+    #[test]
+    fn large_board_test() {
+        let mut game_board = GameBoard::new(GameConfiguration::new(1000, 1000, 1));
+        game_board.place_mines(&[0], 1);
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(999, 999)));
+        assert_eq!(999_999, game_board.explored_cells());
+        assert_eq!(
+            BoardCell::Explored(NeighbourMines(1)),
+            game_board.get_cell_at(Coordinate(1, 1))
+        );
+
+        assert!(GameConfiguration::try_from("1000 1").is_ok());
+        assert!(matches!(
+            GameConfiguration::try_from("100001 1"),
+            Err(GameConfigurationError::OutOfLimits)
+        ));
+        assert!(!GameConfiguration::new(20_000, 20_000, 1).fits());
+        assert!(matches!(
+            GameConfiguration::try_from("3 20"),
+            Err(GameConfigurationError::TooManyMines)
+        ));
+        assert!(GameConfiguration::try_from("3 9").is_ok());
+    }
+
+    #[test]
+    fn sparse_board_test() {
+        assert!(GameBoard::new(GameConfiguration::new(2048, 2048, 2000))
+            .cells
+            .is_sparse());
+        assert!(!GameBoard::new(GameConfiguration::new(2048, 2048, 100_000))
+            .cells
+            .is_sparse());
+
+        let configuration = GameConfiguration::new(300, 300, 400).with_anti_mines(20);
+        let mut dense = GameBoard::new(configuration);
+        let mut sparse = GameBoard::new(configuration);
+        let size = sparse.cells.len();
+        sparse.cells = PackedCells::sparse(size);
+        sparse.mine_counts = Counts::sparse(size);
+        sparse.flag_counts = Counts::sparse(size);
+
+        for game_board in [&mut sparse, &mut dense] {
+            game_board.generate_world_from_seed(5);
+            game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(7, 7)));
+            game_board.manipulate_cell(BoardCommand::Explore(Coordinate(150, 150)));
+        }
+        assert!(sparse.cells.iter().eq(dense.cells.iter()));
+        assert!(sparse.explored_cells() > 0);
+        assert_eq!(dense.explored_cells(), sparse.explored_cells());
+        assert_eq!(dense.mines_discovered, sparse.mines_discovered);
+    }
+}
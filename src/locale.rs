@@ -0,0 +1,605 @@
+//! Texts shown to the player, in the language chosen with `language` in the config or
+//! taken from `LANG`, English otherwise. A translation is another [`Language`] with a
+//! text for every [`Message`].
+
+use std::{env, sync::Mutex, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clock::TimeFormat, difficulty::Difficulty, FlagGuard, GameResolve, GameResult, MAX_CELLS,
+    MAX_SIDE,
+};
+
+static LANGUAGE: Mutex<Option<Language>> = Mutex::new(None);
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Language {
+    #[default]
+    English,
+    German,
+}
+
+impl Language {
+    /// Language of `LANG`, e.g. `de_DE.UTF-8`, English if it has no translation.
+    pub fn from_env() -> Self {
+        env::var("LANG")
+            .map(|lang| Language::from_code(&lang))
+            .unwrap_or_default()
+    }
+
+    fn from_code(code: &str) -> Self {
+        match code.get(..2).map(str::to_lowercase).as_deref() {
+            Some("de") => Language::German,
+            _ => Language::English,
+        }
+    }
+
+    pub fn text(&self, message: Message) -> String {
+        match self {
+            Language::English => english(message),
+            Language::German => german(message),
+        }
+    }
+}
+
+/// Sets the language of every text from now on.
+pub fn select(language: Language) {
+    *LANGUAGE.lock().expect("Language lock is not poisoned.") = Some(language);
+}
+
+/// The selected language, that of `LANG` until one is selected.
+pub fn language() -> Language {
+    LANGUAGE
+        .lock()
+        .expect("Language lock is not poisoned.")
+        .unwrap_or_else(Language::from_env)
+}
+
+/// The message in the selected language.
+pub fn text(message: Message) -> String {
+    language().text(message)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Message<'a> {
+    Welcome,
+    UnknownOption,
+    Profiles(&'a str),
+    EnterProfile(&'a str),
+    PlayingAs(&'a str),
+    InvalidProfileName,
+    EnterGameConfig,
+    LeaveEmptyToPlay(&'a str),
+    InvalidGameConfig,
+    GameConfigOutOfLimits,
+    GameConfigTooManyMines,
+    WizardIntro,
+    WizardBoard,
+    WizardTheme,
+    WizardCoordinates,
+    WizardSafeStart,
+    WizardAutoChord,
+    WizardForgive,
+    WizardFlagGuard,
+    WizardInvalidAnswer,
+    WizardSaved,
+    ChosenSeedNotRanked,
+    ShareCode(&'a str),
+    CannotShare,
+    BoardSeed(u64),
+    DifficultyEstimate(Difficulty),
+    CheatsDisabled,
+    Cheating,
+    OutsideBoard,
+    SafestCells(&'a str),
+    NoEstimate,
+    RecordingMacro(&'a str),
+    MacroSaved(&'a str, usize),
+    NotRecording,
+    NoMacro(&'a str),
+    CouldNotSaveMacro(&'a str),
+    /// Slots of the profile, one listed per line.
+    Slots(&'a str),
+    NoSlots,
+    SlotSaved(&'a str),
+    SlotLoaded(&'a str),
+    SlotDeleted(&'a str),
+    SlotFailed(&'a str, &'a str),
+    /// The save offered on startup as listed by `saves`.
+    ResumePrevious(&'a str),
+    ImageSaved(&'a str),
+    CouldNotSaveImage(&'a str),
+    SnapshotSaved(&'a str),
+    CouldNotSaveSnapshot(&'a str),
+    /// The build lacks the `png` feature.
+    PngUnsupported,
+    ScriptFailed(&'a str),
+    FlagGuard(FlagGuard),
+    MineForgiven,
+    HitMine,
+    ClaimFailed,
+    YouWon,
+    GameTook(Duration, TimeFormat),
+    Summary(&'a GameResult, TimeFormat),
+    Paused,
+    /// Time of the running game and the personal best of its difficulty, both formatted.
+    GameClock(&'a str, Option<&'a str>),
+    AfterGame,
+    ReplaySaved(&'a str),
+    ReplayPageSaved(&'a str),
+    CouldNotSaveReplay(&'a str),
+    ForgivenNotRanked,
+    NewPersonalBest,
+    AchievementUnlocked(&'a str),
+    CouldNotSavePersonalBest(&'a str),
+    CouldNotSaveStatistics(&'a str),
+    CouldNotSaveAchievements(&'a str),
+    CouldNotSaveHighscores(&'a str),
+    LeaderboardSubmitted,
+    CouldNotSubmit(&'a str),
+    /// The profile was overwritten by a newer one from the sync server.
+    ProfilePulled,
+    CouldNotSync(&'a str),
+    SplitHeader,
+    Finish,
+}
+
+fn english(message: Message) -> String {
+    match message {
+        Message::Welcome => "Welcome to minesweeper\nKeymaps:\nplay-1,\nhighscores-2,\nquit-3,\ndaily-4,\ntournament-5,\ncampaign-6,\nachievements-7,\nstatistics-8,\nhotseat-9,\npuzzles-10,\nadaptive-11,\ngauntlet-12,\nsettings-13".to_string(),
+        Message::UnknownOption => "Unknown option.".to_string(),
+        Message::Profiles(profiles) => format!("Profiles: {}", profiles),
+        Message::EnterProfile(default) => format!(
+            "Enter profile name, a new name creates a profile (empty for {}):",
+            default
+        ),
+        Message::PlayingAs(name) => format!("Playing as {}.", name),
+        Message::InvalidProfileName => {
+            "Profile names may only contain letters, digits, '-' and '_'.".to_string()
+        }
+        Message::EnterGameConfig => {
+            "Enter a board - beginner, intermediate, expert or the size and mines, e.g. 10 10\n\
+             for a board of 10x10 cells with 10 mines."
+                .to_string()
+        }
+        Message::LeaveEmptyToPlay(board) => format!("Leave empty to play {}.", board),
+        Message::InvalidGameConfig => "Try again, a board is beginner, intermediate, expert or looks like 10 10\nFirst one is dimension, second number of mines.".to_string(),
+        Message::GameConfigOutOfLimits => format!(
+            "Boards have at most {} rows and columns and {} cells in all.",
+            MAX_SIDE, MAX_CELLS
+        ),
+        Message::GameConfigTooManyMines => {
+            "There are more mines than cells, try fewer mines.".to_string()
+        }
+        Message::WizardIntro => "Let's set up the game, an empty answer keeps the default."
+            .to_string(),
+        Message::WizardBoard => "Which board do you play?\n\
+             beginner - 9x9 cells with 10 mines\n\
+             intermediate - 16x16 cells with 40 mines\n\
+             expert - 30x16 cells with 99 mines\n\
+             or the size and mines like 10 10, empty asks before every game."
+            .to_string(),
+        Message::WizardTheme => {
+            "How should the board look?\n1 - plain marks between bars (default)\n2 - colour"
+                .to_string()
+        }
+        Message::WizardCoordinates => "How do you name the cells?\n\
+             1 - row and column from 0, like explore(3, 4) (default)\n\
+             2 - row and column from 1\n\
+             3 - x and y from 1, the column first\n\
+             4 - a letter for the column like explore C7"
+            .to_string(),
+        Message::WizardSafeStart => {
+            "Keep the first explore and the cells around it free of mines? (y/n)".to_string()
+        }
+        Message::WizardAutoChord => {
+            "Open around numbers whose flags are all placed after every explore? Such games \
+             are marked as assisted. (y/n)"
+                .to_string()
+        }
+        Message::WizardForgive => {
+            "Forgive the first mine hit of a game? Such games are not ranked. (y/n)".to_string()
+        }
+        Message::WizardFlagGuard => {
+            "Ask again before exploring a flagged cell? (y/n)".to_string()
+        }
+        Message::WizardInvalidAnswer => "Please answer with one of the choices.".to_string(),
+        Message::WizardSaved => {
+            "Saved the settings, settings-13 in the menu changes them.".to_string()
+        }
+        Message::ChosenSeedNotRanked => "Games on a chosen seed are not ranked.".to_string(),
+        Message::ShareCode(code) => format!(
+            "Board code {}, play it with: minesweeper --code {}",
+            code, code
+        ),
+        Message::CannotShare => "Boards shaped by a mask cannot be shared.".to_string(),
+        Message::BoardSeed(seed) => format!("Board seed: {}, play it again with --seed {}.", seed, seed),
+        Message::DifficultyEstimate(difficulty) => format!(
+            "Estimated difficulty: 3BV {}, {:.1}% mines, {}.",
+            difficulty.three_bv,
+            difficulty.density() * 100.0,
+            match difficulty.guesses {
+                Some(0) => "no guessing needed".to_string(),
+                Some(1) => "about 1 guess".to_string(),
+                Some(guesses) => format!("about {} guesses", guesses),
+                None => "guesses unknown on this board".to_string(),
+            }
+        ),
+        Message::CheatsDisabled => {
+            "Cheats are disabled, allow them with allow_cheats in the config.".to_string()
+        }
+        Message::Cheating => "Cheating, the game is not ranked.".to_string(),
+        Message::OutsideBoard => "Coordinate is outside of the board.".to_string(),
+        Message::SafestCells(cells) => format!("Safest cells to explore: {}", cells),
+        Message::NoEstimate => "The board cannot be estimated.".to_string(),
+        Message::RecordingMacro(name) => {
+            format!("Recording the macro {}, type stop to keep it.", name)
+        }
+        Message::MacroSaved(name, commands) => {
+            format!("Kept the macro {} with {} commands.", name, commands)
+        }
+        Message::NotRecording => "No macro is being recorded.".to_string(),
+        Message::NoMacro(name) => format!("There is no macro {}.", name),
+        Message::CouldNotSaveMacro(err) => format!("Could not save the macro: {}", err),
+        Message::Slots(slots) => format!("Saved games:\n{}", slots),
+        Message::NoSlots => "There are no saved games.".to_string(),
+        Message::SlotSaved(slot) => format!("Saved the game to slot {}.", slot),
+        Message::SlotLoaded(slot) => format!(
+            "Loaded slot {}, resumed games set no personal bests.",
+            slot
+        ),
+        Message::SlotDeleted(slot) => format!("Deleted slot {}.", slot),
+        Message::SlotFailed(slot, err) => format!("Slot {} failed: {}", slot, err),
+        Message::ResumePrevious(slot) => format!(
+            "Resume previous game?\n{}\nType y to resume, anything else goes to the menu.",
+            slot
+        ),
+        Message::ImageSaved(path) => format!("Saved the board image to {}.", path),
+        Message::CouldNotSaveImage(err) => format!("Could not save the board image: {}", err),
+        Message::SnapshotSaved(path) => format!("Saved a snapshot of the board to {}.", path),
+        Message::CouldNotSaveSnapshot(err) => format!("Could not save the snapshot: {}", err),
+        Message::PngUnsupported => {
+            "This build cannot export images, it lacks the png feature.".to_string()
+        }
+        Message::ScriptFailed(err) => format!("Script failed: {}", err),
+        Message::FlagGuard(flag_guard) => flag_guard.hint().to_string(),
+        Message::MineForgiven => {
+            "That was a mine! It is flagged now, the next one counts.".to_string()
+        }
+        Message::HitMine => "HIT MINE!".to_string(),
+        Message::ClaimFailed => "WRONG CLAIM! The flags are not exactly on the mines.".to_string(),
+        Message::YouWon => "YOU WON!".to_string(),
+        Message::Paused => "Paused, the clock stands still. Press Enter to go on.".to_string(),
+        Message::GameClock(time, Some(best)) => format!("Time: {}, best {}", time, best),
+        Message::GameClock(time, None) => format!("Time: {}", time),
+        Message::GameTook(time, format) => format!("Game took {}.", format.format(time)),
+        Message::Summary(result, format) => summary(
+            result,
+            format,
+            [
+                "Result", "Time", "3BV", "Efficiency", "Hints used", "Flags", "Seed",
+            ],
+            ["won", "lost", "quit"],
+            "clicks",
+            "wasted",
+        ),
+        Message::AfterGame => {
+            "Type s to save the replay, h to save it as a web page, r to retry the board or \
+             press Enter to go on."
+                .to_string()
+        }
+        Message::ReplaySaved(path) => format!("Saved the replay to {}.", path),
+        Message::ReplayPageSaved(path) => {
+            format!("Saved the replay as a web page to {}, open it in a browser.", path)
+        }
+        Message::CouldNotSaveReplay(err) => format!("Could not save the replay: {}", err),
+        Message::ForgivenNotRanked => {
+            "A mine hit was forgiven, the game is not ranked.".to_string()
+        }
+        Message::NewPersonalBest => "New personal best!".to_string(),
+        Message::AchievementUnlocked(title) => format!("Achievement unlocked: {}!", title),
+        Message::CouldNotSavePersonalBest(err) => {
+            format!("Could not save personal best: {}", err)
+        }
+        Message::CouldNotSaveStatistics(err) => format!("Could not save statistics: {}", err),
+        Message::CouldNotSaveAchievements(err) => {
+            format!("Could not save achievements: {}", err)
+        }
+        Message::CouldNotSaveHighscores(err) => format!("Could not save highscores: {}", err),
+        Message::LeaderboardSubmitted => "Submitted to the online leaderboard.".to_string(),
+        Message::CouldNotSubmit(err) => {
+            format!("Could not submit to the online leaderboard: {}", err)
+        }
+        Message::ProfilePulled => {
+            "Took the newer profile from the sync server.".to_string()
+        }
+        Message::CouldNotSync(err) => format!("Could not sync the profile: {}", err),
+        Message::SplitHeader => format!("{:<8}{:>12}{:>12}{:>10}", "Split", "Time", "PB", "Delta"),
+        Message::Finish => "Finish".to_string(),
+    }
+}
+
+fn german(message: Message) -> String {
+    match message {
+        Message::Welcome => "Willkommen bei Minesweeper\nTasten:\nplay-1 (spielen),\nhighscores-2 (Bestenliste),\nquit-3 (beenden),\ndaily-4 (Tagesrätsel),\ntournament-5 (Turnier),\ncampaign-6 (Kampagne),\nachievements-7 (Erfolge),\nstatistics-8 (Statistik),\nhotseat-9 (Hotseat),\npuzzles-10 (Rätsel),\nadaptive-11 (mitwachsend),\ngauntlet-12 (Spießrutenlauf),\nsettings-13 (Einstellungen)".to_string(),
+        Message::UnknownOption => "Unbekannte Auswahl.".to_string(),
+        Message::Profiles(profiles) => format!("Profile: {}", profiles),
+        Message::EnterProfile(default) => format!(
+            "Profilname eingeben, ein neuer Name legt ein Profil an (leer für {}):",
+            default
+        ),
+        Message::PlayingAs(name) => format!("Spiele als {}.", name),
+        Message::InvalidProfileName => {
+            "Profilnamen dürfen nur Buchstaben, Ziffern, '-' und '_' enthalten.".to_string()
+        }
+        Message::EnterGameConfig => {
+            "Spielfeld eingeben - beginner, intermediate, expert oder Größe und Minen, z.B.\n\
+             10 10 für 10x10 Felder mit 10 Minen."
+                .to_string()
+        }
+        Message::LeaveEmptyToPlay(board) => format!("Leer lassen, um {} zu spielen.", board),
+        Message::InvalidGameConfig => "Nochmal, das Spielfeld ist beginner, intermediate, expert oder sieht so aus: 10 10\nZuerst die Größe, dann die Anzahl der Minen.".to_string(),
+        Message::GameConfigOutOfLimits => format!(
+            "Spielfelder haben höchstens {} Zeilen und Spalten und {} Felder insgesamt.",
+            MAX_SIDE, MAX_CELLS
+        ),
+        Message::GameConfigTooManyMines => {
+            "Es gibt mehr Minen als Felder, versuch es mit weniger Minen.".to_string()
+        }
+        Message::WizardIntro => {
+            "Richten wir das Spiel ein, eine leere Antwort behält die Voreinstellung.".to_string()
+        }
+        Message::WizardBoard => "Auf welchem Spielfeld spielst du?\n\
+             beginner - 9x9 Felder mit 10 Minen\n\
+             intermediate - 16x16 Felder mit 40 Minen\n\
+             expert - 30x16 Felder mit 99 Minen\n\
+             oder Größe und Minen wie 10 10, leer fragt vor jedem Spiel."
+            .to_string(),
+        Message::WizardTheme => "Wie soll das Spielfeld aussehen?\n\
+             1 - schlichte Zeichen zwischen Strichen (Voreinstellung)\n\
+             2 - farbig"
+            .to_string(),
+        Message::WizardCoordinates => "Wie benennst du die Felder?\n\
+             1 - Zeile und Spalte ab 0, wie explore(3, 4) (Voreinstellung)\n\
+             2 - Zeile und Spalte ab 1\n\
+             3 - x und y ab 1, die Spalte zuerst\n\
+             4 - ein Buchstabe für die Spalte wie explore C7"
+            .to_string(),
+        Message::WizardSafeStart => {
+            "Das erste aufgedeckte Feld und seine Nachbarn minenfrei halten? (y/n)".to_string()
+        }
+        Message::WizardAutoChord => {
+            "Nach jedem Aufdecken um Zahlen aufdecken, deren Flaggen alle gesetzt sind? Solche \
+             Spiele werden als unterstützt markiert. (y/n)"
+                .to_string()
+        }
+        Message::WizardForgive => {
+            "Den ersten Minentreffer eines Spiels verzeihen? Solche Spiele werden nicht \
+             gewertet. (y/n)"
+                .to_string()
+        }
+        Message::WizardFlagGuard => {
+            "Vor dem Aufdecken eines markierten Feldes nachfragen? (y/n)".to_string()
+        }
+        Message::WizardInvalidAnswer => "Bitte antworte mit einer der Auswahlen.".to_string(),
+        Message::WizardSaved => {
+            "Einstellungen gespeichert, settings-13 im Menü ändert sie.".to_string()
+        }
+        Message::ChosenSeedNotRanked => {
+            "Spiele mit gewähltem Seed werden nicht gewertet.".to_string()
+        }
+        Message::ShareCode(code) => format!(
+            "Code des Spielfelds {}, spielbar mit: minesweeper --code {}",
+            code, code
+        ),
+        Message::CannotShare => {
+            "Spielfelder in der Form einer Maske können nicht geteilt werden.".to_string()
+        }
+        Message::BoardSeed(seed) => format!(
+            "Seed des Spielfelds: {}, mit --seed {} spielst du es nochmal.",
+            seed, seed
+        ),
+        Message::DifficultyEstimate(difficulty) => format!(
+            "Geschätzte Schwierigkeit: 3BV {}, {:.1}% Minen, {}.",
+            difficulty.three_bv,
+            difficulty.density() * 100.0,
+            match difficulty.guesses {
+                Some(0) => "ohne Raten lösbar".to_string(),
+                Some(1) => "etwa einmal raten".to_string(),
+                Some(guesses) => format!("etwa {} Mal raten", guesses),
+                None => "wie oft geraten wird, ist auf diesem Feld unbekannt".to_string(),
+            }
+        ),
+        Message::CheatsDisabled => {
+            "Schummeln ist aus, allow_cheats in der Konfiguration erlaubt es.".to_string()
+        }
+        Message::Cheating => "Geschummelt, das Spiel wird nicht gewertet.".to_string(),
+        Message::OutsideBoard => "Die Koordinate liegt außerhalb des Spielfelds.".to_string(),
+        Message::SafestCells(cells) => format!("Sicherste Felder zum Aufdecken: {}", cells),
+        Message::NoEstimate => "Das Spielfeld lässt sich nicht schätzen.".to_string(),
+        Message::RecordingMacro(name) => {
+            format!("Das Makro {} wird aufgenommen, stop behält es.", name)
+        }
+        Message::MacroSaved(name, commands) => {
+            format!("Das Makro {} mit {} Befehlen wurde behalten.", name, commands)
+        }
+        Message::NotRecording => "Es wird kein Makro aufgenommen.".to_string(),
+        Message::NoMacro(name) => format!("Es gibt kein Makro {}.", name),
+        Message::CouldNotSaveMacro(err) => {
+            format!("Konnte das Makro nicht speichern: {}", err)
+        }
+        Message::Slots(slots) => format!("Gespeicherte Spiele:\n{}", slots),
+        Message::NoSlots => "Es gibt keine gespeicherten Spiele.".to_string(),
+        Message::SlotSaved(slot) => format!("Das Spiel wurde in {} gespeichert.", slot),
+        Message::SlotLoaded(slot) => format!(
+            "{} geladen, fortgesetzte Spiele setzen keine Bestzeiten.",
+            slot
+        ),
+        Message::SlotDeleted(slot) => format!("{} gelöscht.", slot),
+        Message::SlotFailed(slot, err) => format!("{} fehlgeschlagen: {}", slot, err),
+        Message::ResumePrevious(slot) => format!(
+            "Letztes Spiel fortsetzen?\n{}\ny setzt es fort, alles andere führt zum Menü.",
+            slot
+        ),
+        Message::ImageSaved(path) => format!("Bild des Spielfelds gespeichert unter {}.", path),
+        Message::CouldNotSaveImage(err) => {
+            format!("Das Bild des Spielfelds konnte nicht gespeichert werden: {}", err)
+        }
+        Message::SnapshotSaved(path) => {
+            format!("Schnappschuss des Spielfelds gespeichert unter {}.", path)
+        }
+        Message::CouldNotSaveSnapshot(err) => {
+            format!("Der Schnappschuss konnte nicht gespeichert werden: {}", err)
+        }
+        Message::PngUnsupported => {
+            "Dieser Build kann keine Bilder exportieren, ihm fehlt das png-Feature.".to_string()
+        }
+        Message::ScriptFailed(err) => format!("Skript fehlgeschlagen: {}", err),
+        Message::FlagGuard(FlagGuard::Confirm) => {
+            "Das Feld hat eine Flagge, zum Bestätigen nochmal aufdecken.".to_string()
+        }
+        Message::FlagGuard(_) => {
+            "Das Feld hat eine Flagge, zum Aufdecken erst die Flagge entfernen.".to_string()
+        }
+        Message::MineForgiven => {
+            "Das war eine Mine! Sie hat jetzt eine Flagge, die nächste zählt.".to_string()
+        }
+        Message::HitMine => "MINE GETROFFEN!".to_string(),
+        Message::ClaimFailed => {
+            "FALSCH BEANSPRUCHT! Die Flaggen liegen nicht genau auf den Minen.".to_string()
+        }
+        Message::YouWon => "GEWONNEN!".to_string(),
+        Message::Paused => "Pause, die Uhr steht still. Weiter mit Enter.".to_string(),
+        Message::GameClock(time, Some(best)) => format!("Zeit: {}, Bestzeit {}", time, best),
+        Message::GameClock(time, None) => format!("Zeit: {}", time),
+        Message::GameTook(time, format) => {
+            format!("Das Spiel dauerte {}.", format.format(time))
+        }
+        Message::Summary(result, format) => summary(
+            result,
+            format,
+            [
+                "Ergebnis", "Zeit", "3BV", "Effizienz", "Hinweise", "Flaggen", "Seed",
+            ],
+            ["gewonnen", "verloren", "abgebrochen"],
+            "Klicks",
+            "verschwendet",
+        ),
+        Message::AfterGame => {
+            "s speichert die Wiederholung, h speichert sie als Webseite, r spielt das Feld \
+             nochmal, Enter geht weiter."
+                .to_string()
+        }
+        Message::ReplaySaved(path) => format!("Wiederholung gespeichert unter {}.", path),
+        Message::ReplayPageSaved(path) => format!(
+            "Wiederholung als Webseite gespeichert unter {}, sie öffnet sich im Browser.",
+            path
+        ),
+        Message::CouldNotSaveReplay(err) => {
+            format!("Konnte die Wiederholung nicht speichern: {}", err)
+        }
+        Message::ForgivenNotRanked => {
+            "Ein Minentreffer wurde verziehen, das Spiel wird nicht gewertet.".to_string()
+        }
+        Message::NewPersonalBest => "Neue Bestzeit!".to_string(),
+        Message::AchievementUnlocked(title) => format!("Erfolg freigeschaltet: {}!", title),
+        Message::CouldNotSavePersonalBest(err) => {
+            format!("Konnte die Bestzeit nicht speichern: {}", err)
+        }
+        Message::CouldNotSaveStatistics(err) => {
+            format!("Konnte die Statistik nicht speichern: {}", err)
+        }
+        Message::CouldNotSaveAchievements(err) => {
+            format!("Konnte die Erfolge nicht speichern: {}", err)
+        }
+        Message::CouldNotSaveHighscores(err) => {
+            format!("Konnte die Bestenliste nicht speichern: {}", err)
+        }
+        Message::LeaderboardSubmitted => "An die Online-Bestenliste gesendet.".to_string(),
+        Message::CouldNotSubmit(err) => {
+            format!("Konnte nicht an die Online-Bestenliste senden: {}", err)
+        }
+        Message::ProfilePulled => {
+            "Das neuere Profil vom Sync-Server wurde übernommen.".to_string()
+        }
+        Message::CouldNotSync(err) => {
+            format!("Konnte das Profil nicht synchronisieren: {}", err)
+        }
+        Message::SplitHeader => format!(
+            "{:<8}{:>12}{:>12}{:>10}",
+            "Etappe", "Zeit", "Best", "Diff"
+        ),
+        Message::Finish => "Ziel".to_string(),
+    }
+}
+
+/// Summary after a game, a line per label with the values aligned.
+fn summary(
+    result: &GameResult,
+    format: TimeFormat,
+    labels: [&str; 7],
+    outcomes: [&str; 3],
+    clicks: &str,
+    wasted: &str,
+) -> String {
+    let efficiency = result.efficiency;
+    let outcome = match result.resolve {
+        GameResolve::AllMinesDiscovered => outcomes[0],
+        GameResolve::MineHit => outcomes[1],
+        GameResolve::Quit | GameResolve::Continue => outcomes[2],
+    };
+    let values = [
+        outcome.to_string(),
+        format.format(result.elapsed),
+        format!(
+            "{}/{}, {:.2} 3BV/s",
+            efficiency.three_bv,
+            result.board_three_bv,
+            efficiency.three_bv_per_second(result.elapsed)
+        ),
+        format!(
+            "{}%, {} {} ({} {})",
+            efficiency.percent(),
+            efficiency.clicks,
+            clicks,
+            efficiency.wasted_clicks,
+            wasted
+        ),
+        result.hints_used.to_string(),
+        result.flags_placed.to_string(),
+        // hand made boards are not generated from their seed
+        match result.replay.layout {
+            Some(_) => "-".to_string(),
+            None => result.replay.seed.to_string(),
+        },
+    ];
+    labels
+        .iter()
+        .zip(values)
+        .map(|(label, value)| format!("{:<12}{}", format!("{}:", label), value))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn language_test() {
+        assert_eq!(Language::German, Language::from_code("de_AT.UTF-8"));
+        assert_eq!(Language::English, Language::from_code("C"));
+        assert_eq!(Language::English, Language::from_code("fr_FR.UTF-8"));
+
+        assert_eq!("YOU WON!", Language::English.text(Message::YouWon));
+        assert_eq!(
+            "Das Spiel dauerte 00:03.250.",
+            Language::German.text(Message::GameTook(
+                Duration::from_millis(3250),
+                TimeFormat::Clock
+            ))
+        );
+    }
+}
@@ -0,0 +1,92 @@
+//! Command macros of a profile. `record <name>` starts capturing the commands typed
+//! during a game, `stop` keeps them under the name and `play <name>` runs them again as a
+//! single batch. The commands are kept as typed, relative ones stay relative.
+
+use std::{collections::BTreeMap, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::storage;
+
+pub(crate) const MACROS_FILE: &str = "macros.json";
+
+#[derive(Serialize, Deserialize, Default, Debug, PartialEq, Eq)]
+pub struct Macros {
+    macros: BTreeMap<String, Vec<String>>,
+}
+
+impl Macros {
+    pub fn load() -> Self {
+        storage::load_json(MACROS_FILE)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::save_json(MACROS_FILE, self)
+    }
+
+    /// Commands of the macro, one typed line each.
+    pub fn get(&self, name: &str) -> Option<&[String]> {
+        self.macros.get(name).map(Vec::as_slice)
+    }
+
+    /// Keeps the commands under the name, replacing a macro of the same name.
+    pub fn insert(&mut self, name: &str, commands: Vec<String>) {
+        self.macros.insert(name.to_string(), commands);
+    }
+}
+
+/// Macro being recorded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recording {
+    pub name: String,
+    pub commands: Vec<String>,
+}
+
+impl Recording {
+    pub fn new(name: &str) -> Self {
+        Recording {
+            name: name.to_string(),
+            commands: vec![],
+        }
+    }
+
+    /// Adds the lines, empty parts of a batch are left out.
+    pub fn record(&mut self, line: &str) {
+        self.commands.extend(
+            line.split(';')
+                .map(str::trim)
+                .filter(|command| !command.is_empty())
+                .map(str::to_string),
+        );
+    }
+
+    /// Keeps the recording in the profile's macros, returns how many commands it has.
+    pub fn save(self) -> io::Result<usize> {
+        let mut macros = Macros::load();
+        let length = self.commands.len();
+        macros.insert(&self.name, self.commands);
+        macros.save()?;
+        Ok(length)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn recording_test() {
+        let mut recording = Recording::new("corner");
+        recording.record("flag(0, 0); ; flag(0, +1)");
+        recording.record(" explore(1, 1) ");
+        assert_eq!(
+            vec!["flag(0, 0)", "flag(0, +1)", "explore(1, 1)"],
+            recording.commands
+        );
+
+        let mut macros = Macros::default();
+        macros.insert("corner", recording.commands);
+        assert_eq!(3, macros.get("corner").unwrap().len());
+        assert_eq!(None, macros.get("edge"));
+    }
+}
@@ -1,560 +1,1814 @@
 use std::{
-    fmt::{Display, Write},
-    io::stdin,
-    num::ParseIntError,
-    time::SystemTime,
+    fs::File,
+    io::{self, stdin, stdout},
+    net::{Shutdown, TcpListener},
+    path::{Path, PathBuf},
+    process,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex, OnceLock,
+    },
+    thread,
+    time::Duration,
 };
 
-use rand;
-use rand::seq::SliceRandom;
-
-fn main() {
-    println!("{}", welcome_msg());
-
-    game_loop();
-}
-
-fn welcome_msg() -> &'static str {
-    "Welcome to minesweeper\nKeymaps:\nplay-1,\nhighscores-2,\nquit-3"
-}
+use clap::{CommandFactory, Parser};
+use cli::{Cli, Command, HighscoresAction, ProfileAction, Protocol, RaceRole};
+use input::Terminal;
+use minesweeper::{
+    achievements::Achievement,
+    bench,
+    bundle::{BundleError, ProfileBundle},
+    campaign,
+    clock::GameClock,
+    config::Config,
+    console::{self, Stdout},
+    control,
+    cube::{CubeBoard, CubeCommand},
+    daily::DailyChallenge,
+    endless::{EndlessBoard, EndlessCommand, EndlessRecord, Position},
+    export,
+    gauntlet::{self, GauntletTable},
+    heatmap::Heatmap,
+    highscores::{DailyHighscores, HighscoreTable, Highscores},
+    hotseat::HotSeat,
+    interrupt,
+    layout::{EditCommand, Layout},
+    leaderboard::{self, Submission},
+    locale::{self, Language, Message},
+    mask::Mask,
+    multiboard::{self, MultiBoard},
+    multiplayer::{self, ClientMessage, Host, HostEvent, ServerMessage, Spectators},
+    pace::Pace,
+    profile::Profile,
+    protocol, puzzles,
+    race::{self, Race, RaceEvent},
+    rawvf,
+    replay::Replay,
+    save,
+    session::LastGame,
+    share::{self, SharedBoard},
+    solver::{self, Verdict},
+    statistics::Statistics,
+    storage, sync,
+    tournament::{Tournament, TournamentResult},
+    wizard, BoardCommand, BoardCommandError, Coordinate, CoordinateConvention, GameBoard,
+    GameConfiguration, GameConfigurationError, GameResolve, GameResult,
+};
+use tracing::Level;
 
-fn clear_console() {
-    print!("{esc}[2J{esc}[1;1H", esc = 27 as char)
-}
+mod cli;
+mod completions;
+mod input;
+#[cfg(feature = "tui")]
+mod tui;
 
-fn game_loop() {
-    // init game
+static SPECTATORS: OnceLock<Spectators> = OnceLock::new();
+static DEBUG_SHOW_MINES: AtomicBool = AtomicBool::new(false);
 
-    // game loop
-    // - draw board state
-    // - wait for input
-    // - execute command
-    // - show command result and start a wait thread that is polled
-    // - continue after 3 secs
+fn main() {
+    let cli = Cli::parse();
+    init_logging(&cli);
 
-    println!("Enter game config - example: 10 10\nThis means board 10x10 with 10 mines.");
-    let mut config = String::new();
-    stdin()
-        .read_line(&mut config)
-        .expect("Did not enter string?");
+    let shared_config = Config::load_shared();
+    if let Err(err) = storage::init(shared_config.storage.unwrap_or_default()) {
+        eprintln!("Could not open storage: {}", err);
+        process::exit(1);
+    }
+    locale::select(shared_config.language.unwrap_or_else(Language::from_env));
 
-    let mut game_board = GameBoard::new(GameConfiguration::try_from(&config[..])
-        .expect("Try again, config should look like the following: 10 10\nFirst one is dimension, second number of mines."));
+    if cli.protocol == Protocol::Json {
+        if let Err(err) = protocol::serve_lines(stdin().lock(), stdout().lock()) {
+            eprintln!("Could not serve the json protocol: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
 
-    game_board.generate_world();
+    #[cfg(unix)]
+    if let (Some(path), None) = (&cli.control_socket, &cli.command) {
+        match control::listen(path) {
+            Ok(requests) => input::enable_control(requests),
+            Err(err) => {
+                eprintln!("Could not listen on {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        }
+    }
 
-    let now = SystemTime::now();
+    match cli.profile {
+        Some(profile) => storage::select_profile(&profile),
+        None if cli.command.is_some() => storage::select_profile(storage::DEFAULT_PROFILE),
+        None => select_profile(),
+    }
+    // commands leave the sync to the sessions they are run beside, a pulled profile
+    // brings its language
+    let _sync = shared_config
+        .sync_url
+        .clone()
+        .filter(|_| cli.command.is_none())
+        .map(SyncSession::start);
+    locale::select(Config::load().language.unwrap_or_else(Language::from_env));
+
+    DEBUG_SHOW_MINES.store(cli.debug_show_mines, Ordering::Relaxed);
+
+    if let Err(err) = interrupt::install() {
+        eprintln!("Could not handle Ctrl+C: {}", err);
+    }
 
-    loop {
-        println!("{}", &game_board);
-        let mut cmd = String::new();
-        stdin()
-            .read_line(&mut cmd)
-            .expect("Did not enter a string?!");
-        clear_console();
+    if let Some(address) = cli.spectators {
+        match Spectators::bind(&address) {
+            Ok(spectators) => {
+                println!("Spectators can watch on {}.", spectators.local_addr());
+                let _ = SPECTATORS.set(spectators);
+            }
+            Err(err) => {
+                eprintln!("Could not stream to spectators on {}: {}", address, err);
+                process::exit(1);
+            }
+        }
+    }
 
-        if let Ok(cmd) = BoardCommand::try_from(&cmd[..]) {
-            let resolve = game_board.manipulate_cell(cmd);
-            match resolve {
-                GameResolve::Quit => break,
-                GameResolve::Continue => continue,
-                GameResolve::MineHit => {
-                    println!("HIT MINE!");
-                    break;
-                }
-                GameResolve::AllMinesDiscovered => {
-                    println!("YOU WON!");
-                    break;
-                }
+    if cli.again {
+        let Some(last_game) = LastGame::load() else {
+            eprintln!("There is no previous game to play again.");
+            process::exit(1);
+        };
+        println!("{}", locale::text(Message::ChosenSeedNotRanked));
+        play_board(last_game.configuration, last_game.seed, false);
+        return;
+    }
+    if let Some(code) = cli.code {
+        match SharedBoard::decode(&code) {
+            Ok(shared) => play_shared(shared),
+            Err(err) => {
+                eprintln!("Could not read the board code: {}", err);
+                process::exit(1);
+            }
+        }
+        return;
+    }
+    if let Some(url) = cli.url {
+        let text = match share::fetch(&url) {
+            Ok(text) => text,
+            Err(err) => {
+                eprintln!("Could not download the board from {}: {}", url, err);
+                process::exit(1);
+            }
+        };
+        match SharedBoard::parse(&text) {
+            Ok(shared) => play_shared(shared),
+            Err(err) => {
+                eprintln!("Could not read the board from {}: {}", url, err);
+                process::exit(1);
             }
         }
+        return;
     }
-    if let Ok(elapsed) = now.elapsed() {
-        println!("Game took {} s.", elapsed.as_secs())
+    if let Some(seed) = cli.seed {
+        let board = cli
+            .board
+            .or_else(|| read_game_configuration(&Config::load()));
+        if let Some(board) = board {
+            println!("{}", locale::text(Message::ChosenSeedNotRanked));
+            play_board(board, seed, false);
+        }
+        return;
     }
-}
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct Coordinate(u16, u16);
+    if let Some(command) = cli.command {
+        run_command(command);
+        return;
+    }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-enum BoardCommandError {
-    MalformedString,
-    MalformedCoordinate,
-    CoordinateParsing(ParseIntError),
-    NotFound,
+    #[cfg(feature = "tui")]
+    if cli.tui {
+        if let Err(err) = tui::run(cli.debug_show_mines) {
+            eprintln!("Could not run the terminal UI: {}", err);
+            process::exit(1);
+        }
+        return;
+    }
+
+    if Config::load_profile().is_none() {
+        settings();
+    }
+    offer_resume();
+    loop {
+        println!("{}", locale::text(Message::Welcome));
+
+        let choice = input::read_line();
+
+        match choice.trim().to_lowercase().as_str() {
+            "1" | "play" => new_game(),
+            "2" | "highscores" => show_highscores(),
+            "3" | "quit" | "" => break,
+            "4" | "daily" => daily_challenge(),
+            "5" | "tournament" => tournament(),
+            "6" | "campaign" => campaign(),
+            "7" | "achievements" => show_achievements(),
+            "8" | "statistics" => show_statistics(),
+            "9" | "hotseat" => hot_seat(),
+            "10" | "puzzles" => puzzles(),
+            "11" | "adaptive" => adaptive(),
+            "12" | "gauntlet" => gauntlet(),
+            "13" | "settings" => settings(),
+            _ => println!("{}", locale::text(Message::UnknownOption)),
+        }
+    }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum BoardCommand {
-    Pass,
-    Quit,
-    ClearMark(Coordinate),
-    SetMarkFlag(Coordinate),
-    SetMarkNote(Coordinate),
-    Explore(Coordinate),
+/// The table of the game log and, once games were lost, where their mines were hit.
+fn show_statistics() {
+    let statistics = Statistics::load();
+    println!("{}", statistics);
+    let heatmap = Heatmap::of(statistics.games());
+    if heatmap.games() > 0 {
+        println!("{}", heatmap);
+    }
 }
 
-impl TryFrom<&str> for BoardCommand {
-    type Error = BoardCommandError;
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let value = value.to_lowercase().trim().to_string();
+/// Pulls the profile from the sync server and pushes it back once the session ends.
+struct SyncSession {
+    url: String,
+}
 
-        if value == "pass" {
-            return Ok(BoardCommand::Pass);
+impl SyncSession {
+    fn start(url: String) -> Self {
+        match sync::pull(&url, storage::active_profile()) {
+            Ok(true) => println!("{}", locale::text(Message::ProfilePulled)),
+            Ok(false) => {}
+            Err(err) => println!("{}", locale::text(Message::CouldNotSync(&err.to_string()))),
         }
+        SyncSession { url }
+    }
+}
 
-        if value == "quit" {
-            return Ok(BoardCommand::Quit);
+impl Drop for SyncSession {
+    fn drop(&mut self) {
+        if let Err(err) = sync::push(&self.url, storage::active_profile()) {
+            println!("{}", locale::text(Message::CouldNotSync(&err.to_string())));
         }
+    }
+}
 
-        let command_coordinate = value
-            .split_once('(')
-            .ok_or(BoardCommandError::MalformedString)?;
+/// Runs the wizard over the profile's own config and saves the answers.
+fn settings() {
+    let config = wizard::run(
+        Config::load_profile().unwrap_or_default(),
+        &mut Terminal,
+        &mut Stdout,
+    );
+    match config.save() {
+        Ok(()) => println!("{}", locale::text(Message::WizardSaved)),
+        Err(err) => println!("Could not save the settings: {}", err),
+    }
+}
 
-        let value = command_coordinate.1;
+/// Offers the latest save of the profile before the menu, the autosave of a game left
+/// unfinished included.
+fn offer_resume() {
+    let Some(latest) = save::slots().into_iter().max_by_key(|slot| slot.saved_at) else {
+        return;
+    };
+    println!(
+        "{}",
+        locale::text(Message::ResumePrevious(&latest.to_string()))
+    );
+    if !input::read_line().trim().eq_ignore_ascii_case("y") {
+        return;
+    }
 
-        let value = value.trim();
-        let (value_x, value_y) = value
-            .split_once(',')
-            .ok_or(BoardCommandError::MalformedCoordinate)?;
+    match save::open_slot(&latest.slot) {
+        Ok((mut game_board, elapsed)) => {
+            game_board.set_show_mines(DEBUG_SHOW_MINES.load(Ordering::Relaxed));
+            console::resume_loop(
+                game_board,
+                elapsed,
+                &mut Terminal,
+                &mut Stdout,
+                SPECTATORS.get(),
+            );
+        }
+        Err(err) => println!(
+            "{}",
+            locale::text(Message::SlotFailed(&latest.slot, &err.to_string()))
+        ),
+    }
+}
 
-        let value_x = value_x
-            .parse::<u16>()
-            .map_err(|err| BoardCommandError::CoordinateParsing(err))?;
+fn run_command(command: Command) {
+    match command {
+        Command::ExportStats { output_dir } => match export::export_stats(&output_dir) {
+            Ok(paths) => {
+                for path in paths {
+                    println!("Wrote {}", path.display());
+                }
+            }
+            Err(err) => {
+                eprintln!("Could not export statistics: {}", err);
+                process::exit(1);
+            }
+        },
+        Command::Host { address, board } => host_game(&address, board),
+        Command::Join { address } => join_game(&address),
+        Command::Spectate { address } => spectate_game(&address),
+        Command::Masked { mask, mines } => masked_game(&mask, mines),
+        Command::Endless { seed, density } => endless_game(seed, density),
+        Command::Cube {
+            width,
+            height,
+            depth,
+            mines,
+            seed,
+        } => cube_game(width, height, depth, mines, seed),
+        Command::Multiboard { count, board, seed } => multiboard_game(count, board, seed),
+        Command::Move {
+            command,
+            game,
+            board,
+            seed,
+        } => move_in_game(&command, &game, board, seed),
+        Command::Edit {
+            layout,
+            width,
+            height,
+        } => edit_layout(&layout, width, height),
+        Command::ValidatePuzzle { file } => validate_puzzle(&file),
+        Command::Bench {
+            sizes,
+            densities,
+            runs,
+            seed,
+        } => print!(
+            "{}",
+            bench::table(&bench::run(&sizes, &densities, runs, seed))
+        ),
+        Command::Verify { replay } => verify_replay(&replay),
+        Command::Import { video, output } => {
+            let output = output.unwrap_or_else(|| video.with_extension("json"));
+            import_video(&video, &output)
+        }
+        Command::Analyze { replay } => analyze_replay(&replay),
+        #[cfg(feature = "gif")]
+        Command::Gif { replay, output } => {
+            let output = output.unwrap_or_else(|| replay.with_extension("gif"));
+            replay_gif(&replay, &output)
+        }
+        Command::Race { role } => race_game(role),
+        Command::Highscores {
+            action: HighscoresAction::Import { file, daily },
+        } => import_highscores(&file, daily),
+        Command::Completions { shell } => {
+            let slots: Vec<String> = save::slots().into_iter().map(|slot| slot.slot).collect();
+            print!("{}", completions::script(shell, Cli::command(), &slots));
+        }
+        Command::Profile {
+            action: ProfileAction::Export { output },
+        } => export_profile(output),
+        Command::Profile {
+            action: ProfileAction::Import { file, name },
+        } => import_profile(&file, name),
+        #[cfg(feature = "websocket")]
+        Command::Websocket { address } => {
+            println!("Serving WebSocket games on {}.", address);
+            if let Err(err) = minesweeper::websocket::serve(&address) {
+                eprintln!("Could not serve on {}: {}", address, err);
+                process::exit(1);
+            }
+        }
+        #[cfg(feature = "grpc")]
+        Command::Grpc { address } => {
+            println!("Serving gRPC games on {}.", address);
+            if let Err(err) = minesweeper::grpc::serve(&address) {
+                eprintln!("Could not serve on {}: {}", address, err);
+                process::exit(1);
+            }
+        }
+    }
+}
 
-        let value_y = value_y.replace(&['\n', ')'], "").trim().to_string();
-        let value_y = value_y
-            .parse::<u16>()
-            .map_err(|err| BoardCommandError::CoordinateParsing(err))?;
+/// Logs to the log file or, when verbose, to the standard error, otherwise nothing is
+/// logged.
+fn init_logging(cli: &Cli) {
+    let level = match (cli.verbose, &cli.log_file) {
+        (0, None) => return,
+        (0, Some(_)) => Level::INFO,
+        (1, _) => Level::DEBUG,
+        _ => Level::TRACE,
+    };
+    let logger = tracing_subscriber::fmt().with_max_level(level);
+    match &cli.log_file {
+        Some(path) => match File::create(path) {
+            Ok(file) => logger.with_writer(Mutex::new(file)).with_ansi(false).init(),
+            Err(err) => {
+                eprintln!("Could not create log file {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        },
+        None => logger.with_writer(io::stderr).init(),
+    }
+}
 
-        let command = command_coordinate.0.trim();
+fn select_profile() {
+    let profiles = storage::profiles();
+    if !profiles.is_empty() {
+        println!("{}", locale::text(Message::Profiles(&profiles.join(", "))));
+    }
+    println!(
+        "{}",
+        locale::text(Message::EnterProfile(storage::DEFAULT_PROFILE))
+    );
 
-        match command {
-            "clear" => Ok(BoardCommand::ClearMark(Coordinate(value_x, value_y))),
-            "flag" => Ok(BoardCommand::SetMarkFlag(Coordinate(value_x, value_y))),
-            "note" => Ok(BoardCommand::SetMarkNote(Coordinate(value_x, value_y))),
-            "explore" => Ok(BoardCommand::Explore(Coordinate(value_x, value_y))),
-            _ => Err(BoardCommandError::NotFound),
+    loop {
+        let name = input::read_line();
+
+        let name = match name.trim() {
+            "" => storage::DEFAULT_PROFILE,
+            name => name,
+        };
+        if storage::is_valid_profile_name(name) {
+            storage::select_profile(name);
+            println!("{}", locale::text(Message::PlayingAs(name)));
+            return;
         }
+        println!("{}", locale::text(Message::InvalidProfileName));
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct NeighbourMines(u8);
-
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum Mark {
-    NoMark,
-    MarkNote,
-    MarkFlag,
+fn clear_console() {
+    print!("{esc}[2J{esc}[1;1H", esc = 27 as char)
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-struct CellInfo(Mark, NeighbourMines);
+fn new_game() {
+    let config = Config::load();
+    let Some(game_configuration) = read_game_configuration(&config) else {
+        return;
+    };
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
-enum BoardCell {
-    Explored(NeighbourMines),
-    NoMine(CellInfo),
-    Mine(Mark),
+    play_board(game_configuration, rand::random(), true);
 }
 
-#[derive(Clone, Copy)]
-struct GameConfiguration {
-    width: u16,
-    height: u16,
-    total_mines: u32,
+/// Plays a board shared as a code or link, which is never ranked.
+fn play_shared(shared: SharedBoard) {
+    match shared {
+        SharedBoard::Seeded {
+            configuration,
+            seed,
+        } => {
+            println!("{}", locale::text(Message::ChosenSeedNotRanked));
+            play_board(configuration, seed, false);
+        }
+        shared => {
+            game_loop(shared.board());
+        }
+    }
 }
 
-impl GameConfiguration {
-    pub fn new(width: u16, height: u16, total_mines: u32) -> Self {
-        GameConfiguration {
-            width,
-            height,
-            total_mines,
-        }
+/// Plays the board generated from the seed and remembers it for `--again`, only ranked
+/// games enter the highscores and the online leaderboard.
+fn play_board(game_configuration: GameConfiguration, seed: u64, ranked: bool) {
+    let config = Config::load();
+    let mut game_board = GameBoard::new(game_configuration);
+    game_board.generate_world_from_seed(seed);
+    let last_game = LastGame {
+        configuration: game_configuration,
+        seed,
+    };
+    if let Err(err) = last_game.save() {
+        println!("Could not remember the board: {}", err);
     }
 
-    pub fn w(&self) -> u16 {
-        self.width
+    let result = game_loop(game_board);
+    if !ranked || result.resolve != GameResolve::AllMinesDiscovered || !result.is_ranked() {
+        return;
     }
 
-    pub fn h(&self) -> u16 {
-        self.height
+    let mut highscores = Highscores::load();
+    if record_highscore(highscores.table_mut(&game_configuration), &result) {
+        if let Err(err) = highscores.save() {
+            println!(
+                "{}",
+                locale::text(Message::CouldNotSaveHighscores(&err.to_string()))
+            );
+        }
     }
 
-    pub fn mines(&self) -> u32 {
-        self.total_mines
+    if let Some(url) = config.leaderboard_url {
+        let submission = Submission::new(storage::active_profile(), &result.replay);
+        match leaderboard::submit(&url, &submission) {
+            Ok(()) => println!("{}", locale::text(Message::LeaderboardSubmitted)),
+            Err(err) => println!(
+                "{}",
+                locale::text(Message::CouldNotSubmit(&err.to_string()))
+            ),
+        }
     }
 }
 
-impl Default for GameConfiguration {
-    fn default() -> Self {
-        GameConfiguration {
-            width: 5,
-            height: 5,
-            total_mines: 10,
+fn masked_game(path: &Path, mines: u32) {
+    let mask = match Mask::load(path) {
+        Ok(mask) => mask,
+        Err(err) => {
+            eprintln!("Could not read mask {}: {}", path.display(), err);
+            process::exit(1);
         }
+    };
+    let config = Config::load();
+    let game_configuration =
+        config.apply_variants(GameConfiguration::new(mask.width(), mask.height(), mines));
+    if !game_configuration.fits_cells(mask.cell_count() as u64) {
+        eprintln!(
+            "The mask has {} cells, {} mines do not fit.",
+            mask.cell_count(),
+            game_configuration.mines_to_discover()
+        );
+        process::exit(1);
     }
-}
+    let mut game_board = GameBoard::with_mask(game_configuration, mask);
+    game_board.generate_world(&mut rand::thread_rng());
 
-#[derive(Debug)]
-enum GameConfigurationError {
-    MalformedString,
-    MalformedInteger(ParseIntError),
+    game_loop(game_board);
 }
 
-impl TryFrom<&str> for GameConfiguration {
-    type Error = GameConfigurationError;
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        let game_config = value
-            .split_once(" ")
-            .ok_or(GameConfigurationError::MalformedString);
-        let (dimensions, mines) = game_config.unwrap();
+fn cube_game(width: u16, height: u16, depth: u16, mines: u32, seed: Option<u64>) {
+    let seed = seed.unwrap_or_else(rand::random);
+    let Some(mut board) = CubeBoard::new(width, height, depth, mines, seed) else {
+        eprintln!(
+            "{} mines do not fit into a {}x{}x{} cube.",
+            mines, width, height, depth
+        );
+        process::exit(1);
+    };
+    println!("Commands take three coordinates, e.g. explore(1, 2, 0) for the first layer.\nSwitch the shown layer with up, down or layer(z).");
+
+    let clock = GameClock::start();
+    let resolve = loop {
+        println!("{}", board);
+        let line = input::read_line();
+        clear_console();
 
-        Ok(GameConfiguration {
-            width: dimensions
-                .trim()
-                .parse::<u16>()
-                .or_else(|err| Err(GameConfigurationError::MalformedInteger(err)))?,
-            height: dimensions
-                .trim()
-                .parse::<u16>()
-                .or_else(|err| Err(GameConfigurationError::MalformedInteger(err)))?,
-            total_mines: mines
-                .trim()
-                .parse::<u32>()
-                .or_else(|err| Err(GameConfigurationError::MalformedInteger(err)))?,
-        })
+        let command = match CubeCommand::try_from(&line[..]) {
+            Ok(command) => command,
+            Err(_) if line.is_empty() => CubeCommand::Quit,
+            Err(_) => {
+                println!("Unknown command, e.g. explore(1, 2, 0), flag(0, 0, 3) or layer(2).");
+                continue;
+            }
+        };
+        if let CubeCommand::ClearMark(coordinate)
+        | CubeCommand::SetMarkFlag(coordinate)
+        | CubeCommand::SetMarkNote(coordinate)
+        | CubeCommand::Explore(coordinate) = command
+        {
+            if !board.is_on_board(coordinate) {
+                println!("{}", locale::text(Message::OutsideBoard));
+                continue;
+            }
+        }
+
+        match board.manipulate_cell(command) {
+            GameResolve::Continue => continue,
+            resolve => break resolve,
+        }
+    };
+
+    println!("{}", board);
+    match resolve {
+        GameResolve::MineHit => println!("{}", locale::text(Message::HitMine)),
+        GameResolve::AllMinesDiscovered => println!("{}", locale::text(Message::YouWon)),
+        _ => {}
     }
+    println!(
+        "{}",
+        locale::text(Message::GameTook(
+            clock.elapsed(),
+            Config::load().time_format.unwrap_or_default()
+        ))
+    );
 }
 
-#[derive(Clone, Copy, PartialEq, Eq, Debug)]
-enum GameResolve {
-    Quit,
-    Continue,
-    MineHit,
-    AllMinesDiscovered,
-}
+/// Applies one command to the game of the save slot and prints the board, starting a new
+/// game if the slot is empty. Finished games are taken out of the slot, unranked.
+fn move_in_game(command: &str, slot: &str, board: Option<GameConfiguration>, seed: Option<u64>) {
+    let config = Config::load();
+    let (mut game_board, elapsed) = match save::open_slot(slot) {
+        Ok(game) => game,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => {
+            let configuration = board
+                .or(config.default_board)
+                .unwrap_or(GameConfiguration::beginner());
+            let mut game_board = GameBoard::new(config.apply_variants(configuration));
+            game_board.generate_world_from_seed(seed.unwrap_or_else(rand::random));
+            config.apply_rules(&mut game_board);
+            (game_board, Duration::ZERO)
+        }
+        Err(err) => {
+            eprintln!("Could not open the game {}: {}", slot, err);
+            process::exit(1);
+        }
+    };
+    // the convention and theme are not saved with the board
+    game_board.set_coordinate_convention(config.coordinates.unwrap_or_default());
+    game_board.set_theme(config.theme.unwrap_or_default());
+
+    let command = match game_board
+        .coordinate_convention()
+        .parse_after(command, game_board.last_cell())
+    {
+        Ok(command) if game_board.is_on_board(command) => command,
+        Ok(_) | Err(BoardCommandError::OutsideBoard) => {
+            eprintln!("{}", locale::text(Message::OutsideBoard));
+            process::exit(1);
+        }
+        Err(_) => {
+            eprintln!("Could not read the command \"{}\".", command);
+            process::exit(1);
+        }
+    };
+    let resolve = game_board.manipulate_cell(command);
+
+    // the game is kept before it is shown, so a reader gone early loses no move
+    let kept = match resolve {
+        GameResolve::Continue => save::save_slot(&game_board, slot, elapsed),
+        // a game over on its first move never had a slot
+        _ => save::delete_slot(slot).or_else(|err| match err.kind() {
+            io::ErrorKind::NotFound => Ok(()),
+            _ => Err(err),
+        }),
+    };
+    if let Err(err) = kept {
+        eprintln!("Could not keep the game {}: {}", slot, err);
+        process::exit(1);
+    }
 
-struct GameBoard {
-    game_configuration: GameConfiguration,
-    mines_discovered: u32,
-    cells: Vec<BoardCell>,
+    println!("{}", game_board);
+    match resolve {
+        GameResolve::AllMinesDiscovered => println!("{}", locale::text(Message::YouWon)),
+        GameResolve::MineHit => println!("{}", locale::text(Message::HitMine)),
+        _ => {}
+    }
 }
 
-impl GameBoard {
-    fn new(game_configuration: GameConfiguration) -> GameBoard {
-        GameBoard {
-            game_configuration,
-            mines_discovered: 0,
-            cells: vec![
-                BoardCell::NoMine(CellInfo(Mark::NoMark, NeighbourMines(0)));
-                game_configuration.w() as usize * game_configuration.h() as usize
-            ],
+fn multiboard_game(count: usize, board: Option<GameConfiguration>, seed: Option<u64>) {
+    let config = Config::load();
+    let configuration = board
+        .or(config.default_board)
+        .unwrap_or(GameConfiguration::intermediate());
+    let convention = config.coordinates.unwrap_or_default();
+    let time_format = config.time_format.unwrap_or_default();
+    let Some(mut boards) = MultiBoard::new(configuration, count, seed.unwrap_or_else(rand::random))
+    else {
+        eprintln!("A multiboard game needs at least one board.");
+        process::exit(1);
+    };
+    println!(
+        "Playing {} boards at once, switch with board and its number, e.g. board 2.",
+        count
+    );
+
+    let resolve = loop {
+        println!("{}", boards.selector(|time| time_format.format(time)));
+        println!("{}", boards.active());
+        let line = input::read_line();
+        clear_console();
+
+        if let Some(number) = multiboard::board_number(&line) {
+            if !boards.select(number) {
+                println!("There is no board {}.", number);
+            }
+            continue;
         }
+        let command = match convention.parse(&line) {
+            Ok(command) => command,
+            Err(_) if line.trim().is_empty() => BoardCommand::Quit,
+            Err(BoardCommandError::OutsideBoard) => {
+                println!("{}", locale::text(Message::OutsideBoard));
+                continue;
+            }
+            Err(_) => {
+                println!("Unknown command.");
+                continue;
+            }
+        };
+        if !boards.active().is_on_board(command) {
+            println!("{}", locale::text(Message::OutsideBoard));
+            continue;
+        }
+
+        match boards.apply(command) {
+            GameResolve::Continue => continue,
+            resolve => break resolve,
+        }
+    };
+
+    println!("{}", boards.selector(|time| time_format.format(time)));
+    println!("{}", boards.active());
+    match resolve {
+        GameResolve::MineHit => println!("{}", locale::text(Message::HitMine)),
+        GameResolve::AllMinesDiscovered => println!("{}", locale::text(Message::YouWon)),
+        _ => {}
     }
+    println!(
+        "{}",
+        locale::text(Message::GameTook(boards.elapsed(), time_format))
+    );
+}
 
-    fn generate_world(&mut self) {
-        let mut mine_positions: Vec<u32> = (0..(self.game_configuration.h() as u32
-            * self.game_configuration.w() as u32))
-            .collect();
-        mine_positions.shuffle(&mut rand::thread_rng());
+fn endless_game(seed: Option<u64>, density: f64) {
+    let mut board = EndlessBoard::with_density(seed.unwrap_or_else(rand::random), density);
+    let mut center = Position(0, 0);
+    println!(
+        "Endless board {}, the start at explore(0, 0) is safe.",
+        board.seed()
+    );
+
+    let resolve = loop {
+        println!("{}", board.render(center, 20, 12));
+        println!("Score: {}", board.score());
+        let line = input::read_line();
+        clear_console();
 
-        for mine_lin_index in &mine_positions[0..self.game_configuration.total_mines as usize] {
-            self.cells[*mine_lin_index as usize] = BoardCell::Mine(Mark::NoMark);
+        let command = match EndlessCommand::try_from(&line[..]) {
+            Ok(command) => command,
+            Err(_) if line.is_empty() => EndlessCommand::Quit,
+            Err(_) => {
+                println!("Unknown command, coordinates may be negative, e.g. explore(-3, 12).");
+                continue;
+            }
+        };
+        if let EndlessCommand::ClearMark(position)
+        | EndlessCommand::SetMarkFlag(position)
+        | EndlessCommand::SetMarkNote(position)
+        | EndlessCommand::Explore(position) = command
+        {
+            center = position;
         }
 
-        // this one goes through all fields, a bit unnecessary
-        // for row in 0..self.game_configuration.h() {
-        //     for col in 0..self.game_configuration.w() {}
-        // }
+        match board.apply(command) {
+            GameResolve::Continue | GameResolve::AllMinesDiscovered => continue,
+            resolve => break resolve,
+        }
+    };
 
-        // quiet inefficient, but I am lazy atm
-        for mine_lin_index in &mine_positions[0..self.game_configuration.total_mines as usize] {
-            let mut neighbours: Vec<Coordinate> = vec![];
-            self.add_neighbours(
-                &mut neighbours,
-                Coordinate(
-                    (mine_lin_index / self.game_configuration.w() as u32) as u16,
-                    (mine_lin_index % self.game_configuration.w() as u32) as u16,
-                ),
-            );
+    println!("{}", board.render(center, 20, 12));
+    if resolve == GameResolve::MineHit {
+        println!("{}", locale::text(Message::HitMine));
+    }
+    println!("Score: {} cells revealed.", board.score());
 
-            for neighbour in neighbours {
-                let lin_index = self.compute_linear_index(neighbour);
-                self.cells[lin_index] = match &self.cells[lin_index] {
-                    &BoardCell::NoMine(cell_info) => BoardCell::NoMine(CellInfo(
-                        Mark::NoMark,
-                        NeighbourMines(cell_info.1 .0 + 1),
-                    )),
-                    &anything => anything,
-                }
-            }
+    let mut record = EndlessRecord::load();
+    if record.submit(board.score()) {
+        println!("New best endless score!");
+        if let Err(err) = record.save() {
+            println!("Could not save endless score: {}", err);
         }
+    } else {
+        println!("Best endless score: {}.", record.best_score);
     }
+}
 
-    fn manipulate_cell(&mut self, command: BoardCommand) -> GameResolve {
-        let command_result = match command {
-            BoardCommand::Quit => GameResolve::Quit,
-            BoardCommand::Pass => GameResolve::Continue,
-            BoardCommand::ClearMark(coordinate) => self.clear_mark(coordinate),
-            BoardCommand::SetMarkFlag(coordinate) => self.set_mark_flag(coordinate),
-            BoardCommand::SetMarkNote(coordinate) => self.set_mark_note(coordinate),
-            BoardCommand::Explore(coordinate) => self.explore(coordinate),
+fn edit_layout(path: &Path, width: u32, height: u32) {
+    let mut layout = if path.exists() {
+        match Layout::load(path) {
+            Ok(layout) => layout,
+            Err(err) => {
+                eprintln!("Could not read layout {}: {}", path.display(), err);
+                process::exit(1);
+            }
+        }
+    } else if width == 0 || height == 0 {
+        eprintln!("A layout needs at least one row and column.");
+        process::exit(1);
+    } else {
+        Layout::new(width, height)
+    };
+    println!("Place mines with mine(x, y) and remove(x, y), reveal(x1, y1, x2, y2) reveals a region when the game starts and hide(...) hides it again.\nsave writes {}, quit leaves the editor.", path.display());
+
+    let mut saved = true;
+    loop {
+        println!("{}", layout.render());
+        println!("{} mines.", layout.mines());
+        let line = input::read_line();
+        clear_console();
+
+        let command = match EditCommand::try_from(&line[..]) {
+            Ok(command) => command,
+            Err(_) if line.is_empty() => EditCommand::Quit,
+            Err(_) => {
+                println!("Unknown command, e.g. mine(1, 2), reveal(0, 0, 3, 3) or save.");
+                continue;
+            }
         };
+        if !command
+            .coordinates()
+            .iter()
+            .all(|coordinate| layout.is_on_layout(*coordinate))
+        {
+            println!("Coordinate is outside of the layout.");
+            continue;
+        }
 
-        match command_result {
-            GameResolve::Continue | GameResolve::AllMinesDiscovered => {
-                if self.mines_discovered == self.game_configuration.mines() {
-                    GameResolve::AllMinesDiscovered
-                } else {
-                    GameResolve::Continue
+        match command {
+            EditCommand::Save => match layout.save(path) {
+                Ok(()) => {
+                    saved = true;
+                    println!("Saved {}.", path.display());
                 }
+                Err(err) => println!("Could not save {}: {}", path.display(), err),
+            },
+            EditCommand::Quit if !saved => {
+                saved = true;
+                println!("There are unsaved changes, quit again to discard them.");
+            }
+            EditCommand::Quit => break,
+            command => {
+                layout.edit(command);
+                saved = false;
             }
-            other => other,
         }
     }
+}
 
-    fn clear_mark(&mut self, coordinate: Coordinate) -> GameResolve {
-        let linear_index = self.compute_linear_index(coordinate);
-
-        match &self.cells[linear_index] {
-            &BoardCell::NoMine(ref cell_info) => {
-                self.cells[linear_index] = BoardCell::NoMine(CellInfo(Mark::NoMark, cell_info.1));
+fn validate_puzzle(path: &Path) {
+    let layout = match Layout::load(path) {
+        Ok(layout) => layout,
+        Err(err) => {
+            eprintln!("Could not read layout {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    };
+
+    match solver::solve(&layout) {
+        Verdict::Solvable => println!("{} can be solved without guessing.", path.display()),
+        Verdict::Stuck(positions) => {
+            println!(
+                "{} needs guessing, the solver is stuck at these cells:",
+                path.display()
+            );
+            for Coordinate(x, y) in positions {
+                println!("({}, {})", x, y);
             }
-            &BoardCell::Mine(ref mark) => {
-                if let Mark::MarkFlag = mark {
-                    self.mines_discovered -= 1;
-                }
-                self.cells[linear_index] = BoardCell::Mine(Mark::NoMark);
+            process::exit(1);
+        }
+    }
+}
+
+fn import_video(path: &Path, output: &Path) {
+    let replay = match std::fs::read(path) {
+        Ok(bytes) => match rawvf::import(&bytes) {
+            Ok(replay) => replay,
+            Err(err) => {
+                eprintln!("Could not import {}: {}", path.display(), err);
+                process::exit(1);
             }
-            _ => {}
+        },
+        Err(err) => {
+            eprintln!("Could not read {}: {}", path.display(), err);
+            process::exit(1);
         }
+    };
+    let json = serde_json::to_string_pretty(&replay).expect("Serializing a replay does not fail.");
+    match std::fs::write(output, json) {
+        Ok(()) => println!(
+            "Wrote {}, {} moves in {:.3} s.",
+            output.display(),
+            replay.moves.len(),
+            replay.duration().as_secs_f64()
+        ),
+        Err(err) => {
+            eprintln!("Could not write {}: {}", output.display(), err);
+            process::exit(1);
+        }
+    }
+}
 
-        GameResolve::Continue
+fn analyze_replay(path: &Path) {
+    match Replay::load(path) {
+        Ok(replay) => println!("{}", Pace::of(&replay)),
+        Err(err) => {
+            eprintln!("Could not read replay {}: {}", path.display(), err);
+            process::exit(1);
+        }
     }
+}
 
-    fn set_mark_flag(&mut self, coordinate: Coordinate) -> GameResolve {
-        let linear_index = self.compute_linear_index(coordinate);
+fn verify_replay(path: &Path) {
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(err) => {
+            eprintln!("Could not read replay {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    };
+
+    match replay.verify() {
+        Ok(resolve) => {
+            let outcome = match resolve {
+                GameResolve::AllMinesDiscovered => "won",
+                GameResolve::MineHit => "lost",
+                GameResolve::Quit => "quit",
+                GameResolve::Continue => "unfinished",
+            };
+            println!(
+                "{} is valid, {} game {} after {} moves in {:.3} s.",
+                path.display(),
+                replay.configuration.key(),
+                outcome,
+                replay.moves.len(),
+                replay.duration().as_secs_f64()
+            );
+        }
+        Err(err) => {
+            println!("{} is not valid, {}.", path.display(), err);
+            process::exit(1);
+        }
+    }
+}
 
-        match &self.cells[linear_index] {
-            &BoardCell::NoMine(ref cell_info) => {
-                self.cells[linear_index] = BoardCell::NoMine(CellInfo(Mark::MarkFlag, cell_info.1))
+#[cfg(feature = "gif")]
+fn replay_gif(path: &Path, output: &Path) {
+    let replay = match Replay::load(path) {
+        Ok(replay) => replay,
+        Err(err) => {
+            eprintln!("Could not read replay {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    };
+    let gif = match minesweeper::gif::animate(&replay) {
+        Ok(gif) => gif,
+        Err(err) => {
+            eprintln!("Could not animate {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    };
+    match std::fs::write(output, gif) {
+        Ok(()) => println!("Wrote {}", output.display()),
+        Err(err) => {
+            eprintln!("Could not write {}: {}", output.display(), err);
+            process::exit(1);
+        }
+    }
+}
+
+fn read_game_configuration(config: &Config) -> Option<GameConfiguration> {
+    let default_board = config.default_board;
+    println!("{}", locale::text(Message::EnterGameConfig));
+    if let Some(default_board) = default_board {
+        println!(
+            "{}",
+            locale::text(Message::LeaveEmptyToPlay(&default_board.key()))
+        );
+    }
+    let line = input::read_line();
+
+    match (line.trim(), default_board) {
+        ("", Some(default_board)) => Some(default_board),
+        _ => match GameConfiguration::named(&line)
+            .map(Ok)
+            .unwrap_or_else(|| GameConfiguration::try_from(&line[..]))
+        {
+            Ok(game_configuration) => Some(config.apply_variants(game_configuration)),
+            Err(GameConfigurationError::OutOfLimits) => {
+                println!("{}", locale::text(Message::GameConfigOutOfLimits));
+                None
             }
-            &BoardCell::Mine(ref mark) => {
-                match &mark {
-                    Mark::NoMark | Mark::MarkNote => self.mines_discovered += 1,
-                    _ => {}
-                }
-                self.cells[linear_index] = BoardCell::Mine(Mark::MarkFlag);
+            Err(GameConfigurationError::TooManyMines) => {
+                println!("{}", locale::text(Message::GameConfigTooManyMines));
+                None
             }
-            _ => {}
-        }
+            Err(_) => {
+                println!("{}", locale::text(Message::InvalidGameConfig));
+                None
+            }
+        },
+    }
+}
 
-        GameResolve::Continue
+fn hot_seat() {
+    let config = Config::load();
+    let Some(game_configuration) = read_game_configuration(&config) else {
+        return;
+    };
+    let players = [1, 2].map(|number| {
+        println!("Name of player {} (empty for Player {}):", number, number);
+        let name = input::read_line();
+        match name.trim() {
+            "" => format!("Player {}", number),
+            name => name.to_string(),
+        }
+    });
+
+    let mut game_board = GameBoard::new(game_configuration);
+    game_board.generate_world(&mut rand::thread_rng());
+    game_board.set_coordinate_convention(config.coordinates.unwrap_or_default());
+    let mut hot_seat = HotSeat::new(players);
+    if let Some(spectators) = SPECTATORS.get() {
+        spectators.start(game_configuration, game_board.seed());
     }
 
-    fn set_mark_note(&mut self, coordinate: Coordinate) -> GameResolve {
-        let linear_index = self.compute_linear_index(coordinate);
+    loop {
+        println!("{}", &game_board);
+        println!("{}", hot_seat);
+        println!("{}'s turn:", hot_seat.current_player());
+        let cmd = input::read_line();
+        clear_console();
 
-        match &self.cells[linear_index] {
-            &BoardCell::NoMine(ref cell_info) => {
-                self.cells[linear_index] = BoardCell::NoMine(CellInfo(Mark::MarkNote, cell_info.1))
+        let cmd = match game_board
+            .coordinate_convention()
+            .parse_after(&cmd, game_board.last_cell())
+        {
+            Ok(cmd) if !HotSeat::allows(cmd) => {
+                println!("Claims and plugin commands are not played in turns.");
+                continue;
             }
-            &BoardCell::Mine(ref mark) => {
-                if let Mark::MarkFlag = mark {
-                    self.mines_discovered -= 1;
-                }
-                self.cells[linear_index] = BoardCell::Mine(Mark::MarkNote);
+            Ok(cmd) if game_board.is_on_board(cmd) => cmd,
+            Ok(_) | Err(BoardCommandError::OutsideBoard) => {
+                println!("{}", locale::text(Message::OutsideBoard));
+                continue;
+            }
+            Err(_) => continue,
+        };
+
+        if let Some(spectators) = SPECTATORS.get() {
+            spectators.broadcast_applied(hot_seat.current_player(), cmd);
+        }
+        match hot_seat.play_turn(&mut game_board, cmd) {
+            GameResolve::Continue => {}
+            // explores of mines are scored as detonations, any other mine going off ends
+            // the game for both
+            GameResolve::MineHit => {
+                println!("A mine went off, the game is over.");
+                break;
+            }
+            GameResolve::Quit => {
+                println!("Game ended early.");
+                break;
+            }
+            GameResolve::AllMinesDiscovered => {
+                println!("Board finished!");
+                break;
             }
-            _ => {}
         }
+    }
 
-        GameResolve::Continue
+    println!("{}", &game_board);
+    println!("{}", hot_seat);
+    match hot_seat.winner() {
+        Some(winner) => println!("{} wins!", winner),
+        None => println!("It's a tie!"),
     }
+}
+
+fn daily_challenge() {
+    let daily = DailyChallenge::today();
+    println!("Daily challenge for {}.", daily.date());
 
-    fn explore(&mut self, coordinate: Coordinate) -> GameResolve {
-        let linear_index = self.compute_linear_index(coordinate);
+    let mut game_board = GameBoard::new(GameConfiguration::expert());
+    game_board.generate_world_from_seed(daily.seed());
 
-        match &self.cells[linear_index] {
-            &BoardCell::NoMine(_) => {
-                self.explore_cells(coordinate);
-                GameResolve::Continue
+    let result = shared_game_loop(game_board);
+    if result.resolve != GameResolve::AllMinesDiscovered || !result.is_ranked() {
+        return;
+    }
+
+    let mut daily_highscores = DailyHighscores::load();
+    if record_highscore(daily_highscores.table_mut(daily.date()), &result) {
+        if let Err(err) = daily_highscores.save() {
+            println!("Could not save daily highscores: {}", err);
+        }
+    }
+}
+
+fn tournament() {
+    let tournament = Tournament::this_week();
+    println!(
+        "Weekly tournament {}, {} boards.\nKeymaps:\nplay-1,\nverify result file-2",
+        tournament.week(),
+        tournament.board_count()
+    );
+
+    let choice = input::read_line();
+
+    match choice.trim() {
+        "1" => play_tournament(&tournament),
+        "2" => {
+            println!("Enter path of the result file:");
+            let path = input::read_line();
+
+            match TournamentResult::load(Path::new(path.trim())) {
+                Ok(result) => match result.verify() {
+                    Ok(()) => println!("{}\nResult verified.", result),
+                    Err(err) => println!("Verification failed: {}", err),
+                },
+                Err(err) => println!("Could not read result file: {}", err),
             }
-            &BoardCell::Mine(_) => GameResolve::MineHit,
-            _ => GameResolve::Continue,
         }
+        _ => println!("Unknown option."),
     }
+}
+
+fn play_tournament(tournament: &Tournament) {
+    let mut result = TournamentResult::new(tournament);
 
-    fn compute_linear_index(&self, coordinate: Coordinate) -> usize {
-        (coordinate.0 * self.game_configuration.w() + coordinate.1) as usize
+    for (index, seed) in tournament.seeds().into_iter().enumerate() {
+        println!(
+            "Board {} of {}, press enter to start.",
+            index + 1,
+            tournament.board_count()
+        );
+        input::read_line();
+
+        let mut game_board = GameBoard::new(tournament.configuration());
+        game_board.generate_world_from_seed(seed);
+        result.add(shared_game_loop(game_board).replay);
     }
 
-    fn explore_cells(&mut self, coordinate: Coordinate) {
-        let mut queue: Vec<Coordinate> = vec![coordinate];
+    println!("{}", result);
+    match result.save() {
+        Ok(path) => println!("Result saved to {}", path.display()),
+        Err(err) => println!("Could not save tournament result: {}", err),
+    }
+}
 
-        while let Some(cell_coordinate) = queue.pop() {
-            let linear_index = self.compute_linear_index(cell_coordinate);
-            match &self.cells[linear_index] {
-                &BoardCell::Explored(_) => continue,
-                &BoardCell::NoMine(ref cell_info) => {
-                    self.add_neighbours(&mut queue, cell_coordinate);
-                    self.cells[linear_index] = BoardCell::Explored(cell_info.1);
-                }
-                _ => {}
+fn campaign() {
+    let profile = Profile::load();
+    let unlocked = profile.campaign_completed.min(campaign::LEVELS.len() - 1);
+
+    for (index, level) in campaign::LEVELS.iter().enumerate() {
+        let status = if index < profile.campaign_completed {
+            "completed"
+        } else if index == unlocked {
+            "unlocked"
+        } else {
+            "locked"
+        };
+        println!("{:>2}. {} [{}]", index + 1, level.description(), status);
+    }
+    println!("Choose a level, enter plays level {}:", unlocked + 1);
+
+    let choice = input::read_line();
+
+    let index = match choice.trim() {
+        "" => unlocked,
+        choice => match choice.parse::<usize>() {
+            Ok(level) if (1..=unlocked + 1).contains(&level) => level - 1,
+            _ => {
+                println!("That level is not unlocked.");
+                return;
             }
+        },
+    };
+
+    let level = &campaign::LEVELS[index];
+    let mut game_board = GameBoard::new(level.configuration);
+    game_board.generate_world_from_seed(level.seed);
+
+    let result = shared_game_loop(game_board);
+    if result.resolve != GameResolve::AllMinesDiscovered {
+        return;
+    }
+    if !level.is_completed_by(result.elapsed) {
+        println!("Too slow, the level is not completed.");
+        return;
+    }
+
+    println!("Level {} completed!", index + 1);
+    // the game itself updates the profile, so it is read again
+    let mut profile = Profile::load();
+    if index == profile.campaign_completed {
+        profile.campaign_completed += 1;
+        if let Err(err) = profile.save() {
+            println!("Could not save campaign progress: {}", err);
         }
     }
+}
 
-    fn add_neighbours(&self, queue: &mut Vec<Coordinate>, center: Coordinate) {
-        for i in -1..=1 {
-            for j in -1..=1 {
-                let x = center.0 as i32 + i;
-                let y = center.1 as i32 + j;
+fn adaptive() {
+    let rating = Profile::load().rating;
+    let configuration = rating.configuration();
+    println!(
+        "Adaptive board at {}% mines: {}x{} with {} mines.",
+        rating.density_percent,
+        configuration.w(),
+        configuration.h(),
+        configuration.mines()
+    );
+
+    let mut game_board = GameBoard::new(configuration);
+    game_board.generate_world(&mut rand::thread_rng());
+    let result = game_loop(game_board);
+
+    // the game itself updates the profile, so it is read again
+    let mut profile = Profile::load();
+    profile.rating.record(result.resolve);
+    println!(
+        "The next adaptive board has {}% mines.",
+        profile.rating.density_percent
+    );
+    if let Err(err) = profile.save() {
+        println!("Could not save the adaptive rating: {}", err);
+    }
+}
 
-                if x == center.0 as i32 && y == center.1 as i32
-                    || x < 0
-                    || y < 0
-                    || x >= self.game_configuration.h() as i32
-                    || y >= self.game_configuration.w() as i32
-                {
-                    continue;
-                }
+fn gauntlet() {
+    let mut score = 0;
+    let mut stages = 0;
+    loop {
+        let configuration = gauntlet::stage(stages);
+        println!(
+            "Gauntlet board {}: {}x{} with {} mines, {} cells revealed so far.",
+            stages + 1,
+            configuration.w(),
+            configuration.h(),
+            configuration.mines(),
+            score
+        );
 
-                queue.push(Coordinate(x as u16, y as u16))
-            }
+        let mut game_board = GameBoard::new(configuration);
+        game_board.generate_world(&mut rand::thread_rng());
+        let result = game_loop(game_board);
+        score += result.cells_revealed as u64;
+        if result.resolve != GameResolve::AllMinesDiscovered {
+            break;
         }
+        stages += 1;
+    }
+    println!(
+        "The gauntlet ends after {} boards with {} cells revealed.",
+        stages, score
+    );
+
+    let mut table = GauntletTable::load();
+    if table.qualifies(score) {
+        let name = read_player_name();
+        table.insert(name, score, stages);
+        println!("{}", table);
+        if let Err(err) = table.save() {
+            println!(
+                "{}",
+                locale::text(Message::CouldNotSaveHighscores(&err.to_string()))
+            );
+        }
+    }
+}
+
+fn puzzles() {
+    let profile = Profile::load();
+    for (index, puzzle) in puzzles::PUZZLES.iter().enumerate() {
+        let status = if profile.puzzles_solved.contains(puzzle.name) {
+            " [solved]"
+        } else {
+            ""
+        };
+        println!("{:>2}. {}{}", index + 1, puzzle.description(), status);
     }
+    println!("Choose a puzzle, no guessing is needed to solve any of them:");
+
+    let choice = input::read_line();
+    let puzzle = match choice.trim().parse::<usize>() {
+        Ok(index) if (1..=puzzles::PUZZLES.len()).contains(&index) => &puzzles::PUZZLES[index - 1],
+        _ => {
+            println!("There is no such puzzle.");
+            return;
+        }
+    };
 
-    fn get_dimensions(&self) -> (u16, u16) {
-        (self.game_configuration.w(), self.game_configuration.h())
+    let result = game_loop(GameBoard::from_layout(puzzle.layout()));
+    if result.resolve != GameResolve::AllMinesDiscovered {
+        return;
     }
 
-    fn get_cell_at(&self, coordinate: Coordinate) -> &BoardCell {
-        &self.cells[self.compute_linear_index(coordinate)]
+    println!("Puzzle {} solved!", puzzle.name);
+    // the game itself updates the profile, so it is read again
+    let mut profile = Profile::load();
+    if profile.puzzles_solved.insert(puzzle.name.to_string()) {
+        if let Err(err) = profile.save() {
+            println!("Could not save puzzle progress: {}", err);
+        }
     }
 }
 
-impl Display for GameBoard {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        let (width, height) = self.get_dimensions();
+fn host_game(address: &str, board: Option<GameConfiguration>) {
+    let configuration = board
+        .or(Config::load().default_board)
+        .unwrap_or(GameConfiguration::intermediate());
+    let convention = Config::load().coordinates.unwrap_or_default();
+    let mut game_board = GameBoard::new(configuration);
+    game_board.generate_world(&mut rand::thread_rng());
+    game_board.set_coordinate_convention(convention);
+
+    let host = match Host::bind(address, configuration, game_board.seed()) {
+        Ok(host) => host,
+        Err(err) => {
+            eprintln!("Could not host on {}: {}", address, err);
+            process::exit(1);
+        }
+    };
+    println!(
+        "Hosting {} on {}, other players can join now.",
+        configuration.key(),
+        host.local_addr()
+    );
+    println!("{}", &game_board);
+
+    let player = storage::active_profile().to_string();
+    let sender = host.sender();
+    thread::spawn(move || loop {
+        let command = match read_coop_command(convention) {
+            Some(command) => command,
+            None => continue,
+        };
+        if sender
+            .send(HostEvent::Command(player.clone(), command))
+            .is_err()
+        {
+            break;
+        }
+    });
 
-        write!(f, "{:>3}", "");
-        for col in 0..width {
-            write!(f, "{:>3}", col);
+    loop {
+        match host.next_event() {
+            HostEvent::Joined(player) => println!("{} joined.", player),
+            HostEvent::Left(player) => println!("{} left.", player),
+            HostEvent::Command(player, command) => {
+                if !game_board.is_on_board(command) {
+                    continue;
+                }
+                host.broadcast_applied(&player, command);
+                if apply_coop_command(&mut game_board, &player, command) {
+                    break;
+                }
+            }
         }
-        write!(f, "\n");
+    }
+}
 
-        for row in 0..height {
-            write!(f, "{:>3}|", row);
+fn join_game(address: &str) {
+    let (mut client, configuration, seed) =
+        match multiplayer::join(address, storage::active_profile()) {
+            Ok(joined) => joined,
+            Err(err) => {
+                eprintln!("Could not join {}: {}", address, err);
+                process::exit(1);
+            }
+        };
+    let mut writer = match client.writer() {
+        Ok(writer) => writer,
+        Err(err) => {
+            eprintln!("Could not join {}: {}", address, err);
+            process::exit(1);
+        }
+    };
+
+    let convention = Config::load().coordinates.unwrap_or_default();
+    let mut game_board = GameBoard::new(configuration);
+    game_board.generate_world_from_seed(seed);
+    game_board.set_coordinate_convention(convention);
+    println!("Joined {} on {}.", configuration.key(), address);
+    println!("{}", &game_board);
+
+    thread::spawn(move || loop {
+        let message = match read_coop_command(convention) {
+            // Quitting only leaves the game, it goes on for the other players.
+            Some(BoardCommand::Quit) => {
+                let _ = writer.shutdown(Shutdown::Both);
+                break;
+            }
+            Some(command) => ClientMessage::Command {
+                command: command.to_string(),
+            },
+            None => continue,
+        };
+        if multiplayer::send(&mut writer, &message).is_err() {
+            break;
+        }
+    });
 
-            for col in 0..width {
-                let symbol = match self.get_cell_at(Coordinate(row, col)) {
-                    BoardCell::NoMine(cell_info) => match cell_info.0 {
-                        Mark::NoMark => "|X|".to_string(),
-                        Mark::MarkNote => "|N|".to_string(),
-                        Mark::MarkFlag => "|F|".to_string(),
-                    },
-                    BoardCell::Mine(mark_info) => match mark_info {
-                        Mark::NoMark => "|X|".to_string(),
-                        Mark::MarkNote => "|N|".to_string(),
-                        Mark::MarkFlag => "|F|".to_string(),
-                    },
-                    BoardCell::Explored(neighbour_info) => {
-                        if neighbour_info.0 == 0 {
-                            "| |".to_string()
-                        } else {
-                            format!("|{}|", neighbour_info.0.to_string())
-                        }
+    loop {
+        match client.receive() {
+            Ok(Some(ServerMessage::Applied { player, command })) => {
+                if let Ok(command) = BoardCommand::try_from(&command[..]) {
+                    if apply_coop_command(&mut game_board, &player, command) {
+                        break;
                     }
+                }
+            }
+            Ok(Some(ServerMessage::Joined { player })) => println!("{} joined.", player),
+            Ok(Some(ServerMessage::Left { player })) => println!("{} left.", player),
+            Ok(Some(ServerMessage::Start { .. })) => {}
+            Ok(None) | Err(_) => {
+                println!("Left the game.");
+                break;
+            }
+        }
+    }
+}
+
+fn race_game(role: RaceRole) {
+    let joined = match role {
+        RaceRole::Host { address, board } => {
+            let configuration = board
+                .or(Config::load().default_board)
+                .unwrap_or(GameConfiguration::intermediate());
+            TcpListener::bind(&address).and_then(|listener| {
+                println!(
+                    "Racing on {}, waiting for an opponent on {}.",
+                    configuration.key(),
+                    listener.local_addr()?
+                );
+                let seed = rand::random();
+                Race::host(&listener, configuration, seed).map(|race| (race, configuration, seed))
+            })
+        }
+        RaceRole::Join { address } => Race::join(&address),
+    };
+    let (mut race, configuration, seed) = match joined {
+        Ok(joined) => joined,
+        Err(err) => {
+            eprintln!("Could not start the race: {}", err);
+            process::exit(1);
+        }
+    };
+
+    let convention = Config::load().coordinates.unwrap_or_default();
+    let mut game_board = GameBoard::new(configuration);
+    game_board.generate_world_from_seed(seed);
+    game_board.set_coordinate_convention(convention);
+
+    let sender = race.sender();
+    thread::spawn(move || loop {
+        if let Some(command) = read_coop_command(convention) {
+            if sender.send(RaceEvent::Command(command)).is_err() {
+                break;
+            }
+        }
+    });
+
+    let clock = GameClock::start();
+    let mut percent = 0;
+    loop {
+        println!("{}", &game_board);
+        println!("{}", race.status_line(percent));
+
+        let event = race.next_event();
+        clear_console();
+        match event {
+            RaceEvent::Command(command) => {
+                if !game_board.is_on_board(command) {
+                    println!("{}", locale::text(Message::OutsideBoard));
+                    continue;
+                }
+
+                let resolve = game_board.manipulate_cell(command);
+                percent = race::progress(&game_board);
+                let reported = match resolve {
+                    GameResolve::Continue => race.report_progress(percent),
+                    GameResolve::AllMinesDiscovered => race.report_finished(true),
+                    GameResolve::Quit | GameResolve::MineHit => race.report_finished(false),
                 };
+                if let Err(err) = reported {
+                    println!("Could not reach the opponent: {}", err);
+                }
 
-                write!(f, "{:>3}", symbol)
-                    .expect("Writing a new symbol failed in game board display.");
+                match resolve {
+                    GameResolve::Continue => continue,
+                    GameResolve::Quit => println!("You gave up, the opponent wins."),
+                    GameResolve::MineHit => println!("HIT MINE! The opponent wins."),
+                    GameResolve::AllMinesDiscovered => {
+                        println!("You cleared the board first, YOU WON!")
+                    }
+                }
+                break;
+            }
+            RaceEvent::OpponentProgress(_) => {}
+            RaceEvent::OpponentFinished { won: true } => {
+                println!("The opponent cleared the board first, you lost.");
+                break;
+            }
+            RaceEvent::OpponentFinished { won: false } => {
+                println!("The opponent is out, you are the last one standing, YOU WON!");
+                break;
+            }
+            RaceEvent::OpponentLeft => {
+                println!("The opponent left the race, YOU WON!");
+                break;
             }
-            write!(f, "\n").expect("Writing new line failed in game board display.");
         }
+    }
+
+    println!("{}", &game_board);
+    println!(
+        "Race took {}.",
+        Config::load()
+            .time_format
+            .unwrap_or_default()
+            .format(clock.elapsed())
+    );
+}
+
+fn spectate_game(address: &str) {
+    let mut client = match multiplayer::spectate(address) {
+        Ok(client) => client,
+        Err(err) => {
+            eprintln!("Could not spectate {}: {}", address, err);
+            process::exit(1);
+        }
+    };
+    println!("Watching {}, waiting for a game.", address);
 
-        Ok(())
+    let mut game_board = None;
+    loop {
+        match client.receive() {
+            Ok(Some(ServerMessage::Start {
+                configuration,
+                seed,
+            })) => {
+                let mut new_board = GameBoard::new(configuration);
+                new_board.generate_world_from_seed(seed);
+                new_board.set_coordinate_convention(Config::load().coordinates.unwrap_or_default());
+                clear_console();
+                println!("{}", &new_board);
+                println!("A new game of {} started.", configuration.key());
+                game_board = Some(new_board);
+            }
+            Ok(Some(ServerMessage::Applied { player, command })) => {
+                if let (Some(game_board), Ok(command)) =
+                    (game_board.as_mut(), BoardCommand::try_from(&command[..]))
+                {
+                    apply_coop_command(game_board, &player, command);
+                }
+            }
+            Ok(Some(ServerMessage::Joined { player })) => println!("{} joined.", player),
+            Ok(Some(ServerMessage::Left { player })) => println!("{} left.", player),
+            Ok(None) | Err(_) => {
+                println!("The game is over, stopped watching.");
+                break;
+            }
+        }
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Reads a command of the local player, quits once the input is closed.
+fn read_coop_command(convention: CoordinateConvention) -> Option<BoardCommand> {
+    let mut cmd = String::new();
+    match stdin().read_line(&mut cmd) {
+        Ok(0) | Err(_) => return Some(BoardCommand::Quit),
+        Ok(_) => {}
+    }
 
-    #[test]
-    fn create_command_test() {
-        let command = "pass";
-        assert_eq!(BoardCommand::Pass, command.try_into().unwrap());
+    match convention.parse(&cmd) {
+        Ok(command) => Some(command),
+        Err(BoardCommandError::OutsideBoard) => {
+            println!("{}", locale::text(Message::OutsideBoard));
+            None
+        }
+        Err(_) => {
+            println!("Unknown command.");
+            None
+        }
+    }
+}
 
-        let command = "quit";
-        assert_eq!(BoardCommand::Quit, command.try_into().unwrap());
+/// Applies a command of any player to the shared board, returns whether the game ended.
+fn apply_coop_command(game_board: &mut GameBoard, player: &str, command: BoardCommand) -> bool {
+    let resolve = game_board.manipulate_cell(command);
+    clear_console();
+    println!("{}", game_board);
+    println!("{}: {}", player, command);
+
+    match resolve {
+        GameResolve::Continue => false,
+        GameResolve::Quit => {
+            println!("{} ended the game.", player);
+            true
+        }
+        GameResolve::MineHit => {
+            println!("{}", locale::text(Message::HitMine));
+            true
+        }
+        GameResolve::AllMinesDiscovered => {
+            println!("{}", locale::text(Message::YouWon));
+            true
+        }
+    }
+}
 
-        let command = "clear(0, 0)";
-        assert_eq!(
-            BoardCommand::ClearMark(Coordinate(0, 0)),
-            command.try_into().unwrap()
-        );
+fn show_achievements() {
+    let profile = Profile::load();
 
-        let command = "note(2,1)";
-        assert_eq!(
-            BoardCommand::SetMarkNote(Coordinate(2, 1)),
-            command.try_into().unwrap()
+    for achievement in Achievement::ALL {
+        let status = if profile.achievements.is_unlocked(achievement) {
+            "x"
+        } else {
+            " "
+        };
+        println!(
+            "[{}] {} - {}",
+            status,
+            achievement.title(),
+            achievement.description()
         );
+    }
+    println!(
+        "Cells revealed over all games: {}\n",
+        profile.achievements.cells_revealed()
+    );
+}
 
-        let command = "flag(100, 21)";
-        assert_eq!(
-            BoardCommand::SetMarkFlag(Coordinate(100, 21)),
-            command.try_into().unwrap()
-        );
+fn export_profile(output: Option<PathBuf>) {
+    let profile = storage::active_profile();
+    let output = output.unwrap_or_else(|| PathBuf::from(format!("{}.profile.json", profile)));
+    let written = ProfileBundle::of(storage::backend(), profile)
+        .and_then(|bundle| std::fs::write(&output, bundle.to_json()).map(|()| bundle));
+    match written {
+        Ok(bundle) => println!(
+            "Exported profile {} with {} games to {}.",
+            profile,
+            bundle.games(),
+            output.display()
+        ),
+        Err(err) => {
+            eprintln!("Could not export profile {}: {}", profile, err);
+            process::exit(1);
+        }
+    }
+}
 
-        let command = "explore(20, 20)";
-        assert_eq!(
-            BoardCommand::Explore(Coordinate(20, 20)),
-            command.try_into().unwrap()
+fn import_profile(path: &Path, name: Option<String>) {
+    let bundle = match std::fs::read_to_string(path)
+        .map_err(BundleError::Io)
+        .and_then(|content| ProfileBundle::from_json(&content))
+    {
+        Ok(bundle) => bundle,
+        Err(err) => {
+            eprintln!("Could not read {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    };
+    let name = name.unwrap_or_else(|| bundle.profile.clone());
+    if !storage::is_valid_profile_name(&name) {
+        eprintln!(
+            "The bundle's profile name {} is invalid, choose one with --as.",
+            name
         );
+        process::exit(1);
     }
+    match bundle.restore(storage::backend(), &name) {
+        Ok(()) => println!(
+            "Imported profile {} with {} games, play it with --profile {}.",
+            name,
+            bundle.games(),
+            name
+        ),
+        Err(BundleError::ProfileExists(_)) => {
+            eprintln!(
+                "The profile {} exists already, import it under another name with --as.",
+                name
+            );
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("Could not import profile {}: {}", name, err);
+            process::exit(1);
+        }
+    }
+}
 
-    #[test]
-    fn fail_to_create_command_test() {
-        let command = "asd";
-        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
-        assert_eq!(Err(BoardCommandError::MalformedString), result);
+fn import_highscores(path: &Path, daily: bool) {
+    let content = match std::fs::read_to_string(path) {
+        Ok(content) => content,
+        Err(err) => {
+            eprintln!("Could not read {}: {}", path.display(), err);
+            process::exit(1);
+        }
+    };
+    let merged = if daily {
+        serde_json::from_str::<DailyHighscores>(&content).map(|other| {
+            let mut highscores = DailyHighscores::load();
+            (highscores.merge(&other), highscores.save())
+        })
+    } else {
+        serde_json::from_str::<Highscores>(&content).map(|other| {
+            let mut highscores = Highscores::load();
+            (highscores.merge(&other), highscores.save())
+        })
+    };
+    match merged {
+        Ok((added, Ok(()))) => println!("Imported {} new entries from {}.", added, path.display()),
+        Ok((_, Err(err))) => {
+            eprintln!("Could not save the highscores: {}", err);
+            process::exit(1);
+        }
+        Err(err) => {
+            eprintln!("{} is not a highscore file: {}", path.display(), err);
+            process::exit(1);
+        }
+    }
+}
 
-        let command = "mark(10,10,10)";
-        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
-        // not the best way to handle these errors in such a way. One thing is that the msg is lost
-        if let Err(BoardCommandError::CoordinateParsing(_)) = result {
-            assert!(true);
-        } else {
-            assert!(false);
+fn show_highscores() {
+    let highscores = Highscores::load();
+    for (title, table) in highscores.tables_for_display() {
+        println!("{}:", title);
+        match table {
+            Some(table) => println!("{}", table),
+            None => println!("No entries yet.\n"),
         }
+    }
 
-        let command = "mark(10.10)";
-        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
-        assert_eq!(Err(BoardCommandError::MalformedCoordinate), result);
+    let daily = DailyChallenge::today();
+    let daily_highscores = DailyHighscores::load();
+    println!("Daily challenge {}:", daily.date());
+    match daily_highscores.table(daily.date()) {
+        Some(table) => println!("{}", table),
+        None => println!("No entries yet.\n"),
+    }
 
-        let command = "flag(1000_000, 10)";
-        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
-        if let Err(BoardCommandError::CoordinateParsing(_)) = result {
-            assert!(true);
-        } else {
-            assert!(false);
-        }
+    println!("Gauntlet:");
+    let gauntlet = GauntletTable::load();
+    if gauntlet.entries().is_empty() {
+        println!("No entries yet.\n");
+    } else {
+        println!("{}", gauntlet);
+    }
 
-        let command = "flag((1000, 20))";
-        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
-        if let Err(BoardCommandError::CoordinateParsing(_)) = result {
-            assert!(true);
-        } else {
-            assert!(false);
+    if let Some(url) = Config::load().leaderboard_url {
+        show_global_highscores(&url);
+    }
+}
+
+fn show_global_highscores(url: &str) {
+    let classic = [
+        GameConfiguration::beginner(),
+        GameConfiguration::intermediate(),
+        GameConfiguration::expert(),
+    ];
+
+    for configuration in classic {
+        println!(
+            "Global {}:",
+            configuration.difficulty_name().unwrap_or_default()
+        );
+        match leaderboard::top_list(url, &configuration) {
+            Ok(entries) if entries.is_empty() => println!("No entries yet.\n"),
+            Ok(entries) => {
+                for (rank, entry) in entries.iter().enumerate() {
+                    println!(
+                        "{:>3}. {:<20}{:>10.2} s",
+                        rank + 1,
+                        entry.player,
+                        entry.time_ms as f64 / 1000.0
+                    );
+                }
+                println!();
+            }
+            Err(err) => {
+                println!("Could not fetch the online leaderboard: {}\n", err);
+                return;
+            }
         }
+    }
+}
+
+/// Enters the time into the table if it qualifies, returns whether it did.
+fn record_highscore(table: &mut HighscoreTable, result: &GameResult) -> bool {
+    if !table.qualifies(result.elapsed) {
+        return false;
+    }
+
+    let name = read_player_name();
+    table.insert(
+        name,
+        result.elapsed,
+        Some(result.efficiency),
+        result.assisted,
+    );
+    println!("{}", table);
+    true
+}
 
-        let command = "test(10, 10)";
-        let result: Result<BoardCommand, BoardCommandError> = command.try_into();
-        assert_eq!(Err(BoardCommandError::NotFound), result);
+fn read_player_name() -> String {
+    println!(
+        "New highscore! Enter your name (empty for {}):",
+        storage::active_profile()
+    );
+    let name = input::read_line();
+
+    match name.trim() {
+        "" => storage::active_profile().to_string(),
+        name => name.to_string(),
     }
 }
+
+/// Plays the board on the terminal.
+fn game_loop(mut game_board: GameBoard) -> GameResult {
+    game_board.set_show_mines(DEBUG_SHOW_MINES.load(Ordering::Relaxed));
+    console::game_loop(game_board, &mut Terminal, &mut Stdout, SPECTATORS.get())
+}
+
+/// [`game_loop`] for a board everyone plays from the same seed, without safe start.
+fn shared_game_loop(mut game_board: GameBoard) -> GameResult {
+    game_board.set_show_mines(DEBUG_SHOW_MINES.load(Ordering::Relaxed));
+    console::shared_game_loop(game_board, &mut Terminal, &mut Stdout, SPECTATORS.get())
+}
@@ -0,0 +1,161 @@
+//! Masks give boards arbitrary shapes. A mask file has one line per row, `#` is a cell of
+//! the board and `.` a hole, e.g. a diamond:
+//!
+//! ```text
+//! ..#..
+//! .###.
+//! #####
+//! .###.
+//! ..#..
+//! ```
+//!
+//! Holes never hold mines, are not neighbours of any cell and are not drawn.
+
+use std::{fmt::Display, fs, io, path::Path};
+
+use serde::{Deserialize, Serialize};
+
+use crate::Coordinate;
+
+/// Shape of a board, kept as the text of the mask file in replays.
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+#[serde(try_from = "String", into = "String")]
+pub struct Mask {
+    width: u32,
+    height: u32,
+    holes: Vec<bool>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MaskError {
+    Empty,
+    UnevenRows(usize),
+    UnknownSymbol(char),
+    TooLarge,
+}
+
+impl Display for MaskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MaskError::Empty => write!(f, "the mask has no cells"),
+            MaskError::UnevenRows(row) => {
+                write!(f, "row {} is not as long as the first one", row + 1)
+            }
+            MaskError::UnknownSymbol(symbol) => {
+                write!(f, "'{}' is neither a cell '#' nor a hole '.'", symbol)
+            }
+            MaskError::TooLarge => write!(f, "the mask is too large for a board"),
+        }
+    }
+}
+
+impl Mask {
+    /// Reads the mask file, parse errors are reported as invalid data.
+    pub fn load(path: &Path) -> io::Result<Mask> {
+        let content = fs::read_to_string(path)?;
+        Mask::try_from(&content[..])
+            .map_err(|err| io::Error::new(io::ErrorKind::InvalidData, err.to_string()))
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Whether the coordinate lies in a hole, coordinates off the mask are no holes.
+    pub fn is_hole(&self, coordinate: Coordinate) -> bool {
+        coordinate.0 < self.height
+            && coordinate.1 < self.width
+            && self.holes[coordinate.0 as usize * self.width as usize + coordinate.1 as usize]
+    }
+
+    /// Number of cells that are part of the board.
+    pub fn cell_count(&self) -> u32 {
+        self.holes.iter().filter(|hole| !**hole).count() as u32
+    }
+}
+
+impl TryFrom<&str> for Mask {
+    type Error = MaskError;
+
+    fn try_from(value: &str) -> Result<Self, Self::Error> {
+        let rows: Vec<&str> = value
+            .lines()
+            .map(str::trim)
+            .filter(|row| !row.is_empty())
+            .collect();
+        let width = rows.first().map_or(0, |row| row.chars().count());
+
+        let mut holes = Vec::with_capacity(width * rows.len());
+        for (index, row) in rows.iter().enumerate() {
+            if row.chars().count() != width {
+                return Err(MaskError::UnevenRows(index));
+            }
+            for symbol in row.chars() {
+                match symbol {
+                    '#' => holes.push(false),
+                    '.' => holes.push(true),
+                    symbol => return Err(MaskError::UnknownSymbol(symbol)),
+                }
+            }
+        }
+
+        if !holes.contains(&false) {
+            return Err(MaskError::Empty);
+        }
+        Ok(Mask {
+            width: u32::try_from(width).map_err(|_| MaskError::TooLarge)?,
+            height: u32::try_from(rows.len()).map_err(|_| MaskError::TooLarge)?,
+            holes,
+        })
+    }
+}
+
+impl TryFrom<String> for Mask {
+    type Error = MaskError;
+
+    fn try_from(value: String) -> Result<Self, Self::Error> {
+        Mask::try_from(&value[..])
+    }
+}
+
+impl From<Mask> for String {
+    fn from(mask: Mask) -> Self {
+        mask.to_string()
+    }
+}
+
+impl Display for Mask {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        for row in self.holes.chunks(self.width as usize) {
+            let row: String = row
+                .iter()
+                .map(|hole| if *hole { '.' } else { '#' })
+                .collect();
+            writeln!(f, "{}", row)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_mask_test() {
+        let mask = Mask::try_from("..#..\n.###.\n#####\n.###.\n..#..\n").unwrap();
+        assert_eq!((5, 5), (mask.width(), mask.height()));
+        assert_eq!(13, mask.cell_count());
+        assert!(mask.is_hole(Coordinate(0, 0)));
+        assert!(!mask.is_hole(Coordinate(2, 0)));
+        assert_eq!(mask, Mask::try_from(mask.to_string()).unwrap());
+
+        assert_eq!(Err(MaskError::UnevenRows(1)), Mask::try_from("##\n#"));
+        assert_eq!(Err(MaskError::UnknownSymbol('x')), Mask::try_from("#x"));
+        assert_eq!(Err(MaskError::Empty), Mask::try_from("...\n..."));
+    }
+}
@@ -0,0 +1,151 @@
+//! Several boards played at once, the multiboard format. One board is shown at a time
+//! and `board 2` switches to the second one. Every board keeps its own clock, which only
+//! runs while the board is shown. A mine hit on any board loses the match, clearing all of
+//! them wins it.
+
+use std::time::Duration;
+
+use crate::{clock::GameClock, BoardCommand, GameBoard, GameConfiguration, GameResolve};
+
+struct Slot {
+    game_board: GameBoard,
+    clock: GameClock,
+    resolve: GameResolve,
+}
+
+pub struct MultiBoard {
+    slots: Vec<Slot>,
+    /// Index of the board shown.
+    active: usize,
+}
+
+/// Number of the board the line switches to, e.g. 2 for `board 2`.
+pub fn board_number(line: &str) -> Option<usize> {
+    line.trim().strip_prefix("board")?.trim().parse().ok()
+}
+
+impl MultiBoard {
+    /// Boards of the configuration, the n-th one generated from the n-th seed after the
+    /// given one. None without boards.
+    pub fn new(configuration: GameConfiguration, count: usize, seed: u64) -> Option<Self> {
+        if count == 0 {
+            return None;
+        }
+        let slots = (0..count)
+            .map(|index| {
+                let mut game_board = GameBoard::new(configuration);
+                game_board.generate_world_from_seed(seed.wrapping_add(index as u64));
+                let mut clock = GameClock::start();
+                if index != 0 {
+                    clock.pause();
+                }
+                Slot {
+                    game_board,
+                    clock,
+                    resolve: GameResolve::Continue,
+                }
+            })
+            .collect();
+        Some(MultiBoard { slots, active: 0 })
+    }
+
+    pub fn active(&self) -> &GameBoard {
+        &self.slots[self.active].game_board
+    }
+
+    /// Shows the board of the number counting from 1, returns whether there is one.
+    pub fn select(&mut self, number: usize) -> bool {
+        if !(1..=self.slots.len()).contains(&number) {
+            return false;
+        }
+        self.slots[self.active].clock.pause();
+        self.active = number - 1;
+        let slot = &mut self.slots[self.active];
+        if slot.resolve == GameResolve::Continue {
+            slot.clock.resume();
+        }
+        true
+    }
+
+    /// Applies the command to the board shown and returns how the match stands.
+    pub fn apply(&mut self, command: BoardCommand) -> GameResolve {
+        let slot = &mut self.slots[self.active];
+        if slot.resolve == GameResolve::Continue {
+            slot.resolve = slot.game_board.manipulate_cell(command);
+            if slot.resolve != GameResolve::Continue {
+                slot.clock.pause();
+            }
+        }
+        self.resolve()
+    }
+
+    /// Lost with the first lost board, won once every board is cleared.
+    pub fn resolve(&self) -> GameResolve {
+        let resolves = || self.slots.iter().map(|slot| slot.resolve);
+        if let Some(lost) =
+            resolves().find(|resolve| matches!(resolve, GameResolve::MineHit | GameResolve::Quit))
+        {
+            lost
+        } else if resolves().all(|resolve| resolve == GameResolve::AllMinesDiscovered) {
+            GameResolve::AllMinesDiscovered
+        } else {
+            GameResolve::Continue
+        }
+    }
+
+    /// Time played on all boards together.
+    pub fn elapsed(&self) -> Duration {
+        self.slots.iter().map(|slot| slot.clock.elapsed()).sum()
+    }
+
+    /// One line listing the boards with the shown one in brackets, e.g.
+    /// `[1: 4.210 s]  2: won 12.003 s  3: 0.000 s`.
+    pub fn selector(&self, format: impl Fn(Duration) -> String) -> String {
+        self.slots
+            .iter()
+            .enumerate()
+            .map(|(index, slot)| {
+                let state = match slot.resolve {
+                    GameResolve::AllMinesDiscovered => "won ",
+                    GameResolve::MineHit => "lost ",
+                    GameResolve::Continue | GameResolve::Quit => "",
+                };
+                let board = format!("{}: {}{}", index + 1, state, format(slot.clock.elapsed()));
+                if index == self.active {
+                    format!("[{}]", board)
+                } else {
+                    format!(" {} ", board)
+                }
+            })
+            .collect::<Vec<String>>()
+            .join(" ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coordinate;
+
+    #[test]
+    fn multiboard_test() {
+        assert_eq!(Some(2), board_number(" board 2"));
+        assert_eq!(None, board_number("board two"));
+        assert!(MultiBoard::new(GameConfiguration::new(3, 3, 0), 0, 1).is_none());
+
+        // boards without mines are cleared by a single explore
+        let mut boards = MultiBoard::new(GameConfiguration::new(3, 3, 0), 2, 1).unwrap();
+        assert!(!boards.select(3));
+        assert_eq!(
+            GameResolve::Continue,
+            boards.apply(BoardCommand::Explore(Coordinate(0, 0)))
+        );
+        assert!(boards.selector(|_| String::new()).starts_with("[1: won ]"));
+        assert!(boards.select(2));
+        assert_eq!(0, boards.active().explored_cells());
+        assert_eq!(
+            GameResolve::AllMinesDiscovered,
+            boards.apply(BoardCommand::Explore(Coordinate(2, 2)))
+        );
+    }
+}
@@ -0,0 +1,419 @@
+//! Cooperative multiplayer over TCP. The host owns the `GameBoard`, applies the commands of
+//! every player and broadcasts them, joined players replay the same commands on a board
+//! generated from the same seed so all terminals stay in sync.
+//!
+//! Spectators receive the same broadcast but their commands are ignored, they can watch a
+//! hosted game or a local game streamed through [`Spectators`].
+//!
+//! Messages are json, one per line.
+
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs},
+    sync::{
+        mpsc::{self, Receiver, Sender},
+        Arc, Mutex,
+    },
+    thread,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::{storage, BoardCommand, GameConfiguration};
+
+/// Sent by a joined player to the host.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Join { player: String },
+    Spectate,
+    Command { command: String },
+}
+
+/// Sent by the host to every joined player.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Start {
+        configuration: GameConfiguration,
+        seed: u64,
+    },
+    Joined {
+        player: String,
+    },
+    Left {
+        player: String,
+    },
+    Applied {
+        player: String,
+        command: String,
+    },
+}
+
+/// Something the host has to act on, the local player sends its commands through
+/// [`Host::sender`] as well.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HostEvent {
+    Joined(String),
+    Left(String),
+    Command(String, BoardCommand),
+}
+
+pub fn send<T: Serialize>(stream: &mut TcpStream, message: &T) -> io::Result<()> {
+    let mut line = serde_json::to_string(message)?;
+    line.push('\n');
+    stream.write_all(line.as_bytes())
+}
+
+/// Reads line separated messages from a stream.
+pub struct MessageReader {
+    reader: BufReader<TcpStream>,
+}
+
+impl MessageReader {
+    pub fn new(stream: TcpStream) -> MessageReader {
+        MessageReader {
+            reader: BufReader::new(stream),
+        }
+    }
+
+    /// Next message, `None` once the other side closed the connection.
+    pub fn receive<T: DeserializeOwned>(&mut self) -> io::Result<Option<T>> {
+        let mut line = String::new();
+        if self.reader.read_line(&mut line)? == 0 {
+            return Ok(None);
+        }
+        Ok(Some(serde_json::from_str(&line)?))
+    }
+}
+
+#[derive(Default)]
+struct Session {
+    clients: Vec<TcpStream>,
+    history: Vec<ServerMessage>,
+}
+
+impl Session {
+    fn catch_up(&self, mut client: TcpStream) -> io::Result<TcpStream> {
+        for message in &self.history {
+            send(&mut client, message)?;
+        }
+        Ok(client)
+    }
+
+    /// Catches the client up with the history and adds it to the broadcast.
+    fn add_client(&mut self, client: TcpStream) -> io::Result<()> {
+        let client = self.catch_up(client)?;
+        self.clients.push(client);
+        Ok(())
+    }
+
+    fn broadcast(&mut self, message: ServerMessage) {
+        // Players whose connection broke are dropped, their reader reports them as left.
+        self.clients
+            .retain_mut(|client| send(client, &message).is_ok());
+        self.history.push(message);
+    }
+}
+
+/// Hosts a cooperative game, players can join at any time and catch up from the history.
+pub struct Host {
+    address: SocketAddr,
+    session: Arc<Mutex<Session>>,
+    sender: Sender<HostEvent>,
+    events: Receiver<HostEvent>,
+}
+
+impl Host {
+    pub fn bind<A: ToSocketAddrs>(
+        address: A,
+        configuration: GameConfiguration,
+        seed: u64,
+    ) -> io::Result<Host> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let session = Arc::new(Mutex::new(Session {
+            clients: Vec::new(),
+            history: vec![ServerMessage::Start {
+                configuration,
+                seed,
+            }],
+        }));
+        let (sender, events) = mpsc::channel();
+
+        let accept_session = Arc::clone(&session);
+        let accept_sender = sender.clone();
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let session = Arc::clone(&accept_session);
+                let sender = accept_sender.clone();
+                thread::spawn(move || serve_client(stream, session, sender));
+            }
+        });
+
+        Ok(Host {
+            address,
+            session,
+            sender,
+            events,
+        })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    pub fn sender(&self) -> Sender<HostEvent> {
+        self.sender.clone()
+    }
+
+    /// Blocks until a player joins, leaves or sends a command.
+    pub fn next_event(&self) -> HostEvent {
+        self.events
+            .recv()
+            .expect("Host keeps a sender, the channel stays open.")
+    }
+
+    /// Sends a command that was applied to the board to every player.
+    pub fn broadcast_applied(&self, player: &str, command: BoardCommand) {
+        self.session
+            .lock()
+            .expect("Session lock is not poisoned.")
+            .broadcast(ServerMessage::Applied {
+                player: player.to_string(),
+                command: command.to_string(),
+            });
+    }
+}
+
+fn serve_client(stream: TcpStream, session: Arc<Mutex<Session>>, sender: Sender<HostEvent>) {
+    let Ok(writer) = stream.try_clone() else {
+        return;
+    };
+    let mut reader = MessageReader::new(stream);
+
+    let player = match reader.receive() {
+        Ok(Some(ClientMessage::Join { player })) if storage::is_valid_profile_name(&player) => {
+            player
+        }
+        Ok(Some(ClientMessage::Spectate)) => {
+            let _ = session
+                .lock()
+                .expect("Session lock is not poisoned.")
+                .add_client(writer);
+            return;
+        }
+        _ => return,
+    };
+
+    {
+        let mut session = session.lock().expect("Session lock is not poisoned.");
+        let Ok(writer) = session.catch_up(writer) else {
+            return;
+        };
+        session.broadcast(ServerMessage::Joined {
+            player: player.clone(),
+        });
+        session.clients.push(writer);
+    }
+    if sender.send(HostEvent::Joined(player.clone())).is_err() {
+        return;
+    }
+
+    while let Ok(Some(message)) = reader.receive() {
+        if let ClientMessage::Command { command } = message {
+            if let Ok(command) = BoardCommand::try_from(&command[..]) {
+                if sender
+                    .send(HostEvent::Command(player.clone(), command))
+                    .is_err()
+                {
+                    return;
+                }
+            }
+        }
+    }
+
+    session
+        .lock()
+        .expect("Session lock is not poisoned.")
+        .broadcast(ServerMessage::Left {
+            player: player.clone(),
+        });
+    let _ = sender.send(HostEvent::Left(player));
+}
+
+/// Streams the games of the local player to spectators connecting on the address.
+pub struct Spectators {
+    address: SocketAddr,
+    session: Arc<Mutex<Session>>,
+}
+
+impl Spectators {
+    pub fn bind<A: ToSocketAddrs>(address: A) -> io::Result<Spectators> {
+        let listener = TcpListener::bind(address)?;
+        let address = listener.local_addr()?;
+        let session = Arc::new(Mutex::new(Session::default()));
+
+        let accept_session = Arc::clone(&session);
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                let session = Arc::clone(&accept_session);
+                thread::spawn(move || {
+                    let Ok(writer) = stream.try_clone() else {
+                        return;
+                    };
+                    if let Ok(Some(ClientMessage::Spectate)) = MessageReader::new(stream).receive()
+                    {
+                        let _ = session
+                            .lock()
+                            .expect("Session lock is not poisoned.")
+                            .add_client(writer);
+                    }
+                });
+            }
+        });
+
+        Ok(Spectators { address, session })
+    }
+
+    pub fn local_addr(&self) -> SocketAddr {
+        self.address
+    }
+
+    /// Starts streaming a new game, spectators connecting later only see this one.
+    pub fn start(&self, configuration: GameConfiguration, seed: u64) {
+        let mut session = self.session.lock().expect("Session lock is not poisoned.");
+        session.history.clear();
+        session.broadcast(ServerMessage::Start {
+            configuration,
+            seed,
+        });
+    }
+
+    pub fn broadcast_applied(&self, player: &str, command: BoardCommand) {
+        self.session
+            .lock()
+            .expect("Session lock is not poisoned.")
+            .broadcast(ServerMessage::Applied {
+                player: player.to_string(),
+                command: command.to_string(),
+            });
+    }
+}
+
+/// Connection of a joined player or spectator, created by [`join`] or [`spectate`].
+pub struct Client {
+    stream: TcpStream,
+    reader: MessageReader,
+}
+
+/// Joins the game hosted at the address, returns the client with the board to play on.
+pub fn join<A: ToSocketAddrs>(
+    address: A,
+    player: &str,
+) -> io::Result<(Client, GameConfiguration, u64)> {
+    let mut stream = TcpStream::connect(address)?;
+    send(
+        &mut stream,
+        &ClientMessage::Join {
+            player: player.to_string(),
+        },
+    )?;
+
+    let mut reader = MessageReader::new(stream.try_clone()?);
+    match reader.receive()? {
+        Some(ServerMessage::Start {
+            configuration,
+            seed,
+        }) => Ok((Client { stream, reader }, configuration, seed)),
+        _ => Err(io::Error::new(
+            io::ErrorKind::InvalidData,
+            "host did not start the game",
+        )),
+    }
+}
+
+/// Watches the game hosted or streamed at the address, the first message is the start
+/// of the game.
+pub fn spectate<A: ToSocketAddrs>(address: A) -> io::Result<Client> {
+    let mut stream = TcpStream::connect(address)?;
+    send(&mut stream, &ClientMessage::Spectate)?;
+    let reader = MessageReader::new(stream.try_clone()?);
+    Ok(Client { stream, reader })
+}
+
+impl Client {
+    /// Stream the commands of the player are sent through with [`send`].
+    pub fn writer(&self) -> io::Result<TcpStream> {
+        self.stream.try_clone()
+    }
+
+    pub fn receive(&mut self) -> io::Result<Option<ServerMessage>> {
+        self.reader.receive()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Coordinate;
+
+    #[test]
+    fn commands_are_broadcast_test() {
+        let configuration = GameConfiguration::beginner();
+        let host = Host::bind("127.0.0.1:0", configuration, 42).unwrap();
+
+        let (mut client, joined_configuration, seed) = join(host.local_addr(), "guest").unwrap();
+        assert_eq!((configuration, 42), (joined_configuration, seed));
+        assert_eq!(HostEvent::Joined("guest".to_string()), host.next_event());
+
+        let command = BoardCommand::SetMarkFlag(Coordinate(1, 2));
+        send(
+            &mut client.writer().unwrap(),
+            &ClientMessage::Command {
+                command: command.to_string(),
+            },
+        )
+        .unwrap();
+        assert_eq!(
+            HostEvent::Command("guest".to_string(), command),
+            host.next_event()
+        );
+
+        host.broadcast_applied("guest", command);
+        assert_eq!(
+            Some(ServerMessage::Applied {
+                player: "guest".to_string(),
+                command: command.to_string()
+            }),
+            client.receive().unwrap()
+        );
+    }
+
+    #[test]
+    fn spectators_receive_the_game_test() {
+        let spectators = Spectators::bind("127.0.0.1:0").unwrap();
+        let mut spectator = spectate(spectators.local_addr()).unwrap();
+        let configuration = GameConfiguration::beginner();
+
+        // The spectator is added asynchronously, the history catches it up either way.
+        spectators.start(configuration, 9);
+        let command = BoardCommand::Explore(Coordinate(0, 0));
+        spectators.broadcast_applied("solo", command);
+
+        assert_eq!(
+            Some(ServerMessage::Start {
+                configuration,
+                seed: 9
+            }),
+            spectator.receive().unwrap()
+        );
+        assert_eq!(
+            Some(ServerMessage::Applied {
+                player: "solo".to_string(),
+                command: command.to_string()
+            }),
+            spectator.receive().unwrap()
+        );
+    }
+}
@@ -0,0 +1,116 @@
+//! How long every move of a replay took, drawn as a bar per move so the moves a player
+//! hesitated on stand out.
+
+use std::{fmt::Display, time::Duration};
+
+use crate::replay::Replay;
+
+/// Width of the longest bar.
+const BAR_WIDTH: usize = 40;
+
+/// Moves marked as the longest hesitations.
+const SLOWEST: usize = 3;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Pace {
+    /// Command and time taken of every move.
+    moves: Vec<(String, Duration)>,
+}
+
+impl Pace {
+    pub fn of(replay: &Replay) -> Self {
+        Pace {
+            moves: replay
+                .moves
+                .iter()
+                .map(|replay_move| replay_move.command.clone())
+                .zip(replay.move_times())
+                .collect(),
+        }
+    }
+
+    /// Indices of the moves that took longest, longest first.
+    pub fn slowest(&self, count: usize) -> Vec<usize> {
+        let mut indices: Vec<usize> = (0..self.moves.len()).collect();
+        indices.sort_by_key(|&index| std::cmp::Reverse(self.moves[index].1));
+        indices.truncate(count);
+        indices
+    }
+
+    /// Time taken by half of the moves or less.
+    pub fn median(&self) -> Duration {
+        let mut times: Vec<Duration> = self.moves.iter().map(|(_, time)| *time).collect();
+        times.sort();
+        times.get(times.len() / 2).copied().unwrap_or_default()
+    }
+}
+
+impl Display for Pace {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let longest = self
+            .moves
+            .iter()
+            .map(|(_, time)| time.as_millis())
+            .max()
+            .unwrap_or_default()
+            .max(1);
+        let slowest = self.slowest(SLOWEST);
+        let command_width = self
+            .moves
+            .iter()
+            .map(|(command, _)| command.len())
+            .max()
+            .unwrap_or_default();
+
+        for (index, (command, time)) in self.moves.iter().enumerate() {
+            let bar = (time.as_millis() * BAR_WIDTH as u128).div_ceil(longest) as usize;
+            writeln!(
+                f,
+                "{:>4} {:<command_width$} {:>8.2} s {}{}",
+                index + 1,
+                command,
+                time.as_secs_f64(),
+                "█".repeat(bar),
+                if slowest.contains(&index) { " <" } else { "" },
+            )?;
+        }
+        write!(
+            f,
+            "{} moves, half of them within {:.2} s, < marks the longest pauses.",
+            self.moves.len(),
+            self.median().as_secs_f64()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoardCommand, Coordinate, GameConfiguration};
+
+    #[test]
+    fn pace_test() {
+        let mut replay = Replay::new(GameConfiguration::beginner(), 1);
+        for (col, millis) in [(0, 500), (1, 4_500), (2, 5_000), (3, 5_500), (4, 7_500)] {
+            replay.record(
+                BoardCommand::SetMarkNote(Coordinate(0, col)),
+                Duration::from_millis(millis),
+            );
+        }
+        let pace = Pace::of(&replay);
+
+        assert_eq!(vec![1, 4, 0], pace.slowest(3));
+        assert_eq!(Duration::from_millis(500), pace.median());
+        let shown = pace.to_string();
+        let lines: Vec<&str> = shown.lines().collect();
+        assert_eq!(
+            format!("   2 note(0, 1)     4.00 s {} <", "█".repeat(40)),
+            lines[1]
+        );
+        assert_eq!(
+            format!("   3 note(0, 2)     0.50 s {}", "█".repeat(5)),
+            lines[2]
+        );
+        assert!(lines[5].starts_with("5 moves"));
+    }
+}
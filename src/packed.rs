@@ -0,0 +1,536 @@
+//! Cells of the board packed into a byte each, which keeps boards of millions of cells
+//! small. The two low bits hold the mark, then a bit for mines and one for explored
+//! cells, the high four bits the count of neighbouring mines. Counts that do not fit,
+//! negative ones next to anti-mines or large ones with several mines per cell, are kept
+//! aside.
+//!
+//! The bytes are either owned or mapped from a save file, see [`crate::save`]. Large
+//! boards with few mines keep [`SparseCells`] instead, which only take memory for the
+//! cells that are not empty.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt::Debug,
+    io::{self, Write},
+    ops::{Deref, DerefMut},
+};
+
+use crate::{BoardCell, CellInfo, Mark, NeighbourMines};
+
+const MARK_BITS: u8 = 0b0011;
+const MINE_BIT: u8 = 0b0100;
+const EXPLORED_BIT: u8 = 0b1000;
+const COUNT_SHIFT: u8 = 4;
+/// Count that marks the real count as kept aside.
+const COUNT_ASIDE: u8 = 0b1111;
+/// Bytes written at once by [`write_bytes`].
+const WRITE_BLOCK: usize = 1 << 16;
+
+/// Byte per cell of the board, owned or mapped copy-on-write from a save file so that
+/// only the pages that are used get read.
+pub(crate) enum Bytes {
+    Owned(Vec<u8>),
+    #[cfg(not(target_arch = "wasm32"))]
+    Mapped(memmap2::MmapMut),
+}
+
+impl Bytes {
+    pub(crate) fn zeroed(size: usize) -> Self {
+        Bytes::Owned(vec![0; size])
+    }
+}
+
+impl Deref for Bytes {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        match self {
+            Bytes::Owned(bytes) => bytes,
+            #[cfg(not(target_arch = "wasm32"))]
+            Bytes::Mapped(map) => map,
+        }
+    }
+}
+
+impl DerefMut for Bytes {
+    fn deref_mut(&mut self) -> &mut [u8] {
+        match self {
+            Bytes::Owned(bytes) => bytes,
+            #[cfg(not(target_arch = "wasm32"))]
+            Bytes::Mapped(map) => map,
+        }
+    }
+}
+
+/// Clones own their bytes, changes to them never reach the save file.
+impl Clone for Bytes {
+    fn clone(&self) -> Self {
+        Bytes::Owned(self.to_vec())
+    }
+}
+
+impl Debug for Bytes {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Bytes({} cells)", self.len())
+    }
+}
+
+impl PartialEq for Bytes {
+    fn eq(&self, other: &Self) -> bool {
+        self[..] == other[..]
+    }
+}
+
+impl Eq for Bytes {}
+
+/// Signed count per cell, e.g. the mines in every cell. Sparse boards keep only the
+/// counts that are not zero.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum Counts {
+    Dense(Bytes),
+    Sparse {
+        size: usize,
+        counts: HashMap<usize, i8>,
+    },
+}
+
+impl Counts {
+    pub(crate) fn new(size: usize) -> Self {
+        Counts::Dense(Bytes::zeroed(size))
+    }
+
+    pub(crate) fn sparse(size: usize) -> Self {
+        Counts::Sparse {
+            size,
+            counts: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn from_bytes(bytes: Bytes) -> Self {
+        Counts::Dense(bytes)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            Counts::Dense(bytes) => bytes.len(),
+            Counts::Sparse { size, .. } => *size,
+        }
+    }
+
+    pub(crate) fn get(&self, index: usize) -> i8 {
+        match self {
+            Counts::Dense(bytes) => bytes[index] as i8,
+            Counts::Sparse { counts, .. } => counts.get(&index).copied().unwrap_or(0),
+        }
+    }
+
+    pub(crate) fn set(&mut self, index: usize, count: i8) {
+        match self {
+            Counts::Dense(bytes) => bytes[index] = count as u8,
+            Counts::Sparse { counts, .. } if count == 0 => {
+                counts.remove(&index);
+            }
+            Counts::Sparse { counts, .. } => {
+                counts.insert(index, count);
+            }
+        }
+    }
+
+    /// Writes a byte per cell, the way [`Counts::from_bytes`] reads them.
+    pub(crate) fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        match self {
+            Counts::Dense(bytes) => writer.write_all(bytes),
+            Counts::Sparse { .. } => write_bytes(writer, self.len(), |index| self.get(index) as u8),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum PackedCells {
+    Dense {
+        cells: Bytes,
+        large_counts: HashMap<usize, i16>,
+    },
+    Sparse(SparseCells),
+}
+
+impl PackedCells {
+    /// Unexplored cells without mines or marks.
+    pub(crate) fn new(size: usize) -> Self {
+        PackedCells::Dense {
+            cells: Bytes::zeroed(size),
+            large_counts: HashMap::new(),
+        }
+    }
+
+    /// Same cells as [`PackedCells::new`] for boards where almost every cell stays empty.
+    pub(crate) fn sparse(size: usize) -> Self {
+        PackedCells::Sparse(SparseCells {
+            size,
+            ..SparseCells::default()
+        })
+    }
+
+    /// Cells as written by [`PackedCells::write_to`] and [`PackedCells::large_counts`].
+    pub(crate) fn from_parts(cells: Bytes, large_counts: HashMap<usize, i16>) -> Self {
+        PackedCells::Dense {
+            cells,
+            large_counts,
+        }
+    }
+
+    /// Counts that do not fit into the byte of their cell.
+    pub(crate) fn large_counts(&self) -> HashMap<usize, i16> {
+        match self {
+            PackedCells::Dense { large_counts, .. } => large_counts.clone(),
+            PackedCells::Sparse(sparse) => sparse
+                .counts
+                .iter()
+                .filter(|(_, count)| !fits(**count))
+                .map(|(index, count)| (*index, *count))
+                .collect(),
+        }
+    }
+
+    /// Writes a byte per cell, counts that do not fit are left to
+    /// [`PackedCells::large_counts`].
+    pub(crate) fn write_to(&self, writer: &mut impl Write) -> io::Result<()> {
+        match self {
+            PackedCells::Dense { cells, .. } => writer.write_all(cells),
+            PackedCells::Sparse(_) => write_bytes(writer, self.len(), |index| {
+                let (flags, count) = split(self.get(index));
+                pack(flags, count)
+            }),
+        }
+    }
+
+    pub(crate) fn is_sparse(&self) -> bool {
+        matches!(self, PackedCells::Sparse(_))
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        match self {
+            PackedCells::Dense { cells, .. } => cells.len(),
+            PackedCells::Sparse(sparse) => sparse.size,
+        }
+    }
+
+    pub(crate) fn get(&self, index: usize) -> BoardCell {
+        match self {
+            PackedCells::Dense {
+                cells,
+                large_counts,
+            } => {
+                let packed = cells[index];
+                let count = match packed >> COUNT_SHIFT {
+                    COUNT_ASIDE => large_counts[&index],
+                    count => count as i16,
+                };
+                join(packed & !(COUNT_ASIDE << COUNT_SHIFT), count)
+            }
+            PackedCells::Sparse(sparse) => sparse.get(index),
+        }
+    }
+
+    pub(crate) fn set(&mut self, index: usize, cell: BoardCell) {
+        match self {
+            PackedCells::Dense {
+                cells,
+                large_counts,
+            } => {
+                let (flags, count) = split(cell);
+                if fits(count) {
+                    if cells[index] >> COUNT_SHIFT == COUNT_ASIDE {
+                        large_counts.remove(&index);
+                    }
+                } else {
+                    large_counts.insert(index, count);
+                }
+                cells[index] = pack(flags, count);
+            }
+            PackedCells::Sparse(sparse) => sparse.set(index, cell),
+        }
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = BoardCell> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+
+    /// Number of explored cells, sparse boards add up their runs instead of visiting
+    /// every cell.
+    pub(crate) fn explored(&self) -> usize {
+        match self {
+            PackedCells::Dense { .. } => self
+                .iter()
+                .filter(|cell| matches!(cell, BoardCell::Explored(_)))
+                .count(),
+            PackedCells::Sparse(sparse) => {
+                sparse.explored.iter().map(|(start, end)| end - start).sum()
+            }
+        }
+    }
+}
+
+/// Cells of a board that stays almost empty. Explored cells are kept as runs of indices,
+/// since openings on such boards are large, and only cells with a mine, a mark or a
+/// neighbouring mine are kept by index. Every other cell is unexplored and empty.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub(crate) struct SparseCells {
+    size: usize,
+    /// Start and end, exclusive, of every run of explored cells. Adjacent runs are
+    /// merged.
+    explored: BTreeMap<usize, usize>,
+    /// Mine bit and mark of unexplored cells that have either.
+    flags: HashMap<usize, u8>,
+    /// Neighbouring mines of the cells that have any.
+    counts: HashMap<usize, i16>,
+}
+
+impl SparseCells {
+    fn get(&self, index: usize) -> BoardCell {
+        let flags = if self.is_explored(index) {
+            EXPLORED_BIT
+        } else {
+            self.flags.get(&index).copied().unwrap_or(0)
+        };
+        join(flags, self.counts.get(&index).copied().unwrap_or(0))
+    }
+
+    fn set(&mut self, index: usize, cell: BoardCell) {
+        let (flags, count) = split(cell);
+        if flags & EXPLORED_BIT != 0 {
+            self.explore(index);
+        } else {
+            self.unexplore(index);
+        }
+
+        if flags == 0 || flags & EXPLORED_BIT != 0 {
+            self.flags.remove(&index);
+        } else {
+            self.flags.insert(index, flags);
+        }
+        if count == 0 {
+            self.counts.remove(&index);
+        } else {
+            self.counts.insert(index, count);
+        }
+    }
+
+    /// The run that holds the index.
+    fn run(&self, index: usize) -> Option<(usize, usize)> {
+        self.explored
+            .range(..=index)
+            .next_back()
+            .filter(|(_, end)| index < **end)
+            .map(|(start, end)| (*start, *end))
+    }
+
+    fn is_explored(&self, index: usize) -> bool {
+        self.run(index).is_some()
+    }
+
+    fn explore(&mut self, index: usize) {
+        if self.is_explored(index) {
+            return;
+        }
+
+        let mut start = index;
+        let mut end = index + 1;
+        if let Some((before, _)) = self
+            .explored
+            .range(..index)
+            .next_back()
+            .filter(|(_, end)| **end == index)
+        {
+            start = *before;
+        }
+        if let Some(after) = self.explored.remove(&end) {
+            end = after;
+        }
+        self.explored.insert(start, end);
+    }
+
+    fn unexplore(&mut self, index: usize) {
+        let Some((start, end)) = self.run(index) else {
+            return;
+        };
+
+        self.explored.remove(&start);
+        if start < index {
+            self.explored.insert(start, index);
+        }
+        if index + 1 < end {
+            self.explored.insert(index + 1, end);
+        }
+    }
+}
+
+/// Mine and explored bits and mark of the cell, and its count.
+fn split(cell: BoardCell) -> (u8, i16) {
+    let (flags, mark, count) = match cell {
+        BoardCell::Explored(neighbour_mines) => (EXPLORED_BIT, Mark::NoMark, neighbour_mines.0),
+        BoardCell::NoMine(CellInfo(mark, neighbour_mines)) => (0, mark, neighbour_mines.0),
+        BoardCell::Mine(mark) => (MINE_BIT, mark, 0),
+    };
+    let mark = match mark {
+        Mark::NoMark => 0,
+        Mark::MarkNote => 1,
+        Mark::MarkFlag => 2,
+    };
+    (flags | mark, count)
+}
+
+fn join(flags: u8, count: i16) -> BoardCell {
+    let mark = match flags & MARK_BITS {
+        0 => Mark::NoMark,
+        1 => Mark::MarkNote,
+        _ => Mark::MarkFlag,
+    };
+
+    if flags & EXPLORED_BIT != 0 {
+        BoardCell::Explored(NeighbourMines(count))
+    } else if flags & MINE_BIT != 0 {
+        BoardCell::Mine(mark)
+    } else {
+        BoardCell::NoMine(CellInfo(mark, NeighbourMines(count)))
+    }
+}
+
+/// Whether the count fits into the byte of its cell.
+fn fits(count: i16) -> bool {
+    matches!(u8::try_from(count), Ok(count) if count < COUNT_ASIDE)
+}
+
+fn pack(flags: u8, count: i16) -> u8 {
+    let count = if fits(count) {
+        count as u8
+    } else {
+        COUNT_ASIDE
+    };
+    count << COUNT_SHIFT | flags
+}
+
+/// Writes the byte of every cell a block at a time.
+fn write_bytes(writer: &mut impl Write, len: usize, byte: impl Fn(usize) -> u8) -> io::Result<()> {
+    let mut block = Vec::with_capacity(WRITE_BLOCK);
+    for start in (0..len).step_by(WRITE_BLOCK) {
+        block.clear();
+        block.extend((start..len.min(start + WRITE_BLOCK)).map(&byte));
+        writer.write_all(&block)?;
+    }
+    Ok(())
+}
+
+/// Set of cell indices, a bit per cell.
+pub(crate) struct BitSet(Vec<u64>);
+
+impl BitSet {
+    pub(crate) fn new(size: usize) -> Self {
+        BitSet(vec![0; size.div_ceil(64)])
+    }
+
+    /// Adds the index, returns whether it was not in the set yet.
+    pub(crate) fn insert(&mut self, index: usize) -> bool {
+        let bit = 1 << (index % 64);
+        let word = &mut self.0[index / 64];
+        let inserted = *word & bit == 0;
+        *word |= bit;
+        inserted
+    }
+
+    pub(crate) fn contains(&self, index: usize) -> bool {
+        self.0[index / 64] & 1 << (index % 64) != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn packed_cells_test() {
+        let cells = [
+            BoardCell::Explored(NeighbourMines(8)),
+            BoardCell::NoMine(CellInfo(Mark::MarkNote, NeighbourMines(14))),
+            BoardCell::NoMine(CellInfo(Mark::MarkFlag, NeighbourMines(-3))),
+            BoardCell::Explored(NeighbourMines(500)),
+            BoardCell::Mine(Mark::MarkFlag),
+            BoardCell::Mine(Mark::NoMark),
+        ];
+        let mut packed = PackedCells::new(cells.len());
+        assert_eq!(
+            BoardCell::NoMine(CellInfo(Mark::NoMark, NeighbourMines(0))),
+            packed.get(0)
+        );
+
+        for (index, cell) in cells.iter().enumerate() {
+            packed.set(index, *cell);
+        }
+        assert_eq!(cells.to_vec(), packed.iter().collect::<Vec<_>>());
+        assert_eq!(2, packed.large_counts().len());
+
+        // a count that fits again no longer needs to be kept aside
+        packed.set(3, BoardCell::Explored(NeighbourMines(1)));
+        assert_eq!(BoardCell::Explored(NeighbourMines(1)), packed.get(3));
+        assert_eq!(1, packed.large_counts().len());
+    }
+
+    #[test]
+    fn sparse_cells_test() {
+        let mut sparse = PackedCells::sparse(10);
+        let mut dense = PackedCells::new(10);
+        let cells = [
+            (4, BoardCell::Explored(NeighbourMines(0))),
+            (6, BoardCell::Explored(NeighbourMines(-2))),
+            (5, BoardCell::Explored(NeighbourMines(1))),
+            (0, BoardCell::Mine(Mark::MarkFlag)),
+            (
+                1,
+                BoardCell::NoMine(CellInfo(Mark::MarkNote, NeighbourMines(20))),
+            ),
+            (3, BoardCell::Explored(NeighbourMines(0))),
+        ];
+        for (index, cell) in cells {
+            sparse.set(index, cell);
+            dense.set(index, cell);
+        }
+        assert_eq!(
+            dense.iter().collect::<Vec<_>>(),
+            sparse.iter().collect::<Vec<_>>()
+        );
+        assert_eq!(4, sparse.explored());
+        assert_eq!(dense.large_counts(), sparse.large_counts());
+        let PackedCells::Sparse(cells) = &sparse else {
+            unreachable!()
+        };
+        assert_eq!(BTreeMap::from([(3, 7)]), cells.explored);
+
+        // the run splits when a cell in it is covered again
+        sparse.set(
+            5,
+            BoardCell::NoMine(CellInfo(Mark::NoMark, NeighbourMines(1))),
+        );
+        assert_eq!(3, sparse.explored());
+
+        let mut bytes = vec![];
+        sparse.write_to(&mut bytes).unwrap();
+        dense.set(
+            5,
+            BoardCell::NoMine(CellInfo(Mark::NoMark, NeighbourMines(1))),
+        );
+        let mut dense_bytes = vec![];
+        dense.write_to(&mut dense_bytes).unwrap();
+        assert_eq!(dense_bytes, bytes);
+    }
+
+    #[test]
+    fn bit_set_test() {
+        let mut set = BitSet::new(130);
+        assert!(set.insert(129));
+        assert!(set.insert(64));
+        assert!(!set.insert(129));
+        assert!(set.insert(0));
+        assert!(set.contains(64));
+        assert!(!set.contains(63));
+    }
+}
@@ -0,0 +1,199 @@
+//! Commands added by other crates. A [`CommandPlugin`] is [`register`]ed once, the
+//! parser of [`BoardCommand`] then hands it the commands it does not know itself and
+//! [`GameBoard::manipulate_cell`] lets it execute them.
+
+use std::sync::{Arc, RwLock};
+
+use crate::{BoardCommand, BoardCommandError, Coordinate, GameBoard, GameResolve};
+
+static PLUGINS: RwLock<Vec<Arc<dyn CommandPlugin>>> = RwLock::new(Vec::new());
+
+/// Command of a plugin as parsed, the plugin reads back the cell and argument it filled
+/// in when the command is executed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PluginCommand {
+    /// Index of the plugin in the registry.
+    pub(crate) plugin: usize,
+    pub cell: Option<Coordinate>,
+    pub argument: i32,
+}
+
+impl PluginCommand {
+    pub fn new(cell: Option<Coordinate>, argument: i32) -> Self {
+        PluginCommand {
+            plugin: 0,
+            cell,
+            argument,
+        }
+    }
+}
+
+pub trait CommandPlugin: Send + Sync {
+    /// Name the command is typed with, e.g. `chord`.
+    fn verb(&self) -> &'static str;
+
+    /// Applies a command parsed by this plugin to the board.
+    fn execute(&self, command: PluginCommand, game_board: &mut GameBoard) -> GameResolve;
+
+    /// Parses the typed command, none if it is not a command of this plugin. By default
+    /// the verb is typed alone, with a cell like `chord(3, 4)` or with a cell and an
+    /// argument like `chord(3, 4, 2)`.
+    fn parse(&self, value: &str) -> Option<Result<PluginCommand, BoardCommandError>> {
+        let value = value.trim().to_lowercase();
+        if value == self.verb() {
+            return Some(Ok(PluginCommand::new(None, 0)));
+        }
+        let arguments = value
+            .strip_prefix(self.verb())?
+            .trim()
+            .strip_prefix('(')?
+            .strip_suffix(')')?;
+
+        let numbers: Result<Vec<i64>, _> = arguments
+            .split(',')
+            .map(|number| number.trim().parse::<i64>())
+            .collect();
+        let numbers = match numbers {
+            Ok(numbers) if (2..=3).contains(&numbers.len()) => numbers,
+            _ => return Some(Err(BoardCommandError::MalformedCoordinate)),
+        };
+        let Some(argument) = numbers
+            .get(2)
+            .map_or(Some(0), |argument| i32::try_from(*argument).ok())
+        else {
+            return Some(Err(BoardCommandError::MalformedCoordinate));
+        };
+        Some(
+            u32::try_from(numbers[0])
+                .ok()
+                .zip(u32::try_from(numbers[1]).ok())
+                .map(|(x, y)| PluginCommand::new(Some(Coordinate(x, y)), argument))
+                .ok_or(BoardCommandError::OutsideBoard),
+        )
+    }
+
+    /// The command as typed, read back by [`parse`](Self::parse) when a replay is
+    /// loaded.
+    fn format(&self, command: PluginCommand) -> String {
+        match (command.cell, command.argument) {
+            (None, _) => self.verb().to_string(),
+            (Some(Coordinate(x, y)), 0) => format!("{}({}, {})", self.verb(), x, y),
+            (Some(Coordinate(x, y)), argument) => {
+                format!("{}({}, {}, {})", self.verb(), x, y, argument)
+            }
+        }
+    }
+}
+
+/// Adds the plugin to the parser, it replaces a plugin of the same verb.
+pub fn register(plugin: impl CommandPlugin + 'static) {
+    let mut plugins = PLUGINS.write().expect("Plugin lock is not poisoned.");
+    let plugin: Arc<dyn CommandPlugin> = Arc::new(plugin);
+    match plugins
+        .iter()
+        .position(|known| known.verb() == plugin.verb())
+    {
+        Some(index) => plugins[index] = plugin,
+        None => plugins.push(plugin),
+    }
+}
+
+fn plugin(command: PluginCommand) -> Option<Arc<dyn CommandPlugin>> {
+    PLUGINS
+        .read()
+        .expect("Plugin lock is not poisoned.")
+        .get(command.plugin)
+        .cloned()
+}
+
+/// The command of the first plugin that knows it, none if no plugin does.
+pub(crate) fn parse(value: &str) -> Option<Result<BoardCommand, BoardCommandError>> {
+    let plugins = PLUGINS
+        .read()
+        .expect("Plugin lock is not poisoned.")
+        .clone();
+    plugins.iter().enumerate().find_map(|(index, plugin)| {
+        let command = plugin.parse(value)?;
+        Some(command.map(|command| {
+            BoardCommand::Plugin(PluginCommand {
+                plugin: index,
+                ..command
+            })
+        }))
+    })
+}
+
+pub(crate) fn verb(command: PluginCommand) -> &'static str {
+    plugin(command).map_or("plugin", |plugin| plugin.verb())
+}
+
+pub(crate) fn format(command: PluginCommand) -> String {
+    plugin(command).map_or_else(|| "plugin".to_string(), |plugin| plugin.format(command))
+}
+
+/// Lets the plugin of the command execute it, commands of unknown plugins do nothing.
+pub(crate) fn execute(command: PluginCommand, game_board: &mut GameBoard) -> GameResolve {
+    match plugin(command) {
+        Some(plugin) => plugin.execute(command, game_board),
+        None => GameResolve::Continue,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layout::Layout, BoardCell, CellInfo, Mark};
+
+    /// Explores the neighbours of an explored cell that are not flagged.
+    struct Chord;
+
+    impl CommandPlugin for Chord {
+        fn verb(&self) -> &'static str {
+            "chord"
+        }
+
+        fn execute(&self, command: PluginCommand, game_board: &mut GameBoard) -> GameResolve {
+            let Some(cell) = command.cell else {
+                return GameResolve::Continue;
+            };
+            for neighbour in game_board.neighbours(cell) {
+                let hidden = match game_board.get_cell_at(neighbour) {
+                    BoardCell::Explored(_) => false,
+                    BoardCell::Mine(mark) | BoardCell::NoMine(CellInfo(mark, _)) => {
+                        mark != Mark::MarkFlag
+                    }
+                };
+                if hidden {
+                    let resolve = game_board.manipulate_cell(BoardCommand::Explore(neighbour));
+                    if resolve != GameResolve::Continue {
+                        return resolve;
+                    }
+                }
+            }
+            GameResolve::Continue
+        }
+    }
+
+    #[test]
+    fn plugin_command_test() {
+        assert_eq!(
+            Err(BoardCommandError::NotFound),
+            BoardCommand::try_from("chord(1, 1)")
+        );
+        register(Chord);
+
+        let command = BoardCommand::try_from("chord(1, 1)").unwrap();
+        assert_eq!("chord", command.verb());
+        assert_eq!("chord(1, 1)", command.to_string());
+        assert_eq!(Some(Coordinate(1, 1)), command.cell());
+        assert_eq!(
+            Err(BoardCommandError::MalformedCoordinate),
+            BoardCommand::try_from("chord(1)")
+        );
+
+        // the mine next to the cell is not flagged
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*##\n###\n...\n").unwrap());
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 1)));
+        assert_eq!(GameResolve::MineHit, game_board.manipulate_cell(command));
+    }
+}
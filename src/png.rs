@@ -0,0 +1,92 @@
+//! The visible board as a PNG image in the classic look, so a position can be shared
+//! without spoiling it.
+
+use std::io::{self, Write};
+
+use flate2::{write::ZlibEncoder, Compression};
+
+use crate::{
+    tiles::{self, Canvas},
+    GameBoard,
+};
+
+/// The board as it is shown, encoded as a PNG file.
+pub fn render(game_board: &GameBoard) -> Vec<u8> {
+    encode(&tiles::draw(game_board)).expect("Encoding into memory does not fail.")
+}
+
+/// 8 bit RGB image without interlacing, every scanline unfiltered.
+fn encode(canvas: &Canvas) -> io::Result<Vec<u8>> {
+    let mut header = vec![];
+    header.extend_from_slice(&(canvas.width as u32).to_be_bytes());
+    header.extend_from_slice(&(canvas.height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+    let mut encoder = ZlibEncoder::new(vec![], Compression::default());
+    for scanline in canvas.pixels.chunks(canvas.width * 3) {
+        encoder.write_all(&[0])?;
+        encoder.write_all(scanline)?;
+    }
+    let data = encoder.finish()?;
+
+    let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+    chunk(&mut png, b"IHDR", &header);
+    chunk(&mut png, b"IDAT", &data);
+    chunk(&mut png, b"IEND", &[]);
+    Ok(png)
+}
+
+fn chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    let mut hasher = crc32fast::Hasher::new();
+    hasher.update(kind);
+    hasher.update(data);
+    png.extend_from_slice(&hasher.finalize().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use flate2::read::ZlibDecoder;
+
+    use super::*;
+    use crate::{
+        layout::Layout,
+        tiles::{Rgb, LIGHT, NUMBER_COLOURS, SHADOW},
+        BoardCommand, Coordinate,
+    };
+
+    fn pixel(pixels: &[u8], width: usize, x: usize, y: usize) -> Rgb {
+        // every scanline starts with its filter type
+        let offset = y * (width * 3 + 1) + 1 + x * 3;
+        [pixels[offset], pixels[offset + 1], pixels[offset + 2]]
+    }
+
+    #[test]
+    fn render_test() {
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*##\n###\n").unwrap());
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 2)));
+        let png = render(&game_board);
+
+        assert_eq!(b"\x89PNG\r\n\x1a\n", &png[..8]);
+        assert_eq!(&png[12..16], b"IHDR");
+        assert_eq!(48u32.to_be_bytes(), png[16..20]);
+        assert_eq!(32u32.to_be_bytes(), png[20..24]);
+        assert!(png.ends_with(&[0, 0, 0, 0, b'I', b'E', b'N', b'D', 0xae, 0x42, 0x60, 0x82]));
+
+        let length = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        assert_eq!(&png[37..41], b"IDAT");
+        let mut pixels = vec![];
+        ZlibDecoder::new(&png[41..41 + length])
+            .read_to_end(&mut pixels)
+            .unwrap();
+        assert_eq!(32 * (48 * 3 + 1), pixels.len());
+        // the covered mine is raised, the explored one sunken with a blue one in its middle
+        assert_eq!(LIGHT, pixel(&pixels, 48, 0, 0));
+        assert_eq!(SHADOW, pixel(&pixels, 48, 16, 16));
+        assert_eq!(NUMBER_COLOURS[0], pixel(&pixels, 48, 24, 24));
+    }
+}
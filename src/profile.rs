@@ -0,0 +1,31 @@
+//! Player progress that is kept across sessions.
+
+use std::{collections::BTreeSet, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{achievements::Achievements, adaptive::Rating, storage};
+
+pub(crate) const PROFILE_FILE: &str = "profile.json";
+
+#[derive(Serialize, Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Profile {
+    /// Number of campaign levels completed, the next one is unlocked.
+    pub campaign_completed: usize,
+    /// Names of the bundled puzzles solved.
+    pub puzzles_solved: BTreeSet<String>,
+    pub achievements: Achievements,
+    /// Density of the next board of the adaptive mode.
+    pub rating: Rating,
+}
+
+impl Profile {
+    pub fn load() -> Self {
+        storage::load_json(PROFILE_FILE)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::save_json(PROFILE_FILE, self)
+    }
+}
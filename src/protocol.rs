@@ -0,0 +1,370 @@
+//! Structured game protocol for programs driving the engine, shared by the WebSocket server
+//! and the json mode on stdin/stdout. Responses are tagged by `type`:
+//!
+//! - `{"type": "board", "seed": 42, "status": "playing", "view": {...}}` with `status`
+//!   `playing`, `won`, `lost` or `quit` and `view` holding the `width`, `height` and the
+//!   `cells` as rows of `{"state": "hidden"}`, `{"state": "flag"}`, `{"state": "note"}`,
+//!   `{"state": "explored", "mines": 2}` with negative counts next to anti-mines,
+//!   `{"state": "void"}` for holes in the shape of the board or, once the game is lost,
+//!   `{"state": "mine"}`.
+//! - `{"type": "error", "message": "..."}` when a request could not be applied.
+//!
+//! In the json mode every line in is a command tagged by `cmd`, coordinates are `x` for the
+//! row and `y` for the column like in the terminal notation:
+//!
+//! - `{"cmd": "new_game", "width": 9, "height": 9, "mines": 10}`, with an optional `seed`,
+//!   `wrap_edges`, `adjacency` of `standard`, `knight` or `extended`, `mines_per_cell`
+//!   `anti_mines` and `distribution` of `uniform`, `clustered`, `anti_clustered` or
+//!   `edge_weighted` and `symmetry` of `asymmetric`, `mirror` or `rotational`.
+//! - `{"cmd": "flag", "x": 3, "y": 4}`, with an optional `count` of suspected mines,
+//!   -1 for an anti-mine.
+//! - `{"cmd": "explore", "x": 3, "y": 4}`, likewise `note` and `clear`.
+//! - `{"cmd": "board"}` to query the board, `{"cmd": "quit"}` to give up.
+//!
+//! `proto/minesweeper.proto` describes the same protocol as a gRPC service, served by the
+//! `grpc` module behind the `grpc` feature.
+
+use std::io::{self, BufRead, Write};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    generator::{Distribution, Symmetry},
+    Adjacency, BoardCell, BoardCommand, CellInfo, Coordinate, GameBoard, GameConfiguration,
+    GameResolve, Mark, NeighbourMines, MAX_CELLS, MAX_MINES_PER_CELL, MAX_SIDE,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    Board {
+        seed: u64,
+        status: Status,
+        view: BoardView,
+    },
+    Error {
+        message: String,
+    },
+}
+
+impl Response {
+    pub fn board(game_board: &GameBoard, status: Status) -> Response {
+        Response::Board {
+            seed: game_board.seed(),
+            status,
+            view: BoardView::new(game_board, status),
+        }
+    }
+
+    pub fn error(message: &str) -> Response {
+        Response::Error {
+            message: message.to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Status {
+    Playing,
+    Won,
+    Lost,
+    Quit,
+}
+
+impl From<GameResolve> for Status {
+    fn from(resolve: GameResolve) -> Self {
+        match resolve {
+            GameResolve::Continue => Status::Playing,
+            GameResolve::AllMinesDiscovered => Status::Won,
+            GameResolve::MineHit => Status::Lost,
+            GameResolve::Quit => Status::Quit,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum CellView {
+    Hidden,
+    Flag,
+    Note,
+    Mine,
+    Explored {
+        mines: i16,
+    },
+    /// Hole in the shape of the board.
+    Void,
+}
+
+impl CellView {
+    /// What the player may see of the cell, mines are only shown once the game is lost.
+    pub fn new(cell: BoardCell, status: Status) -> CellView {
+        match cell {
+            BoardCell::Explored(NeighbourMines(mines)) => CellView::Explored { mines },
+            BoardCell::Mine(_) if status == Status::Lost => CellView::Mine,
+            BoardCell::Mine(mark) | BoardCell::NoMine(CellInfo(mark, _)) => match mark {
+                Mark::NoMark => CellView::Hidden,
+                Mark::MarkFlag => CellView::Flag,
+                Mark::MarkNote => CellView::Note,
+            },
+        }
+    }
+
+    /// What the player may see of the cell at the coordinate, including holes and the
+    /// mines if the board shows them for debugging.
+    pub fn at(game_board: &GameBoard, coordinate: Coordinate, status: Status) -> CellView {
+        if game_board.is_hole(coordinate) {
+            CellView::Void
+        } else if game_board.shows_mines()
+            && game_board.get_cell_at(coordinate) == BoardCell::Mine(Mark::NoMark)
+        {
+            CellView::Mine
+        } else {
+            CellView::new(game_board.get_cell_at(coordinate), status)
+        }
+    }
+}
+
+/// What the player may see of the board, mines are only shown once the game is lost.
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+pub struct BoardView {
+    pub width: u32,
+    pub height: u32,
+    pub cells: Vec<Vec<CellView>>,
+}
+
+impl BoardView {
+    pub fn new(game_board: &GameBoard, status: Status) -> BoardView {
+        let (width, height) = game_board.get_dimensions();
+        let cells = (0..height)
+            .map(|row| {
+                (0..width)
+                    .map(|col| CellView::at(game_board, Coordinate(row, col), status))
+                    .collect()
+            })
+            .collect();
+
+        BoardView {
+            width,
+            height,
+            cells,
+        }
+    }
+}
+
+/// Command of the json mode, one per line.
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(tag = "cmd", rename_all = "snake_case")]
+pub enum JsonCommand {
+    NewGame {
+        width: u32,
+        height: u32,
+        mines: u32,
+        #[serde(default)]
+        seed: Option<u64>,
+        #[serde(default)]
+        wrap_edges: bool,
+        #[serde(default)]
+        adjacency: Adjacency,
+        #[serde(default)]
+        mines_per_cell: Option<u8>,
+        #[serde(default)]
+        anti_mines: u32,
+        #[serde(default)]
+        distribution: Distribution,
+        #[serde(default)]
+        symmetry: Symmetry,
+    },
+    Explore {
+        x: u32,
+        y: u32,
+    },
+    Flag {
+        x: u32,
+        y: u32,
+        /// Number of mines the cell is suspected to hold.
+        #[serde(default)]
+        count: Option<i8>,
+    },
+    Note {
+        x: u32,
+        y: u32,
+    },
+    Clear {
+        x: u32,
+        y: u32,
+    },
+    Board,
+    Quit,
+}
+
+/// Cells of the largest board a client over the network may start, every response carries
+/// the whole board.
+pub const MAX_NETWORK_CELLS: u64 = 10_000;
+
+/// Game of one client, turns requests into responses.
+pub struct Session {
+    game: Option<(GameBoard, Status)>,
+    max_cells: u64,
+}
+
+impl Default for Session {
+    fn default() -> Self {
+        Session::with_max_cells(MAX_CELLS)
+    }
+}
+
+impl Session {
+    /// Session starting boards of at most `max_cells` cells.
+    pub fn with_max_cells(max_cells: u64) -> Self {
+        Session {
+            game: None,
+            max_cells: max_cells.min(MAX_CELLS),
+        }
+    }
+
+    pub fn new_game(&mut self, configuration: GameConfiguration, seed: Option<u64>) -> Response {
+        if !(1..=MAX_MINES_PER_CELL).contains(&configuration.mines_per_cell()) {
+            return Response::error(&format!(
+                "a cell may hold 1 to {} mines",
+                MAX_MINES_PER_CELL
+            ));
+        }
+        let (width, height) = (configuration.w(), configuration.h());
+        if !configuration.within_limits() || width as u64 * height as u64 > self.max_cells {
+            return Response::error(&format!(
+                "the board must be 1 to {} cells wide and high with at most {} cells",
+                MAX_SIDE, self.max_cells
+            ));
+        }
+        if !configuration.fits() {
+            return Response::error("there are more mines than the board can hold");
+        }
+
+        let mut game_board = GameBoard::new(configuration);
+        match seed {
+            Some(seed) => game_board.generate_world_from_seed(seed),
+            None => game_board.generate_world(&mut rand::thread_rng()),
+        }
+        self.game = Some((game_board, Status::Playing));
+        self.board()
+    }
+
+    pub fn apply(&mut self, command: BoardCommand) -> Response {
+        let Some((game_board, status)) = self.game.as_mut() else {
+            return Response::error("no game started, send new_game first");
+        };
+        if *status != Status::Playing {
+            return Response::error("the game is over, send new_game to play again");
+        }
+        if !game_board.is_on_board(command) {
+            return Response::error("coordinate is outside of the board");
+        }
+
+        *status = game_board.manipulate_cell(command).into();
+        self.board()
+    }
+
+    /// Status of the game, none before the first was started.
+    pub fn status(&self) -> Option<Status> {
+        self.game.as_ref().map(|(_, status)| *status)
+    }
+
+    pub fn board(&self) -> Response {
+        match &self.game {
+            Some((game_board, status)) => Response::board(game_board, *status),
+            None => Response::error("no game started, send new_game first"),
+        }
+    }
+
+    pub fn handle_json(&mut self, command: JsonCommand) -> Response {
+        match command {
+            JsonCommand::NewGame {
+                width,
+                height,
+                mines,
+                seed,
+                wrap_edges,
+                adjacency,
+                mines_per_cell,
+                anti_mines,
+                distribution,
+                symmetry,
+            } => self.new_game(
+                GameConfiguration::new(width, height, mines)
+                    .with_wrap_edges(wrap_edges)
+                    .with_adjacency(adjacency)
+                    .with_mines_per_cell(mines_per_cell.unwrap_or(1))
+                    .with_anti_mines(anti_mines)
+                    .with_distribution(distribution)
+                    .with_symmetry(symmetry),
+                seed,
+            ),
+            JsonCommand::Explore { x, y } => self.apply(BoardCommand::Explore(Coordinate(x, y))),
+            JsonCommand::Flag { x, y, count } => self.apply(match count {
+                Some(count) => BoardCommand::SetMarkFlagCount(Coordinate(x, y), count),
+                None => BoardCommand::SetMarkFlag(Coordinate(x, y)),
+            }),
+            JsonCommand::Note { x, y } => self.apply(BoardCommand::SetMarkNote(Coordinate(x, y))),
+            JsonCommand::Clear { x, y } => self.apply(BoardCommand::ClearMark(Coordinate(x, y))),
+            JsonCommand::Board => self.board(),
+            JsonCommand::Quit => self.apply(BoardCommand::Quit),
+        }
+    }
+}
+
+/// Runs the json mode until the input is closed, answering every line with one line.
+pub fn serve_lines<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut session = Session::default();
+    for line in input.lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        let response = match serde_json::from_str(&line) {
+            Ok(command) => session.handle_json(command),
+            Err(err) => Response::error(&format!("malformed command, {}", err)),
+        };
+        serde_json::to_writer(&mut output, &response)?;
+        writeln!(output)?;
+        output.flush()?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn serve_lines_test() {
+        let input = concat!(
+            "{\"cmd\":\"explore\",\"x\":0,\"y\":0}\n",
+            "{\"cmd\":\"new_game\",\"width\":4,\"height\":3,\"mines\":0,\"seed\":1}\n",
+            "{\"cmd\":\"explore\",\"x\":3,\"y\":4}\n",
+            "nonsense\n",
+        );
+        let mut output = Vec::new();
+        serve_lines(input.as_bytes(), &mut output).unwrap();
+
+        let responses: Vec<Response> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(4, responses.len());
+        assert!(matches!(responses[0], Response::Error { .. }));
+        let Response::Board { view, status, .. } = &responses[1] else {
+            panic!("new game should respond with the board");
+        };
+        assert_eq!(Status::Playing, *status);
+        assert_eq!((4, 3), (view.width, view.height));
+        assert_eq!(
+            Response::error("coordinate is outside of the board"),
+            responses[2]
+        );
+        assert!(matches!(responses[3], Response::Error { .. }));
+    }
+}
@@ -0,0 +1,76 @@
+//! Bundled puzzle pack, hand picked layouts that can be solved from their opening without
+//! guessing. The layout files live in `puzzles/` and are embedded into the binary.
+
+use crate::layout::Layout;
+
+pub struct Puzzle {
+    pub name: &'static str,
+    /// Text of the layout file.
+    pub layout: &'static str,
+}
+
+impl Puzzle {
+    pub fn layout(&self) -> Layout {
+        Layout::try_from(self.layout).expect("bundled puzzles are valid layouts")
+    }
+
+    pub fn description(&self) -> String {
+        let layout = self.layout();
+        format!(
+            "{} - {}x{} with {} mines",
+            self.name,
+            layout.width(),
+            layout.height(),
+            layout.mines()
+        )
+    }
+}
+
+pub const PUZZLES: [Puzzle; 6] = [
+    Puzzle {
+        name: "First light",
+        layout: include_str!("../puzzles/first-light.txt"),
+    },
+    Puzzle {
+        name: "Corner case",
+        layout: include_str!("../puzzles/corner-case.txt"),
+    },
+    Puzzle {
+        name: "Narrow pass",
+        layout: include_str!("../puzzles/narrow-pass.txt"),
+    },
+    Puzzle {
+        name: "Scattered",
+        layout: include_str!("../puzzles/scattered.txt"),
+    },
+    Puzzle {
+        name: "Deep field",
+        layout: include_str!("../puzzles/deep-field.txt"),
+    },
+    Puzzle {
+        name: "Long haul",
+        layout: include_str!("../puzzles/long-haul.txt"),
+    },
+];
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{replay::Replay, GameBoard, GameResolve};
+
+    #[test]
+    fn bundled_puzzles_test() {
+        for puzzle in &PUZZLES {
+            let layout = Layout::try_from(puzzle.layout);
+            assert!(layout.is_ok(), "{} is no valid layout", puzzle.name);
+
+            // every puzzle starts with an opening and replays on its own layout
+            let game_board = GameBoard::from_layout(layout.unwrap());
+            assert!(game_board.explored_cells() > 0);
+            assert_eq!(
+                Ok(GameResolve::Continue),
+                Replay::of(&game_board).simulate()
+            );
+        }
+    }
+}
@@ -0,0 +1,195 @@
+//! Versus race over the network. Both players get an identical seeded board, every move
+//! reports the progress to the opponent. The first to clear the board wins, hitting a
+//! mine or quitting hands the win to the opponent.
+
+use std::{
+    io,
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    sync::mpsc::{self, Receiver, Sender},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    multiplayer::{self, MessageReader},
+    BoardCommand, GameBoard, GameConfiguration,
+};
+
+const PROGRESS_BAR_WIDTH: usize = 20;
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RaceMessage {
+    Start {
+        configuration: GameConfiguration,
+        seed: u64,
+    },
+    Progress {
+        percent: u8,
+    },
+    Finished {
+        won: bool,
+    },
+}
+
+/// Something the racing player has to act on, local commands are sent through
+/// [`Race::sender`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RaceEvent {
+    Command(BoardCommand),
+    OpponentProgress(u8),
+    OpponentFinished { won: bool },
+    OpponentLeft,
+}
+
+pub struct Race {
+    writer: TcpStream,
+    sender: Sender<RaceEvent>,
+    events: Receiver<RaceEvent>,
+    opponent_percent: u8,
+}
+
+impl Race {
+    /// Waits for an opponent on the listener and starts the race on the board.
+    pub fn host(
+        listener: &TcpListener,
+        configuration: GameConfiguration,
+        seed: u64,
+    ) -> io::Result<Race> {
+        let (mut stream, _) = listener.accept()?;
+        multiplayer::send(
+            &mut stream,
+            &RaceMessage::Start {
+                configuration,
+                seed,
+            },
+        )?;
+        let reader = MessageReader::new(stream.try_clone()?);
+        Ok(Race::start(stream, reader))
+    }
+
+    /// Joins the race hosted at the address, returns the board to race on.
+    pub fn join<A: ToSocketAddrs>(address: A) -> io::Result<(Race, GameConfiguration, u64)> {
+        let stream = TcpStream::connect(address)?;
+        let mut reader = MessageReader::new(stream.try_clone()?);
+        match reader.receive()? {
+            Some(RaceMessage::Start {
+                configuration,
+                seed,
+            }) => Ok((Race::start(stream, reader), configuration, seed)),
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                "host did not start the race",
+            )),
+        }
+    }
+
+    fn start(writer: TcpStream, mut reader: MessageReader) -> Race {
+        let (sender, events) = mpsc::channel();
+
+        let opponent_sender = sender.clone();
+        thread::spawn(move || loop {
+            let event = match reader.receive() {
+                Ok(Some(RaceMessage::Progress { percent })) => RaceEvent::OpponentProgress(percent),
+                Ok(Some(RaceMessage::Finished { won })) => RaceEvent::OpponentFinished { won },
+                Ok(Some(RaceMessage::Start { .. })) => continue,
+                Ok(None) | Err(_) => RaceEvent::OpponentLeft,
+            };
+            let left = event == RaceEvent::OpponentLeft;
+            if opponent_sender.send(event).is_err() || left {
+                break;
+            }
+        });
+
+        Race {
+            writer,
+            sender,
+            events,
+            opponent_percent: 0,
+        }
+    }
+
+    pub fn sender(&self) -> Sender<RaceEvent> {
+        self.sender.clone()
+    }
+
+    /// Blocks until the local player or the opponent does something.
+    pub fn next_event(&mut self) -> RaceEvent {
+        let event = self
+            .events
+            .recv()
+            .expect("Race keeps a sender, the channel stays open.");
+        if let RaceEvent::OpponentProgress(percent) = event {
+            self.opponent_percent = percent;
+        }
+        event
+    }
+
+    pub fn report_progress(&mut self, percent: u8) -> io::Result<()> {
+        multiplayer::send(&mut self.writer, &RaceMessage::Progress { percent })
+    }
+
+    pub fn report_finished(&mut self, won: bool) -> io::Result<()> {
+        multiplayer::send(&mut self.writer, &RaceMessage::Finished { won })
+    }
+
+    pub fn status_line(&self, own_percent: u8) -> String {
+        format!(
+            "You {} Opponent {}",
+            progress_bar(own_percent),
+            progress_bar(self.opponent_percent)
+        )
+    }
+}
+
+/// Share of the board's 3BV that is solved.
+pub fn progress(game_board: &GameBoard) -> u8 {
+    let (solved, total) = game_board.three_bv();
+    if total == 0 {
+        return 100;
+    }
+    (solved * 100 / total) as u8
+}
+
+fn progress_bar(percent: u8) -> String {
+    let filled = PROGRESS_BAR_WIDTH * percent.min(100) as usize / 100;
+    format!(
+        "[{}{}] {:>3}%",
+        "#".repeat(filled),
+        ".".repeat(PROGRESS_BAR_WIDTH - filled),
+        percent
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::Shutdown;
+
+    use super::*;
+
+    #[test]
+    fn progress_is_relayed_test() {
+        let configuration = GameConfiguration::beginner();
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        let joining = thread::spawn(move || Race::join(address).unwrap());
+        let mut host = Race::host(&listener, configuration, 7).unwrap();
+        let (mut guest, joined_configuration, seed) = joining.join().unwrap();
+        assert_eq!((configuration, 7), (joined_configuration, seed));
+
+        guest.report_progress(50).unwrap();
+        assert_eq!(RaceEvent::OpponentProgress(50), host.next_event());
+        assert_eq!(
+            "You [....................]   0% Opponent [##########..........]  50%",
+            host.status_line(0)
+        );
+
+        guest.report_finished(true).unwrap();
+        assert_eq!(RaceEvent::OpponentFinished { won: true }, host.next_event());
+
+        guest.writer.shutdown(Shutdown::Both).unwrap();
+        assert_eq!(RaceEvent::OpponentLeft, host.next_event());
+    }
+}
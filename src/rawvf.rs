@@ -0,0 +1,317 @@
+//! Imports games recorded by other Minesweeper clones from RAWVF, the text format the
+//! community's video tools share. Minesweeper Arbiter's AVF and Viennasweeper's RMV videos
+//! are binary formats without a published layout and are not read here, their converters
+//! `avf2rawvf` and `rmv2rawvf` turn them into RAWVF. An RMV video is recognized by its
+//! signature so the error names the converter it needs.
+//!
+//! A RAWVF file lists the board and every mouse event, e.g.
+//!
+//! ```text
+//! Width: 8
+//! Height: 8
+//! Mines: 10
+//! Board:
+//! 0000*000
+//! ...
+//! Events:
+//! 0.00 lc 3 4 (40 56)
+//! 0.11 lr 3 4 (40 56)
+//! ```
+//!
+//! with columns and rows counted from one. The clicks are replayed on the board and turned
+//! into the moves they made, a chord into exploring the neighbours it opened.
+
+use std::{fmt::Display, time::Duration};
+
+use crate::{
+    layout::Layout,
+    protocol::{CellView, Status},
+    replay::Replay,
+    BoardCommand, Coordinate, GameBoard, GameConfiguration, GameResolve,
+};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RawvfError {
+    /// A Viennasweeper video, which has to be converted with `rmv2rawvf` first.
+    Rmv,
+    /// Another binary video, usually Minesweeper Arbiter's, to be converted first.
+    Binary,
+    MissingField(&'static str),
+    MalformedBoard,
+    MalformedEvent(usize),
+}
+
+impl Display for RawvfError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RawvfError::Rmv => write!(
+                f,
+                "the file is an RMV video, convert it to RAWVF with rmv2rawvf first"
+            ),
+            RawvfError::Binary => write!(
+                f,
+                "the file is not RAWVF, convert AVF videos with avf2rawvf first"
+            ),
+            RawvfError::MissingField(field) => write!(f, "the {} field is missing", field),
+            RawvfError::MalformedBoard => write!(f, "the board is malformed"),
+            RawvfError::MalformedEvent(line) => {
+                write!(f, "the event in line {} is malformed", line)
+            }
+        }
+    }
+}
+
+/// Mouse buttons held down while the events are replayed.
+#[derive(Default)]
+struct Buttons {
+    left: bool,
+    right: bool,
+    /// Both buttons are down, the next release chords.
+    chording: bool,
+    /// A chord was released while the other button is still down, whose release does
+    /// nothing.
+    released_chord: bool,
+}
+
+/// The game of the RAWVF file as a replay of the board it was played on.
+pub fn import(bytes: &[u8]) -> Result<Replay, RawvfError> {
+    if bytes.starts_with(b"*rmv") {
+        return Err(RawvfError::Rmv);
+    }
+    let text = std::str::from_utf8(bytes).map_err(|_| RawvfError::Binary)?;
+    let field = |name: &'static str| {
+        text.lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix(':'))
+            .map(str::trim)
+    };
+    let level = field("Level").unwrap_or_default().to_lowercase();
+    let standard = match level.as_str() {
+        "beginner" => Some(GameConfiguration::beginner()),
+        "intermediate" => Some(GameConfiguration::intermediate()),
+        "expert" => Some(GameConfiguration::expert()),
+        _ => None,
+    };
+    let number = |name: &'static str, standard: Option<u32>| {
+        field(name)
+            .and_then(|value| value.parse::<u32>().ok())
+            .or(standard)
+            .ok_or(RawvfError::MissingField(name))
+    };
+    let width = number("Width", standard.map(|configuration| configuration.w()))?;
+    let height = number("Height", standard.map(|configuration| configuration.h()))?;
+    let marks = field("Marks").is_some_and(|marks| marks.eq_ignore_ascii_case("on"));
+
+    let mut lines = text.lines().enumerate();
+    lines
+        .find(|(_, line)| line.trim() == "Board:")
+        .ok_or(RawvfError::MissingField("Board"))?;
+    let mut rows = String::new();
+    for _ in 0..height {
+        let (_, row) = lines.next().ok_or(RawvfError::MalformedBoard)?;
+        let row = row.trim();
+        if row.chars().count() != width as usize {
+            return Err(RawvfError::MalformedBoard);
+        }
+        for symbol in row.chars() {
+            rows.push(match symbol {
+                '*' => '*',
+                '0' => '#',
+                _ => return Err(RawvfError::MalformedBoard),
+            });
+        }
+        rows.push('\n');
+    }
+    let layout = Layout::try_from(rows.as_str()).map_err(|_| RawvfError::MalformedBoard)?;
+    if !GameConfiguration::new(width, height, 0).fits() {
+        return Err(RawvfError::MalformedBoard);
+    }
+    let mut game_board = GameBoard::from_layout(layout);
+    let mut replay = Replay::of(&game_board);
+
+    lines
+        .find(|(_, line)| line.trim() == "Events:")
+        .ok_or(RawvfError::MissingField("Events"))?;
+    let mut buttons = Buttons::default();
+    for (index, line) in lines {
+        let mut words = line.split_whitespace();
+        let (Some(time), Some(event)) = (words.next(), words.next()) else {
+            continue;
+        };
+        let time: f64 = time
+            .parse()
+            .map_err(|_| RawvfError::MalformedEvent(index + 1))?;
+        let elapsed = Duration::from_secs_f64(time.max(0.0));
+        if !matches!(event, "lc" | "lr" | "rc" | "rr" | "mc" | "mr") {
+            continue;
+        }
+        let mut position = words.map(str::parse::<u32>);
+        let cell = match (position.next(), position.next()) {
+            (Some(Ok(col @ 1..)), Some(Ok(row @ 1..))) if col <= width && row <= height => {
+                Some(Coordinate(row - 1, col - 1))
+            }
+            // clicks beside the board
+            (Some(Ok(_)), Some(Ok(_))) => None,
+            _ => return Err(RawvfError::MalformedEvent(index + 1)),
+        };
+
+        let commands = match event {
+            "lc" => {
+                buttons.left = true;
+                buttons.chording |= buttons.right;
+                vec![]
+            }
+            "rc" => {
+                buttons.right = true;
+                buttons.chording |= buttons.left;
+                match cell {
+                    Some(cell) if !buttons.chording => toggle_mark(&game_board, cell, marks),
+                    _ => vec![],
+                }
+            }
+            "lr" | "rr" => {
+                let chord = buttons.chording;
+                let explore = event == "lr" && !buttons.right && !buttons.released_chord;
+                if event == "lr" {
+                    buttons.left = false;
+                } else {
+                    buttons.right = false;
+                }
+                buttons.chording = false;
+                buttons.released_chord =
+                    (buttons.released_chord || chord) && (buttons.left || buttons.right);
+                match cell {
+                    Some(cell) if chord => chord_cells(&game_board, cell),
+                    Some(cell) if explore => explore_cell(&game_board, cell),
+                    _ => vec![],
+                }
+            }
+            "mr" => cell.map_or(vec![], |cell| chord_cells(&game_board, cell)),
+            _ => vec![],
+        };
+        for command in commands {
+            replay.record(command, elapsed);
+            if game_board.manipulate_cell(command) != GameResolve::Continue {
+                return Ok(replay);
+            }
+        }
+    }
+    Ok(replay)
+}
+
+fn explore_cell(game_board: &GameBoard, cell: Coordinate) -> Vec<BoardCommand> {
+    match CellView::at(game_board, cell, Status::Playing) {
+        CellView::Hidden | CellView::Note => vec![BoardCommand::Explore(cell)],
+        _ => vec![],
+    }
+}
+
+/// A right click cycles through a flag, a question mark if marks are on, and no mark.
+fn toggle_mark(game_board: &GameBoard, cell: Coordinate, marks: bool) -> Vec<BoardCommand> {
+    match CellView::at(game_board, cell, Status::Playing) {
+        CellView::Hidden => vec![BoardCommand::SetMarkFlag(cell)],
+        CellView::Flag if marks => vec![BoardCommand::SetMarkNote(cell)],
+        CellView::Flag | CellView::Note => vec![BoardCommand::ClearMark(cell)],
+        _ => vec![],
+    }
+}
+
+/// Explores the covered neighbours of a number whose flags are all placed.
+fn chord_cells(game_board: &GameBoard, cell: Coordinate) -> Vec<BoardCommand> {
+    let CellView::Explored { mines } = CellView::at(game_board, cell, Status::Playing) else {
+        return vec![];
+    };
+    let (width, height) = game_board.get_dimensions();
+    let neighbours: Vec<Coordinate> = (cell.0.saturating_sub(1)..=(cell.0 + 1).min(height - 1))
+        .flat_map(|row| {
+            (cell.1.saturating_sub(1)..=(cell.1 + 1).min(width - 1))
+                .map(move |col| Coordinate(row, col))
+        })
+        .filter(|neighbour| *neighbour != cell)
+        .collect();
+    let flags = neighbours
+        .iter()
+        .filter(|neighbour| {
+            CellView::at(game_board, **neighbour, Status::Playing) == CellView::Flag
+        })
+        .count();
+    if mines <= 0 || flags != mines as usize {
+        return vec![];
+    }
+    neighbours
+        .into_iter()
+        .flat_map(|neighbour| explore_cell(game_board, neighbour))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const VIDEO: &str = "RawVF_Version: Rev5
+Program: Minesweeper Arbiter
+Level: Beginner
+Width: 4
+Height: 3
+Mines: 2
+Marks: Off
+Board:
+*000
+000*
+0000
+Events:
+0.00 start
+0.00 mv 2 3 (24 40)
+0.10 lc 2 3 (24 40)
+0.21 lr 2 3 (24 40)
+1.50 rc 1 1 (8 8)
+1.62 rr 1 1 (8 8)
+2.40 lc 1 2 (8 24)
+2.45 rc 1 2 (8 24)
+2.60 lr 1 2 (8 24)
+2.70 rr 1 2 (8 24)
+3.10 lc 3 1 (40 8)
+3.20 lr 3 1 (40 8)
+3.90 lc 4 2 (56 24)
+4.00 lr 4 2 (56 24)
+4.00 blast
+4.50 lc 4 3 (56 40)
+4.60 lr 4 3 (56 40)
+";
+
+    #[test]
+    fn import_test() {
+        let replay = import(VIDEO.as_bytes()).unwrap();
+        let commands: Vec<&str> = replay
+            .moves
+            .iter()
+            .map(|replay_move| &replay_move.command[..])
+            .collect();
+        // the chord at the 1 below the flag opens the cell beside it, the game ends at the mine
+        assert_eq!(
+            vec![
+                "explore(2, 1)",
+                "flag(0, 0)",
+                "explore(0, 1)",
+                "explore(0, 2)",
+                "explore(1, 3)"
+            ],
+            commands
+        );
+        assert_eq!(210, replay.moves[0].time_ms);
+        assert_eq!(Ok(GameResolve::MineHit), replay.simulate());
+
+        assert_eq!(Err(RawvfError::Rmv), import(b"*rmv\x00\x01\xff\xfe"));
+        assert_eq!(
+            Err(RawvfError::Binary),
+            import(b"\x11\x00\x00\x00\x00\x03\xff")
+        );
+        assert_eq!(
+            Err(RawvfError::MalformedBoard),
+            import(VIDEO.replace("000*\n", "00*\n").as_bytes())
+        );
+        assert_eq!(
+            Err(RawvfError::MalformedEvent(24)),
+            import(VIDEO.replace("3.20 lr", "soon lr").as_bytes())
+        );
+    }
+}
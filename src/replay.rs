@@ -0,0 +1,374 @@
+//! Replays record every command of a game so it can be re-simulated through the engine.
+
+use std::{fmt::Display, fs, io, path::Path, time::Duration};
+
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::{
+    layout::Layout, mask::Mask, BoardCommand, Expansion, GameBoard, GameConfiguration, GameResolve,
+    SafeStart,
+};
+
+/// Key of the replay checksums. It ships with every build, so the checksum catches edited
+/// replay files but not a forger rebuilding the game.
+const SEAL_KEY: &[u8] = b"minesweeper replay seal v1";
+
+fn hmac_sha256(key: &[u8], message: &[u8]) -> [u8; 32] {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length.");
+    mac.update(message);
+    mac.finalize().into_bytes().into()
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|byte| format!("{:02x}", byte)).collect()
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct ReplayMove {
+    pub time_ms: u64,
+    pub command: String,
+    /// Time since the move before, missing in replays from before it was recorded.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub delta_ms: Option<u64>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Replay {
+    pub configuration: GameConfiguration,
+    pub seed: u64,
+    /// Shape of the board, if it is not rectangular.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mask: Option<Mask>,
+    /// Hand made board the game was played on, the seed is unused then.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layout: Option<Layout>,
+    /// Whether the first mine hit was to be forgiven.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub forgiving: bool,
+    /// Cells kept free of mines around the first explore.
+    #[serde(default, skip_serializing_if = "SafeStart::is_off")]
+    pub safe_start: SafeStart,
+    /// How far the explores opened the board.
+    #[serde(default, skip_serializing_if = "Expansion::is_classic")]
+    pub expansion: Expansion,
+    /// Whether satisfied numbers were chorded after every explore.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub auto_chord: bool,
+    /// Whether only a claim could win the game.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub strict_flags: bool,
+    pub moves: Vec<ReplayMove>,
+    /// How the game ended as the player claims, sealed together with the moves.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<GameResolve>,
+    /// Hex encoded HMAC-SHA256 of the rest of the replay, set once the game is over.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub checksum: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ReplayError {
+    InvalidConfiguration,
+    MalformedCommand(usize),
+    UnorderedTime(usize),
+    MoveAfterEnd(usize),
+    Unsealed,
+    ChecksumMismatch,
+    OutcomeMismatch,
+}
+
+impl Display for ReplayError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ReplayError::InvalidConfiguration => write!(f, "the board configuration is invalid"),
+            ReplayError::MalformedCommand(index) => write!(f, "move {} is malformed", index + 1),
+            ReplayError::UnorderedTime(index) => {
+                write!(f, "move {} happened before the previous one", index + 1)
+            }
+            ReplayError::MoveAfterEnd(index) => {
+                write!(f, "move {} was made after the game ended", index + 1)
+            }
+            ReplayError::Unsealed => write!(f, "the replay has no checksum"),
+            ReplayError::ChecksumMismatch => {
+                write!(f, "the replay was edited after the game")
+            }
+            ReplayError::OutcomeMismatch => {
+                write!(f, "the moves do not end the game as claimed")
+            }
+        }
+    }
+}
+
+impl Replay {
+    pub fn new(configuration: GameConfiguration, seed: u64) -> Self {
+        Replay {
+            configuration,
+            seed,
+            mask: None,
+            layout: None,
+            forgiving: false,
+            safe_start: SafeStart::Off,
+            expansion: Expansion::Classic,
+            auto_chord: false,
+            strict_flags: false,
+            moves: vec![],
+            outcome: None,
+            checksum: None,
+        }
+    }
+
+    /// Replay of a game on the board, keeping its shape.
+    pub fn of(game_board: &GameBoard) -> Self {
+        Replay {
+            mask: game_board.mask().cloned(),
+            layout: game_board.layout().cloned(),
+            forgiving: game_board.is_forgiving(),
+            safe_start: game_board.safe_start(),
+            expansion: game_board.expansion(),
+            auto_chord: game_board.is_auto_chord(),
+            strict_flags: game_board.is_strict_flags(),
+            ..Replay::new(game_board.configuration(), game_board.seed())
+        }
+    }
+
+    pub fn record(&mut self, command: BoardCommand, elapsed: Duration) {
+        let time_ms = elapsed.as_millis() as u64;
+        let previous_ms = self.moves.last().map_or(0, |last| last.time_ms);
+        self.moves.push(ReplayMove {
+            time_ms,
+            command: command.to_string(),
+            delta_ms: Some(time_ms.saturating_sub(previous_ms)),
+        });
+    }
+
+    /// Time taken for every move, from the start or the move before.
+    pub fn move_times(&self) -> Vec<Duration> {
+        let mut previous_ms = 0;
+        self.moves
+            .iter()
+            .map(|replay_move| {
+                let delta_ms = replay_move
+                    .delta_ms
+                    .unwrap_or_else(|| replay_move.time_ms.saturating_sub(previous_ms));
+                previous_ms = replay_move.time_ms;
+                Duration::from_millis(delta_ms)
+            })
+            .collect()
+    }
+
+    /// Hex encoded SHA-256 of the replay, identifies it without sending it around.
+    pub fn hash(&self) -> String {
+        let content = serde_json::to_string(self).unwrap_or_default();
+        hex(&Sha256::digest(content.as_bytes()))
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Checksums the seed, the board, every timed move and the outcome, to be called once
+    /// the game is over.
+    pub fn seal(&mut self, outcome: GameResolve) {
+        self.outcome = Some(outcome);
+        self.checksum = Some(self.signature());
+    }
+
+    /// Checks the replay was not edited since it was sealed. The key is part of every
+    /// build, so a matching checksum is no proof the game was really played.
+    pub fn verify_checksum(&self) -> Result<(), ReplayError> {
+        match &self.checksum {
+            None => Err(ReplayError::Unsealed),
+            Some(checksum) if *checksum == self.signature() => Ok(()),
+            Some(_) => Err(ReplayError::ChecksumMismatch),
+        }
+    }
+
+    /// Checks the replay is sealed, every move is legal and the moves end the game as
+    /// claimed, and returns how it ended.
+    pub fn verify(&self) -> Result<GameResolve, ReplayError> {
+        self.verify_checksum()?;
+        let resolve = self.simulate()?;
+        match self.outcome {
+            Some(outcome) if outcome != resolve => Err(ReplayError::OutcomeMismatch),
+            _ => Ok(resolve),
+        }
+    }
+
+    fn signature(&self) -> String {
+        let unsealed = Replay {
+            checksum: None,
+            ..self.clone()
+        };
+        let content = serde_json::to_string(&unsealed).unwrap_or_default();
+        hex(&hmac_sha256(SEAL_KEY, content.as_bytes()))
+    }
+
+    /// Time of the last move, which is when the game was decided.
+    pub fn duration(&self) -> Duration {
+        Duration::from_millis(self.moves.last().map_or(0, |last| last.time_ms))
+    }
+
+    /// The board of the replay before the first move.
+    pub fn board(&self) -> Result<GameBoard, ReplayError> {
+        let configuration = self.configuration;
+        let fits = match &self.mask {
+            Some(mask) => {
+                configuration.within_limits() && configuration.fits_cells(mask.cell_count() as u64)
+            }
+            None => configuration.fits(),
+        };
+        let layout_matches = self
+            .layout
+            .as_ref()
+            .is_none_or(|layout| layout.configuration() == configuration);
+        if !fits || !layout_matches {
+            return Err(ReplayError::InvalidConfiguration);
+        }
+
+        let mut game_board = match (&self.layout, &self.mask) {
+            (Some(layout), _) => GameBoard::from_layout(layout.clone()),
+            (None, Some(mask)) => GameBoard::with_mask(configuration, mask.clone()),
+            (None, None) => GameBoard::new(configuration),
+        };
+        if self.layout.is_none() {
+            game_board.generate_world_from_seed(self.seed);
+        }
+        game_board.set_forgiving(self.forgiving);
+        game_board.set_safe_start(self.safe_start);
+        game_board.set_expansion(self.expansion);
+        game_board.set_auto_chord(self.auto_chord);
+        game_board.set_strict_flags(self.strict_flags);
+        Ok(game_board)
+    }
+
+    /// Plays every move on a freshly generated board and returns how the game ended,
+    /// `GameResolve::Continue` means the replay stops before the game was decided.
+    pub fn simulate(&self) -> Result<GameResolve, ReplayError> {
+        let mut game_board = self.board()?;
+
+        let mut resolve = GameResolve::Continue;
+        let mut previous_time_ms = 0;
+        for (index, replay_move) in self.moves.iter().enumerate() {
+            if resolve != GameResolve::Continue {
+                return Err(ReplayError::MoveAfterEnd(index));
+            }
+            if replay_move.time_ms < previous_time_ms {
+                return Err(ReplayError::UnorderedTime(index));
+            }
+            previous_time_ms = replay_move.time_ms;
+
+            let command = BoardCommand::try_from(&replay_move.command[..])
+                .map_err(|_| ReplayError::MalformedCommand(index))?;
+            if !game_board.is_on_board(command) {
+                return Err(ReplayError::MalformedCommand(index));
+            }
+            resolve = game_board.manipulate_cell(command);
+        }
+
+        Ok(resolve)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoardCell, Coordinate};
+
+    #[test]
+    fn simulate_replay_test() {
+        let mut replay = Replay::new(GameConfiguration::new(3, 3, 9), 7);
+        replay.record(
+            BoardCommand::SetMarkNote(Coordinate(0, 0)),
+            Duration::from_secs(1),
+        );
+        replay.record(
+            BoardCommand::Explore(Coordinate(1, 1)),
+            Duration::from_secs(2),
+        );
+        assert_eq!(Ok(GameResolve::MineHit), replay.simulate());
+        assert_eq!(Duration::from_secs(2), replay.duration());
+        assert_eq!(
+            vec![Duration::from_secs(1), Duration::from_secs(1)],
+            replay.move_times()
+        );
+        // replays from before the times of the moves were recorded
+        replay.moves[1].delta_ms = None;
+        assert_eq!(Duration::from_secs(1), replay.move_times()[1]);
+
+        replay.record(BoardCommand::Pass, Duration::from_secs(3));
+        assert_eq!(Err(ReplayError::MoveAfterEnd(2)), replay.simulate());
+    }
+
+    #[test]
+    fn checksum_test() {
+        // RFC 4231, test case 2
+        assert_eq!(
+            "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843",
+            hex(&hmac_sha256(b"Jefe", b"what do ya want for nothing?"))
+        );
+
+        let mut replay = Replay::new(GameConfiguration::beginner(), 3);
+        replay.record(
+            BoardCommand::Explore(Coordinate(0, 0)),
+            Duration::from_millis(1200),
+        );
+        assert_eq!(Err(ReplayError::Unsealed), replay.verify_checksum());
+        replay.seal(GameResolve::Continue);
+        assert_eq!(Ok(()), replay.verify_checksum());
+        assert_eq!(
+            Ok(()),
+            serde_json::from_str::<Replay>(&serde_json::to_string(&replay).unwrap())
+                .unwrap()
+                .verify_checksum()
+        );
+
+        replay.moves[0].time_ms = 900;
+        assert_eq!(Err(ReplayError::ChecksumMismatch), replay.verify_checksum());
+    }
+
+    #[test]
+    fn verify_replay_test() {
+        let mut replay = Replay::new(GameConfiguration::new(3, 3, 0), 1);
+        replay.record(
+            BoardCommand::Explore(Coordinate(1, 1)),
+            Duration::from_secs(4),
+        );
+        replay.seal(GameResolve::AllMinesDiscovered);
+        assert_eq!(Ok(GameResolve::AllMinesDiscovered), replay.verify());
+
+        replay.seal(GameResolve::MineHit);
+        assert_eq!(Err(ReplayError::OutcomeMismatch), replay.verify());
+
+        replay.record(BoardCommand::Pass, Duration::from_secs(5));
+        replay.seal(GameResolve::AllMinesDiscovered);
+        assert_eq!(Err(ReplayError::MoveAfterEnd(1)), replay.verify());
+    }
+
+    #[test]
+    fn strict_flags_replay_test() {
+        let mut game_board = GameBoard::new(GameConfiguration::new(3, 3, 1));
+        game_board.generate_world_from_seed(2);
+        game_board.set_strict_flags(true);
+        let mine = (0..9)
+            .map(|index| Coordinate(index / 3, index % 3))
+            .find(|coordinate| matches!(game_board.get_cell_at(*coordinate), BoardCell::Mine(_)))
+            .unwrap();
+
+        let mut replay = Replay::of(&game_board);
+        for (second, command) in [BoardCommand::SetMarkFlag(mine), BoardCommand::Claim]
+            .into_iter()
+            .enumerate()
+        {
+            replay.record(command, Duration::from_secs(second as u64 + 1));
+        }
+        replay.seal(GameResolve::AllMinesDiscovered);
+
+        let replay: Replay =
+            serde_json::from_str(&serde_json::to_string(&replay).unwrap()).unwrap();
+        assert!(replay.strict_flags);
+        assert_eq!(Ok(GameResolve::AllMinesDiscovered), replay.verify());
+    }
+}
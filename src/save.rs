@@ -0,0 +1,399 @@
+//! Save files of boards in progress. A json header with everything but the cells is
+//! followed by the packed cells, the mine counts and the flag counts, a byte per cell
+//! each. Opening a save maps the three grids copy-on-write instead of reading them, so a
+//! board of millions of cells opens at once and only the pages played on are read.
+//!
+//! Games in progress are kept in named slots of the profile, `save <slot>` and
+//! `load <slot>` during a game. The header tells when and how far a slot was played
+//! without reading the cells.
+
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    fs::{self, File},
+    io::{self, Read, Write},
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    ansi::Theme,
+    calendar,
+    clock::TimeFormat,
+    layout::Layout,
+    mask::Mask,
+    packed::{Bytes, Counts, PackedCells},
+    storage, CoordinateConvention, Expansion, FlagGuard, GameBoard, GameConfiguration, SafeStart,
+};
+
+const MAGIC: &[u8; 8] = b"MSWSAVE1";
+
+/// Slot the game in progress is kept in after every move, it is deleted once the game
+/// is over.
+pub const AUTOSAVE_SLOT: &str = "autosave";
+
+const SLOTS_DIR: &str = "saves";
+const SLOT_EXTENSION: &str = "save";
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    configuration: GameConfiguration,
+    seed: u64,
+    mines_discovered: u32,
+    /// Missing in saves from before wrong flags were counted.
+    #[serde(default)]
+    wrong_flags: u32,
+    large_counts: HashMap<usize, i16>,
+    mask: Option<Mask>,
+    layout: Option<Layout>,
+    flag_guard: FlagGuard,
+    forgiving: bool,
+    /// Missing in saves from before the rule.
+    #[serde(default)]
+    strict_flags: bool,
+    /// Missing in saves from before the safe start.
+    #[serde(default)]
+    safe_start: SafeStart,
+    /// Missing in saves from before the expansion could be chosen.
+    #[serde(default)]
+    expansion: Expansion,
+    /// Missing in saves from before the assist.
+    #[serde(default)]
+    auto_chord: bool,
+    mistakes_forgiven: u32,
+    show_mines: bool,
+    cheated: bool,
+    /// Time played before the save, missing in saves from before slots like the two
+    /// fields after it.
+    #[serde(default)]
+    elapsed_ms: u64,
+    /// Seconds since the unix epoch.
+    #[serde(default)]
+    saved_at: u64,
+    #[serde(default)]
+    explored_cells: u32,
+    /// Missing in saves from before it was kept, the cells are counted then.
+    #[serde(default)]
+    safe_cells_left: Option<u64>,
+}
+
+/// Writes the board to the file. The file is replaced rather than written into, boards
+/// opened from it keep their mapping.
+pub fn save(game_board: &GameBoard, path: &Path) -> io::Result<()> {
+    save_played(game_board, path, Duration::ZERO)
+}
+
+/// Same as [`save`], keeping the time played so far.
+pub fn save_played(game_board: &GameBoard, path: &Path, elapsed: Duration) -> io::Result<()> {
+    let header = serde_json::to_vec(&Header {
+        configuration: game_board.game_configuration,
+        seed: game_board.seed,
+        mines_discovered: game_board.mines_discovered,
+        wrong_flags: game_board.wrong_flags,
+        large_counts: game_board.cells.large_counts(),
+        mask: game_board.mask.clone(),
+        layout: game_board.layout.clone(),
+        flag_guard: game_board.flag_guard,
+        forgiving: game_board.forgiving,
+        strict_flags: game_board.strict_flags,
+        safe_start: game_board.safe_start,
+        expansion: game_board.expansion,
+        auto_chord: game_board.auto_chord,
+        mistakes_forgiven: game_board.mistakes_forgiven,
+        show_mines: game_board.show_mines,
+        cheated: game_board.cheated,
+        elapsed_ms: elapsed.as_millis() as u64,
+        saved_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |since| since.as_secs()),
+        explored_cells: game_board.explored_cells(),
+        safe_cells_left: Some(game_board.safe_cells_left),
+    })?;
+    let header_length = u32::try_from(header.len()).map_err(io::Error::other)?;
+
+    let temporary = path.with_extension("tmp");
+    let mut file = io::BufWriter::new(File::create(&temporary)?);
+    file.write_all(MAGIC)?;
+    file.write_all(&header_length.to_le_bytes())?;
+    file.write_all(&header)?;
+    game_board.cells.write_to(&mut file)?;
+    game_board.mine_counts.write_to(&mut file)?;
+    game_board.flag_counts.write_to(&mut file)?;
+    file.into_inner()?.sync_all()?;
+    fs::rename(temporary, path)
+}
+
+/// Opens a board saved with [`save`].
+pub fn open(path: &Path) -> io::Result<GameBoard> {
+    open_played(path).map(|(game_board, _)| game_board)
+}
+
+/// Opens a board saved with [`save_played`] with the time played before the save.
+pub fn open_played(path: &Path) -> io::Result<(GameBoard, Duration)> {
+    let mut file = File::open(path)?;
+    let (header, header_length) = read_header(&mut file)?;
+
+    let configuration = header.configuration;
+    if !configuration.within_limits() {
+        return Err(invalid("the board is out of limits"));
+    }
+    let size = configuration.w() as u64 * configuration.h() as u64;
+    let offset = MAGIC.len() as u64 + 4 + header_length;
+    if file.metadata()?.len() != offset + 3 * size {
+        return Err(invalid("the save is truncated"));
+    }
+
+    let size = size as usize;
+    let grid = |index: u64| map(&file, offset + index * size as u64, size);
+    let mut game_board = GameBoard {
+        game_configuration: configuration,
+        seed: header.seed,
+        mines_discovered: header.mines_discovered,
+        wrong_flags: header.wrong_flags,
+        safe_cells_left: header.safe_cells_left.unwrap_or_default(),
+        cells: PackedCells::from_parts(grid(0)?, header.large_counts),
+        mine_counts: Counts::from_bytes(grid(1)?),
+        flag_counts: Counts::from_bytes(grid(2)?),
+        mask: header.mask,
+        layout: header.layout,
+        flag_guard: header.flag_guard,
+        convention: CoordinateConvention::default(),
+        theme: Theme::default(),
+        last_cell: None,
+        held_back: None,
+        forgiving: header.forgiving,
+        strict_flags: header.strict_flags,
+        safe_start: header.safe_start,
+        expansion: header.expansion,
+        auto_chord: header.auto_chord,
+        mistakes_forgiven: header.mistakes_forgiven,
+        show_mines: header.show_mines,
+        cheated: header.cheated,
+    };
+    if header.safe_cells_left.is_none() {
+        game_board.safe_cells_left = game_board.count_safe_cells_left();
+    }
+    Ok((game_board, Duration::from_millis(header.elapsed_ms)))
+}
+
+/// Reads the header and returns it with its length in bytes.
+fn read_header(file: &mut File) -> io::Result<(Header, u64)> {
+    let mut magic = [0; 8];
+    file.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(invalid("not a minesweeper save"));
+    }
+    let mut header_length = [0; 4];
+    file.read_exact(&mut header_length)?;
+    let header_length = u32::from_le_bytes(header_length) as u64;
+    // the length is read before anything else is checked, a broken one must not allocate
+    if header_length
+        > file
+            .metadata()?
+            .len()
+            .saturating_sub(MAGIC.len() as u64 + 4)
+    {
+        return Err(invalid("the header runs past the end of the save"));
+    }
+    let mut header = vec![0; header_length as usize];
+    file.read_exact(&mut header)?;
+    let header = serde_json::from_slice(&header).map_err(|err| invalid(&err.to_string()))?;
+    Ok((header, header_length))
+}
+
+/// A slot as listed by `saves`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlotInfo {
+    pub slot: String,
+    pub configuration: GameConfiguration,
+    pub explored_cells: u32,
+    pub elapsed: Duration,
+    /// Seconds since the unix epoch.
+    pub saved_at: u64,
+}
+
+impl Display for SlotInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let seconds_of_day = self.saved_at % 86_400;
+        write!(
+            f,
+            "{:<16}{:<14}{:>8} explored{:>12}  {} {:02}:{:02}",
+            self.slot,
+            self.configuration.key(),
+            self.explored_cells,
+            TimeFormat::Clock.format(self.elapsed),
+            calendar::format_date(self.saved_at / 86_400),
+            seconds_of_day / 3600,
+            seconds_of_day / 60 % 60
+        )
+    }
+}
+
+fn slots_dir() -> PathBuf {
+    storage::profile_dir().join(SLOTS_DIR)
+}
+
+/// Slot names double as file names, so they follow the rules of profile names.
+fn slot_path(dir: &Path, slot: &str) -> io::Result<PathBuf> {
+    if !storage::is_valid_profile_name(slot) {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "slot names may only contain letters, digits, '-' and '_'",
+        ));
+    }
+    Ok(dir.join(slot).with_extension(SLOT_EXTENSION))
+}
+
+/// Keeps the board in the slot of the active profile, replacing the slot's game.
+pub fn save_slot(game_board: &GameBoard, slot: &str, elapsed: Duration) -> io::Result<()> {
+    save_slot_in(&slots_dir(), game_board, slot, elapsed)
+}
+
+/// Opens the game of the slot with the time played on it.
+pub fn open_slot(slot: &str) -> io::Result<(GameBoard, Duration)> {
+    open_played(&slot_path(&slots_dir(), slot)?)
+}
+
+pub fn delete_slot(slot: &str) -> io::Result<()> {
+    fs::remove_file(slot_path(&slots_dir(), slot)?)
+}
+
+/// Slots of the active profile by name, unreadable ones are left out.
+pub fn slots() -> Vec<SlotInfo> {
+    slots_in(&slots_dir())
+}
+
+fn save_slot_in(
+    dir: &Path,
+    game_board: &GameBoard,
+    slot: &str,
+    elapsed: Duration,
+) -> io::Result<()> {
+    let path = slot_path(dir, slot)?;
+    fs::create_dir_all(dir)?;
+    save_played(game_board, &path, elapsed)
+}
+
+fn slots_in(dir: &Path) -> Vec<SlotInfo> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return vec![];
+    };
+    let mut slots: Vec<SlotInfo> = entries
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != SLOT_EXTENSION {
+                return None;
+            }
+            let (header, _) = read_header(&mut File::open(&path).ok()?).ok()?;
+            Some(SlotInfo {
+                slot: path.file_stem()?.to_str()?.to_string(),
+                configuration: header.configuration,
+                explored_cells: header.explored_cells,
+                elapsed: Duration::from_millis(header.elapsed_ms),
+                saved_at: header.saved_at,
+            })
+        })
+        .collect();
+    slots.sort_by(|a, b| a.slot.cmp(&b.slot));
+    slots
+}
+
+fn invalid(message: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidData, message.to_string())
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn map(file: &File, offset: u64, size: usize) -> io::Result<Bytes> {
+    // Safety: the mapping is private, so changes to the board stay in memory, and saves
+    // replace the file instead of writing into it, so the mapped bytes never change.
+    let map = unsafe {
+        memmap2::MmapOptions::new()
+            .offset(offset)
+            .len(size)
+            .map_copy(file)?
+    };
+    Ok(Bytes::Mapped(map))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn map(file: &File, offset: u64, size: usize) -> io::Result<Bytes> {
+    use std::io::{Seek, SeekFrom};
+
+    let mut file = file;
+    file.seek(SeekFrom::Start(offset))?;
+    let mut bytes = vec![0; size];
+    file.read_exact(&mut bytes)?;
+    Ok(Bytes::Owned(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{BoardCell, BoardCommand, Coordinate};
+
+    #[test]
+    fn save_test() {
+        let path = std::env::temp_dir().join(format!("minesweeper-save-{}", std::process::id()));
+        let mut game_board =
+            GameBoard::new(GameConfiguration::new(300, 200, 500).with_anti_mines(20));
+        game_board.generate_world_from_seed(11);
+        game_board.manipulate_cell(BoardCommand::SetMarkFlag(Coordinate(3, 4)));
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(100, 100)));
+        save(&game_board, &path).unwrap();
+
+        let mut opened = open(&path).unwrap();
+        assert_eq!(game_board.to_string(), opened.to_string());
+        assert_eq!(game_board.three_bv(), opened.three_bv());
+        assert_eq!(game_board.safe_cells_left, opened.safe_cells_left);
+
+        // playing on changes the board but not the save
+        let mine = (0..200)
+            .flat_map(|row| (0..300).map(move |col| Coordinate(row, col)))
+            .find(|coordinate| matches!(opened.get_cell_at(*coordinate), BoardCell::Mine(_)))
+            .unwrap();
+        opened.manipulate_cell(BoardCommand::SetMarkNote(mine));
+        assert_ne!(game_board.to_string(), opened.to_string());
+        assert_eq!(game_board.to_string(), open(&path).unwrap().to_string());
+        save(&opened, &path).unwrap();
+        assert_eq!(opened.to_string(), open(&path).unwrap().to_string());
+
+        fs::write(&path, b"MSWSAVE1").unwrap();
+        assert!(open(&path).is_err());
+        let mut truncated = MAGIC.to_vec();
+        truncated.extend_from_slice(&u32::MAX.to_le_bytes());
+        truncated.extend_from_slice(b"{}");
+        fs::write(&path, truncated).unwrap();
+        assert!(open(&path).is_err_and(|err| err.kind() == io::ErrorKind::InvalidData));
+        let _ = fs::remove_file(path);
+    }
+
+    #[test]
+    fn slots_test() {
+        let dir = std::env::temp_dir().join(format!("minesweeper-slots-{}", std::process::id()));
+        let mut game_board = GameBoard::new(GameConfiguration::new(9, 9, 0));
+        game_board.manipulate_cell(BoardCommand::SetMarkNote(Coordinate(0, 0)));
+        assert!(save_slot_in(&dir, &game_board, "../escape", Duration::ZERO).is_err());
+        save_slot_in(&dir, &game_board, "second", Duration::from_secs(5)).unwrap();
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(8, 8)));
+        save_slot_in(&dir, &game_board, "first", Duration::from_millis(62_345)).unwrap();
+        fs::write(dir.join("broken.save"), b"MSWSAVE1").unwrap();
+
+        let slots = slots_in(&dir);
+        assert_eq!(
+            vec!["first", "second"],
+            slots.iter().map(|slot| &slot.slot[..]).collect::<Vec<_>>()
+        );
+        assert_eq!(81, slots[0].explored_cells);
+        assert_eq!(0, slots[1].explored_cells);
+        assert!(slots[0]
+            .to_string()
+            .starts_with("first           9x9-0               81 explored   01:02.345  20"));
+
+        let (opened, elapsed) = open_played(&slot_path(&dir, "first").unwrap()).unwrap();
+        assert_eq!(Duration::from_millis(62_345), elapsed);
+        assert_eq!(game_board.to_string(), opened.to_string());
+        let _ = fs::remove_dir_all(dir);
+    }
+}
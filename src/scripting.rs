@@ -0,0 +1,450 @@
+//! Rhai scripts from the `scripts` directory of the data directory, built with the
+//! `scripting` feature. A script may add commands to the console with
+//! `register_command("autoflag", "auto_flag")`, the function then gets the board and
+//! returns the commands to apply. Scripts react to the game with the functions
+//! `on_start(board)`, `on_command(board, command)` and `on_end(board, outcome)`, the
+//! first two may return commands as well. Commands are written with board coordinates, e.g.
+//! `"flag(2, 3)"`, and a script sees no more of the board than the player does.
+
+use std::{cell::RefCell, fmt::Display, fs, path::Path, rc::Rc};
+
+use rhai::{Array, Dynamic, Engine, Scope, AST};
+
+use crate::{storage, BoardCell, BoardCommand, Coordinate, GameBoard, GameResolve, Mark};
+
+const SCRIPTS_DIR: &str = "scripts";
+const SCRIPT_EXTENSION: &str = "rhai";
+
+/// Operations a single call may take, a runaway script cannot hang the game.
+const MAX_OPERATIONS: u64 = 10_000_000;
+
+/// Nesting allowed in scripts, the default of debug builds is too tight for loops over
+/// the board.
+const MAX_EXPRESSION_DEPTH: usize = 64;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ScriptError {
+    /// File name of the script.
+    pub script: String,
+    pub message: String,
+}
+
+impl Display for ScriptError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}: {}", self.script, self.message)
+    }
+}
+
+/// What happened in the game.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Event {
+    Start,
+    /// A command of the player was applied.
+    Command(BoardCommand),
+    End(GameResolve),
+}
+
+struct Script {
+    name: String,
+    ast: AST,
+}
+
+pub struct Scripts {
+    engine: Engine,
+    scripts: Vec<Script>,
+    /// Name of every registered command with its script and function.
+    commands: Vec<(String, usize, String)>,
+}
+
+impl Scripts {
+    /// Loads the scripts of the scripts directory, scripts that fail to compile or run
+    /// are left out.
+    pub fn load() -> (Scripts, Vec<ScriptError>) {
+        Scripts::load_from(&storage::data_dir().join(SCRIPTS_DIR))
+    }
+
+    pub fn load_from(dir: &Path) -> (Scripts, Vec<ScriptError>) {
+        let mut files: Vec<_> = fs::read_dir(dir)
+            .into_iter()
+            .flatten()
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == SCRIPT_EXTENSION))
+            .collect();
+        files.sort();
+
+        let mut errors = vec![];
+        let mut sources = vec![];
+        for path in files {
+            let name = path
+                .file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string();
+            match fs::read_to_string(&path) {
+                Ok(source) => sources.push((name, source)),
+                Err(err) => errors.push(ScriptError {
+                    script: name,
+                    message: err.to_string(),
+                }),
+            }
+        }
+        let (scripts, load_errors) = Scripts::from_sources(&sources);
+        errors.extend(load_errors);
+        (scripts, errors)
+    }
+
+    /// Compiles the scripts, given by name and source, and runs them to register their
+    /// commands.
+    pub fn from_sources(sources: &[(String, String)]) -> (Scripts, Vec<ScriptError>) {
+        let mut engine = Engine::new();
+        engine.set_max_operations(MAX_OPERATIONS);
+        engine.set_max_expr_depths(MAX_EXPRESSION_DEPTH, MAX_EXPRESSION_DEPTH);
+        engine
+            .register_type_with_name::<BoardView>("Board")
+            .register_get("width", |board: &mut BoardView| board.width as i64)
+            .register_get("height", |board: &mut BoardView| board.height as i64)
+            .register_get("mines", |board: &mut BoardView| board.mines as i64)
+            .register_fn(
+                "is_explored",
+                |board: &mut BoardView, row: i64, col: i64| {
+                    matches!(board.cell(row, col), Some(CellView::Explored(_)))
+                },
+            )
+            .register_fn("is_flagged", |board: &mut BoardView, row: i64, col: i64| {
+                matches!(board.cell(row, col), Some(CellView::Hidden(Mark::MarkFlag)))
+            })
+            .register_fn(
+                "count",
+                |board: &mut BoardView, row: i64, col: i64| match board.cell(row, col) {
+                    Some(CellView::Explored(count)) => count as i64,
+                    _ => 0,
+                },
+            )
+            .register_fn("neighbours", |board: &mut BoardView, row: i64, col: i64| {
+                board.neighbours(row, col)
+            });
+
+        let registered = Rc::new(RefCell::new(vec![]));
+        let sink = registered.clone();
+        engine.register_fn("register_command", move |name: &str, function: &str| {
+            sink.borrow_mut()
+                .push((name.to_lowercase(), function.to_string()))
+        });
+
+        let mut scripts = Scripts {
+            engine,
+            scripts: vec![],
+            commands: vec![],
+        };
+        let mut errors = vec![];
+        for (name, source) in sources {
+            let error = |err: &dyn Display| ScriptError {
+                script: name.clone(),
+                message: err.to_string(),
+            };
+            let ast = match scripts.engine.compile(source) {
+                Ok(ast) => ast,
+                Err(err) => {
+                    errors.push(error(&err));
+                    continue;
+                }
+            };
+            if let Err(err) = scripts.engine.run_ast(&ast) {
+                errors.push(error(&err));
+                registered.borrow_mut().clear();
+                continue;
+            }
+
+            let index = scripts.scripts.len();
+            scripts.commands.extend(
+                registered
+                    .borrow_mut()
+                    .drain(..)
+                    .map(|(command, function)| (command, index, function)),
+            );
+            scripts.scripts.push(Script {
+                name: name.clone(),
+                ast,
+            });
+        }
+        (scripts, errors)
+    }
+
+    /// Runs the command a script registered under the name, none if there is none.
+    pub fn command(
+        &self,
+        name: &str,
+        game_board: &GameBoard,
+    ) -> Option<Result<Vec<BoardCommand>, ScriptError>> {
+        let name = name.trim().to_lowercase();
+        let (_, index, function) = self
+            .commands
+            .iter()
+            .find(|(command, ..)| *command == name)?;
+        let script = &self.scripts[*index];
+        let view = BoardView::of(game_board);
+        Some(self.call(script, function, (view,), game_board))
+    }
+
+    /// Lets every script react to the event, in the order of their file names.
+    pub fn react(
+        &self,
+        event: Event,
+        game_board: &GameBoard,
+    ) -> Result<Vec<BoardCommand>, ScriptError> {
+        let (function, arity) = match event {
+            Event::Start => ("on_start", 1),
+            Event::Command(_) => ("on_command", 2),
+            Event::End(_) => ("on_end", 2),
+        };
+        let handlers: Vec<&Script> = self
+            .scripts
+            .iter()
+            .filter(|script| {
+                script
+                    .ast
+                    .iter_functions()
+                    .any(|f| f.name == function && f.params.len() == arity)
+            })
+            .collect();
+        if handlers.is_empty() {
+            return Ok(vec![]);
+        }
+
+        let view = BoardView::of(game_board);
+        let mut commands = vec![];
+        for script in handlers {
+            commands.extend(match event {
+                Event::Start => self.call(script, function, (view.clone(),), game_board)?,
+                Event::Command(command) => self.call(
+                    script,
+                    function,
+                    (view.clone(), command.to_string()),
+                    game_board,
+                )?,
+                Event::End(resolve) => {
+                    let outcome = match resolve {
+                        GameResolve::AllMinesDiscovered => "won",
+                        GameResolve::MineHit => "lost",
+                        GameResolve::Quit | GameResolve::Continue => "quit",
+                    };
+                    self.call(
+                        script,
+                        function,
+                        (view.clone(), outcome.to_string()),
+                        game_board,
+                    )?
+                }
+            });
+        }
+        Ok(commands)
+    }
+
+    /// Calls the function, it may return nothing, a command or an array of them.
+    fn call(
+        &self,
+        script: &Script,
+        function: &str,
+        args: impl rhai::FuncArgs,
+        game_board: &GameBoard,
+    ) -> Result<Vec<BoardCommand>, ScriptError> {
+        let error = |message: String| ScriptError {
+            script: script.name.clone(),
+            message,
+        };
+        let result: Dynamic = self
+            .engine
+            .call_fn(&mut Scope::new(), &script.ast, function, args)
+            .map_err(|err| error(err.to_string()))?;
+        if result.is_unit() {
+            return Ok(vec![]);
+        }
+
+        let values = match result.is_array() {
+            true => result.cast::<Array>(),
+            false => vec![result],
+        };
+        values
+            .into_iter()
+            .map(|value| {
+                let text = value
+                    .into_string()
+                    .map_err(|kind| error(format!("commands are strings, not {}", kind)))?;
+                match BoardCommand::try_from(&text[..]) {
+                    Ok(command) if game_board.is_on_board(command) => Ok(command),
+                    _ => Err(error(format!("{} is no command on the board", text))),
+                }
+            })
+            .collect()
+    }
+}
+
+/// What the player knows of a cell.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CellView {
+    Explored(i16),
+    Hidden(Mark),
+}
+
+/// Snapshot of the board handed to the scripts.
+#[derive(Debug, Clone)]
+struct BoardView {
+    width: u32,
+    height: u32,
+    mines: u32,
+    /// Cells row by row, none for holes.
+    cells: Rc<Vec<Option<CellView>>>,
+    neighbours: Rc<Vec<Vec<Coordinate>>>,
+}
+
+impl BoardView {
+    fn of(game_board: &GameBoard) -> BoardView {
+        let (width, height) = game_board.get_dimensions();
+        let coordinates: Vec<Coordinate> = (0..height)
+            .flat_map(|row| (0..width).map(move |col| Coordinate(row, col)))
+            .collect();
+        let cells = coordinates
+            .iter()
+            .map(|coordinate| {
+                if game_board.is_hole(*coordinate) {
+                    return None;
+                }
+                Some(match game_board.get_cell_at(*coordinate) {
+                    BoardCell::Explored(neighbour_mines) => CellView::Explored(neighbour_mines.0),
+                    BoardCell::NoMine(cell_info) => CellView::Hidden(cell_info.0),
+                    BoardCell::Mine(mark) => CellView::Hidden(mark),
+                })
+            })
+            .collect();
+        let neighbours = coordinates
+            .iter()
+            .map(|coordinate| game_board.neighbours(*coordinate))
+            .collect();
+
+        BoardView {
+            width,
+            height,
+            mines: game_board.configuration().mines(),
+            cells: Rc::new(cells),
+            neighbours: Rc::new(neighbours),
+        }
+    }
+
+    fn index(&self, row: i64, col: i64) -> Option<usize> {
+        let on_board =
+            (0..self.height as i64).contains(&row) && (0..self.width as i64).contains(&col);
+        on_board.then(|| row as usize * self.width as usize + col as usize)
+    }
+
+    fn cell(&self, row: i64, col: i64) -> Option<CellView> {
+        self.cells[self.index(row, col)?]
+    }
+
+    /// Neighbours of the cell as `[row, col]` arrays.
+    fn neighbours(&self, row: i64, col: i64) -> Array {
+        let Some(index) = self.index(row, col) else {
+            return vec![];
+        };
+        self.neighbours[index]
+            .iter()
+            .map(|Coordinate(row, col)| {
+                Dynamic::from_array(vec![Dynamic::from(*row as i64), Dynamic::from(*col as i64)])
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::layout::Layout;
+
+    /// Flags the hidden neighbours of every number that has as many of them.
+    const AUTO_FLAG: &str = r#"
+        register_command("autoflag", "auto_flag");
+
+        fn auto_flag(board) {
+            let commands = [];
+            for row in 0..board.height {
+                for col in 0..board.width {
+                    if !board.is_explored(row, col) || board.count(row, col) == 0 {
+                        continue;
+                    }
+                    let hidden = [];
+                    for cell in board.neighbours(row, col) {
+                        if !board.is_explored(cell[0], cell[1]) { hidden.push(cell); }
+                    }
+                    if hidden.len() == board.count(row, col) {
+                        for cell in hidden {
+                            let command = `flag(${cell[0]}, ${cell[1]})`;
+                            if !board.is_flagged(cell[0], cell[1]) && !commands.contains(command) {
+                                commands.push(command);
+                            }
+                        }
+                    }
+                }
+            }
+            commands
+        }
+
+        fn on_command(board, command) {
+            if command.starts_with("explore") { auto_flag(board) }
+        }
+    "#;
+
+    fn scripts(sources: &[(&str, &str)]) -> (Scripts, Vec<ScriptError>) {
+        let sources: Vec<(String, String)> = sources
+            .iter()
+            .map(|(name, source)| (name.to_string(), source.to_string()))
+            .collect();
+        Scripts::from_sources(&sources)
+    }
+
+    #[test]
+    fn command_test() {
+        let (scripts, errors) = scripts(&[
+            ("autoflag.rhai", AUTO_FLAG),
+            ("broken.rhai", "fn on_start(board) {"),
+        ]);
+        assert_eq!(1, errors.len());
+        assert_eq!("broken.rhai", errors[0].script);
+
+        let game_board = GameBoard::from_layout(Layout::try_from("*..\n...\n").unwrap());
+        assert!(scripts.command("unknown", &game_board).is_none());
+        assert_eq!(
+            Ok(vec![BoardCommand::SetMarkFlag(Coordinate(0, 0))]),
+            scripts.command(" AutoFlag", &game_board).unwrap()
+        );
+        assert_eq!(
+            Ok(vec![BoardCommand::SetMarkFlag(Coordinate(0, 0))]),
+            scripts.react(
+                Event::Command(BoardCommand::Explore(Coordinate(2, 2))),
+                &game_board
+            )
+        );
+        assert_eq!(Ok(vec![]), scripts.react(Event::Start, &game_board));
+    }
+
+    #[test]
+    fn bad_commands_test() {
+        let (scripts, errors) = scripts(&[(
+            "bad.rhai",
+            r#"
+                fn on_start(board) { "flag(7, 7)" }
+                fn on_end(board, outcome) { 42 }
+            "#,
+        )]);
+        assert!(errors.is_empty());
+
+        let game_board = GameBoard::from_layout(Layout::try_from("*..\n...\n").unwrap());
+        let err = scripts.react(Event::Start, &game_board).unwrap_err();
+        assert_eq!(
+            "bad.rhai: flag(7, 7) is no command on the board",
+            err.to_string()
+        );
+        assert!(scripts
+            .react(Event::End(GameResolve::MineHit), &game_board)
+            .is_err());
+    }
+}
@@ -0,0 +1,27 @@
+//! State kept from one session to the next, the board of the last game so that `--again`
+//! can play it once more.
+
+use std::io;
+
+use serde::{Deserialize, Serialize};
+
+use crate::{storage, GameConfiguration};
+
+pub(crate) const LAST_GAME_FILE: &str = "last_game.json";
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LastGame {
+    pub configuration: GameConfiguration,
+    pub seed: u64,
+}
+
+impl LastGame {
+    /// The last game of the active profile, none before the first one.
+    pub fn load() -> Option<Self> {
+        storage::load_json(LAST_GAME_FILE)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::save_json(LAST_GAME_FILE, &Some(self))
+    }
+}
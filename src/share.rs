@@ -0,0 +1,314 @@
+//! Board codes short enough for a chat message, e.g. `AQAeEGOHrUs`. A code holds the board
+//! and its seed, or every cell of a hand made board, packed into bytes and written in URL
+//! safe base64. `share` prints the code of the board played and `--code` plays it, `--url`
+//! plays the code or layout a web page holds, e.g. a community's weekly challenge.
+
+use std::{fmt::Display, io};
+
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+
+use crate::{
+    layout::{EditCommand, Layout, LayoutCell},
+    Coordinate, GameBoard, GameConfiguration, MAX_CELLS, MAX_SIDE,
+};
+
+/// Format of the codes, the first byte of every code.
+const VERSION: u8 = 1;
+
+/// Kinds of board, the second byte of a code.
+const SEEDED: u8 = 0;
+/// Seeded board of a configuration with variants, kept as json after the seed.
+const SEEDED_VARIANTS: u8 = 1;
+const LAYOUT: u8 = 2;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SharedBoard {
+    Seeded {
+        configuration: GameConfiguration,
+        seed: u64,
+    },
+    Layout(Layout),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ShareError {
+    Malformed,
+    UnknownVersion(u8),
+    InvalidBoard,
+}
+
+impl Display for ShareError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ShareError::Malformed => write!(f, "the code is malformed"),
+            ShareError::UnknownVersion(version) => {
+                write!(f, "the code is of version {}, which is unknown", version)
+            }
+            ShareError::InvalidBoard => write!(f, "the board of the code cannot be played"),
+        }
+    }
+}
+
+impl SharedBoard {
+    /// The board as it was generated or laid out, none for masked boards whose shape a
+    /// code does not hold.
+    pub fn of(game_board: &GameBoard) -> Option<Self> {
+        if game_board.mask().is_some() {
+            return None;
+        }
+        Some(match game_board.layout() {
+            Some(layout) => SharedBoard::Layout(layout.clone()),
+            None => SharedBoard::Seeded {
+                configuration: game_board.configuration(),
+                seed: game_board.seed(),
+            },
+        })
+    }
+
+    pub fn code(&self) -> String {
+        let mut bytes = vec![VERSION];
+        match self {
+            SharedBoard::Seeded {
+                configuration,
+                seed,
+            } => {
+                let plain = GameConfiguration::new(
+                    configuration.w(),
+                    configuration.h(),
+                    configuration.mines(),
+                );
+                bytes.push(if plain == *configuration {
+                    SEEDED
+                } else {
+                    SEEDED_VARIANTS
+                });
+                write_varint(&mut bytes, u64::from(configuration.w()));
+                write_varint(&mut bytes, u64::from(configuration.h()));
+                write_varint(&mut bytes, u64::from(configuration.mines()));
+                write_varint(&mut bytes, *seed);
+                if plain != *configuration {
+                    bytes.extend(
+                        serde_json::to_vec(configuration)
+                            .expect("Serializing a configuration does not fail."),
+                    );
+                }
+            }
+            SharedBoard::Layout(layout) => {
+                bytes.push(LAYOUT);
+                write_varint(&mut bytes, u64::from(layout.width()));
+                write_varint(&mut bytes, u64::from(layout.height()));
+                // two bits a cell, four cells a byte
+                let cells: Vec<u8> = coordinates(layout.width(), layout.height())
+                    .map(|coordinate| match layout.cell(coordinate) {
+                        LayoutCell::Hidden => 0,
+                        LayoutCell::Mine => 1,
+                        LayoutCell::Revealed => 2,
+                    })
+                    .collect();
+                for quad in cells.chunks(4) {
+                    bytes.push(
+                        quad.iter()
+                            .enumerate()
+                            .fold(0, |byte, (index, cell)| byte | cell << (index * 2)),
+                    );
+                }
+            }
+        }
+        URL_SAFE_NO_PAD.encode(bytes)
+    }
+
+    pub fn decode(code: &str) -> Result<Self, ShareError> {
+        let bytes = URL_SAFE_NO_PAD
+            .decode(code.trim())
+            .map_err(|_| ShareError::Malformed)?;
+        let mut reader = bytes.iter().copied();
+        match reader.next() {
+            Some(VERSION) => {}
+            Some(version) => return Err(ShareError::UnknownVersion(version)),
+            None => return Err(ShareError::Malformed),
+        }
+        let kind = reader.next().ok_or(ShareError::Malformed)?;
+        let width = read_side(&mut reader)?;
+        let height = read_side(&mut reader)?;
+        match kind {
+            SEEDED | SEEDED_VARIANTS => {
+                let mines = u32::try_from(read_varint(&mut reader)?)
+                    .map_err(|_| ShareError::InvalidBoard)?;
+                let seed = read_varint(&mut reader)?;
+                let rest: Vec<u8> = reader.collect();
+                let configuration = match kind {
+                    SEEDED if rest.is_empty() => GameConfiguration::new(width, height, mines),
+                    SEEDED_VARIANTS => serde_json::from_slice::<GameConfiguration>(&rest)
+                        .ok()
+                        .filter(|configuration| {
+                            (configuration.w(), configuration.h()) == (width, height)
+                                && configuration.mines() == mines
+                        })
+                        .ok_or(ShareError::Malformed)?,
+                    _ => return Err(ShareError::Malformed),
+                };
+                if !configuration.fits() {
+                    return Err(ShareError::InvalidBoard);
+                }
+                Ok(SharedBoard::Seeded {
+                    configuration,
+                    seed,
+                })
+            }
+            LAYOUT => {
+                if u64::from(width) * u64::from(height) > MAX_CELLS {
+                    return Err(ShareError::InvalidBoard);
+                }
+                let mut layout = Layout::new(width, height);
+                let packed: Vec<u8> = reader.collect();
+                if packed.len() as u64 != (u64::from(width) * u64::from(height)).div_ceil(4) {
+                    return Err(ShareError::Malformed);
+                }
+                for (index, coordinate) in coordinates(width, height).enumerate() {
+                    match packed[index / 4] >> (index % 4 * 2) & 0b11 {
+                        0 => {}
+                        1 => layout.edit(EditCommand::Mine(coordinate)),
+                        2 => layout.edit(EditCommand::Reveal(coordinate, coordinate)),
+                        _ => return Err(ShareError::Malformed),
+                    }
+                }
+                Ok(SharedBoard::Layout(layout))
+            }
+            _ => Err(ShareError::Malformed),
+        }
+    }
+
+    /// The board of a code or of the text of a layout file.
+    pub fn parse(text: &str) -> Result<Self, ShareError> {
+        let text = text.trim();
+        SharedBoard::decode(text).or_else(|err| match Layout::try_from(text) {
+            Ok(layout)
+                if u64::from(layout.width()) * u64::from(layout.height()) <= MAX_CELLS
+                    && layout.width().max(layout.height()) <= MAX_SIDE =>
+            {
+                Ok(SharedBoard::Layout(layout))
+            }
+            Ok(_) => Err(ShareError::InvalidBoard),
+            Err(_) => Err(err),
+        })
+    }
+
+    /// A fresh board to play, generated from the seed or laid out.
+    pub fn board(&self) -> GameBoard {
+        match self {
+            SharedBoard::Seeded {
+                configuration,
+                seed,
+            } => {
+                let mut game_board = GameBoard::new(*configuration);
+                game_board.generate_world_from_seed(*seed);
+                game_board
+            }
+            SharedBoard::Layout(layout) => GameBoard::from_layout(layout.clone()),
+        }
+    }
+}
+
+/// Downloads the code or layout at the URL.
+#[cfg(feature = "online")]
+pub fn fetch(url: &str) -> io::Result<String> {
+    ureq::get(url)
+        .call()
+        .map_err(io::Error::other)?
+        .into_string()
+}
+
+#[cfg(not(feature = "online"))]
+pub fn fetch(_url: &str) -> io::Result<String> {
+    Err(io::Error::other(
+        "boards from URLs need the `online` feature",
+    ))
+}
+
+/// Cells of the layout row by row.
+fn coordinates(width: u32, height: u32) -> impl Iterator<Item = Coordinate> {
+    (0..height).flat_map(move |row| (0..width).map(move |col| Coordinate(row, col)))
+}
+
+/// Seven bits a byte, least significant first, the high bit set on all but the last.
+fn write_varint(bytes: &mut Vec<u8>, mut value: u64) {
+    while value >= 0x80 {
+        bytes.push(value as u8 | 0x80);
+        value >>= 7;
+    }
+    bytes.push(value as u8);
+}
+
+fn read_varint(reader: &mut impl Iterator<Item = u8>) -> Result<u64, ShareError> {
+    let mut value = 0u64;
+    for shift in (0..64).step_by(7) {
+        let byte = reader.next().ok_or(ShareError::Malformed)?;
+        value |= u64::from(byte & 0x7f)
+            .checked_shl(shift)
+            .ok_or(ShareError::Malformed)?;
+        if byte & 0x80 == 0 {
+            return Ok(value);
+        }
+    }
+    Err(ShareError::Malformed)
+}
+
+fn read_side(reader: &mut impl Iterator<Item = u8>) -> Result<u32, ShareError> {
+    match read_varint(reader)? {
+        side @ 1..=0xffff_ffff if side <= u64::from(MAX_SIDE) => Ok(side as u32),
+        _ => Err(ShareError::InvalidBoard),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Adjacency;
+
+    #[test]
+    fn share_code_test() {
+        let seeded = SharedBoard::Seeded {
+            configuration: GameConfiguration::expert(),
+            seed: 1_234_567,
+        };
+        let code = seeded.code();
+        assert_eq!(11, code.len());
+        assert_eq!(Ok(seeded.clone()), SharedBoard::decode(&code));
+        assert_eq!(
+            SharedBoard::of(&seeded.board()),
+            Some(seeded.clone()),
+            "the board of a code shares the same code"
+        );
+
+        let variants = SharedBoard::Seeded {
+            configuration: GameConfiguration::new(8, 8, 10).with_adjacency(Adjacency::Knight),
+            seed: 3,
+        };
+        assert_eq!(Ok(variants.clone()), SharedBoard::decode(&variants.code()));
+
+        let layout = SharedBoard::Layout(Layout::try_from("..#*\n..##\n#*##\n").unwrap());
+        assert_eq!(Ok(layout.clone()), SharedBoard::decode(&layout.code()));
+        assert_eq!(2, layout.board().configuration().mines());
+
+        assert_eq!(
+            Ok(layout.clone()),
+            SharedBoard::parse("\n  ..#*\n  ..##\n  #*##\n")
+        );
+        assert_eq!(Ok(seeded), SharedBoard::parse(&format!("{}\n", code)));
+
+        assert_eq!(
+            Err(ShareError::Malformed),
+            SharedBoard::decode("not a code!")
+        );
+        assert_eq!(
+            Err(ShareError::Malformed),
+            SharedBoard::parse("<html>not a board</html>")
+        );
+        assert_eq!(
+            Err(ShareError::UnknownVersion(9)),
+            SharedBoard::decode("CQ")
+        );
+        // a zero wide board
+        assert_eq!(Err(ShareError::InvalidBoard), SharedBoard::decode("AQAACQ"));
+    }
+}
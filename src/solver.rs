@@ -0,0 +1,526 @@
+//! Solver checking whether a layout can be solved from its opening without guessing. It
+//! plays like a careful player: a cell is only revealed or flagged when every placement
+//! of the mines that agrees with the numbers shown, and with the total mine count, agrees
+//! on it.
+
+use crate::{
+    layout::{Layout, LayoutCell},
+    Coordinate,
+};
+
+/// Placements tried in a single deduction before the solver gives up on it, keeps huge
+/// open frontiers from taking forever.
+const MAX_SEARCH_NODES: usize = 2_000_000;
+/// Placements tried in a deduction while counting guesses, where giving up is just
+/// another guess and a board has many deductions to make.
+const MAX_GUESS_SEARCH_NODES: usize = 20_000;
+/// Placements tried and cells looked at before counting guesses is given up, boards
+/// that need guessing at every turn take long to count.
+const MAX_GUESS_WORK: usize = 1_000_000;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Verdict {
+    Solvable,
+    /// Guessing is needed, the hidden cells at the edge of what was revealed could each
+    /// still be a mine or not.
+    Stuck(Vec<Coordinate>),
+}
+
+/// Runs the solver from the revealed cells of the layout.
+pub fn solve(layout: &Layout) -> Verdict {
+    let mut solver = Solver::new(layout);
+    loop {
+        if solver.is_solved() {
+            return Verdict::Solvable;
+        }
+        if !solver.step() {
+            return Verdict::Stuck(solver.undecided());
+        }
+    }
+}
+
+/// Guesses the solver needs to clear the layout, the expected guesses of a careful
+/// player who is lucky. Whenever it is stuck it reveals a safe cell at the edge of what
+/// is revealed, the one with the fewest mines around. A layout without revealed cells
+/// is opened the same way, that first click is not counted. None if counting is given
+/// up, on large boards that need guessing at every turn.
+pub fn count_guesses(layout: &Layout) -> Option<u32> {
+    let mut solver = Solver::new(layout);
+    solver.max_nodes = MAX_GUESS_SEARCH_NODES;
+    if !solver.revealed.contains(&true) {
+        solver.guess();
+    }
+    let mut guesses = 0;
+    while !solver.is_solved() {
+        // every step looks at each cell a few times
+        solver.work += 4 * solver.revealed.len();
+        if solver.work > MAX_GUESS_WORK {
+            return None;
+        }
+        if solver.step_local() || solver.step_groups() || solver.step() {
+            continue;
+        }
+        if !solver.guess() {
+            break;
+        }
+        guesses += 1;
+    }
+    Some(guesses)
+}
+
+struct Solver<'a> {
+    layout: &'a Layout,
+    revealed: Vec<bool>,
+    flagged: Vec<bool>,
+    /// Placements tried in a deduction before giving up on it.
+    max_nodes: usize,
+    /// Placements tried and cells looked at so far.
+    work: usize,
+}
+
+/// What the placements found so far allow.
+#[derive(Default)]
+struct Outcomes {
+    can_be_mine: Vec<bool>,
+    can_be_safe: Vec<bool>,
+    /// Whether the cells away from the revealed ones may be free of mines, or full.
+    interior_can_be_free: bool,
+    interior_can_be_full: bool,
+    /// Whether a placement leaves the interior partly mined.
+    interior_can_be_mixed: bool,
+}
+
+impl<'a> Solver<'a> {
+    fn new(layout: &'a Layout) -> Self {
+        let cells = layout.width() as usize * layout.height() as usize;
+        let mut solver = Solver {
+            layout,
+            revealed: vec![false; cells],
+            flagged: vec![false; cells],
+            max_nodes: MAX_SEARCH_NODES,
+            work: 0,
+        };
+        for index in 0..cells {
+            solver.revealed[index] = layout.cell(solver.coordinate(index)) == LayoutCell::Revealed;
+        }
+        solver
+    }
+
+    fn coordinate(&self, index: usize) -> Coordinate {
+        let width = self.layout.width() as usize;
+        Coordinate((index / width) as u32, (index % width) as u32)
+    }
+
+    fn neighbours(&self, index: usize) -> Vec<usize> {
+        let Coordinate(row, col) = self.coordinate(index);
+        let width = self.layout.width() as usize;
+        let mut neighbours = vec![];
+        for i in row.saturating_sub(1)..=row + 1 {
+            for j in col.saturating_sub(1)..=col + 1 {
+                let neighbour = Coordinate(i, j);
+                if neighbour != Coordinate(row, col) && self.layout.is_on_layout(neighbour) {
+                    neighbours.push(i as usize * width + j as usize);
+                }
+            }
+        }
+        neighbours
+    }
+
+    fn is_unknown(&self, index: usize) -> bool {
+        !self.revealed[index] && !self.flagged[index]
+    }
+
+    fn is_solved(&self) -> bool {
+        let safe_cells = self.revealed.len() - self.layout.mines() as usize;
+        self.revealed.iter().filter(|revealed| **revealed).count() == safe_cells
+    }
+
+    /// Hidden cells next to revealed ones, all hidden cells if nothing is revealed.
+    fn frontier(&self) -> Vec<usize> {
+        let unknown = (0..self.revealed.len()).filter(|index| self.is_unknown(*index));
+        if !self.revealed.contains(&true) {
+            return unknown.collect();
+        }
+        unknown
+            .filter(|index| {
+                self.neighbours(*index)
+                    .iter()
+                    .any(|neighbour| self.revealed[*neighbour])
+            })
+            .collect()
+    }
+
+    fn undecided(&self) -> Vec<Coordinate> {
+        self.frontier()
+            .into_iter()
+            .map(|index| self.coordinate(index))
+            .collect()
+    }
+
+    /// Reveals the safe frontier cell with the fewest mines around, any safe cell if the
+    /// frontier only has mines left. Returns whether there was one.
+    fn guess(&mut self) -> bool {
+        let safe = |index: &usize| self.layout.cell(self.coordinate(*index)) != LayoutCell::Mine;
+        let mut candidates: Vec<usize> = self.frontier().into_iter().filter(safe).collect();
+        if candidates.is_empty() {
+            candidates = (0..self.revealed.len())
+                .filter(|index| self.is_unknown(*index))
+                .filter(safe)
+                .collect();
+        }
+        let Some(cell) = candidates
+            .into_iter()
+            .min_by_key(|index| self.layout.neighbour_mines(self.coordinate(*index)))
+        else {
+            return false;
+        };
+        self.revealed[cell] = true;
+        true
+    }
+
+    /// Reveals or flags every cell that can be deduced, returns whether there was one.
+    fn step(&mut self) -> bool {
+        let frontier = self.frontier();
+        let unknown = (0..self.revealed.len())
+            .filter(|index| self.is_unknown(*index))
+            .count();
+        let interior = unknown - frontier.len();
+        let flagged = self.flagged.iter().filter(|flagged| **flagged).count();
+        let remaining = self.layout.mines() as usize - flagged;
+
+        let constraints = self.constraints(&frontier);
+        let Some(outcomes) = self.search(frontier.len(), &constraints, Some((remaining, interior)))
+        else {
+            return false;
+        };
+        let mut progress = false;
+        for (position, index) in frontier.iter().enumerate() {
+            progress |= self.decide(*index, outcomes.decision(position));
+        }
+
+        if interior > 0 && !outcomes.interior_can_be_mixed {
+            for index in 0..self.revealed.len() {
+                if !self.is_unknown(index) || frontier.contains(&index) {
+                    continue;
+                }
+                if outcomes.interior_can_be_free && !outcomes.interior_can_be_full {
+                    self.revealed[index] = true;
+                    progress = true;
+                } else if outcomes.interior_can_be_full && !outcomes.interior_can_be_free {
+                    self.flagged[index] = true;
+                    progress = true;
+                }
+            }
+        }
+        progress
+    }
+
+    /// Deduces around every number from the numbers sharing cells with it alone. Cheap
+    /// however long the frontier is, and settles most of what a player sees at a glance.
+    fn step_local(&mut self) -> bool {
+        let frontier = self.frontier();
+        let constraints = self.constraints(&frontier);
+        let mut constraints_of = vec![vec![]; frontier.len()];
+        for (constraint, (cells, _)) in constraints.iter().enumerate() {
+            for cell in cells {
+                constraints_of[*cell].push(constraint);
+            }
+        }
+
+        let mut decisions = vec![];
+        for (cells, _) in &constraints {
+            let mut nearby: Vec<usize> = cells
+                .iter()
+                .flat_map(|cell| constraints_of[*cell].iter().copied())
+                .collect();
+            nearby.sort_unstable();
+            nearby.dedup();
+            let mut window: Vec<usize> = nearby
+                .iter()
+                .flat_map(|constraint| constraints[*constraint].0.iter().copied())
+                .collect();
+            window.sort_unstable();
+            window.dedup();
+
+            let local = |cell: &usize| window.binary_search(cell).unwrap_or_default();
+            let local_constraints: Vec<(Vec<usize>, usize)> = nearby
+                .iter()
+                .map(|constraint| {
+                    let (cells, needed) = &constraints[*constraint];
+                    (cells.iter().map(local).collect(), *needed)
+                })
+                .collect();
+            if let Some(outcomes) = self.search(window.len(), &local_constraints, None) {
+                for cell in cells {
+                    decisions.push((frontier[*cell], outcomes.decision(local(cell))));
+                }
+            }
+        }
+        let mut progress = false;
+        for (index, decision) in decisions {
+            progress |= self.decide(index, decision);
+        }
+        progress
+    }
+
+    /// Like [`Solver::step`] but searches every group of frontier cells that share numbers
+    /// on its own and leaves the total mine count aside. Far cheaper on long frontiers,
+    /// what only the count settles is left to a full step.
+    fn step_groups(&mut self) -> bool {
+        let frontier = self.frontier();
+        let constraints = self.constraints(&frontier);
+
+        // cells seen by the same number end up with the same root
+        let mut parent: Vec<usize> = (0..frontier.len()).collect();
+        let root = |parent: &mut Vec<usize>, mut cell: usize| {
+            while parent[cell] != cell {
+                parent[cell] = parent[parent[cell]];
+                cell = parent[cell];
+            }
+            cell
+        };
+        for (cells, _) in &constraints {
+            for pair in cells.windows(2) {
+                let (first, second) = (root(&mut parent, pair[0]), root(&mut parent, pair[1]));
+                parent[first] = second;
+            }
+        }
+
+        let mut members = vec![vec![]; frontier.len()];
+        let mut local = vec![0; frontier.len()];
+        let mut roots = vec![0; frontier.len()];
+        for position in 0..frontier.len() {
+            roots[position] = root(&mut parent, position);
+            local[position] = members[roots[position]].len();
+            members[roots[position]].push(position);
+        }
+        let mut group_constraints = vec![vec![]; frontier.len()];
+        for (cells, needed) in constraints {
+            let group = roots[cells[0]];
+            let cells = cells.iter().map(|cell| local[*cell]).collect();
+            group_constraints[group].push((cells, needed));
+        }
+
+        // every group is searched before a cell is decided, which would change the numbers
+        let mut decisions = vec![];
+        for (group, cells) in members.iter().enumerate() {
+            if cells.is_empty() {
+                continue;
+            }
+            if let Some(outcomes) = self.search(cells.len(), &group_constraints[group], None) {
+                for (local, position) in cells.iter().enumerate() {
+                    decisions.push((frontier[*position], outcomes.decision(local)));
+                }
+            }
+        }
+        let mut progress = false;
+        for (index, decision) in decisions {
+            progress |= self.decide(index, decision);
+        }
+        progress
+    }
+
+    /// Constraints of the revealed numbers as positions of the cells and the mines missing.
+    fn constraints(&self, cells: &[usize]) -> Vec<(Vec<usize>, usize)> {
+        let mut position_of = vec![None; self.revealed.len()];
+        for (position, index) in cells.iter().enumerate() {
+            position_of[*index] = Some(position);
+        }
+        let mut constraints = vec![];
+        for index in (0..self.revealed.len()).filter(|index| self.revealed[*index]) {
+            let neighbours = self.neighbours(index);
+            let positions: Vec<usize> = neighbours
+                .iter()
+                .filter(|neighbour| self.is_unknown(**neighbour))
+                .filter_map(|neighbour| position_of[*neighbour])
+                .collect();
+            if positions.is_empty() {
+                continue;
+            }
+            let flags = neighbours
+                .iter()
+                .filter(|neighbour| self.flagged[**neighbour])
+                .count();
+            let mines = self.layout.neighbour_mines(self.coordinate(index)) as usize;
+            constraints.push((positions, mines.saturating_sub(flags)));
+        }
+        constraints
+    }
+
+    /// What the placements of mines on the cells allow, none if the search gave up.
+    fn search(
+        &mut self,
+        cell_count: usize,
+        constraints: &[(Vec<usize>, usize)],
+        mine_count: Option<(usize, usize)>,
+    ) -> Option<Outcomes> {
+        let mut search = Search::new(cell_count, constraints, mine_count, self.max_nodes);
+        search.place(0, 0);
+        self.work += search.nodes;
+        (search.nodes <= search.max_nodes).then_some(search.outcomes)
+    }
+
+    /// Reveals the cell if it is safe or flags it if it is a mine, returns whether it did.
+    fn decide(&mut self, index: usize, decision: Option<bool>) -> bool {
+        match decision {
+            Some(true) => self.flagged[index] = true,
+            Some(false) => self.revealed[index] = true,
+            None => return false,
+        }
+        true
+    }
+}
+
+impl Outcomes {
+    /// Whether the cell at the position is surely a mine, none if it may be either.
+    fn decision(&self, position: usize) -> Option<bool> {
+        match (self.can_be_mine[position], self.can_be_safe[position]) {
+            (true, false) => Some(true),
+            (false, true) => Some(false),
+            _ => None,
+        }
+    }
+}
+
+/// Backtracking over the mine placements of the frontier.
+struct Search {
+    constraints_of: Vec<Vec<usize>>,
+    needed: Vec<usize>,
+    mines_in: Vec<usize>,
+    open_in: Vec<usize>,
+    assignment: Vec<bool>,
+    /// Mines left and cells away from the frontier, a placement has to leave a number of
+    /// mines that fits those cells. None leaves the total mine count aside.
+    mine_count: Option<(usize, usize)>,
+    nodes: usize,
+    max_nodes: usize,
+    outcomes: Outcomes,
+}
+
+impl Search {
+    fn new(
+        cell_count: usize,
+        constraints: &[(Vec<usize>, usize)],
+        mine_count: Option<(usize, usize)>,
+        max_nodes: usize,
+    ) -> Self {
+        let mut search = Search {
+            constraints_of: vec![vec![]; cell_count],
+            needed: constraints.iter().map(|(_, needed)| *needed).collect(),
+            mines_in: vec![0; constraints.len()],
+            open_in: constraints.iter().map(|(cells, _)| cells.len()).collect(),
+            assignment: vec![false; cell_count],
+            mine_count,
+            nodes: 0,
+            max_nodes,
+            outcomes: Outcomes {
+                can_be_mine: vec![false; cell_count],
+                can_be_safe: vec![false; cell_count],
+                ..Outcomes::default()
+            },
+        };
+        for (constraint, (cells, _)) in constraints.iter().enumerate() {
+            for cell in cells {
+                search.constraints_of[*cell].push(constraint);
+            }
+        }
+        search
+    }
+
+    fn place(&mut self, position: usize, mines: usize) {
+        self.nodes += 1;
+        if self.nodes > self.max_nodes
+            || self
+                .mine_count
+                .is_some_and(|(remaining, _)| mines > remaining)
+        {
+            return;
+        }
+        if position == self.assignment.len() {
+            if let Some((remaining, interior)) = self.mine_count {
+                if remaining - mines > interior {
+                    return;
+                }
+            }
+            for (position, mine) in self.assignment.iter().enumerate() {
+                if *mine {
+                    self.outcomes.can_be_mine[position] = true;
+                } else {
+                    self.outcomes.can_be_safe[position] = true;
+                }
+            }
+            if let Some((remaining, interior)) = self.mine_count {
+                let interior_mines = remaining - mines;
+                if interior_mines == 0 {
+                    self.outcomes.interior_can_be_free = true;
+                } else if interior_mines == interior {
+                    self.outcomes.interior_can_be_full = true;
+                } else {
+                    self.outcomes.interior_can_be_mixed = true;
+                }
+            }
+            return;
+        }
+
+        for mine in [false, true] {
+            self.assignment[position] = mine;
+            let mut consistent = true;
+            for constraint in &self.constraints_of[position] {
+                self.open_in[*constraint] -= 1;
+                self.mines_in[*constraint] += mine as usize;
+                let (mines_in, open_in) = (self.mines_in[*constraint], self.open_in[*constraint]);
+                consistent &= mines_in <= self.needed[*constraint]
+                    && mines_in + open_in >= self.needed[*constraint];
+            }
+            if consistent {
+                self.place(position + 1, mines + mine as usize);
+            }
+            for constraint in &self.constraints_of[position] {
+                self.open_in[*constraint] += 1;
+                self.mines_in[*constraint] -= mine as usize;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::puzzles::PUZZLES;
+
+    #[test]
+    fn solve_layout_test() {
+        for puzzle in &PUZZLES {
+            assert_eq!(
+                Verdict::Solvable,
+                solve(&puzzle.layout()),
+                "{} needs guessing",
+                puzzle.name
+            );
+        }
+
+        // the two hidden cells are a coin flip
+        let layout = Layout::try_from("*#\n..\n").unwrap();
+        assert_eq!(
+            Verdict::Stuck(vec![Coordinate(0, 0), Coordinate(0, 1)]),
+            solve(&layout)
+        );
+
+        // the total mine count settles the cell behind the mine
+        let layout = Layout::try_from(".#*#\n").unwrap();
+        assert_eq!(Verdict::Solvable, solve(&layout));
+    }
+
+    #[test]
+    fn count_guesses_test() {
+        for puzzle in &PUZZLES {
+            assert_eq!(Some(0), count_guesses(&puzzle.layout()));
+        }
+
+        // opened at the empty corner, the two cells on the right are a coin flip
+        let layout = Layout::try_from("###*\n####\n").unwrap();
+        assert_eq!(Some(1), count_guesses(&layout));
+        let layout = Layout::try_from("*#\n..\n").unwrap();
+        assert_eq!(Some(1), count_guesses(&layout));
+    }
+}
@@ -0,0 +1,83 @@
+//! Audible feedback on mine hits, wins and invalid commands. The terminal bell rings for
+//! every cue enabled with `sounds` in the config, built with the `audio` feature a short
+//! tone is played as well.
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Cue {
+    MineHit,
+    Win,
+    InvalidCommand,
+}
+
+impl Cue {
+    /// Frequency in Hz and length in ms of the tone of the cue.
+    pub fn tone(&self) -> (f32, u64) {
+        match self {
+            Cue::MineHit => (220.0, 400),
+            Cue::Win => (880.0, 300),
+            Cue::InvalidCommand => (440.0, 80),
+        }
+    }
+}
+
+/// Which cues are heard, all are silent by default.
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(default)]
+pub struct Sounds {
+    pub mine_hit: bool,
+    pub win: bool,
+    pub invalid_command: bool,
+}
+
+impl Sounds {
+    pub fn plays(&self, cue: Cue) -> bool {
+        match cue {
+            Cue::MineHit => self.mine_hit,
+            Cue::Win => self.win,
+            Cue::InvalidCommand => self.invalid_command,
+        }
+    }
+}
+
+/// Plays the tone of the cue on the default output device without waiting for it,
+/// failures are ignored since the bell rings anyway.
+#[cfg(feature = "audio")]
+pub fn play(cue: Cue) {
+    std::thread::spawn(move || {
+        let _ = play_tone(cue.tone());
+    });
+}
+
+#[cfg(feature = "audio")]
+fn play_tone((frequency, millis): (f32, u64)) -> Result<(), Box<dyn std::error::Error>> {
+    use cpal::traits::{DeviceTrait, HostTrait, StreamTrait};
+
+    let device = cpal::default_host()
+        .default_output_device()
+        .ok_or("no output device")?;
+    let config = device.default_output_config()?;
+    if config.sample_format() != cpal::SampleFormat::F32 {
+        return Err("output device does not take f32 samples".into());
+    }
+    let sample_rate = config.sample_rate().0 as f32;
+    let channels = config.channels() as usize;
+
+    let mut sample = 0.0f32;
+    let stream = device.build_output_stream(
+        &config.into(),
+        move |data: &mut [f32], _| {
+            for frame in data.chunks_mut(channels) {
+                let value = (sample * frequency * std::f32::consts::TAU / sample_rate).sin();
+                frame.fill(value * 0.2);
+                sample += 1.0;
+            }
+        },
+        |_| {},
+        None,
+    )?;
+    stream.play()?;
+    std::thread::sleep(std::time::Duration::from_millis(millis));
+    Ok(())
+}
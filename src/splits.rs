@@ -0,0 +1,220 @@
+//! Speedrun style split times, taken when fixed percentages of the board's 3BV are solved.
+
+use std::{collections::BTreeMap, fmt::Write, io, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    locale::{self, Message},
+    storage,
+};
+
+pub(crate) const PERSONAL_BESTS_FILE: &str = "personal_bests.json";
+
+pub const SPLIT_PERCENTAGES: [u32; 4] = [25, 50, 75, 100];
+
+#[derive(Default)]
+pub struct SplitTracker {
+    splits: [Option<Duration>; 4],
+    finish: Option<Duration>,
+}
+
+impl SplitTracker {
+    pub fn new() -> Self {
+        SplitTracker::default()
+    }
+
+    /// Records the splits reached with the given progress and returns the indices
+    /// of the ones that were reached just now.
+    pub fn update(&mut self, solved: u32, total: u32, elapsed: Duration) -> Vec<usize> {
+        let mut reached = vec![];
+        if total == 0 {
+            return reached;
+        }
+
+        for (index, percentage) in SPLIT_PERCENTAGES.iter().enumerate() {
+            if self.splits[index].is_none() && solved * 100 >= percentage * total {
+                self.splits[index] = Some(elapsed);
+                reached.push(index);
+            }
+        }
+
+        reached
+    }
+
+    pub fn finish(&mut self, elapsed: Duration) {
+        self.finish = Some(elapsed);
+    }
+
+    pub fn split_line(&self, index: usize, personal_best: Option<&PersonalBest>) -> String {
+        let time = self.splits[index].unwrap_or_default();
+        let best = personal_best.and_then(|best| best.split(index));
+
+        format!(
+            "Split {}%: {}{}",
+            SPLIT_PERCENTAGES[index],
+            format_time(time),
+            best.map(|best| format!(" ({})", format_delta(time, best)))
+                .unwrap_or_default()
+        )
+    }
+
+    pub fn summary(&self, personal_best: Option<&PersonalBest>) -> String {
+        let mut summary = String::new();
+        let _ = writeln!(summary, "{}", locale::text(Message::SplitHeader));
+
+        let rows = SPLIT_PERCENTAGES
+            .iter()
+            .enumerate()
+            .map(|(index, percentage)| {
+                (
+                    format!("{}%", percentage),
+                    self.splits[index],
+                    personal_best.and_then(|best| best.split(index)),
+                )
+            })
+            .chain(std::iter::once((
+                locale::text(Message::Finish),
+                self.finish,
+                personal_best.map(|best| best.finish()),
+            )));
+
+        for (label, time, best) in rows {
+            let delta = match (time, best) {
+                (Some(time), Some(best)) => format_delta(time, best),
+                _ => "-".to_string(),
+            };
+            let _ = writeln!(
+                summary,
+                "{:<8}{:>12}{:>12}{:>10}",
+                label,
+                time.map(format_time).unwrap_or("-".to_string()),
+                best.map(format_time).unwrap_or("-".to_string()),
+                delta
+            );
+        }
+
+        summary
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PersonalBest {
+    splits_ms: Vec<Option<u64>>,
+    finish_ms: u64,
+}
+
+impl PersonalBest {
+    fn split(&self, index: usize) -> Option<Duration> {
+        self.splits_ms
+            .get(index)
+            .copied()
+            .flatten()
+            .map(Duration::from_millis)
+    }
+
+    pub fn finish(&self) -> Duration {
+        Duration::from_millis(self.finish_ms)
+    }
+}
+
+/// Best winning run for every difficulty, see [`crate::GameConfiguration::key`].
+#[derive(Serialize, Deserialize, Default)]
+pub struct PersonalBests {
+    runs: BTreeMap<String, PersonalBest>,
+}
+
+impl PersonalBests {
+    pub fn load() -> Self {
+        storage::load_json(PERSONAL_BESTS_FILE)
+    }
+
+    pub fn save(&self) -> io::Result<()> {
+        storage::save_json(PERSONAL_BESTS_FILE, self)
+    }
+
+    /// Personal bests ordered by difficulty.
+    pub fn iter(&self) -> impl Iterator<Item = (&String, &PersonalBest)> {
+        self.runs.iter()
+    }
+
+    pub fn get(&self, difficulty: &str) -> Option<&PersonalBest> {
+        self.runs.get(difficulty)
+    }
+
+    /// Keeps the run if it finished faster than the current personal best, returns
+    /// whether it did.
+    pub fn submit(&mut self, difficulty: &str, tracker: &SplitTracker) -> bool {
+        let Some(finish) = tracker.finish else {
+            return false;
+        };
+
+        let finish_ms = finish.as_millis() as u64;
+        if let Some(best) = self.runs.get(difficulty) {
+            if best.finish_ms <= finish_ms {
+                return false;
+            }
+        }
+
+        self.runs.insert(
+            difficulty.to_string(),
+            PersonalBest {
+                splits_ms: tracker
+                    .splits
+                    .iter()
+                    .map(|split| split.map(|split| split.as_millis() as u64))
+                    .collect(),
+                finish_ms,
+            },
+        );
+        true
+    }
+}
+
+fn format_time(time: Duration) -> String {
+    format!("{:.2} s", time.as_secs_f64())
+}
+
+fn format_delta(time: Duration, best: Duration) -> String {
+    let delta = time.as_secs_f64() - best.as_secs_f64();
+    format!("{:+.2}", delta)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn splits_are_taken_once_test() {
+        let mut tracker = SplitTracker::new();
+
+        assert_eq!(vec![0], tracker.update(3, 10, Duration::from_secs(1)));
+        assert_eq!(
+            Vec::<usize>::new(),
+            tracker.update(4, 10, Duration::from_secs(2))
+        );
+        assert_eq!(
+            vec![1, 2, 3],
+            tracker.update(10, 10, Duration::from_secs(3))
+        );
+        assert_eq!(Some(Duration::from_secs(1)), tracker.splits[0]);
+        assert_eq!(Some(Duration::from_secs(3)), tracker.splits[3]);
+    }
+
+    #[test]
+    fn personal_best_only_improves_test() {
+        let mut personal_bests = PersonalBests::default();
+        let mut tracker = SplitTracker::new();
+        tracker.update(10, 10, Duration::from_secs(5));
+        tracker.finish(Duration::from_secs(5));
+        assert!(personal_bests.submit("10x10-10", &tracker));
+
+        let mut slower = SplitTracker::new();
+        slower.finish(Duration::from_secs(6));
+        assert!(!personal_bests.submit("10x10-10", &slower));
+        assert_eq!(
+            Some(Duration::from_secs(5)),
+            personal_bests.get("10x10-10").unwrap().split(3)
+        );
+    }
+}
@@ -0,0 +1,260 @@
+//! Log of every finished game of a profile.
+
+use std::{collections::BTreeMap, fmt::Display, io};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{calendar, storage, BoardCommand, GameConfiguration, GameResolve, GameResult};
+
+#[derive(Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    Won,
+    Lost,
+    Quit,
+}
+
+impl Display for Outcome {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Outcome::Won => write!(f, "won"),
+            Outcome::Lost => write!(f, "lost"),
+            Outcome::Quit => write!(f, "quit"),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct GameRecord {
+    /// Days since the unix epoch.
+    pub day: u64,
+    pub configuration: GameConfiguration,
+    pub seed: u64,
+    pub outcome: Outcome,
+    pub time_ms: u64,
+    pub flags_placed: u32,
+    pub cells_revealed: u32,
+    /// Whether a mine hit was forgiven.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub forgiven: bool,
+    /// Whether satisfied numbers were chorded for the player.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub assisted: bool,
+    /// Row and column of the move that hit a mine in a lost game.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mine_hit: Option<(u32, u32)>,
+}
+
+impl GameRecord {
+    pub fn new(result: &GameResult) -> Self {
+        GameRecord {
+            day: calendar::today(),
+            configuration: result.replay.configuration,
+            seed: result.replay.seed,
+            outcome: match result.resolve {
+                GameResolve::AllMinesDiscovered => Outcome::Won,
+                GameResolve::MineHit => Outcome::Lost,
+                GameResolve::Quit | GameResolve::Continue => Outcome::Quit,
+            },
+            time_ms: result.elapsed.as_millis() as u64,
+            flags_placed: result.flags_placed,
+            cells_revealed: result.cells_revealed,
+            forgiven: result.forgiven,
+            assisted: result.assisted,
+            mine_hit: match result.resolve {
+                GameResolve::MineHit => result
+                    .replay
+                    .moves
+                    .last()
+                    .and_then(|last| BoardCommand::try_from(&last.command[..]).ok())
+                    .and_then(|command| command.cell())
+                    .map(|cell| (cell.0, cell.1)),
+                _ => None,
+            },
+        }
+    }
+}
+
+/// Adds a finished game to the active profile's history.
+pub fn record(game: GameRecord) -> io::Result<()> {
+    storage::backend().append_game(storage::active_profile(), &game)
+}
+
+#[derive(Default, Debug)]
+pub struct Statistics {
+    games: Vec<GameRecord>,
+}
+
+impl Statistics {
+    pub fn load() -> Self {
+        Statistics {
+            games: storage::backend()
+                .games(storage::active_profile())
+                .unwrap_or_default(),
+        }
+    }
+
+    pub fn games(&self) -> &[GameRecord] {
+        &self.games
+    }
+
+    /// Percentage of the games won on every day a game was played, oldest first.
+    pub fn daily_win_rates(&self) -> Vec<(u64, u32)> {
+        let mut days: BTreeMap<u64, (u32, u32)> = BTreeMap::new();
+        for game in &self.games {
+            let entry = days.entry(game.day).or_default();
+            entry.0 += u32::from(game.outcome == Outcome::Won);
+            entry.1 += 1;
+        }
+        days.into_iter()
+            .map(|(day, (won, played))| (day, won * 100 / played))
+            .collect()
+    }
+
+    /// Times of the won games of every difficulty, in the order they were played.
+    pub fn win_times(&self) -> BTreeMap<String, Vec<u64>> {
+        let mut times: BTreeMap<String, Vec<u64>> = BTreeMap::new();
+        for game in self
+            .games
+            .iter()
+            .filter(|game| game.outcome == Outcome::Won)
+        {
+            times
+                .entry(game.configuration.key())
+                .or_default()
+                .push(game.time_ms);
+        }
+        times
+    }
+
+    /// Wins in a row, a lost or quit game ends a streak.
+    pub fn streaks(&self) -> Streaks {
+        let mut streaks = Streaks::default();
+        for game in &self.games {
+            if game.outcome == Outcome::Won {
+                streaks.current += 1;
+                streaks.longest = streaks.longest.max(streaks.current);
+            } else {
+                streaks.current = 0;
+            }
+        }
+        streaks
+    }
+}
+
+#[derive(Default, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Streaks {
+    pub current: u32,
+    pub longest: u32,
+}
+
+/// Counts the times in buckets of equal width from the fastest to the slowest, every
+/// bucket as its lower bound and count.
+pub fn histogram(times: &[u64], buckets: usize) -> Vec<(u64, u64)> {
+    let (Some(&fastest), Some(&slowest)) = (times.iter().min(), times.iter().max()) else {
+        return vec![];
+    };
+    let buckets = buckets.max(1) as u64;
+    let width = (slowest - fastest + 1).div_ceil(buckets);
+    let mut counts: Vec<(u64, u64)> = (0..buckets)
+        .map(|bucket| (fastest + bucket * width, 0))
+        .take_while(|(lower, _)| *lower <= slowest)
+        .collect();
+    for time in times {
+        counts[((time - fastest) / width) as usize].1 += 1;
+    }
+    counts
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // played, won and the fastest win for every difficulty
+        let mut per_difficulty: BTreeMap<String, (u32, u32, Option<u64>)> = BTreeMap::new();
+        for game in &self.games {
+            let entry = per_difficulty
+                .entry(game.configuration.key())
+                .or_insert((0, 0, None));
+            entry.0 += 1;
+            if game.outcome == Outcome::Won {
+                entry.1 += 1;
+                entry.2 = Some(entry.2.map_or(game.time_ms, |best| best.min(game.time_ms)));
+            }
+        }
+
+        writeln!(
+            f,
+            "{:<14}{:>8}{:>8}{:>10}{:>12}",
+            "Difficulty", "Played", "Won", "Win rate", "Best"
+        )?;
+        for (difficulty, (played, won, best)) in per_difficulty {
+            writeln!(
+                f,
+                "{:<14}{:>8}{:>8}{:>9.0}%{:>12}",
+                difficulty,
+                played,
+                won,
+                won as f64 * 100.0 / played as f64,
+                best.map_or("-".to_string(), |best| format!(
+                    "{:.2} s",
+                    best as f64 / 1000.0
+                ))
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn game(day: u64, outcome: Outcome, time_ms: u64) -> GameRecord {
+        GameRecord {
+            day,
+            configuration: GameConfiguration::beginner(),
+            seed: 0,
+            outcome,
+            time_ms,
+            flags_placed: 0,
+            cells_revealed: 0,
+            forgiven: false,
+            assisted: false,
+            mine_hit: None,
+        }
+    }
+
+    #[test]
+    fn dashboard_figures_test() {
+        let statistics = Statistics {
+            games: vec![
+                game(3, Outcome::Won, 12_000),
+                game(3, Outcome::Lost, 4_000),
+                game(4, Outcome::Won, 30_000),
+                game(4, Outcome::Won, 21_000),
+                game(4, Outcome::Won, 14_000),
+                game(5, Outcome::Quit, 1_000),
+                game(5, Outcome::Won, 18_000),
+            ],
+        };
+
+        assert_eq!(
+            vec![(3, 50), (4, 100), (5, 50)],
+            statistics.daily_win_rates()
+        );
+        assert_eq!(
+            Streaks {
+                current: 1,
+                longest: 3
+            },
+            statistics.streaks()
+        );
+        let times = &statistics.win_times()[&GameConfiguration::beginner().key()];
+        assert_eq!(&vec![12_000, 30_000, 21_000, 14_000, 18_000], times);
+        assert_eq!(
+            vec![(12_000, 3), (18_001, 1), (24_002, 1)],
+            histogram(times, 3)
+        );
+        assert_eq!(vec![(5, 2)], histogram(&[5, 5], 4));
+        assert!(histogram(&[], 4).is_empty());
+    }
+}
@@ -0,0 +1,96 @@
+//! The default backend, every document is a json file in the profile's directory.
+
+use std::{fs, io, path::PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use super::{write_atomically, Storage};
+use crate::statistics::GameRecord;
+
+const GAMES_DOCUMENT: &str = "statistics.json";
+
+#[derive(Serialize, Deserialize, Default)]
+struct GameLog {
+    games: Vec<GameRecord>,
+}
+
+pub struct JsonStorage {
+    profiles_dir: PathBuf,
+}
+
+impl JsonStorage {
+    pub fn new(data_dir: PathBuf) -> Self {
+        JsonStorage {
+            profiles_dir: data_dir.join("profiles"),
+        }
+    }
+
+    fn game_log(&self, profile: &str) -> io::Result<GameLog> {
+        match self.load_document(profile, GAMES_DOCUMENT)? {
+            Some(content) => Ok(serde_json::from_str(&content)?),
+            None => Ok(GameLog::default()),
+        }
+    }
+}
+
+impl Storage for JsonStorage {
+    fn profiles(&self) -> io::Result<Vec<String>> {
+        let entries = match fs::read_dir(&self.profiles_dir) {
+            Ok(entries) => entries,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err),
+        };
+
+        let mut profiles: Vec<String> = entries
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| entry.path().is_dir())
+            .filter_map(|entry| entry.file_name().into_string().ok())
+            .collect();
+        profiles.sort();
+        Ok(profiles)
+    }
+
+    fn load_document(&self, profile: &str, name: &str) -> io::Result<Option<String>> {
+        match fs::read_to_string(self.profiles_dir.join(profile).join(name)) {
+            Ok(content) => Ok(Some(content)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(err),
+        }
+    }
+
+    fn save_document(&self, profile: &str, name: &str, content: &str) -> io::Result<()> {
+        let dir = self.profiles_dir.join(profile);
+        fs::create_dir_all(&dir)?;
+        write_atomically(&dir.join(name), content)
+    }
+
+    fn append_game(&self, profile: &str, game: &GameRecord) -> io::Result<()> {
+        self.append_games(profile, std::slice::from_ref(game))
+    }
+
+    // the log is written once instead of once per game
+    fn append_games(&self, profile: &str, games: &[GameRecord]) -> io::Result<()> {
+        let mut game_log = self.game_log(profile)?;
+        game_log.games.extend_from_slice(games);
+        self.save_document(
+            profile,
+            GAMES_DOCUMENT,
+            &serde_json::to_string_pretty(&game_log)?,
+        )
+    }
+
+    fn replace_games(&self, profile: &str, games: &[GameRecord]) -> io::Result<()> {
+        let game_log = GameLog {
+            games: games.to_vec(),
+        };
+        self.save_document(
+            profile,
+            GAMES_DOCUMENT,
+            &serde_json::to_string_pretty(&game_log)?,
+        )
+    }
+
+    fn games(&self, profile: &str) -> io::Result<Vec<GameRecord>> {
+        Ok(self.game_log(profile)?.games)
+    }
+}
@@ -0,0 +1,65 @@
+//! Backend keeping everything in memory, for tests of code written against [`Storage`].
+
+use std::{collections::BTreeMap, io, sync::Mutex};
+
+use super::Storage;
+use crate::statistics::GameRecord;
+
+#[derive(Default)]
+pub struct MemoryStorage {
+    documents: Mutex<BTreeMap<(String, String), String>>,
+    games: Mutex<Vec<(String, GameRecord)>>,
+}
+
+impl Storage for MemoryStorage {
+    fn profiles(&self) -> io::Result<Vec<String>> {
+        let mut profiles: Vec<String> = self
+            .documents
+            .lock()
+            .unwrap()
+            .keys()
+            .map(|(profile, _)| profile.clone())
+            .chain(self.games.lock().unwrap().iter().map(|(p, _)| p.clone()))
+            .collect();
+        profiles.sort();
+        profiles.dedup();
+        Ok(profiles)
+    }
+
+    fn load_document(&self, profile: &str, name: &str) -> io::Result<Option<String>> {
+        let key = (profile.to_string(), name.to_string());
+        Ok(self.documents.lock().unwrap().get(&key).cloned())
+    }
+
+    fn save_document(&self, profile: &str, name: &str, content: &str) -> io::Result<()> {
+        let key = (profile.to_string(), name.to_string());
+        self.documents
+            .lock()
+            .unwrap()
+            .insert(key, content.to_string());
+        Ok(())
+    }
+
+    fn append_game(&self, profile: &str, game: &GameRecord) -> io::Result<()> {
+        let entry = (profile.to_string(), game.clone());
+        self.games.lock().unwrap().push(entry);
+        Ok(())
+    }
+
+    fn replace_games(&self, profile: &str, games: &[GameRecord]) -> io::Result<()> {
+        self.games
+            .lock()
+            .unwrap()
+            .retain(|(owner, _)| owner != profile);
+        self.append_games(profile, games)
+    }
+
+    fn games(&self, profile: &str) -> io::Result<Vec<GameRecord>> {
+        let games = self.games.lock().unwrap();
+        Ok(games
+            .iter()
+            .filter(|(owner, _)| owner == profile)
+            .map(|(_, game)| game.clone())
+            .collect())
+    }
+}
@@ -0,0 +1,236 @@
+//! Everything the game keeps between sessions goes through a [`Storage`] backend,
+//! json files in the data directory by default or an SQLite database with the
+//! `sqlite` feature. Every player profile has its own data.
+
+use std::{
+    env, fs, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+
+use crate::statistics::GameRecord;
+
+mod json;
+#[cfg(test)]
+pub(crate) mod memory;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+pub const DEFAULT_PROFILE: &str = "default";
+
+static ACTIVE_PROFILE: OnceLock<String> = OnceLock::new();
+static BACKEND: OnceLock<Box<dyn Storage>> = OnceLock::new();
+
+pub trait Storage: Send + Sync {
+    /// Names of all profiles that have stored data, sorted.
+    fn profiles(&self) -> io::Result<Vec<String>>;
+
+    /// Reads a json document of the profile, `None` if it was never saved.
+    fn load_document(&self, profile: &str, name: &str) -> io::Result<Option<String>>;
+
+    fn save_document(&self, profile: &str, name: &str, content: &str) -> io::Result<()>;
+
+    /// Adds a finished game to the profile's history.
+    fn append_game(&self, profile: &str, game: &GameRecord) -> io::Result<()>;
+
+    /// Adds many finished games at once, e.g. of an imported profile.
+    fn append_games(&self, profile: &str, games: &[GameRecord]) -> io::Result<()> {
+        games
+            .iter()
+            .try_for_each(|game| self.append_game(profile, game))
+    }
+
+    /// Replaces the profile's whole game history, e.g. with the one of another computer.
+    fn replace_games(&self, profile: &str, games: &[GameRecord]) -> io::Result<()>;
+
+    /// The profile's game history, oldest game first.
+    fn games(&self, profile: &str) -> io::Result<Vec<GameRecord>>;
+}
+
+#[derive(Serialize, Deserialize, Default, Clone, Copy, Debug, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    #[default]
+    Json,
+    Sqlite,
+}
+
+/// Directory holding persisted game data, `MINESWEEPER_DATA_DIR` overrides the
+/// default of `~/.minesweeper`.
+pub fn data_dir() -> PathBuf {
+    if let Ok(dir) = env::var("MINESWEEPER_DATA_DIR") {
+        return PathBuf::from(dir);
+    }
+
+    match env::var("HOME") {
+        Ok(home) => PathBuf::from(home).join(".minesweeper"),
+        Err(_) => PathBuf::from(".minesweeper"),
+    }
+}
+
+/// Opens the backend used for the rest of the session, without a call the json
+/// backend is used.
+pub fn init(backend: Backend) -> io::Result<()> {
+    let storage: Box<dyn Storage> = match backend {
+        Backend::Json => Box::new(json::JsonStorage::new(data_dir())),
+        #[cfg(feature = "sqlite")]
+        Backend::Sqlite => Box::new(sqlite::SqliteStorage::open(
+            &data_dir().join("minesweeper.db"),
+        )?),
+        #[cfg(not(feature = "sqlite"))]
+        Backend::Sqlite => {
+            return Err(io::Error::other(
+                "the sqlite backend needs the `sqlite` feature",
+            ))
+        }
+    };
+
+    let _ = BACKEND.set(storage);
+    Ok(())
+}
+
+pub fn backend() -> &'static dyn Storage {
+    BACKEND
+        .get_or_init(|| Box::new(json::JsonStorage::new(data_dir())))
+        .as_ref()
+}
+
+/// Names of all profiles that have been used before, sorted.
+pub fn profiles() -> Vec<String> {
+    backend().profiles().unwrap_or_default()
+}
+
+/// Profile names double as directory names, so only a safe subset of characters is allowed.
+pub fn is_valid_profile_name(name: &str) -> bool {
+    !name.is_empty()
+        && name.len() <= 32
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Selects the profile whose data is read and written for the rest of the session.
+pub fn select_profile(name: &str) {
+    let _ = ACTIVE_PROFILE.set(name.to_string());
+}
+
+pub fn active_profile() -> &'static str {
+    ACTIVE_PROFILE
+        .get()
+        .map_or(DEFAULT_PROFILE, |name| name.as_str())
+}
+
+/// Directory for files of the active profile that are meant to be shared.
+pub fn profile_dir() -> PathBuf {
+    data_dir().join("profiles").join(active_profile())
+}
+
+/// Reads a json document of the active profile, a missing or broken document yields
+/// the default value. A broken document is kept as `<name>.broken` first, so saving the
+/// default does not lose it.
+pub fn load_json<T: DeserializeOwned + Default>(name: &str) -> T {
+    load_document_json(backend(), active_profile(), name)
+}
+
+fn load_document_json<T: DeserializeOwned + Default>(
+    storage: &dyn Storage,
+    profile: &str,
+    name: &str,
+) -> T {
+    let Some(content) = storage.load_document(profile, name).ok().flatten() else {
+        return T::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        let backup = format!("{}.broken", name);
+        tracing::warn!(%err, profile, name, backup, "broken document replaced by defaults");
+        if let Err(err) = storage.save_document(profile, &backup, &content) {
+            tracing::warn!(%err, backup, "could not keep the broken document");
+        }
+        T::default()
+    })
+}
+
+pub fn save_json<T: Serialize>(name: &str, value: &T) -> io::Result<()> {
+    let content = serde_json::to_string_pretty(value)?;
+    backend().save_document(active_profile(), name, &content)
+}
+
+/// Reads a json file shared by all profiles, these are always plain files in the
+/// data directory. A broken file is kept as `<file_name>.broken` like a broken document.
+pub fn load_global_json<T: DeserializeOwned + Default>(file_name: &str) -> T {
+    let path = data_dir().join(file_name);
+    let Ok(content) = fs::read_to_string(&path) else {
+        return T::default();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|err| {
+        let backup = path.with_file_name(format!("{}.broken", file_name));
+        tracing::warn!(%err, ?backup, "broken file replaced by defaults");
+        if let Err(err) = fs::write(&backup, &content) {
+            tracing::warn!(%err, ?backup, "could not keep the broken file");
+        }
+        T::default()
+    })
+}
+
+/// Writes a json file into the active profile's directory, no matter the backend,
+/// and returns its path.
+pub fn export_json<T: Serialize>(file_name: &str, value: &T) -> io::Result<PathBuf> {
+    export_file(file_name, serde_json::to_string_pretty(value)?.as_bytes())
+}
+
+/// Writes a file into the active profile's directory, no matter the backend, and returns
+/// its path.
+pub fn export_file(file_name: &str, content: &[u8]) -> io::Result<PathBuf> {
+    let dir = profile_dir();
+    fs::create_dir_all(&dir)?;
+    let path = dir.join(file_name);
+    fs::write(&path, content)?;
+    Ok(path)
+}
+
+fn write_atomically(path: &Path, content: &str) -> io::Result<()> {
+    let temporary = path.with_extension("tmp");
+    fs::write(&temporary, content)?;
+    fs::rename(temporary, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn profile_name_test() {
+        assert!(is_valid_profile_name("mom"));
+        assert!(is_valid_profile_name("player_2-b"));
+        assert!(!is_valid_profile_name(""));
+        assert!(!is_valid_profile_name("../escape"));
+        assert!(!is_valid_profile_name("with space"));
+    }
+
+    #[test]
+    fn broken_document_test() {
+        let storage = memory::MemoryStorage::default();
+        let load = |name| load_document_json::<Vec<u32>>(&storage, "anna", name);
+        assert_eq!(Vec::<u32>::new(), load("missing.json"));
+
+        storage
+            .save_document("anna", "kept.json", "[1, 2]")
+            .unwrap();
+        assert_eq!(vec![1, 2], load("kept.json"));
+        assert_eq!(
+            None,
+            storage.load_document("anna", "kept.json.broken").unwrap()
+        );
+
+        storage
+            .save_document("anna", "broken.json", "[1, 2")
+            .unwrap();
+        assert_eq!(Vec::<u32>::new(), load("broken.json"));
+        assert_eq!(
+            Some("[1, 2".to_string()),
+            storage.load_document("anna", "broken.json.broken").unwrap()
+        );
+    }
+}
@@ -0,0 +1,289 @@
+//! SQLite backend, keeps all profiles in one database so long game histories don't
+//! have to be rewritten on every game and several terminals can use it at once.
+
+use std::{io, path::Path, sync::Mutex, time::Duration};
+
+use rusqlite::{params, Connection, OptionalExtension};
+
+use super::Storage;
+use crate::{
+    generator::{Distribution, Symmetry},
+    statistics::{GameRecord, Outcome},
+    Adjacency, GameConfiguration,
+};
+
+const SCHEMA: &str = "
+    CREATE TABLE IF NOT EXISTS documents (
+        profile TEXT NOT NULL,
+        name TEXT NOT NULL,
+        content TEXT NOT NULL,
+        PRIMARY KEY (profile, name)
+    );
+    CREATE TABLE IF NOT EXISTS games (
+        id INTEGER PRIMARY KEY AUTOINCREMENT,
+        profile TEXT NOT NULL,
+        day INTEGER NOT NULL,
+        difficulty TEXT NOT NULL,
+        width INTEGER NOT NULL,
+        height INTEGER NOT NULL,
+        mines INTEGER NOT NULL,
+        seed TEXT NOT NULL,
+        outcome TEXT NOT NULL,
+        time_ms INTEGER NOT NULL,
+        flags_placed INTEGER NOT NULL,
+        cells_revealed INTEGER NOT NULL,
+        wrap_edges INTEGER NOT NULL DEFAULT 0,
+        adjacency TEXT NOT NULL DEFAULT 'standard',
+        mines_per_cell INTEGER NOT NULL DEFAULT 1,
+        anti_mines INTEGER NOT NULL DEFAULT 0,
+        distribution TEXT NOT NULL DEFAULT 'uniform',
+        symmetry TEXT NOT NULL DEFAULT 'asymmetric',
+        forgiven INTEGER NOT NULL DEFAULT 0,
+        assisted INTEGER NOT NULL DEFAULT 0,
+        mine_hit_row INTEGER,
+        mine_hit_col INTEGER
+    );
+    CREATE INDEX IF NOT EXISTS games_by_profile ON games (profile, difficulty);
+";
+
+/// Columns added after the first release, with their definitions.
+const MIGRATED_COLUMNS: [(&str, &str); 10] = [
+    ("wrap_edges", "wrap_edges INTEGER NOT NULL DEFAULT 0"),
+    ("adjacency", "adjacency TEXT NOT NULL DEFAULT 'standard'"),
+    (
+        "mines_per_cell",
+        "mines_per_cell INTEGER NOT NULL DEFAULT 1",
+    ),
+    ("anti_mines", "anti_mines INTEGER NOT NULL DEFAULT 0"),
+    (
+        "distribution",
+        "distribution TEXT NOT NULL DEFAULT 'uniform'",
+    ),
+    ("symmetry", "symmetry TEXT NOT NULL DEFAULT 'asymmetric'"),
+    ("forgiven", "forgiven INTEGER NOT NULL DEFAULT 0"),
+    ("assisted", "assisted INTEGER NOT NULL DEFAULT 0"),
+    ("mine_hit_row", "mine_hit_row INTEGER"),
+    ("mine_hit_col", "mine_hit_col INTEGER"),
+];
+
+pub struct SqliteStorage {
+    connection: Mutex<Connection>,
+}
+
+fn to_io_error(err: rusqlite::Error) -> io::Error {
+    io::Error::other(err)
+}
+
+impl SqliteStorage {
+    pub fn open(path: &Path) -> io::Result<Self> {
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        SqliteStorage::with_connection(Connection::open(path).map_err(to_io_error)?)
+    }
+
+    fn with_connection(connection: Connection) -> io::Result<Self> {
+        // other terminals may hold the lock for a moment, wait for them instead of failing
+        connection
+            .busy_timeout(Duration::from_secs(5))
+            .map_err(to_io_error)?;
+        connection
+            .pragma_update(None, "journal_mode", "WAL")
+            .map_err(to_io_error)?;
+        connection.execute_batch(SCHEMA).map_err(to_io_error)?;
+        // databases from before the board variants lack their columns
+        for (column, definition) in MIGRATED_COLUMNS {
+            let query = format!("SELECT {} FROM games LIMIT 0", column);
+            if connection.prepare(&query).is_err() {
+                connection
+                    .execute_batch(&format!("ALTER TABLE games ADD COLUMN {}", definition))
+                    .map_err(to_io_error)?;
+            }
+        }
+
+        Ok(SqliteStorage {
+            connection: Mutex::new(connection),
+        })
+    }
+
+    fn connection(&self) -> std::sync::MutexGuard<'_, Connection> {
+        self.connection
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn profiles(&self) -> io::Result<Vec<String>> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare(
+                "SELECT profile FROM documents UNION SELECT profile FROM games ORDER BY profile",
+            )
+            .map_err(to_io_error)?;
+        let profiles = statement
+            .query_map([], |row| row.get(0))
+            .map_err(to_io_error)?
+            .collect::<Result<Vec<String>, _>>()
+            .map_err(to_io_error)?;
+        Ok(profiles)
+    }
+
+    fn load_document(&self, profile: &str, name: &str) -> io::Result<Option<String>> {
+        self.connection()
+            .query_row(
+                "SELECT content FROM documents WHERE profile = ?1 AND name = ?2",
+                params![profile, name],
+                |row| row.get(0),
+            )
+            .optional()
+            .map_err(to_io_error)
+    }
+
+    fn save_document(&self, profile: &str, name: &str, content: &str) -> io::Result<()> {
+        self.connection()
+            .execute(
+                "INSERT INTO documents (profile, name, content) VALUES (?1, ?2, ?3)
+                 ON CONFLICT (profile, name) DO UPDATE SET content = excluded.content",
+                params![profile, name, content],
+            )
+            .map(|_| ())
+            .map_err(to_io_error)
+    }
+
+    fn append_game(&self, profile: &str, game: &GameRecord) -> io::Result<()> {
+        let configuration = &game.configuration;
+        self.connection()
+            .execute(
+                "INSERT INTO games (profile, day, difficulty, width, height, mines, seed,
+                    outcome, time_ms, flags_placed, cells_revealed, wrap_edges,
+                    adjacency, mines_per_cell, anti_mines, distribution, symmetry, forgiven,
+                    assisted, mine_hit_row, mine_hit_col)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15, ?16,
+                    ?17, ?18, ?19, ?20, ?21)",
+                params![
+                    profile,
+                    game.day as i64,
+                    configuration.key(),
+                    configuration.w(),
+                    configuration.h(),
+                    configuration.mines(),
+                    // sqlite integers are signed, seeds use the whole u64 range
+                    game.seed.to_string(),
+                    game.outcome.to_string(),
+                    game.time_ms as i64,
+                    game.flags_placed,
+                    game.cells_revealed,
+                    configuration.wrap_edges(),
+                    configuration.adjacency().to_string(),
+                    configuration.mines_per_cell(),
+                    configuration.anti_mines(),
+                    configuration.distribution().to_string(),
+                    configuration.symmetry().to_string(),
+                    game.forgiven,
+                    game.assisted,
+                    game.mine_hit.map(|(row, _)| row),
+                    game.mine_hit.map(|(_, col)| col),
+                ],
+            )
+            .map(|_| ())
+            .map_err(to_io_error)
+    }
+
+    fn replace_games(&self, profile: &str, games: &[GameRecord]) -> io::Result<()> {
+        self.connection()
+            .execute("DELETE FROM games WHERE profile = ?1", params![profile])
+            .map_err(to_io_error)?;
+        self.append_games(profile, games)
+    }
+
+    fn games(&self, profile: &str) -> io::Result<Vec<GameRecord>> {
+        let connection = self.connection();
+        let mut statement = connection
+            .prepare(
+                "SELECT day, width, height, mines, seed, outcome, time_ms, flags_placed,
+                    cells_revealed, wrap_edges, adjacency, mines_per_cell,
+                    anti_mines, distribution, symmetry, forgiven, assisted, mine_hit_row,
+                    mine_hit_col
+                 FROM games WHERE profile = ?1 ORDER BY id",
+            )
+            .map_err(to_io_error)?;
+
+        let games = statement
+            .query_map(params![profile], |row| {
+                let seed: String = row.get(4)?;
+                let outcome: String = row.get(5)?;
+                let adjacency: String = row.get(10)?;
+                let distribution: String = row.get(13)?;
+                let symmetry: String = row.get(14)?;
+                Ok(GameRecord {
+                    day: row.get::<_, i64>(0)? as u64,
+                    configuration: GameConfiguration::new(row.get(1)?, row.get(2)?, row.get(3)?)
+                        .with_wrap_edges(row.get(9)?)
+                        .with_adjacency(Adjacency::try_from(&adjacency[..]).unwrap_or_default())
+                        .with_mines_per_cell(row.get(11)?)
+                        .with_anti_mines(row.get(12)?)
+                        .with_distribution(
+                            Distribution::try_from(&distribution[..]).unwrap_or_default(),
+                        )
+                        .with_symmetry(Symmetry::try_from(&symmetry[..]).unwrap_or_default()),
+                    seed: seed.parse().unwrap_or_default(),
+                    outcome: match outcome.as_str() {
+                        "won" => Outcome::Won,
+                        "lost" => Outcome::Lost,
+                        _ => Outcome::Quit,
+                    },
+                    time_ms: row.get::<_, i64>(6)? as u64,
+                    flags_placed: row.get(7)?,
+                    cells_revealed: row.get(8)?,
+                    forgiven: row.get(15)?,
+                    assisted: row.get(16)?,
+                    mine_hit: match (row.get(17)?, row.get(18)?) {
+                        (Some(hit_row), Some(hit_col)) => Some((hit_row, hit_col)),
+                        _ => None,
+                    },
+                })
+            })
+            .map_err(to_io_error)?
+            .collect::<Result<Vec<GameRecord>, _>>()
+            .map_err(to_io_error)?;
+        Ok(games)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn sqlite_round_trip_test() {
+        let storage =
+            SqliteStorage::with_connection(Connection::open_in_memory().unwrap()).unwrap();
+
+        storage.save_document("mom", "profile.json", "{}").unwrap();
+        storage
+            .save_document("mom", "profile.json", "{\"a\":1}")
+            .unwrap();
+        assert_eq!(
+            Some("{\"a\":1}".to_string()),
+            storage.load_document("mom", "profile.json").unwrap()
+        );
+        assert_eq!(None, storage.load_document("dad", "profile.json").unwrap());
+
+        let game = GameRecord {
+            day: 20_000,
+            configuration: GameConfiguration::expert(),
+            seed: u64::MAX,
+            outcome: Outcome::Won,
+            time_ms: 95_123,
+            flags_placed: 99,
+            cells_revealed: 381,
+            forgiven: true,
+            assisted: true,
+            mine_hit: Some((15, 29)),
+        };
+        storage.append_game("dad", &game).unwrap();
+        assert_eq!(vec![game], storage.games("dad").unwrap());
+        assert_eq!(vec!["dad", "mom"], storage.profiles().unwrap());
+    }
+}
@@ -0,0 +1,169 @@
+//! The board as an SVG image for embedding in write-ups, in the same classic look as the
+//! PNG export. Either the board as it is shown or its solution, every cell revealed.
+
+use std::fmt::Write;
+
+use crate::{BoardCell, Coordinate, GameBoard, Mark};
+
+/// Side length of a cell in user units.
+const TILE: u32 = 16;
+
+const BACKGROUND: &str = "#808080";
+const FACE: &str = "#c0c0c0";
+const LIGHT: &str = "#ffffff";
+const SHADOW: &str = "#808080";
+
+/// Colours of the numbers one to eight.
+const NUMBER_COLOURS: [&str; 8] = [
+    "#0000ff", "#008000", "#ff0000", "#000080", "#800000", "#008080", "#000000", "#808080",
+];
+
+/// The board as an SVG document, with `solution` every mine and number is revealed.
+pub fn render(game_board: &GameBoard, solution: bool) -> String {
+    let (width, height) = game_board.get_dimensions();
+    let (pixel_width, pixel_height) = (width * TILE, height * TILE);
+    let mut svg = format!(
+        "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{0}\" height=\"{1}\" \
+         viewBox=\"0 0 {0} {1}\" font-family=\"monospace\" font-weight=\"bold\" \
+         text-anchor=\"middle\">\n<rect width=\"{0}\" height=\"{1}\" fill=\"{2}\"/>\n",
+        pixel_width, pixel_height, BACKGROUND
+    );
+    for row in 0..height {
+        for col in 0..width {
+            let coordinate = Coordinate(row, col);
+            if game_board.is_hole(coordinate) {
+                continue;
+            }
+            let (x, y) = (col * TILE, row * TILE);
+            match game_board.get_cell_at(coordinate) {
+                BoardCell::Explored(neighbours) => number(&mut svg, x, y, neighbours.0),
+                BoardCell::NoMine(info) if solution => number(&mut svg, x, y, info.1 .0),
+                BoardCell::Mine(_) if solution => {
+                    mine(&mut svg, x, y, game_board.mine_count(coordinate))
+                }
+                BoardCell::NoMine(info) => covered(&mut svg, x, y, info.0),
+                BoardCell::Mine(Mark::NoMark) if game_board.shows_mines() => {
+                    mine(&mut svg, x, y, game_board.mine_count(coordinate))
+                }
+                BoardCell::Mine(mark) => covered(&mut svg, x, y, mark),
+            }
+        }
+    }
+    svg.push_str("</svg>\n");
+    svg
+}
+
+fn sunken(svg: &mut String, x: u32, y: u32) {
+    let _ = writeln!(
+        svg,
+        "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>",
+        x + 1,
+        y + 1,
+        TILE - 1,
+        TILE - 1,
+        FACE
+    );
+}
+
+fn covered(svg: &mut String, x: u32, y: u32, mark: Mark) {
+    let _ = writeln!(
+        svg,
+        "<rect x=\"{x}\" y=\"{y}\" width=\"{t}\" height=\"{t}\" fill=\"{SHADOW}\"/>\
+         <rect x=\"{x}\" y=\"{y}\" width=\"{l}\" height=\"{l}\" fill=\"{LIGHT}\"/>\
+         <rect x=\"{fx}\" y=\"{fy}\" width=\"{f}\" height=\"{f}\" fill=\"{FACE}\"/>",
+        t = TILE,
+        l = TILE - 2,
+        fx = x + 2,
+        fy = y + 2,
+        f = TILE - 4,
+    );
+    match mark {
+        Mark::MarkFlag => {
+            let _ = writeln!(
+                svg,
+                "<polygon points=\"{},{} {},{} {},{}\" fill=\"#ff0000\"/>\
+                 <rect x=\"{}\" y=\"{}\" width=\"1\" height=\"8\" fill=\"#000000\"/>\
+                 <rect x=\"{}\" y=\"{}\" width=\"8\" height=\"2\" fill=\"#000000\"/>",
+                x + 9,
+                y + 3,
+                x + 9,
+                y + 8,
+                x + 4,
+                y + 5,
+                x + 8,
+                y + 3,
+                x + 4,
+                y + 11
+            );
+        }
+        Mark::MarkNote => text(svg, x, y, "?", "#000000"),
+        Mark::NoMark => {}
+    }
+}
+
+fn number(svg: &mut String, x: u32, y: u32, count: i16) {
+    sunken(svg, x, y);
+    match count {
+        0 => {}
+        1..=8 => text(
+            svg,
+            x,
+            y,
+            &count.to_string(),
+            NUMBER_COLOURS[count as usize - 1],
+        ),
+        _ => text(svg, x, y, &count.to_string(), "#000000"),
+    }
+}
+
+/// A black mine, hollow for an anti-mine, with the count if it holds several.
+fn mine(svg: &mut String, x: u32, y: u32, count: i8) {
+    sunken(svg, x, y);
+    let fill = if count < 0 { FACE } else { "#000000" };
+    let _ = writeln!(
+        svg,
+        "<circle cx=\"{}\" cy=\"{}\" r=\"4.5\" fill=\"{}\" stroke=\"#000000\"/>",
+        x + TILE / 2,
+        y + TILE / 2,
+        fill
+    );
+    if count > 1 {
+        text(svg, x, y, &count.to_string(), LIGHT);
+    }
+}
+
+fn text(svg: &mut String, x: u32, y: u32, text: &str, colour: &str) {
+    let size = if text.len() == 1 { 13 } else { 8 };
+    let _ = writeln!(
+        svg,
+        "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>",
+        x + TILE / 2,
+        y + TILE / 2 + size * 3 / 8,
+        size,
+        colour,
+        text
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{layout::Layout, BoardCommand};
+
+    #[test]
+    fn render_test() {
+        let mut game_board = GameBoard::from_layout(Layout::try_from("*##\n###\n").unwrap());
+        game_board.manipulate_cell(BoardCommand::Explore(Coordinate(1, 2)));
+
+        let shown = render(&game_board, false);
+        assert!(shown.starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"48\""));
+        assert!(shown.ends_with("</svg>\n"));
+        // the corner below the mine is not reached by the opening
+        assert_eq!(2, shown.matches(">1</text>").count());
+        assert!(!shown.contains("<circle"));
+
+        let solution = render(&game_board, true);
+        assert_eq!(1, solution.matches("<circle").count());
+        assert_eq!(3, solution.matches(">1</text>").count());
+    }
+}
@@ -0,0 +1,174 @@
+//! The board drawn in the look of the classic game, raised grey tiles for covered cells
+//! and sunken ones with coloured numbers for explored cells, shared by the image exports.
+//! Mines are only drawn when the board shows them.
+
+use crate::{BoardCell, Coordinate, GameBoard, Mark};
+
+/// Side length of a cell in pixels.
+pub(crate) const TILE: usize = 16;
+
+pub(crate) type Rgb = [u8; 3];
+
+pub(crate) const BACKGROUND: Rgb = [128, 128, 128];
+pub(crate) const FACE: Rgb = [192, 192, 192];
+pub(crate) const LIGHT: Rgb = [255, 255, 255];
+pub(crate) const SHADOW: Rgb = [128, 128, 128];
+pub(crate) const BLACK: Rgb = [0, 0, 0];
+pub(crate) const RED: Rgb = [255, 0, 0];
+
+/// Colours of the numbers one to eight.
+pub(crate) const NUMBER_COLOURS: [Rgb; 8] = [
+    [0, 0, 255],
+    [0, 128, 0],
+    [255, 0, 0],
+    [0, 0, 128],
+    [128, 0, 0],
+    [0, 128, 128],
+    [0, 0, 0],
+    [128, 128, 128],
+];
+
+/// Glyphs of 5x7 pixels, a row per byte with the leftmost pixel in the highest of five bits.
+const DIGITS: [[u8; 7]; 10] = [
+    [0x0e, 0x11, 0x13, 0x15, 0x19, 0x11, 0x0e],
+    [0x04, 0x0c, 0x04, 0x04, 0x04, 0x04, 0x0e],
+    [0x0e, 0x11, 0x01, 0x02, 0x04, 0x08, 0x1f],
+    [0x1f, 0x02, 0x04, 0x02, 0x01, 0x11, 0x0e],
+    [0x02, 0x06, 0x0a, 0x12, 0x1f, 0x02, 0x02],
+    [0x1f, 0x10, 0x1e, 0x01, 0x01, 0x11, 0x0e],
+    [0x06, 0x08, 0x10, 0x1e, 0x11, 0x11, 0x0e],
+    [0x1f, 0x01, 0x02, 0x04, 0x08, 0x08, 0x08],
+    [0x0e, 0x11, 0x11, 0x0e, 0x11, 0x11, 0x0e],
+    [0x0e, 0x11, 0x11, 0x0f, 0x01, 0x02, 0x0c],
+];
+const MINUS: [u8; 7] = [0x00, 0x00, 0x00, 0x1f, 0x00, 0x00, 0x00];
+const QUESTION: [u8; 7] = [0x0e, 0x11, 0x01, 0x02, 0x04, 0x00, 0x04];
+
+/// Pixels row by row, three bytes of red, green and blue each.
+pub(crate) struct Canvas {
+    pub(crate) width: usize,
+    pub(crate) height: usize,
+    pub(crate) pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(width: usize, height: usize) -> Self {
+        Canvas {
+            width,
+            height,
+            pixels: BACKGROUND.repeat(width * height),
+        }
+    }
+
+    fn fill(&mut self, x: usize, y: usize, width: usize, height: usize, colour: Rgb) {
+        for row in y..(y + height).min(self.height) {
+            for col in x..(x + width).min(self.width) {
+                let offset = (row * self.width + col) * 3;
+                self.pixels[offset..offset + 3].copy_from_slice(&colour);
+            }
+        }
+    }
+
+    fn glyph(&mut self, x: usize, y: usize, glyph: &[u8; 7], scale: usize, colour: Rgb) {
+        for (row, bits) in glyph.iter().enumerate() {
+            for col in 0..5 {
+                if bits & (0x10 >> col) != 0 {
+                    self.fill(x + col * scale, y + row * scale, scale, scale, colour);
+                }
+            }
+        }
+    }
+
+    /// Text of digits and minus signs centred in the tile, half as large if it is longer
+    /// than one character.
+    fn text(&mut self, x: usize, y: usize, text: &str, colour: Rgb) {
+        let scale = if text.len() == 1 { 2 } else { 1 };
+        let advance = 6 * scale;
+        let width = advance * text.len() - scale;
+        let left = x + TILE.saturating_sub(width) / 2;
+        let top = y + (TILE - 7 * scale) / 2;
+        for (index, character) in text.chars().enumerate() {
+            let glyph = match character.to_digit(10) {
+                Some(digit) => &DIGITS[digit as usize],
+                None => &MINUS,
+            };
+            self.glyph(left + index * advance, top, glyph, scale, colour);
+        }
+    }
+
+    fn raised(&mut self, x: usize, y: usize) {
+        self.fill(x, y, TILE, TILE, SHADOW);
+        self.fill(x, y, TILE - 2, TILE - 2, LIGHT);
+        self.fill(x + 2, y + 2, TILE - 4, TILE - 4, FACE);
+    }
+
+    fn sunken(&mut self, x: usize, y: usize) {
+        self.fill(x, y, TILE, TILE, SHADOW);
+        self.fill(x + 1, y + 1, TILE - 1, TILE - 1, FACE);
+    }
+
+    fn flag(&mut self, x: usize, y: usize) {
+        self.fill(x + 4, y + 3, 5, 5, RED);
+        self.fill(x + 8, y + 3, 1, 8, BLACK);
+        self.fill(x + 4, y + 11, 8, 2, BLACK);
+    }
+
+    fn mine(&mut self, x: usize, y: usize) {
+        self.fill(x + 4, y + 5, 8, 6, BLACK);
+        self.fill(x + 5, y + 4, 6, 8, BLACK);
+        self.fill(x + 7, y + 2, 2, 12, BLACK);
+        self.fill(x + 2, y + 7, 12, 2, BLACK);
+        self.fill(x + 6, y + 6, 2, 2, LIGHT);
+    }
+}
+
+/// The board as it is shown.
+pub(crate) fn draw(game_board: &GameBoard) -> Canvas {
+    let (width, height) = game_board.get_dimensions();
+    let mut canvas = Canvas::new(width as usize * TILE, height as usize * TILE);
+    for row in 0..height {
+        for col in 0..width {
+            let coordinate = Coordinate(row, col);
+            if game_board.is_hole(coordinate) {
+                continue;
+            }
+            let (x, y) = (col as usize * TILE, row as usize * TILE);
+            match game_board.get_cell_at(coordinate) {
+                BoardCell::Explored(neighbours) => {
+                    canvas.sunken(x, y);
+                    match neighbours.0 {
+                        0 => {}
+                        count @ 1..=8 => {
+                            let colour = NUMBER_COLOURS[count as usize - 1];
+                            canvas.text(x, y, &count.to_string(), colour);
+                        }
+                        count => canvas.text(x, y, &count.to_string(), BLACK),
+                    }
+                }
+                BoardCell::NoMine(info) => draw_covered(&mut canvas, x, y, info.0, false),
+                BoardCell::Mine(mark) => {
+                    draw_covered(&mut canvas, x, y, mark, game_board.shows_mines())
+                }
+            }
+        }
+    }
+    canvas
+}
+
+fn draw_covered(canvas: &mut Canvas, x: usize, y: usize, mark: Mark, mine: bool) {
+    match mark {
+        Mark::MarkFlag => {
+            canvas.raised(x, y);
+            canvas.flag(x, y);
+        }
+        Mark::MarkNote => {
+            canvas.raised(x, y);
+            canvas.glyph(x + 3, y + 1, &QUESTION, 2, BLACK);
+        }
+        Mark::NoMark if mine => {
+            canvas.sunken(x, y);
+            canvas.mine(x, y);
+        }
+        Mark::NoMark => canvas.raised(x, y),
+    }
+}
@@ -0,0 +1,217 @@
+//! Weekly tournament, a fixed set of seeded boards per ISO week played back to back.
+
+use std::{fmt::Display, fs, io, path::Path, path::PathBuf, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    calendar,
+    replay::{Replay, ReplayError},
+    storage, GameConfiguration, GameResolve,
+};
+
+const BOARD_COUNT: usize = 5;
+
+pub struct Tournament {
+    year: i64,
+    week: u32,
+}
+
+impl Tournament {
+    pub fn this_week() -> Self {
+        let (year, week) = calendar::iso_week(calendar::today());
+        Tournament { year, week }
+    }
+
+    /// Parses a week formatted as `YYYY-Www`, the inverse of [`Tournament::week`].
+    fn from_week(week: &str) -> Option<Self> {
+        let (year, week) = week.split_once("-W")?;
+        Some(Tournament {
+            year: year.parse().ok()?,
+            week: week.parse().ok()?,
+        })
+    }
+
+    pub fn week(&self) -> String {
+        format!("{:04}-W{:02}", self.year, self.week)
+    }
+
+    pub fn board_count(&self) -> usize {
+        BOARD_COUNT
+    }
+
+    pub fn configuration(&self) -> GameConfiguration {
+        GameConfiguration::intermediate()
+    }
+
+    /// Seeds of the week's boards, in the order they are played.
+    pub fn seeds(&self) -> Vec<u64> {
+        let week_index = (self.year as u64) * 100 + self.week as u64;
+        (0..BOARD_COUNT as u64)
+            .map(|board| {
+                (week_index * BOARD_COUNT as u64 + board).wrapping_mul(0x9E37_79B9_7F4A_7C15)
+                    ^ 0x746F_7572_6E61_6D74
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TournamentError {
+    MalformedWeek,
+    WrongBoardCount,
+    WrongBoard(usize),
+    Replay(usize, ReplayError),
+    ClaimMismatch,
+}
+
+impl Display for TournamentError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TournamentError::MalformedWeek => write!(f, "the tournament week is malformed"),
+            TournamentError::WrongBoardCount => {
+                write!(f, "the tournament has {} boards", BOARD_COUNT)
+            }
+            TournamentError::WrongBoard(index) => {
+                write!(f, "board {} is not the tournament board", index + 1)
+            }
+            TournamentError::Replay(index, err) => write!(f, "board {}: {}", index + 1, err),
+            TournamentError::ClaimMismatch => {
+                write!(f, "the claimed result does not match the replays")
+            }
+        }
+    }
+}
+
+/// Combined result of all boards in a tournament, meant to be shared.
+#[derive(Serialize, Deserialize, Debug)]
+pub struct TournamentResult {
+    week: String,
+    boards_won: usize,
+    total_time_ms: u64,
+    replays: Vec<Replay>,
+}
+
+impl TournamentResult {
+    pub fn new(tournament: &Tournament) -> Self {
+        TournamentResult {
+            week: tournament.week(),
+            boards_won: 0,
+            total_time_ms: 0,
+            replays: vec![],
+        }
+    }
+
+    pub fn add(&mut self, replay: Replay) {
+        if replay.simulate() == Ok(GameResolve::AllMinesDiscovered) {
+            self.boards_won += 1;
+        }
+        self.total_time_ms += replay.duration().as_millis() as u64;
+        self.replays.push(replay);
+    }
+
+    pub fn load(path: &Path) -> io::Result<Self> {
+        let content = fs::read_to_string(path)?;
+        Ok(serde_json::from_str(&content)?)
+    }
+
+    /// Saves the result into the profile's directory and returns its path.
+    pub fn save(&self) -> io::Result<PathBuf> {
+        storage::export_json(&format!("tournament-{}.json", self.week), self)
+    }
+
+    /// Re-simulates every replay and checks it was played on the week's boards and
+    /// that the claimed totals match.
+    pub fn verify(&self) -> Result<(), TournamentError> {
+        let tournament = Tournament::from_week(&self.week).ok_or(TournamentError::MalformedWeek)?;
+        if self.replays.len() != BOARD_COUNT {
+            return Err(TournamentError::WrongBoardCount);
+        }
+
+        let mut boards_won = 0;
+        let mut total_time = Duration::ZERO;
+        for (index, (replay, seed)) in self.replays.iter().zip(tournament.seeds()).enumerate() {
+            if replay.seed != seed || replay.configuration != tournament.configuration() {
+                return Err(TournamentError::WrongBoard(index));
+            }
+            replay
+                .verify_checksum()
+                .map_err(|err| TournamentError::Replay(index, err))?;
+
+            let resolve = replay
+                .simulate()
+                .map_err(|err| TournamentError::Replay(index, err))?;
+            if resolve == GameResolve::AllMinesDiscovered {
+                boards_won += 1;
+            }
+            total_time += replay.duration();
+        }
+
+        if boards_won != self.boards_won || total_time.as_millis() as u64 != self.total_time_ms {
+            return Err(TournamentError::ClaimMismatch);
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for TournamentResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Tournament {}", self.week)?;
+        for (index, replay) in self.replays.iter().enumerate() {
+            let outcome = match replay.simulate() {
+                Ok(GameResolve::AllMinesDiscovered) => "won",
+                _ => "lost",
+            };
+            writeln!(
+                f,
+                "Board {}: {:<5}{:>10.2} s",
+                index + 1,
+                outcome,
+                replay.duration().as_secs_f64()
+            )?;
+        }
+        write!(
+            f,
+            "Boards won: {}/{}, total time {:.2} s",
+            self.boards_won,
+            self.replays.len(),
+            self.total_time_ms as f64 / 1000.0
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BoardCommand;
+
+    #[test]
+    fn verify_tournament_result_test() {
+        let tournament = Tournament {
+            year: 2026,
+            week: 42,
+        };
+        let mut result = TournamentResult::new(&tournament);
+        for seed in tournament.seeds() {
+            let mut replay = Replay::new(tournament.configuration(), seed);
+            replay.record(BoardCommand::Quit, Duration::from_secs(1));
+            replay.seal(GameResolve::Quit);
+            result.add(replay);
+        }
+        assert_eq!(Ok(()), result.verify());
+
+        result.total_time_ms -= 1;
+        assert_eq!(Err(TournamentError::ClaimMismatch), result.verify());
+
+        result.total_time_ms += 1;
+        result.replays[1].moves[0].time_ms = 2000;
+        assert_eq!(
+            Err(TournamentError::Replay(1, ReplayError::ChecksumMismatch)),
+            result.verify()
+        );
+
+        result.replays[0].seed += 1;
+        assert_eq!(Err(TournamentError::WrongBoard(0)), result.verify());
+    }
+}
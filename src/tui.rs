@@ -0,0 +1,670 @@
+//! Terminal UI built on ratatui, started with `--tui`. Shares the engine, highscores and
+//! statistics with the line-based mode.
+//!
+//! Keys: arrows or hjkl move the cursor, space or enter explores, `f` flags, `n` notes,
+//! `c` clears a mark, `w` claims the win under strict flagging, `p` pauses, `e` saves a
+//! coloured snapshot of the board and `q` goes back to the menu. A left click explores, a
+//! right click flags. The statistics dashboard switches between difficulties with the left
+//! and right keys.
+
+use std::{
+    io::{self, stdout},
+    time::Duration,
+};
+
+use minesweeper::{
+    ansi,
+    clock::{GameClock, TimeFormat},
+    config::Config,
+    highscores::Highscores,
+    protocol::{CellView, Status},
+    replay::Replay,
+    statistics::{self, GameRecord, Statistics},
+    storage, BoardCommand, Coordinate, Efficiency, GameBoard, GameConfiguration, GameResult,
+};
+use ratatui::{
+    buffer::Buffer,
+    crossterm::{
+        event::{
+            self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEvent, KeyEventKind,
+            MouseButton, MouseEvent, MouseEventKind,
+        },
+        execute,
+    },
+    layout::{Constraint, Flex, Layout, Rect},
+    style::{Color, Modifier, Style, Stylize},
+    text::Line,
+    widgets::{
+        Bar, BarChart, BarGroup, Block, BorderType, Clear, List, ListState, Paragraph, Sparkline,
+        Widget,
+    },
+    DefaultTerminal, Frame,
+};
+
+const CELL_WIDTH: u16 = 3;
+const TICK: Duration = Duration::from_millis(250);
+
+/// Runs the terminal UI, with the mines shown on every board if asked for debugging.
+pub fn run(show_mines: bool) -> io::Result<()> {
+    let mut terminal = ratatui::init();
+    execute!(stdout(), EnableMouseCapture)?;
+    let result = App::new(show_mines).run(&mut terminal);
+    execute!(stdout(), DisableMouseCapture)?;
+    ratatui::restore();
+    result
+}
+
+enum MenuItem {
+    Play(GameConfiguration),
+    Highscores,
+    Statistics,
+    Quit,
+}
+
+impl MenuItem {
+    fn title(&self) -> String {
+        match self {
+            MenuItem::Play(configuration) => format!(
+                "Play {} ({})",
+                configuration.difficulty_name().unwrap_or("Custom"),
+                configuration.key()
+            ),
+            MenuItem::Highscores => "Highscores".to_string(),
+            MenuItem::Statistics => "Statistics".to_string(),
+            MenuItem::Quit => "Quit".to_string(),
+        }
+    }
+}
+
+struct Game {
+    game_board: GameBoard,
+    status: Status,
+    cursor: Coordinate,
+    replay: Replay,
+    flags_placed: u32,
+    clicks: u32,
+    wasted_clicks: u32,
+    clock: GameClock,
+    time_format: TimeFormat,
+    finished: Option<Duration>,
+    /// Where the board was drawn last, to map mouse clicks to cells.
+    board_area: Rect,
+}
+
+impl Game {
+    fn new(configuration: GameConfiguration, show_mines: bool) -> Game {
+        let mut game_board = GameBoard::new(configuration);
+        game_board.generate_world(&mut rand::thread_rng());
+        let config = Config::load();
+        config.apply_rules(&mut game_board);
+        game_board.set_show_mines(show_mines);
+        let replay = Replay::of(&game_board);
+
+        Game {
+            game_board,
+            status: Status::Playing,
+            cursor: Coordinate(0, 0),
+            replay,
+            flags_placed: 0,
+            clicks: 0,
+            wasted_clicks: 0,
+            clock: GameClock::start(),
+            time_format: config.time_format.unwrap_or_default(),
+            finished: None,
+            board_area: Rect::default(),
+        }
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.finished.unwrap_or_else(|| self.clock.elapsed())
+    }
+
+    fn move_cursor(&mut self, rows: i32, cols: i32) {
+        let (width, height) = self.game_board.get_dimensions();
+        let Coordinate(row, col) = self.cursor;
+        self.cursor = Coordinate(
+            (row as i32 + rows).clamp(0, height as i32 - 1) as u32,
+            (col as i32 + cols).clamp(0, width as i32 - 1) as u32,
+        );
+    }
+
+    fn cell_at(&self, column: u16, row: u16) -> Option<Coordinate> {
+        let area = self.board_area;
+        if !area.contains((column, row).into()) {
+            return None;
+        }
+        let coordinate = Coordinate(
+            u32::from(row - area.y),
+            u32::from((column - area.x) / CELL_WIDTH),
+        );
+        self.game_board
+            .is_on_board(BoardCommand::Explore(coordinate))
+            .then_some(coordinate)
+    }
+
+    /// Applies the command, returns the result once the game is over.
+    fn apply(&mut self, command: BoardCommand) -> Option<GameResult> {
+        if self.status != Status::Playing || self.clock.is_paused() {
+            return None;
+        }
+
+        let elapsed = self.clock.elapsed();
+        self.replay.record(command, elapsed);
+        self.flags_placed += self.game_board.flags_placed_by(command);
+        if !matches!(
+            command,
+            BoardCommand::Pass | BoardCommand::Quit | BoardCommand::Claim
+        ) {
+            self.clicks += 1;
+            self.wasted_clicks += self.game_board.is_wasted(command) as u32;
+        }
+        let resolve = self.game_board.manipulate_cell(command);
+        self.status = resolve.into();
+        if self.status == Status::Playing {
+            return None;
+        }
+
+        self.finished = Some(elapsed);
+        self.replay.seal(resolve);
+        Some(GameResult {
+            resolve,
+            elapsed,
+            replay: self.replay.clone(),
+            flags_placed: self.flags_placed,
+            cells_revealed: self.game_board.explored_cells(),
+            efficiency: Efficiency {
+                three_bv: self.game_board.three_bv().0,
+                clicks: self.clicks,
+                wasted_clicks: self.wasted_clicks,
+            },
+            board_three_bv: self.game_board.three_bv().1,
+            hints_used: 0,
+            forgiven: self.game_board.mistakes_forgiven() > 0,
+            cheated: self.game_board.is_cheated(),
+            scripted: false,
+            assisted: self.game_board.is_assisted(),
+            practice: false,
+        })
+    }
+}
+
+struct App {
+    menu: Vec<MenuItem>,
+    menu_state: ListState,
+    game: Option<Game>,
+    dashboard: Option<Dashboard>,
+    popup: Option<(String, String)>,
+    message: String,
+    running: bool,
+    show_mines: bool,
+}
+
+impl App {
+    fn new(show_mines: bool) -> App {
+        let mut menu = vec![
+            MenuItem::Play(GameConfiguration::beginner()),
+            MenuItem::Play(GameConfiguration::intermediate()),
+            MenuItem::Play(GameConfiguration::expert()),
+        ];
+        if let Some(default_board) = Config::load().default_board {
+            if default_board.difficulty_name().is_none() {
+                menu.push(MenuItem::Play(default_board));
+            }
+        }
+        menu.push(MenuItem::Highscores);
+        menu.push(MenuItem::Statistics);
+        menu.push(MenuItem::Quit);
+
+        App {
+            menu,
+            menu_state: ListState::default().with_selected(Some(0)),
+            game: None,
+            dashboard: None,
+            popup: None,
+            message: "Choose a game from the menu.".to_string(),
+            running: true,
+            show_mines,
+        }
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<()> {
+        while self.running {
+            terminal.draw(|frame| self.draw(frame))?;
+            if event::poll(TICK)? {
+                match event::read()? {
+                    Event::Key(key) if key.kind == KeyEventKind::Press => self.handle_key(key),
+                    Event::Mouse(mouse) => self.handle_mouse(mouse),
+                    _ => {}
+                }
+            }
+        }
+        Ok(())
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [main, status_bar] =
+            Layout::vertical([Constraint::Min(0), Constraint::Length(1)]).areas(frame.area());
+        let [menu_area, board_area] =
+            Layout::horizontal([Constraint::Length(32), Constraint::Min(0)]).areas(main);
+
+        let menu = List::new(self.menu.iter().map(MenuItem::title))
+            .block(Block::bordered().title(" Minesweeper "))
+            .highlight_style(Style::new().add_modifier(Modifier::REVERSED))
+            .highlight_symbol("> ");
+        frame.render_stateful_widget(menu, menu_area, &mut self.menu_state);
+
+        let wraps = self
+            .game
+            .as_ref()
+            .is_some_and(|game| game.game_board.configuration().wrap_edges());
+        let board_block = if wraps {
+            Block::bordered()
+                .border_type(BorderType::Double)
+                .title(" Board (edges wrap) ")
+        } else {
+            Block::bordered().title(" Board ")
+        };
+        let inner = board_block.inner(board_area);
+        if let Some(dashboard) = &self.dashboard {
+            frame.render_widget(dashboard, board_area);
+        } else {
+            frame.render_widget(board_block, board_area);
+        }
+        if let Some(game) = self.game.as_mut() {
+            let (width, height) = terminal_dimensions(&game.game_board);
+            let [area] = Layout::horizontal([Constraint::Length(width.saturating_mul(CELL_WIDTH))])
+                .flex(Flex::Center)
+                .areas(inner);
+            let [area] = Layout::vertical([Constraint::Length(height)])
+                .flex(Flex::Center)
+                .areas(area);
+            game.board_area = area;
+            // the board is hidden while the clock stands still
+            if game.clock.is_paused() {
+                game.board_area = Rect::default();
+                frame.render_widget(
+                    Paragraph::new("Paused, press p to go on.").centered(),
+                    inner,
+                );
+            } else {
+                frame.render_widget(
+                    BoardWidget {
+                        game_board: &game.game_board,
+                        status: game.status,
+                        cursor: game.cursor,
+                    },
+                    area,
+                );
+            }
+        }
+
+        frame.render_widget(Paragraph::new(self.status_line()).reversed(), status_bar);
+
+        if let Some((title, text)) = &self.popup {
+            let [area] = Layout::horizontal([Constraint::Length(44)])
+                .flex(Flex::Center)
+                .areas(frame.area());
+            let height = text.lines().count() as u16 + 3;
+            let [area] = Layout::vertical([Constraint::Length(height)])
+                .flex(Flex::Center)
+                .areas(area);
+            frame.render_widget(Clear, area);
+            frame.render_widget(
+                Paragraph::new(format!("{}\nPress any key.", text))
+                    .block(Block::bordered().title(format!(" {} ", title))),
+                area,
+            );
+        }
+    }
+
+    fn status_line(&self) -> Line<'_> {
+        let Some(game) = &self.game else {
+            return Line::from(format!(" {}", self.message));
+        };
+
+        let configuration = game.game_board.configuration();
+        Line::from(format!(
+            " {} | seed {} | flags {}/{} | {}{} | {}",
+            configuration.key(),
+            game.game_board.seed(),
+            game.flags_placed,
+            configuration.mines(),
+            game.time_format.format(game.elapsed()),
+            if game.clock.is_paused() {
+                " paused"
+            } else {
+                ""
+            },
+            self.message
+        ))
+    }
+
+    fn handle_key(&mut self, key: KeyEvent) {
+        if self.popup.take().is_some() {
+            return;
+        }
+
+        if let Some(dashboard) = self.dashboard.as_mut() {
+            match key.code {
+                KeyCode::Left | KeyCode::Char('h') => return dashboard.switch_difficulty(-1),
+                KeyCode::Right | KeyCode::Char('l') => return dashboard.switch_difficulty(1),
+                KeyCode::Char('q') | KeyCode::Esc => {
+                    self.dashboard = None;
+                    return;
+                }
+                _ => {}
+            }
+        }
+
+        let Some(game) = self.game.as_mut() else {
+            match key.code {
+                KeyCode::Up | KeyCode::Char('k') => self.menu_state.select_previous(),
+                KeyCode::Down | KeyCode::Char('j') => self.menu_state.select_next(),
+                KeyCode::Enter | KeyCode::Char(' ') => self.choose_menu_item(),
+                KeyCode::Char('q') | KeyCode::Esc => self.running = false,
+                _ => {}
+            }
+            return;
+        };
+
+        let cursor = game.cursor;
+        let command = match key.code {
+            KeyCode::Up | KeyCode::Char('k') => return game.move_cursor(-1, 0),
+            KeyCode::Down | KeyCode::Char('j') => return game.move_cursor(1, 0),
+            KeyCode::Left | KeyCode::Char('h') => return game.move_cursor(0, -1),
+            KeyCode::Right | KeyCode::Char('l') => return game.move_cursor(0, 1),
+            KeyCode::Enter | KeyCode::Char(' ') => BoardCommand::Explore(cursor),
+            KeyCode::Char('f') => BoardCommand::SetMarkFlag(cursor),
+            KeyCode::Char('n') => BoardCommand::SetMarkNote(cursor),
+            KeyCode::Char('c') => BoardCommand::ClearMark(cursor),
+            KeyCode::Char('w') => BoardCommand::Claim,
+            KeyCode::Char('p') if game.status == Status::Playing => {
+                if game.clock.is_paused() {
+                    game.clock.resume();
+                } else {
+                    game.clock.pause();
+                }
+                return;
+            }
+            KeyCode::Char('e') => {
+                let message = match ansi::export(&game.game_board, game.status) {
+                    Ok(path) => format!("Saved a snapshot of the board to {}.", path.display()),
+                    Err(err) => format!("Could not save the snapshot: {}", err),
+                };
+                self.message = message;
+                return;
+            }
+            KeyCode::Char('q') | KeyCode::Esc => {
+                self.game = None;
+                self.message = "Choose a game from the menu.".to_string();
+                return;
+            }
+            _ => return,
+        };
+        self.play(command);
+    }
+
+    fn handle_mouse(&mut self, mouse: MouseEvent) {
+        let MouseEventKind::Down(button) = mouse.kind else {
+            return;
+        };
+        if self.popup.take().is_some() {
+            return;
+        }
+
+        let Some(game) = self.game.as_mut() else {
+            return;
+        };
+        let Some(coordinate) = game.cell_at(mouse.column, mouse.row) else {
+            return;
+        };
+        game.cursor = coordinate;
+        match button {
+            MouseButton::Left => self.play(BoardCommand::Explore(coordinate)),
+            MouseButton::Right => self.play(BoardCommand::SetMarkFlag(coordinate)),
+            MouseButton::Middle => {}
+        }
+    }
+
+    fn choose_menu_item(&mut self) {
+        match self
+            .menu_state
+            .selected()
+            .and_then(|index| self.menu.get(index))
+        {
+            Some(MenuItem::Play(configuration)) => {
+                self.dashboard = None;
+                self.game = Some(Game::new(*configuration, self.show_mines));
+                self.message = "Good luck!".to_string();
+            }
+            Some(MenuItem::Highscores) => {
+                let highscores = Highscores::load();
+                let text = highscores
+                    .tables_for_display()
+                    .into_iter()
+                    .map(|(title, table)| match table {
+                        Some(table) => format!("{}\n{}", title, table),
+                        None => format!("{}\nNo entries yet.\n", title),
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n");
+                self.popup = Some(("Highscores".to_string(), text));
+            }
+            Some(MenuItem::Statistics) => {
+                self.dashboard = Some(Dashboard::new(Statistics::load()));
+                self.message = "Left and right switch the difficulty, q closes.".to_string();
+            }
+            Some(MenuItem::Quit) => self.running = false,
+            None => {}
+        }
+    }
+
+    fn play(&mut self, command: BoardCommand) {
+        let Some(game) = self.game.as_mut() else {
+            return;
+        };
+        if game.status == Status::Playing && !game.game_board.passes_flag_guard(command) {
+            self.message = game.game_board.flag_guard().hint().to_string();
+            return;
+        }
+        let mistakes_forgiven = game.game_board.mistakes_forgiven();
+        let result = game.apply(command);
+        if game.game_board.mistakes_forgiven() > mistakes_forgiven {
+            self.message = "That was a mine! It is flagged now, the next one counts.".to_string();
+        }
+        let Some(result) = result else {
+            return;
+        };
+
+        if !result.cheated {
+            if let Err(err) = statistics::record(GameRecord::new(&result)) {
+                self.message = format!("Could not save statistics: {}", err);
+            }
+        }
+
+        match game.status {
+            Status::Won => {
+                let configuration = game.game_board.configuration();
+                let mut highscores = Highscores::load();
+                let table = highscores.table_mut(&configuration);
+                if result.is_ranked() && table.qualifies(result.elapsed) {
+                    table.insert(
+                        storage::active_profile().to_string(),
+                        result.elapsed,
+                        Some(result.efficiency),
+                        result.assisted,
+                    );
+                    if let Err(err) = highscores.save() {
+                        self.message = format!("Could not save highscores: {}", err);
+                    }
+                }
+                let table = highscores.table_mut(&configuration).to_string();
+                self.popup = Some((
+                    format!("You won in {}", game.time_format.format(result.elapsed)),
+                    table,
+                ));
+                self.message = "YOU WON! Press q for the menu.".to_string();
+            }
+            Status::Lost => self.message = "HIT MINE! Press q for the menu.".to_string(),
+            Status::Quit | Status::Playing => {}
+        }
+    }
+}
+
+struct BoardWidget<'a> {
+    game_board: &'a GameBoard,
+    status: Status,
+    cursor: Coordinate,
+}
+
+/// Charts of the game log: the win rate by day, the times of the won games of a difficulty
+/// and the win streaks.
+struct Dashboard {
+    statistics: Statistics,
+    win_times: Vec<(String, Vec<u64>)>,
+    /// Index of the difficulty whose times are shown.
+    difficulty: usize,
+}
+
+impl Dashboard {
+    fn new(statistics: Statistics) -> Dashboard {
+        Dashboard {
+            win_times: statistics.win_times().into_iter().collect(),
+            statistics,
+            difficulty: 0,
+        }
+    }
+
+    fn switch_difficulty(&mut self, step: isize) {
+        if !self.win_times.is_empty() {
+            self.difficulty = (self.difficulty as isize + step)
+                .rem_euclid(self.win_times.len() as isize) as usize;
+        }
+    }
+}
+
+impl Widget for &Dashboard {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let [summary_area, rates_area, times_area] = Layout::vertical([
+            Constraint::Length(4),
+            Constraint::Length(8),
+            Constraint::Min(0),
+        ])
+        .areas(area);
+
+        let games = self.statistics.games();
+        let won = games
+            .iter()
+            .filter(|game| game.outcome == statistics::Outcome::Won)
+            .count();
+        let streaks = self.statistics.streaks();
+        Paragraph::new(format!(
+            "Played {}, won {} ({:.0}%)\nWin streak {}, longest {}",
+            games.len(),
+            won,
+            won as f64 * 100.0 / games.len().max(1) as f64,
+            streaks.current,
+            streaks.longest
+        ))
+        .block(Block::bordered().title(" Statistics "))
+        .render(summary_area, buf);
+
+        let rates_block = Block::bordered().title(" Win rate by day ");
+        let days = rates_block.inner(rates_area).width as usize;
+        let rates: Vec<u64> = self
+            .statistics
+            .daily_win_rates()
+            .iter()
+            .rev()
+            .take(days)
+            .rev()
+            .map(|(_, rate)| u64::from(*rate))
+            .collect();
+        Sparkline::default()
+            .block(rates_block)
+            .data(&rates)
+            .max(100)
+            .style(Style::new().fg(Color::Green))
+            .render(rates_area, buf);
+
+        let Some((difficulty, times)) = self.win_times.get(self.difficulty) else {
+            Paragraph::new("No games won yet.")
+                .block(Block::bordered().title(" Times "))
+                .render(times_area, buf);
+            return;
+        };
+        let times_block = Block::bordered().title(format!(
+            " Times of won {} games, fastest {:.2} s ",
+            difficulty,
+            times.iter().min().copied().unwrap_or_default() as f64 / 1000.0
+        ));
+        let buckets = (times_block.inner(times_area).width / 8).clamp(1, 12);
+        let bars: Vec<Bar> = statistics::histogram(times, buckets as usize)
+            .into_iter()
+            .map(|(lower, count)| {
+                Bar::default()
+                    .value(count)
+                    .label(Line::from(format!("{}s", lower / 1000)))
+            })
+            .collect();
+        BarChart::default()
+            .block(times_block)
+            .data(BarGroup::default().bars(&bars))
+            .bar_width(7)
+            .bar_gap(1)
+            .bar_style(Style::new().fg(Color::Blue))
+            .render(times_area, buf);
+    }
+}
+
+/// Dimensions of the board, cut to what a terminal can show.
+fn terminal_dimensions(game_board: &GameBoard) -> (u16, u16) {
+    let (width, height) = game_board.get_dimensions();
+    (
+        u16::try_from(width).unwrap_or(u16::MAX),
+        u16::try_from(height).unwrap_or(u16::MAX),
+    )
+}
+
+impl Widget for BoardWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let (width, height) = terminal_dimensions(self.game_board);
+        for row in 0..height.min(area.height) {
+            for col in 0..width.min(area.width / CELL_WIDTH) {
+                let coordinate = Coordinate(u32::from(row), u32::from(col));
+                let (symbol, style) = match CellView::at(self.game_board, coordinate, self.status) {
+                    CellView::Hidden => (" · ".to_string(), Style::new().fg(Color::Gray)),
+                    CellView::Flag => (" F ".to_string(), Style::new().fg(Color::Red).bold()),
+                    CellView::Note => (" ? ".to_string(), Style::new().fg(Color::Yellow)),
+                    CellView::Mine => (" * ".to_string(), Style::new().fg(Color::LightRed)),
+                    CellView::Explored { mines: 0 } | CellView::Void => {
+                        ("   ".to_string(), Style::new())
+                    }
+                    CellView::Explored { mines } => (
+                        format!("{:^3}", mines),
+                        Style::new().fg(number_color(mines)),
+                    ),
+                };
+                let style = if coordinate == self.cursor {
+                    style.add_modifier(Modifier::REVERSED)
+                } else {
+                    style
+                };
+                buf.set_string(area.x + col * CELL_WIDTH, area.y + row, symbol, style);
+            }
+        }
+    }
+}
+
+fn number_color(mines: i16) -> Color {
+    match mines {
+        ..=-1 => Color::LightYellow,
+        1 => Color::Blue,
+        2 => Color::Green,
+        3 => Color::Red,
+        4 => Color::Magenta,
+        5 => Color::LightRed,
+        6 => Color::Cyan,
+        _ => Color::White,
+    }
+}
@@ -0,0 +1,63 @@
+//! WebAssembly bindings so a web UI runs on the same game logic, built with the `wasm`
+//! feature. Boards and statuses cross over as the values of the [`protocol`] module.
+//!
+//! ```js
+//! const game = new Game({ width: 9, height: 9, total_mines: 10 });
+//! const status = game.command("explore(3, 4)"); // "playing", "won", "lost" or "quit"
+//! const cells = game.cells(); // rows of { state: "hidden" }, { state: "explored", mines: 2 }, ...
+//! ```
+//!
+//! [`protocol`]: crate::protocol
+
+use wasm_bindgen::prelude::*;
+
+use crate::{
+    protocol::{BoardView, Response, Session, Status},
+    BoardCommand, GameConfiguration,
+};
+
+#[wasm_bindgen]
+pub struct Game {
+    session: Session,
+}
+
+#[wasm_bindgen]
+impl Game {
+    /// Starts a game on the configuration `{ width, height, total_mines }`, the board is
+    /// generated from the seed if one is given.
+    #[wasm_bindgen(constructor)]
+    pub fn new(config: JsValue, seed: Option<u64>) -> Result<Game, JsError> {
+        let configuration: GameConfiguration = serde_wasm_bindgen::from_value(config)?;
+
+        let mut session = Session::default();
+        expect_board(session.new_game(configuration, seed))?;
+        Ok(Game { session })
+    }
+
+    /// Applies a command in the terminal notation and returns the status of the game.
+    pub fn command(&mut self, command: &str) -> Result<JsValue, JsError> {
+        let command =
+            BoardCommand::try_from(command).map_err(|_| JsError::new("malformed command"))?;
+
+        let (_, status, _) = expect_board(self.session.apply(command))?;
+        Ok(serde_wasm_bindgen::to_value(&status)?)
+    }
+
+    /// Rows of the cells as the player may see them.
+    pub fn cells(&self) -> Result<JsValue, JsError> {
+        let (_, _, view) = expect_board(self.session.board())?;
+        Ok(serde_wasm_bindgen::to_value(&view.cells)?)
+    }
+
+    pub fn seed(&self) -> Result<u64, JsError> {
+        let (seed, _, _) = expect_board(self.session.board())?;
+        Ok(seed)
+    }
+}
+
+fn expect_board(response: Response) -> Result<(u64, Status, BoardView), JsError> {
+    match response {
+        Response::Board { seed, status, view } => Ok((seed, status, view)),
+        Response::Error { message } => Err(JsError::new(&message)),
+    }
+}
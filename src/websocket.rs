@@ -0,0 +1,177 @@
+//! WebSocket server exposing the engine to browser frontends, every connection plays its
+//! own game. Messages are json text frames tagged by `type`.
+//!
+//! Sent by the frontend:
+//!
+//! - `{"type": "new_game", "configuration": {"width": 9, "height": 9, "total_mines": 10}}`
+//!   starts a game, an optional `"seed": 42` replays a known board.
+//! - `{"type": "command", "command": "explore(3, 4)"}` applies a command in the notation of
+//!   the terminal game: `explore`, `flag`, `note` or `clear` with `(row, col)`, or `quit`.
+//!
+//! Every message is answered with a [`Response`]. Boards of more than
+//! [`MAX_NETWORK_CELLS`] cells are refused.
+
+use std::{
+    io,
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+    thread,
+};
+
+use serde::{Deserialize, Serialize};
+use tungstenite::{Message, WebSocket};
+
+use crate::{
+    protocol::{Response, Session, MAX_NETWORK_CELLS},
+    BoardCommand, GameConfiguration,
+};
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Request {
+    NewGame {
+        configuration: GameConfiguration,
+        #[serde(default)]
+        seed: Option<u64>,
+    },
+    Command {
+        command: String,
+    },
+}
+
+fn handle(session: &mut Session, request: Request) -> Response {
+    match request {
+        Request::NewGame {
+            configuration,
+            seed,
+        } => session.new_game(configuration, seed),
+        Request::Command { command } => match BoardCommand::try_from(&command[..]) {
+            Ok(command) => session.apply(command),
+            Err(_) => Response::error("malformed command"),
+        },
+    }
+}
+
+/// Accepts WebSocket connections on the address, serving each on its own thread.
+pub fn serve<A: ToSocketAddrs>(address: A) -> io::Result<()> {
+    let listener = TcpListener::bind(address)?;
+    for stream in listener.incoming().flatten() {
+        thread::spawn(move || {
+            if let Ok(socket) = tungstenite::accept(stream) {
+                serve_connection(socket);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn serve_connection(mut socket: WebSocket<TcpStream>) {
+    let mut session = Session::with_max_cells(MAX_NETWORK_CELLS);
+    loop {
+        let response = match socket.read() {
+            Ok(Message::Text(text)) => match serde_json::from_str(&text) {
+                Ok(request) => handle(&mut session, request),
+                Err(err) => Response::error(&format!("malformed message, {}", err)),
+            },
+            Ok(Message::Close(_)) | Err(_) => break,
+            Ok(_) => continue,
+        };
+
+        let text = serde_json::to_string(&response).expect("Responses serialize to json.");
+        if socket.send(Message::Text(text)).is_err() {
+            break;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        protocol::{CellView, Status},
+        BoardCell, Coordinate, GameBoard,
+    };
+
+    #[test]
+    fn session_plays_a_game_test() {
+        let mut session = Session::default();
+        assert!(matches!(
+            handle(
+                &mut session,
+                Request::Command {
+                    command: "explore(0, 0)".to_string()
+                }
+            ),
+            Response::Error { .. }
+        ));
+
+        let request: Request = serde_json::from_str(
+            r#"{"type": "new_game", "configuration": {"width": 3, "height": 3, "total_mines": 1}, "seed": 5}"#,
+        )
+        .unwrap();
+        let Response::Board { view, status, .. } = handle(&mut session, request) else {
+            panic!("new game should respond with the board");
+        };
+        assert_eq!(Status::Playing, status);
+        assert_eq!(vec![CellView::Hidden; 3], view.cells[0]);
+
+        let mut board = GameBoard::new(GameConfiguration::new(3, 3, 1));
+        board.generate_world_from_seed(5);
+        let mine = (0..9)
+            .map(|index| Coordinate(index / 3, index % 3))
+            .find(|coordinate| matches!(board.get_cell_at(*coordinate), BoardCell::Mine(_)))
+            .unwrap();
+
+        let response = handle(
+            &mut session,
+            Request::Command {
+                command: BoardCommand::Explore(mine).to_string(),
+            },
+        );
+        let Response::Board { view, status, .. } = response else {
+            panic!("command should respond with the board");
+        };
+        assert_eq!(Status::Lost, status);
+        assert_eq!(CellView::Mine, view.cells[mine.0 as usize][mine.1 as usize]);
+        assert_eq!(
+            r#"{"state":"explored","mines":1}"#,
+            serde_json::to_string(&CellView::Explored { mines: 1 }).unwrap()
+        );
+    }
+
+    #[test]
+    fn network_board_limit_test() {
+        let mut session = Session::with_max_cells(MAX_NETWORK_CELLS);
+        let new_game = |width, height, mines| Request::NewGame {
+            configuration: GameConfiguration::new(width, height, mines),
+            seed: Some(1),
+        };
+        assert_eq!(
+            Response::error(
+                "the board must be 1 to 100000 cells wide and high with at most 10000 cells"
+            ),
+            handle(&mut session, new_game(10_000, 10_000, 1))
+        );
+        assert_eq!(
+            Response::error("there are more mines than the board can hold"),
+            handle(&mut session, new_game(100, 100, 10_001))
+        );
+        let crowded = serde_json::from_str(
+            r#"{"width":1,"height":1,"total_mines":200,"mines_per_cell":200}"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Response::error("a cell may hold 1 to 127 mines"),
+            handle(
+                &mut session,
+                Request::NewGame {
+                    configuration: crowded,
+                    seed: Some(1),
+                }
+            )
+        );
+        assert!(matches!(
+            handle(&mut session, new_game(100, 100, 20)),
+            Response::Board { .. }
+        ));
+    }
+}
@@ -0,0 +1,143 @@
+//! Walks the player through the settings that matter most before the first game: the
+//! board, how it looks, how cells are named and the assists. The binary runs it for a
+//! profile without a config of its own and from the menu, then saves the answers as the
+//! profile's config.
+
+use crate::{
+    ansi::Theme,
+    config::Config,
+    console::{Input, InputSource, OutputSink},
+    locale::{self, Message},
+    AxisOrder, CoordinateConvention, FlagGuard, GameConfiguration, SafeStart,
+};
+
+/// The config with the answers applied, an empty answer keeps the setting as it is.
+pub fn run(mut config: Config, input: &mut dyn InputSource, output: &mut dyn OutputSink) -> Config {
+    output.line(&locale::text(Message::WizardIntro));
+
+    config.default_board = ask(input, output, Message::WizardBoard, |answer| {
+        GameConfiguration::named(answer).or_else(|| {
+            GameConfiguration::try_from(answer)
+                .ok()
+                .filter(|configuration| configuration.fits())
+        })
+    })
+    .or(config.default_board);
+    config.theme = ask(input, output, Message::WizardTheme, |answer| {
+        match answer.to_lowercase().as_str() {
+            "1" | "plain" => Some(Theme::Plain),
+            "2" | "colour" | "color" => Some(Theme::Colour),
+            _ => None,
+        }
+    })
+    .or(config.theme);
+    config.coordinates = ask(input, output, Message::WizardCoordinates, |answer| {
+        let one_based = CoordinateConvention {
+            one_based: true,
+            ..CoordinateConvention::default()
+        };
+        match answer {
+            "1" => Some(CoordinateConvention::default()),
+            "2" => Some(one_based),
+            "3" => Some(CoordinateConvention {
+                order: AxisOrder::Xy,
+                ..one_based
+            }),
+            "4" => Some(CoordinateConvention {
+                letters: true,
+                ..CoordinateConvention::default()
+            }),
+            _ => None,
+        }
+    })
+    .or(config.coordinates);
+
+    config.safe_start = ask(input, output, Message::WizardSafeStart, yes_no)
+        .map(|safe| {
+            if safe {
+                SafeStart::Square3
+            } else {
+                SafeStart::Off
+            }
+        })
+        .or(config.safe_start);
+    config.auto_chord = ask(input, output, Message::WizardAutoChord, yes_no).or(config.auto_chord);
+    config.forgive_first_mistake =
+        ask(input, output, Message::WizardForgive, yes_no).or(config.forgive_first_mistake);
+    config.flag_guard = ask(input, output, Message::WizardFlagGuard, yes_no)
+        .map(|guard| {
+            if guard {
+                FlagGuard::Confirm
+            } else {
+                FlagGuard::Off
+            }
+        })
+        .or(config.flag_guard);
+    config
+}
+
+/// Asks until the answer is understood, none for an empty answer.
+fn ask<T>(
+    input: &mut dyn InputSource,
+    output: &mut dyn OutputSink,
+    question: Message,
+    parse: impl Fn(&str) -> Option<T>,
+) -> Option<T> {
+    output.line(&locale::text(question));
+    loop {
+        // requests of tools wait until the game runs
+        let Input::Line(answer) = input.next() else {
+            continue;
+        };
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return None;
+        }
+        match parse(answer) {
+            Some(value) => return Some(value),
+            None => output.line(&locale::text(Message::WizardInvalidAnswer)),
+        }
+    }
+}
+
+fn yes_no(answer: &str) -> Option<bool> {
+    match answer.to_lowercase().as_str() {
+        "y" | "yes" | "j" | "ja" => Some(true),
+        "n" | "no" | "nein" => Some(false),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::console::{Script, Transcript};
+
+    #[test]
+    fn wizard_test() {
+        let config = Config {
+            auto_chord: Some(true),
+            ..Config::default()
+        };
+        let mut output = Transcript::default();
+        let config = run(
+            config,
+            &mut Script::new(&["Expert", "2", "5", "3", "y", "", "maybe", "n", "y"]),
+            &mut output,
+        );
+
+        assert_eq!(Some(GameConfiguration::expert()), config.default_board);
+        assert_eq!(Some(Theme::Colour), config.theme);
+        let coordinates = config.coordinates.unwrap();
+        assert!(coordinates.one_based);
+        assert_eq!(AxisOrder::Xy, coordinates.order);
+        assert_eq!(Some(SafeStart::Square3), config.safe_start);
+        // the empty answer keeps the assist that was on
+        assert_eq!(Some(true), config.auto_chord);
+        assert_eq!(Some(false), config.forgive_first_mistake);
+        assert_eq!(Some(FlagGuard::Confirm), config.flag_guard);
+        // the intro and seven questions, the unknown coordinates and the unclear answer
+        // were asked again
+        assert_eq!(10, output.lines.len());
+    }
+}
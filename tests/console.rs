@@ -0,0 +1,195 @@
+use std::{env, fs, time::Duration};
+
+use minesweeper::{
+    console::{self, Script, Transcript},
+    layout::Layout,
+    save,
+    sound::Cue,
+    GameBoard, GameResolve, SafeStart,
+};
+
+/// Board with a single mine in the corner, exploring the opposite corner opens the rest and wins.
+fn corner_mine() -> GameBoard {
+    GameBoard::from_layout(Layout::try_from("*##\n###\n###\n").unwrap())
+}
+
+#[test]
+fn game_session_test() {
+    let data_dir = env::temp_dir().join(format!("minesweeper-console-{}", std::process::id()));
+    env::set_var("MINESWEEPER_DATA_DIR", &data_dir);
+    env::set_var("LANG", "C");
+
+    let mut output = Transcript::default();
+    let result = console::game_loop(
+        corner_mine(),
+        &mut Script::new(&["explore(2, 2)", "r", "explore(0, 0)", "s", ""]),
+        &mut output,
+        None,
+    );
+    // the retry is practice, the result is that of the first game
+    assert_eq!(GameResolve::AllMinesDiscovered, result.resolve);
+    assert!(output.contains("YOU WON!"));
+    assert!(output.contains("HIT MINE!"));
+    assert!(output.contains("Result:     won"));
+    assert!(output.contains("Result:     lost"));
+    assert!(output.contains("3BV:        1/1, "));
+    // hand made boards have no seed to share
+    assert!(output.contains("Seed:       -"));
+    assert!(!output.contains("Board seed"));
+    assert!(output.contains("Saved the replay to "));
+    assert!(fs::read_dir(data_dir.join("profiles/default"))
+        .unwrap()
+        .any(|entry| entry
+            .unwrap()
+            .file_name()
+            .to_string_lossy()
+            .starts_with("replay-")));
+    assert_eq!(1, result.efficiency.clicks);
+    assert_eq!(100, result.efficiency.percent());
+    assert!(output.cues.is_empty());
+
+    fs::write(
+        data_dir.join("config.json"),
+        r#"{"sounds": {"mine_hit": true, "invalid_command": true}, "time_format": "clock"}"#,
+    )
+    .unwrap();
+    let mut output = Transcript::default();
+    let result = console::game_loop(
+        corner_mine(),
+        &mut Script::new(&[
+            "cheat",
+            "describe(0, 1)",
+            "readrow 0",
+            "estimate",
+            "braille",
+            "braille",
+            "pause",
+            "",
+            "flag(7, 7)",
+            "nonsense",
+            "stop",
+            "record corner",
+            "flag(0, 2)",
+            "stop",
+            "play edge",
+            "play corner",
+            "flag(0, 1); nonsense",
+            "flag(0, 1)",
+            "again(0, 2); .; explore(0, 0); flag(1, 1)",
+            "",
+        ]),
+        &mut output,
+        None,
+    );
+    assert_eq!(GameResolve::MineHit, result.resolve);
+    // the batch with a typo is dropped, the one hitting the mine stops there
+    assert_eq!(5, result.flags_placed);
+    assert_eq!(6, result.replay.moves.len());
+    assert_eq!("flag(0, 2)", result.replay.moves[1].command);
+    assert_eq!("flag(0, 2)", result.replay.moves[3].command);
+    assert!(output.contains("No macro is being recorded."));
+    assert!(output.contains("Kept the macro corner with 1 commands."));
+    assert!(output.contains("There is no macro edge."));
+    assert!(!result.cheated);
+    assert!(output.contains("Cheats are disabled"));
+    assert!(output.contains("row 0, column 1: unexplored;"));
+    assert!(output.contains("row 0: columns 0 to 2 unexplored"));
+    assert!(output.contains("Safest cells to explore: ("));
+    assert!(output.contains("  0 ⠿⠇"));
+    assert!(output.contains("Coordinate is outside of the board."));
+    assert!(output.contains("HIT MINE!"));
+    assert!(output.contains("Hints used: 1"));
+    assert!(output.contains("Paused, the clock stands still. Press Enter to go on."));
+    assert!(output.contains("Time:       00:0"));
+    // the clock is shown with every board
+    assert!(output.contains("Time: 00:0"));
+    assert_eq!(
+        vec![
+            Cue::InvalidCommand,
+            Cue::InvalidCommand,
+            Cue::InvalidCommand,
+            Cue::MineHit
+        ],
+        output.cues
+    );
+
+    let mut output = Transcript::default();
+    let result = console::game_loop(
+        corner_mine(),
+        &mut Script::new(&[
+            "saves",
+            "flag(0, 1)",
+            "save first",
+            "saves",
+            "explore(1, 1)",
+            "load first",
+            "delete first",
+            "load first",
+            "explore(0, 0)",
+            "",
+        ]),
+        &mut output,
+        None,
+    );
+    assert_eq!(GameResolve::MineHit, result.resolve);
+    assert!(output.contains("There are no saved games."));
+    assert!(output.contains("Saved the game to slot first."));
+    assert!(output.contains("first           3x3-1"));
+    assert!(output.contains("Loaded slot first, resumed games set no personal bests."));
+    assert!(output.contains("Deleted slot first."));
+    assert!(output.contains("Slot first failed: "));
+    // the explore before the load was left behind with the board
+    assert_eq!(0, result.cells_revealed);
+    // the game in progress is autosaved until it is over
+    assert!(output.contains("autosave        3x3-1"));
+    assert!(save::slots().is_empty());
+
+    // the resumed board keeps its rules, the config forgives nothing
+    let mut game_board = corner_mine();
+    game_board.set_forgiving(true);
+    let mut output = Transcript::default();
+    let result = console::resume_loop(
+        game_board,
+        Duration::from_secs(61),
+        &mut Script::new(&["explore(0, 0)", "explore(2, 2)", ""]),
+        &mut output,
+        None,
+    );
+    assert_eq!(GameResolve::AllMinesDiscovered, result.resolve);
+    assert!(result.forgiven);
+    assert!(result.elapsed > Duration::from_secs(61));
+    assert!(output.contains("Time: 01:01"));
+    assert!(!result.is_ranked());
+
+    // a game won from a loaded slot is practice, saving before every risk does not rank
+    let mut output = Transcript::default();
+    let result = console::game_loop(
+        corner_mine(),
+        &mut Script::new(&["save scum", "load scum", "explore(2, 2)", ""]),
+        &mut output,
+        None,
+    );
+    assert_eq!(GameResolve::AllMinesDiscovered, result.resolve);
+    assert!(result.practice);
+    assert!(!result.is_ranked());
+
+    // shared boards are the same for everyone, safe start stays off
+    fs::write(data_dir.join("config.json"), r#"{"safe_start": "3x3"}"#).unwrap();
+    let result = console::game_loop(
+        corner_mine(),
+        &mut Script::new(&["quit", ""]),
+        &mut Transcript::default(),
+        None,
+    );
+    assert_eq!(SafeStart::Square3, result.replay.safe_start);
+    assert!(!result.is_ranked());
+    let result = console::shared_game_loop(
+        corner_mine(),
+        &mut Script::new(&["quit", ""]),
+        &mut Transcript::default(),
+        None,
+    );
+    assert_eq!(SafeStart::Off, result.replay.safe_start);
+
+    let _ = fs::remove_dir_all(data_dir);
+}